@@ -17,8 +17,7 @@ fn bench_rolling_apply_with_start(b: &mut Bencher) {
         .into();
     let mut v: Expr = v.into();
     v.rename("v".to_string());
-    let start = std::iter::repeat(0)
-        .take(window)
+    let start = std::iter::repeat_n(0, window)
         .chain(0..(length - window))
         .collect::<Vec<_>>();
     let start: ArrOk = Arr1::from_vec(start).into_dyn().into();
@@ -70,8 +69,7 @@ fn bench_rolling_apply_mean(b: &mut Bencher) {
         .into();
     let mut v: Expr = v.into();
     v.rename("v".to_string());
-    let start = std::iter::repeat(0)
-        .take(window)
+    let start = std::iter::repeat_n(0, window)
         .chain(0..(length - window))
         .collect::<Vec<_>>();
     let start: ArrOk = Arr1::from_vec(start).into_dyn().into();
@@ -12,16 +12,16 @@ impl<'a> AggExt for Expr<'a> {
 
     fn ndim(&self) {}
 
-    #[teapy(type = "Numeric")]
+    #[teapy(lazy = "masked_view", type = "Numeric")]
     fn sum(&self, axis: i32, par: bool) {}
 
-    #[teapy(type = "Numeric")]
+    #[teapy(lazy = "masked_view", type = "Numeric")]
     fn mean(&self, min_periods: usize, axis: i32, par: bool) {}
 
-    #[teapy(type = "Numeric")]
+    #[teapy(lazy = "masked_view", type = "Numeric")]
     fn min(&self, axis: i32, par: bool) {}
 
-    #[teapy(type = "Numeric")]
+    #[teapy(lazy = "masked_view", type = "Numeric")]
     fn max(&self, axis: i32, par: bool) {}
 }
 
@@ -32,7 +32,7 @@ impl<'a> ExprAggExt for Expr<'a> {
             let arr = data.into_arr(ctx.clone())?;
             let value = value.view_arr(ctx.as_ref())?;
             let out = match_arrok!(arr; Cast(a) => {
-                let value = match_arrok!(value; Cast(v) => {Ok(v.deref().into_owned().into_scalar()?.cast())},).unwrap();
+                let value = match_arrok!(&*value; Cast(v) => {Ok(Cast::cast(v.deref().into_owned().into_scalar()?))},).unwrap();
                 Ok(a.view().count_v(value, axis, par))
             },).unwrap();
             Ok((out.into(), ctx))
@@ -44,7 +44,8 @@ impl<'a> ExprAggExt for Expr<'a> {
         self.cast_bool().chain_f_ctx(move |(data, ctx)| {
             let arr = data.view_arr(ctx.as_ref())?;
             Ok((
-                match_arrok!(arr; Bool(arr) => { Ok(arr.view().any(axis, par).into()) },).unwrap(),
+                match_arrok!(&*arr; Bool(arr) => { Ok(arr.view().any(axis, par).into()) },)
+                    .unwrap(),
                 ctx,
             ))
         });
@@ -55,7 +56,8 @@ impl<'a> ExprAggExt for Expr<'a> {
         self.cast_bool().chain_f_ctx(move |(data, ctx)| {
             let arr = data.view_arr(ctx.as_ref())?;
             Ok((
-                match_arrok!(arr; Bool(arr) => { Ok(arr.view().all(axis, par).into()) },).unwrap(),
+                match_arrok!(&*arr; Bool(arr) => { Ok(arr.view().all(axis, par).into()) },)
+                    .unwrap(),
                 ctx,
             ))
         });
@@ -66,7 +68,7 @@ impl<'a> ExprAggExt for Expr<'a> {
     fn shape(&mut self) -> &mut Self {
         self.chain_f_ctx(|(data, ctx)| {
             let arr = data.view_arr(ctx.as_ref())?;
-            let out: ArrOk<'a> = match_arrok!(arr; Dynamic(a) => {
+            let out: ArrOk<'a> = match_arrok!(&*arr; Dynamic(a) => {
                 let shape = a.view().shape().to_owned();
                 Ok(Arr1::from_vec(shape).into_dyn().into())
             },)
@@ -92,10 +94,10 @@ pub fn corr<'a>(
         for i in 0..len {
             for j in i..len {
                 let corr = if i != j {
-                    let arri = *unsafe{all_arr.get_unchecked(i)};
-                    let arrj = *unsafe{all_arr.get_unchecked(j)};
-                    match_arrok!(arri; PureNumeric(arri) => {
-                        match_arrok!(arrj; PureNumeric(arrj) => {
+                    let arri = unsafe{all_arr.get_unchecked(i)};
+                    let arrj = unsafe{all_arr.get_unchecked(j)};
+                    match_arrok!(&**arri; PureNumeric(arri) => {
+                        match_arrok!(&**arrj; PureNumeric(arrj) => {
                             Ok(arri.deref().view().to_dim1()?.corr_1d(&arrj.deref().view().to_dim1()?, method, min_periods, stable))
                         },)
                     },).unwrap()
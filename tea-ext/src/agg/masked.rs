@@ -0,0 +1,61 @@
+use teapy_core::match_masked;
+use teapy_core::prelude::*;
+
+/// Sum the valid values of a masked view, return the valid number and the sum.
+#[inline]
+fn nsum<T: IsNone<Inner = T> + Number>(v: &MaskedView<'_, T>) -> (usize, T) {
+    v.titer().vfold_n(T::zero(), |acc, x| acc + x)
+}
+
+/// The float dtypes are aggregated into `NaN` if there is no valid value.
+#[inline]
+fn or_none<T: IsNone<Inner = T>>(v: Option<T>) -> T {
+    v.unwrap_or_else(T::none)
+}
+
+#[inline]
+fn arr0<'b, T>(v: T) -> ArrOk<'b>
+where
+    ArrD<T>: Into<ArrOk<'b>>,
+{
+    ndarray::arr0(v).wrap().into_dyn().into()
+}
+
+/// The aggregations of `BasicAggExt` on masked arrays, ints are aggregated
+/// into the `Opt*` dtypes as their dense arrays are. `None` is returned for
+/// the dtypes that are not supported.
+///
+/// Masked arrays are 1-d, so `axis` and `par` are not used.
+#[ext_trait]
+impl MaskedAggExt for MaskedArray<'_> {
+    fn sum<'b>(&self, _axis: i32, _par: bool) -> TResult<Option<ArrOk<'b>>> {
+        match_masked!(self, Ok(None);
+            PureInt(v) => Ok(Some(arr0(Some(nsum(&v).1)))),
+            PureFloat(v) => Ok(Some(arr0(nsum(&v).1))),
+        )
+    }
+
+    fn mean<'b>(&self, min_periods: usize, _axis: i32, _par: bool) -> TResult<Option<ArrOk<'b>>> {
+        match_masked!(self, Ok(None);
+            PureNumeric(v) => {
+                let (n, sum) = nsum(&v);
+                let mean = if n >= min_periods { sum.f64() / n.f64() } else { f64::NAN };
+                Ok(Some(arr0(mean)))
+            },
+        )
+    }
+
+    fn min<'b>(&self, _axis: i32, _par: bool) -> TResult<Option<ArrOk<'b>>> {
+        match_masked!(self, Ok(None);
+            PureInt(v) => Ok(Some(arr0(v.titer().vmin()))),
+            PureFloat(v) => Ok(Some(arr0(or_none(v.titer().vmin())))),
+        )
+    }
+
+    fn max<'b>(&self, _axis: i32, _par: bool) -> TResult<Option<ArrOk<'b>>> {
+        match_masked!(self, Ok(None);
+            PureInt(v) => Ok(Some(arr0(v.titer().vmax()))),
+            PureFloat(v) => Ok(Some(arr0(or_none(v.titer().vmax())))),
+        )
+    }
+}
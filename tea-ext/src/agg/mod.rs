@@ -1,6 +1,7 @@
 mod corr;
 #[cfg(feature = "lazy")]
 mod impl_lazy;
+mod masked;
 
 #[cfg(feature = "lazy")]
 pub use corr::AutoExprAgg2Ext;
@@ -9,6 +10,7 @@ pub use corr::{Agg2Ext, CorrToolExt1d};
 pub use impl_lazy::{corr, AutoExprAggExt, DataDictCorrExt, ExprAggExt};
 #[cfg(feature = "lazy")]
 use lazy::Expr;
+pub use masked::MaskedAggExt;
 use ndarray::{ArrayBase, Data, Dimension, Ix1, Zip};
 use teapy_core::prelude::*;
 // use teapy_core::utils::{kh_sum, vec_fold, vec_nfold};
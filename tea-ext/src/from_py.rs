@@ -22,6 +22,7 @@ use pyo3::exceptions::PyValueError;
 ))]
 use pyo3::prelude::*;
 use pyo3::types::PyAnyMethods;
+use teapy_core::prelude::TError;
 
 #[inline]
 fn extract_str<'py>(ob: &'py Bound<'_, PyAny>) -> PyResult<Option<std::borrow::Cow<'py, str>>> {
@@ -36,16 +37,9 @@ fn extract_str<'py>(ob: &'py Bound<'_, PyAny>) -> PyResult<Option<std::borrow::C
 impl<'py> FromPyObject<'py> for FillMethod {
     fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
         let s = extract_str(ob)?;
-        let s = s.unwrap_or_else(|| "ffill".into()).to_lowercase();
-        let out = match s.as_str() {
-            "ffill" => FillMethod::Ffill,
-            "bfill" => FillMethod::Bfill,
-            "vfill" => FillMethod::Vfill,
-            _ => Err(PyValueError::new_err(format!(
-                "Not supported fillna method: {s}"
-            )))?,
-        };
-        Ok(out)
+        s.unwrap_or_else(|| "ffill".into())
+            .parse()
+            .map_err(|e: TError| PyValueError::new_err(e.to_string()))
     }
 }
 
@@ -53,15 +47,9 @@ impl<'py> FromPyObject<'py> for FillMethod {
 impl<'py> FromPyObject<'py> for DropNaMethod {
     fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
         let s = extract_str(ob)?;
-        let s = s.unwrap_or_else(|| "any".into()).to_lowercase();
-        let out = match s.as_str() {
-            "all" => DropNaMethod::All,
-            "any" => DropNaMethod::Any,
-            _ => Err(PyValueError::new_err(format!(
-                "Not supported dropna method: {s}"
-            )))?,
-        };
-        Ok(out)
+        s.unwrap_or_else(|| "any".into())
+            .parse()
+            .map_err(|e: TError| PyValueError::new_err(e.to_string()))
     }
 }
 
@@ -74,13 +62,8 @@ impl<'py> FromPyObject<'py> for DropNaMethod {
 impl<'py> FromPyObject<'py> for RollingTimeStartBy {
     fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
         let s = extract_str(ob)?;
-        let s = s.unwrap_or_else(|| "full".into()).to_lowercase();
-        match s.as_str() {
-            "full" => Ok(RollingTimeStartBy::Full),
-            "duration_start" | "durationstart" | "ds" => Ok(RollingTimeStartBy::DurationStart),
-            _ => Err(PyValueError::new_err(format!(
-                "Not supported rolling by time start_by method: {s}"
-            ))),
-        }
+        s.unwrap_or_else(|| "full".into())
+            .parse()
+            .map_err(|e: TError| PyValueError::new_err(e.to_string()))
     }
 }
@@ -26,3 +26,50 @@ pub use agg::*;
 pub use map::*;
 #[cfg(feature = "rolling")]
 pub use rolling::*;
+
+/// Register the lazy methods of the crate, so that the expressions using them
+/// can be rebuilt from their definitions, see `tea_lazy::ExprDef`.
+#[cfg(feature = "lazy")]
+pub fn register_ops() {
+    use lazy::Expr;
+    #[cfg(feature = "agg")]
+    {
+        <Expr as AutoExprAggExt>::register_ops();
+        <Expr as AutoExprAggExtNd>::register_ops();
+        <Expr as AutoExprAgg2Ext>::register_ops();
+        <Expr as ExprAggExt>::register_ops();
+    }
+    #[cfg(feature = "create")]
+    <Expr as create::CreateExt>::register_ops();
+    #[cfg(feature = "map")]
+    {
+        <Expr as AutoExprMapExt>::register_ops();
+        <Expr as AutoExprMapExtNd>::register_ops();
+        <Expr as AutoExprInplaceExt>::register_ops();
+        <Expr as AutoExprF64FuncExt>::register_ops();
+        <Expr as ExprInplaceExt>::register_ops();
+        <Expr as ExprMapExt>::register_ops();
+        <Expr as ExprViewExt>::register_ops();
+        <Expr as ExprListExt>::register_ops();
+        <Expr as ExprDecimalExt>::register_ops();
+        <Expr as ExprStringExt>::register_ops();
+        <Expr as ExprStructExt>::register_ops();
+        #[cfg(feature = "stat")]
+        <Expr as ExprStatExt>::register_ops();
+        #[cfg(feature = "time")]
+        <Expr as ExprTimeExt>::register_ops();
+    }
+    #[cfg(feature = "rolling")]
+    {
+        <Expr as AutoExprFeatureTs>::register_ops();
+        <Expr as AutoExprCmpTs>::register_ops();
+        <Expr as AutoExprNormTs>::register_ops();
+        <Expr as AutoExprRegTs>::register_ops();
+        <Expr as AutoExprBinaryTs>::register_ops();
+        #[cfg(feature = "agg")]
+        {
+            <Expr as RollingExt>::register_ops();
+            <Expr as AutoExprRollingExt>::register_ops();
+        }
+    }
+}
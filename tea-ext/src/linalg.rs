@@ -166,7 +166,8 @@ impl<'a> ExprLinalgExt for Expr<'a> {
 
     pub fn conjugate(&mut self) -> &mut Self {
         self.chain_f_ctx(move |(data, ctx)| {
-            let arr = data.view_arr(ctx.as_ref())?.deref().cast_f64();
+            let arr = data.view_arr(ctx.as_ref())?;
+            let arr = arr.deref().cast_f64();
             let out: Arr2<f64> = conjugate(&arr.view().to_dim2()?);
             Ok((out.to_dimd().into(), ctx))
         });
@@ -219,7 +220,8 @@ impl<'a> ExprLinalgExt for Expr<'a> {
     pub fn get_newey_west_adjust_s(&mut self, resid: Expr<'a>, lag: Expr<'a>) -> &mut Self {
         self.chain_f_ctx(move |(data, ctx)| {
             let x = data.into_arr(ctx.clone())?.cast_f64().into_owned();
-            let resid = resid.view_arr(ctx.as_ref())?.deref().cast_f64();
+            let resid = resid.view_arr(ctx.as_ref())?;
+            let resid = resid.deref().cast_f64();
             let resid_view = resid.view().to_dim1()?;
             let lag = lag
                 .view_arr(ctx.as_ref())?
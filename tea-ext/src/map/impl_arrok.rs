@@ -9,7 +9,74 @@ impl<'a> ArrOkExt for ArrOk<'a> {
         if slc.ndim() > 1 {
             tbail!("The slice must be dim 0 or dim 1 when select on axis");
         }
+        if self.is_compact() {
+            // select on the widened array, the dtype is kept unless a `None`
+            // index introduces missing values into a non-nullable dtype
+            let out = self.view().widen().into_owned().select(slc, axis, check)?;
+            if matches!(slc, ArrOk::OptUsize(_))
+                && !matches!(self, ArrOk::OptU8(_) | ArrOk::OptU64(_))
+            {
+                return Ok(out);
+            }
+            return Ok(match self {
+                ArrOk::I8(_) => out.cast_i8().into(),
+                ArrOk::I16(_) => out.cast_i16().into(),
+                ArrOk::U16(_) => out.cast_u16().into(),
+                ArrOk::U32(_) => out.cast_u32().into(),
+                ArrOk::OptU8(_) => out.cast_opt_u8().into(),
+                ArrOk::OptU64(_) => out.cast_opt_u64().into(),
+                _ => out,
+            });
+        }
         let mut slc = slc.deref();
+        #[cfg(feature = "time")]
+        if let ArrOk::DateTimeTz(a) = self {
+            let utc = ArrOk::DateTimeNs(a.utc.view().into())
+                .select(&slc, axis, check)?
+                .into_owned();
+            return Ok(utc.convert_tz(a.tz).into());
+        }
+        if let ArrOk::Struct(a) = self {
+            let out = a.map_fields(|f| {
+                let f = f.to_dense();
+                let f = f.view().select(&slc.view(), axis, check)?;
+                Ok(f.into_owned())
+            })?;
+            return Ok(out.into());
+        }
+        if let ArrOk::List(_) | ArrOk::Decimal(_) = self {
+            // rows taken by `None` are null
+            let idx: Vec<Option<usize>> = if let ArrOk::OptUsize(slc) = &slc {
+                slc.view().to_dim1()?.0.iter().copied().collect()
+            } else {
+                let rows: ArrOk<'_> = Arr1::from_iter(0..self.len()).into_dyn().into();
+                let rows = rows.select(&slc, axis, check)?.cast_usize();
+                rows.view().0.iter().map(|i| Some(*i)).collect()
+            };
+            return Ok(match self {
+                ArrOk::List(a) => a.take(idx).into(),
+                ArrOk::Decimal(a) => a.take(idx)?.into(),
+                _ => unreachable!(),
+            });
+        }
+        if let ArrOk::Categorical(a) = self {
+            let categories = a.categories.clone();
+            let codes = if let ArrOk::OptUsize(slc) = &slc {
+                // rows taken by `None` are null
+                let codes = a.codes.view().to_dim1()?;
+                slc.view()
+                    .to_dim1()?
+                    .map(|i| i.map_or(NULL_CODE, |i| codes[i]))
+                    .into_dyn()
+            } else {
+                ArrOk::from(a.codes_usize())
+                    .select(&slc, axis, check)?
+                    .cast_usize()
+                    .view()
+                    .map(|c| *c as u32)
+            };
+            return Ok(CatArray::new(codes.into(), categories).into());
+        }
         let out = match_arrok!(self; Dynamic(a) => {
             let a_view = a.view();
             let axis_ = a_view.norm_axis(axis);
@@ -181,6 +248,108 @@ impl<'a> ArrOkExt for ArrOk<'a> {
                         )
                         .unwrap()
                     }
+                    I8(_) | I16(_) | U16(_) | U32(_) => match_arrok!(
+                        arr;
+                        SmallInt(arr) =>
+                        {
+                            let key_view = arr
+                                .view()
+                                .to_dim1()
+                                .expect("Currently only 1 dim array can be sort key");
+                            let (va, vb) = unsafe { (key_view.uget(*a), key_view.uget(*b)) };
+                            if !rev {
+                                Ok(va.cmp(vb))
+                            } else {
+                                Ok(va.cmp(vb).reverse())
+                            }
+                        },
+                    )
+                    .unwrap(),
+                    OptU8(_) | OptU64(_) => match_arrok!(
+                        arr;
+                        OptUInt(arr) =>
+                        {
+                            let key_view = arr
+                                .view()
+                                .to_dim1()
+                                .expect("Currently only 1 dim array can be sort key");
+                            let (va, vb) = unsafe { (key_view.uget(*a), key_view.uget(*b)) };
+                            // missing values are always placed last
+                            match (va, vb) {
+                                (Some(va), Some(vb)) if !rev => Ok(va.cmp(vb)),
+                                (Some(va), Some(vb)) => Ok(va.cmp(vb).reverse()),
+                                (None, None) => Ok(Ordering::Equal),
+                                (None, _) => Ok(Ordering::Greater),
+                                (_, None) => Ok(Ordering::Less),
+                            }
+                        },
+                    )
+                    .unwrap(),
+                    #[cfg(feature = "time")]
+                    DateTimeTz(arr) => {
+                        // timezone-aware datetimes are sorted by their instants
+                        let key_view = arr
+                            .utc
+                            .view()
+                            .to_dim1()
+                            .expect("Currently only 1 dim array can be sort key");
+                        let (va, vb) = unsafe { (key_view.uget(*a), key_view.uget(*b)) };
+                        if !rev {
+                            va.sort_cmp(vb)
+                        } else {
+                            va.sort_cmp_rev(vb)
+                        }
+                    }
+                    #[cfg(feature = "time")]
+                    Date(arr) => {
+                        let key_view = arr
+                            .view()
+                            .to_dim1()
+                            .expect("Currently only 1 dim array can be sort key");
+                        let (va, vb) = unsafe { (key_view.uget(*a), key_view.uget(*b)) };
+                        // NaT is always placed last
+                        match (va.into_opt_i32(), vb.into_opt_i32()) {
+                            (Some(va), Some(vb)) if !rev => va.cmp(&vb),
+                            (Some(va), Some(vb)) => va.cmp(&vb).reverse(),
+                            (None, None) => Ordering::Equal,
+                            (None, _) => Ordering::Greater,
+                            (_, None) => Ordering::Less,
+                        }
+                    }
+                    Decimal(arr) => {
+                        let key_view = arr
+                            .values
+                            .view()
+                            .to_dim1()
+                            .expect("Currently only 1 dim array can be sort key");
+                        let (va, vb) = unsafe { (*key_view.uget(*a), *key_view.uget(*b)) };
+                        // null is always placed last
+                        match (va == DecArray::NULL, vb == DecArray::NULL) {
+                            (false, false) if !rev => va.cmp(&vb),
+                            (false, false) => va.cmp(&vb).reverse(),
+                            (true, true) => Ordering::Equal,
+                            (true, false) => Ordering::Greater,
+                            (false, true) => Ordering::Less,
+                        }
+                    }
+                    Categorical(arr) => {
+                        let codes = arr
+                            .codes
+                            .view()
+                            .to_dim1()
+                            .expect("Currently only 1 dim array can be sort key");
+                        let (va, vb) = unsafe {
+                            (arr.category(*codes.uget(*a)), arr.category(*codes.uget(*b)))
+                        };
+                        // null is always placed last
+                        match (va, vb) {
+                            (Some(va), Some(vb)) if !rev => va.cmp(vb),
+                            (Some(va), Some(vb)) => va.cmp(vb).reverse(),
+                            (None, None) => Ordering::Equal,
+                            (None, Some(_)) => Ordering::Greater,
+                            (Some(_), None) => Ordering::Less,
+                        }
+                    }
                     _ => match_arrok!(
                         arr;
                         Numeric(arr) =>
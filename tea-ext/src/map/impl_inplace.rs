@@ -8,13 +8,26 @@ use teapy_core::prelude::*;
 /// Ffill: use forward value to fill nan.
 /// Bfill: use backward value to fill nan.
 /// Vfill: use a specified value to fill nan
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub enum FillMethod {
     Ffill,
     Bfill,
     Vfill,
 }
 
+impl std::str::FromStr for FillMethod {
+    type Err = TError;
+
+    fn from_str(s: &str) -> TResult<Self> {
+        match s.to_lowercase().as_str() {
+            "ffill" => Ok(FillMethod::Ffill),
+            "bfill" => Ok(FillMethod::Bfill),
+            "vfill" => Ok(FillMethod::Vfill),
+            _ => tbail!("Not supported fillna method: {s}"),
+        }
+    }
+}
+
 // #[cfg(feature = "agg")]
 // #[derive(Copy, Clone)]
 // pub enum WinsorizeMethod {
@@ -199,11 +212,11 @@ impl<T: Send + Sync, S: DataMut<Elem = T>, D: Dimension> InplaceExt for ArrBase<
         let mut arr = self.as_dim1_mut();
         let (mean, var) = arr.meanvar_1d(min_periods);
         if var == 0. {
-            arr.apply_mut(|v| *v = 0.0.cast());
+            arr.apply_mut(|v| *v = Cast::cast(0.0));
         } else if var.is_none() {
-            arr.apply_mut(|v| *v = f64::NAN.cast());
+            arr.apply_mut(|v| *v = Cast::cast(f64::NAN));
         } else {
-            arr.apply_mut(|v| *v = ((v.f64() - mean) / var.sqrt()).cast());
+            arr.apply_mut(|v| *v = Cast::cast((v.f64() - mean) / var.sqrt()));
         }
     }
 
@@ -0,0 +1,42 @@
+use lazy::Expr;
+use teapy_core::prelude::*;
+
+#[ext_trait]
+impl<'a> ExprDecimalExt for Expr<'a> {
+    /// Exact decimal division, the quotient has the given scale and is rounded
+    /// by `mode`. Integers are read as decimals of scale 0 and division by
+    /// zero gives null.
+    fn dec_div(&mut self, rhs: Expr<'a>, scale: u8, mode: RoundMode) -> &mut Self {
+        self.chain_f_ctx(move |(data, ctx)| {
+            let arr = &*data.view_arr(ctx.as_ref())?;
+            let rhs = &*rhs.view_arr(ctx.as_ref())?;
+            let lhs = as_decimal(arr)?;
+            let out = lhs.div(&as_decimal(rhs)?, scale, mode)?;
+            Ok((ArrOk::from(out).into(), ctx))
+        });
+        self
+    }
+
+    /// Round a decimal array to `scale` digits after the decimal point.
+    fn dec_round(&mut self, scale: u8, mode: RoundMode) -> &mut Self {
+        self.chain_f_ctx(move |(data, ctx)| {
+            let arr = data.view_arr(ctx.as_ref())?;
+            let a = as_decimal(&arr)?;
+            let out = if scale < a.scale {
+                a.rescale(scale, mode)?
+            } else {
+                a.into_owned()
+            };
+            Ok((ArrOk::from(out).into(), ctx))
+        });
+        self
+    }
+}
+
+fn as_decimal<'b>(arr: &'b ArrOk<'_>) -> TResult<DecArray<'b>> {
+    match arr {
+        ArrOk::Decimal(a) => Ok(a.view()),
+        arr if arr.is_int() => arr.deref().cast_decimal(19, 0),
+        arr => tbail!("Expect a decimal array, got dtype {}", arr.dtype_name()),
+    }
+}
@@ -32,7 +32,8 @@ impl<'a> ExprViewExt for Expr<'a> {
     fn reshape(&mut self, shape: Expr<'a>) -> &mut Self {
         self.chain_f_ctx(move |(data, ctx)| {
             let arr = data.into_arr(ctx.clone())?;
-            let shape = shape.view_arr(ctx.as_ref())?.deref().cast_usize();
+            let shape = shape.view_arr(ctx.as_ref())?;
+            let shape = shape.deref().cast_usize();
             let out: ArrOk<'a> = match_arrok!(arr; Dynamic(arr) => {
                 let ndim = shape.ndim();
                 if ndim == 0 {
@@ -131,7 +132,8 @@ impl<'a> ExprViewExt for Expr<'a> {
     fn permuted_axes(&mut self, axes: Expr<'a>) -> &mut Self {
         self.chain_f_ctx(move |(data, ctx)| {
             let arr = data.into_arr(ctx.clone())?;
-            let axes = axes.view_arr(ctx.as_ref())?.deref().cast_i32();
+            let axes = axes.view_arr(ctx.as_ref())?;
+            let axes = axes.deref().cast_i32();
             match_arrok!(arr; Dynamic(arr) => {
                 let axes = axes
                     .view()
@@ -187,7 +189,8 @@ impl<'a> ExprViewExt for Expr<'a> {
     fn broadcast(&mut self, shape: Expr<'a>) -> &mut Self {
         self.chain_f_ctx(move |(data, ctx)| {
             let arr = data.into_arr(ctx.clone())?;
-            let shape = shape.view_arr(ctx.as_ref())?.deref().cast_usize();
+            let shape = shape.view_arr(ctx.as_ref())?;
+            let shape = shape.deref().cast_usize();
             let out: ArrOk<'a> = match_arrok!(arr; Dynamic(arr) => {
                 let shape_view = shape.view();
                 let ndim = shape.ndim();
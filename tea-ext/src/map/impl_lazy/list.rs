@@ -0,0 +1,65 @@
+use lazy::Expr;
+use teapy_core::prelude::*;
+
+/// View the list array of the data, other dtypes are not lists.
+macro_rules! view_list {
+    ($arr: expr) => {
+        match $arr {
+            ArrOk::List(a) => a,
+            arr => tbail!("Expect a list array, got dtype {}", arr.dtype_name()),
+        }
+    };
+}
+
+#[ext_trait]
+impl<'a> ExprListExt for Expr<'a> {
+    /// The number of elements of each list, null lists give null.
+    fn list_len(&mut self) -> &mut Self {
+        self.chain_f_ctx(move |(data, ctx)| {
+            let arr = data.view_arr(ctx.as_ref())?;
+            let a = view_list!(&*arr);
+            let out: ArrOk<'a> = a.lens();
+            Ok((out.into(), ctx))
+        });
+        self
+    }
+
+    /// The `i`-th element of each list, lists that are null or too short give a null.
+    fn list_get(&mut self, i: i64) -> &mut Self {
+        self.chain_f_ctx(move |(data, ctx)| {
+            let arr = data.view_arr(ctx.as_ref())?;
+            let a = view_list!(&*arr);
+            Ok((a.get(i).into(), ctx))
+        });
+        self
+    }
+
+    fn list_sum(&mut self) -> &mut Self {
+        self.chain_f_ctx(move |(data, ctx)| {
+            let arr = data.view_arr(ctx.as_ref())?;
+            let a = view_list!(&*arr);
+            Ok((a.sum()?.into(), ctx))
+        });
+        self
+    }
+
+    fn list_mean(&mut self, min_periods: usize) -> &mut Self {
+        self.chain_f_ctx(move |(data, ctx)| {
+            let arr = data.view_arr(ctx.as_ref())?;
+            let a = view_list!(&*arr);
+            Ok((a.mean(min_periods)?.into(), ctx))
+        });
+        self
+    }
+
+    /// Flatten the lists into their elements, empty and null lists give no element.
+    fn explode(&mut self) -> &mut Self {
+        self.chain_f_ctx(move |(data, ctx)| {
+            let arr = data.view_arr(ctx.as_ref())?;
+            let a = view_list!(&*arr);
+            let out: ArrOk<'a> = a.explode().into_owned();
+            Ok((out.into(), ctx))
+        });
+        self
+    }
+}
@@ -1,14 +1,22 @@
 // mod auto_impl;
+mod decimal;
 mod impl_view;
+mod list;
 #[cfg(feature = "stat")]
 mod stat;
+mod string;
+mod structs;
 #[cfg(feature = "time")]
 mod time;
 
 // pub use auto_impl::{AutoExprInplaceExt, AutoExprMapExt};
+pub use decimal::ExprDecimalExt;
 pub use impl_view::ExprViewExt;
+pub use list::ExprListExt;
 #[cfg(feature = "stat")]
 pub use stat::ExprStatExt;
+pub use string::ExprStringExt;
+pub use structs::ExprStructExt;
 #[cfg(feature = "time")]
 pub use time::ExprTimeExt;
 
@@ -29,7 +37,7 @@ impl<'a> ExprInplaceExt for Expr<'a> {
             mask.cast_bool().eval_inplace(ctx.clone())?;
             value.eval_inplace(ctx.clone())?;
             let mask_arr = mask.view_arr(ctx.as_ref())?;
-            let mask_arr = match_arrok!(mask_arr; Bool(a) => { Ok(a) },).unwrap();
+            let mask_arr = match_arrok!(&*mask_arr; Bool(a) => { Ok(a) },).unwrap();
             let value_arr = value.into_arr(ctx.clone())?;
             let mut arr = data.into_arr(ctx.clone())?;
             match_arrok!(&mut arr; Dynamic(a) => {
@@ -47,7 +55,8 @@ impl<'a> ExprInplaceExt for Expr<'a> {
         self.chain_f_ctx(move |(data, ctx)| {
             let mut arr = data.into_arr(ctx.clone())?;
             let n = n.view_arr(ctx.as_ref())?.deref().cast_i32().into_owned().into_scalar()?;
-            let fill = fill.as_ref().map(|f| f.view_arr(ctx.as_ref()).unwrap().deref());
+            let fill = fill.as_ref().map(|f| f.view_arr(ctx.as_ref()).unwrap());
+            let fill = fill.as_deref().map(ArrOk::deref);
             use ArrOk::*;
             let arr = if matches!(&arr, I32(_) | I64(_)) {
                 let mut arr = arr.cast_f64();
@@ -55,7 +64,7 @@ impl<'a> ExprInplaceExt for Expr<'a> {
                 arr.into()
             } else {
                 match_arrok!(&mut arr; Cast(a) => {
-                    let f = fill.map(|f| match_arrok!(f; Cast(f) => {Ok(f.into_owned().into_scalar().unwrap().cast())},).unwrap());
+                    let f = fill.map(|f| match_arrok!(f; Cast(f) => {Ok(Cast::cast(f.into_owned().into_scalar().unwrap()))},).unwrap());
                     a.view_mut().shift(n, f, axis, par);
                     Ok(())
                 },).unwrap();
@@ -70,7 +79,8 @@ impl<'a> ExprInplaceExt for Expr<'a> {
         self.chain_f_ctx(move |(data, ctx)| {
             let mut arr = data.into_arr(ctx.clone())?;
             let n = n.view_arr(ctx.as_ref())?.deref().cast_i32().into_owned().into_scalar()?;
-            let fill = fill.as_ref().map(|f| f.view_arr(ctx.as_ref()).unwrap().deref());
+            let fill = fill.as_ref().map(|f| f.view_arr(ctx.as_ref()).unwrap());
+            let fill = fill.as_deref().map(ArrOk::deref);
             use ArrOk::*;
             let out: ArrOk<'a> = if matches!(&arr, I32(_) | I64(_)) {
                 let mut arr = arr.cast_f64();
@@ -78,7 +88,7 @@ impl<'a> ExprInplaceExt for Expr<'a> {
                 arr.into()
             } else {
                 match_arrok!(&mut arr; Cast(a) => {
-                    let f = fill.map(|f| match_arrok!(f; Cast(f) => {Ok(f.into_owned().into_scalar().unwrap().cast())},).unwrap());
+                    let f = fill.map(|f| match_arrok!(f; Cast(f) => {Ok(Cast::cast(f.into_owned().into_scalar().unwrap()))},).unwrap());
                     a.view_mut().shift(n, f, axis, par);
                     Ok(())
                 },).unwrap();
@@ -97,7 +107,7 @@ impl<'a> ExprInplaceExt for Expr<'a> {
             match_arrok!(&mut arr; Cast(arr) => {
                 let mut arr_view_mut = arr.view_mut();
                 let mut arr_mut = arr_view_mut.slice_mut(slc_info).wrap().into_dyn();
-                match_arrok!(value; Cast(v) => {
+                match_arrok!(&*value; Cast(v) => {
                     let v = v.deref().cast();
                     arr_mut.assign(&v.view());
                     Ok(())
@@ -163,19 +173,31 @@ impl<'a> ExprInplaceExt for Expr<'a> {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum DropNaMethod {
     Any,
     All,
 }
 
+impl std::str::FromStr for DropNaMethod {
+    type Err = TError;
+
+    fn from_str(s: &str) -> TResult<Self> {
+        match s.to_lowercase().as_str() {
+            "all" => Ok(DropNaMethod::All),
+            "any" => Ok(DropNaMethod::Any),
+            _ => tbail!("Not supported dropna method: {s}"),
+        }
+    }
+}
+
 #[ext_trait]
 impl<'a> ExprMapExt for Expr<'a> {
     fn is_in(&mut self, other: Expr<'a>) -> &mut Self {
         self.chain_f_ctx(move |(data, ctx)| {
             let arr = data.view_arr(ctx.as_ref())?;
             let other = other.view_arr(ctx.as_ref())?;
-            let out = match_arrok!(arr; Cast(a) => {
+            let out = match_arrok!(&*arr; Cast(a) => {
                 let other: ArbArray<_> = other.deref().cast();
                 let other_slc = other.view().to_dim1()?.to_slice().unwrap();
                 Ok(a.view().is_in(other_slc))
@@ -191,7 +213,7 @@ impl<'a> ExprMapExt for Expr<'a> {
         self.chain_f_ctx(|(data, ctx)| {
             let arr = data.view_arr(ctx.as_ref())?;
             let out: ArrOk<'a> =
-                match_arrok!(arr; Dynamic(a) => { Ok(a.view().to_owned().into()) },).unwrap();
+                match_arrok!(&*arr; Dynamic(a) => { Ok(a.view().to_owned().into()) },).unwrap();
             Ok((out.into(), ctx))
         });
         self
@@ -201,7 +223,7 @@ impl<'a> ExprMapExt for Expr<'a> {
         self.chain_f_ctx(|(data, ctx)| {
             let arr = data.view_arr(ctx.as_ref())?;
             let out: ArrOk<'a> =
-                match_arrok!(arr; (F32 | F64 | I32 | I64)(a) => { Ok(a.view().abs().into()) },)
+                match_arrok!(&*arr; (F32 | F64 | I32 | I64)(a) => { Ok(a.view().abs().into()) },)
                     .unwrap();
             Ok((out.into(), ctx))
         });
@@ -212,7 +234,7 @@ impl<'a> ExprMapExt for Expr<'a> {
         self.chain_f_ctx(|(data, ctx)| {
             let arr = data.view_arr(ctx.as_ref())?;
             let out: ArrOk<'a> =
-                match_arrok!(arr; (F32 | F64 | I32 | I64)(a) => { Ok(a.view().sign().into()) },)
+                match_arrok!(&*arr; (F32 | F64 | I32 | I64)(a) => { Ok(a.view().sign().into()) },)
                     .unwrap();
             Ok((out.into(), ctx))
         });
@@ -237,10 +259,11 @@ impl<'a> ExprMapExt for Expr<'a> {
     fn pow(&mut self, n: Expr<'a>, par: bool) -> &mut Self {
         self.chain_f_ctx(move |(data, ctx)| {
             let arr = data.view_arr(ctx.as_ref())?;
-            let n = n.view_arr(ctx.as_ref())?.deref();
+            let n = n.view_arr(ctx.as_ref())?;
+            let n = n.deref();
             let out: ArrOk<'a> = if arr.is_int() {
                 if n.is_float() {
-                    match_arrok!(arr; (I32 | I64)(a) => {
+                    match_arrok!(&*arr; (I32 | I64)(a) => {
                         Ok(a.view().pow(&n.cast_usize().view(), par).into())
                     },)
                     .unwrap()
@@ -258,7 +281,7 @@ impl<'a> ExprMapExt for Expr<'a> {
                     .powf(&n.cast_f64().view(), par)
                     .into()
             } else {
-                match_arrok!(arr; PureFloat(a) => {
+                match_arrok!(&*arr; PureFloat(a) => {
                     Ok(a.view().powi(&n.cast_i32().view(), par).into())
                 },)
                 .unwrap()
@@ -272,14 +295,15 @@ impl<'a> ExprMapExt for Expr<'a> {
     fn filter(&mut self, mask: Expr<'a>, axis: Expr<'a>, par: bool) -> &mut Self {
         self.chain_f_ctx(move |(data, ctx)| {
             let arr = data.view_arr(ctx.as_ref())?;
-            let mask = mask.view_arr(ctx.as_ref())?.deref().cast_bool();
+            let mask = mask.view_arr(ctx.as_ref())?;
+            let mask = mask.deref().cast_bool();
             let axis = axis
                 .view_arr(ctx.as_ref())?
                 .deref()
                 .cast_i32()
                 .into_owned()
                 .into_scalar()?;
-            let out: ArrOk<'a> = match_arrok!(arr; Dynamic(a) => {
+            let out: ArrOk<'a> = match_arrok!(&*arr; Dynamic(a) => {
                 Ok(a.view().filter(&mask.view().to_dim1()?, axis, par).into())
             },)
             .unwrap();
@@ -298,7 +322,7 @@ impl<'a> ExprMapExt for Expr<'a> {
                 .cast_i32()
                 .into_owned()
                 .into_scalar()?;
-            let out: ArrOk<'a> = match_arrok!(arr; Numeric(a) => {
+            let out: ArrOk<'a> = match_arrok!(&*arr; Numeric(a) => {
                 match ndim {
                     1 => Ok(a.view().to_dim1()?.dropna_1d().into_dyn().into()),
                     2 => {
@@ -328,14 +352,14 @@ impl<'a> ExprMapExt for Expr<'a> {
     fn select(&mut self, slc: Expr<'a>, axis: Expr<'a>, check: bool) -> &mut Self {
         self.chain_f_ctx(move |(data, ctx)| {
             let slc = slc.view_arr(ctx.as_ref())?;
-            let arr = data.view_arr(ctx.as_ref())?;
+            let arr = &*data.view_arr(ctx.as_ref())?;
             let axis = axis
                 .view_arr(ctx.as_ref())?
                 .deref()
                 .cast_i32()
                 .into_owned()
                 .into_scalar()?;
-            let out = arr.select(slc, axis, check)?;
+            let out = arr.select(&slc, axis, check)?;
             Ok((out.into(), ctx))
         });
         self
@@ -348,17 +372,17 @@ impl<'a> ExprMapExt for Expr<'a> {
             let value = value.clone();
             mask.cast_bool();
             let mask = mask.view_arr(ctx.as_ref())?;
-            let mask_view = match_arrok!(mask; Bool(m) => { Ok(m.view()) },).unwrap();
-            let value = value.view_arr(ctx.as_ref())?.deref();
+            let mask_view = match_arrok!(&*mask; Bool(m) => { Ok(m.view()) },).unwrap();
+            let value = value.view_arr(ctx.as_ref())?;
             let out: ArrOk<'a> = if arr.is_int() & value.is_float() {
                 match_arrok!(arr.deref().cast_float(); Cast(a) => {
-                    let value: ArbArray<_> = value.cast();
+                    let value: ArbArray<_> = value.deref().cast();
                     Ok(a.view().where_(&mask_view, &value.view(), par).into())
                 },)
                 .unwrap()
             } else {
-                match_arrok!(arr; Cast(a) => {
-                    let value: ArbArray<_> = value.cast();
+                match_arrok!(&*arr; Cast(a) => {
+                    let value: ArbArray<_> = value.deref().cast();
                     Ok(a.view().where_(&mask_view, &value.view(), par).into())
                 },)
                 .unwrap()
@@ -377,11 +401,11 @@ impl<'a> ExprMapExt for Expr<'a> {
                 .par_iter()
                 .map(|e| e.view_arr(ctx.as_ref()).unwrap())
                 .collect::<Vec<_>>();
-            let out: ArrOk<'a> = match_arrok!(arr; Cast(a) => {
+            let out: ArrOk<'a> = match_arrok!(&*arr; Cast(a) => {
                 let a = a.deref();
                 let a_view = a.view().no_dim0();
                 let axis = a_view.norm_axis(axis);
-                let other = other_ref.into_par_iter().map(|o| {
+                let other = other_ref.par_iter().map(|o| {
                     let o: ArbArray<_> = o.deref().cast();
                     o
                 }).collect::<Vec<_>>();
@@ -402,7 +426,7 @@ impl<'a> ExprMapExt for Expr<'a> {
                 .par_iter()
                 .map(|e| e.view_arr(ctx.as_ref()).unwrap())
                 .collect::<Vec<_>>();
-            let out: ArrOk<'a> = match_arrok!(arr; Cast(a) => {
+            let out: ArrOk<'a> = match_arrok!(&*arr; Cast(a) => {
                 let a = a.deref();
                 let a_view = a.view().no_dim0();
                 let axis = if axis < 0 {
@@ -410,7 +434,7 @@ impl<'a> ExprMapExt for Expr<'a> {
                 } else {
                     Axis(axis as usize)
                 };
-                let other = other_ref.into_par_iter().map(|o| {
+                let other = other_ref.par_iter().map(|o| {
                     let o: ArbArray<_> = o.deref().cast();
                     o
                 }).collect::<Vec<_>>();
@@ -430,6 +454,7 @@ impl<'a> ExprMapExt for Expr<'a> {
                 .par_iter()
                 .map(|e| e.view_arr(ctx.as_ref()).unwrap())
                 .collect::<Vec<_>>();
+            let by = by.iter().map(|a| &**a).collect::<Vec<_>>();
             let idx = ArrOk::get_sort_idx(&by, rev)?;
             Ok((Arr1::from_vec(idx).into_dyn().into(), ctx))
         });
@@ -0,0 +1,77 @@
+use super::super::{PadSide, StringExt};
+use lazy::Expr;
+use teapy_core::prelude::*;
+
+/// Apply `$f` to the string view of the data, other dtypes are cast to
+/// string first.
+macro_rules! str_map {
+    ($expr: expr, |$a: ident| $f: expr) => {{
+        $expr.cast_string().chain_f_ctx(move |(data, ctx)| {
+            let arr = data.view_arr(ctx.as_ref())?;
+            let out: ArrOk<'a> = match_arrok!(&*arr; String($a) => {
+                let $a = $a.view();
+                Ok($f.into())
+            },)
+            .unwrap();
+            Ok((out.into(), ctx))
+        });
+        $expr
+    }};
+}
+
+#[ext_trait]
+impl<'a> ExprStringExt for Expr<'a> {
+    fn str_contains(&mut self, pat: String, literal: bool) -> &mut Self {
+        str_map!(self, |a| a.str_contains(&pat, literal)?)
+    }
+
+    fn str_starts_with(&mut self, prefix: String) -> &mut Self {
+        str_map!(self, |a| a.str_starts_with(&prefix))
+    }
+
+    fn str_ends_with(&mut self, suffix: String) -> &mut Self {
+        str_map!(self, |a| a.str_ends_with(&suffix))
+    }
+
+    fn str_replace(
+        &mut self,
+        pat: String,
+        value: String,
+        literal: bool,
+        n: Option<usize>,
+    ) -> &mut Self {
+        str_map!(self, |a| a.str_replace(&pat, &value, literal, n)?)
+    }
+
+    fn str_extract(&mut self, pat: String, group: usize) -> &mut Self {
+        str_map!(self, |a| a.str_extract(&pat, group)?)
+    }
+
+    fn str_split(&mut self, sep: String) -> &mut Self {
+        str_map!(self, |a| a.str_split(&sep)?)
+    }
+
+    fn str_slice(&mut self, start: i64, length: Option<usize>) -> &mut Self {
+        str_map!(self, |a| a.str_slice(start, length))
+    }
+
+    fn str_to_upper(&mut self) -> &mut Self {
+        str_map!(self, |a| a.str_to_upper())
+    }
+
+    fn str_to_lower(&mut self) -> &mut Self {
+        str_map!(self, |a| a.str_to_lower())
+    }
+
+    fn str_strip(&mut self, chars: Option<String>) -> &mut Self {
+        str_map!(self, |a| a.str_strip(chars.as_deref()))
+    }
+
+    fn str_len(&mut self) -> &mut Self {
+        str_map!(self, |a| a.str_len())
+    }
+
+    fn str_pad(&mut self, width: usize, side: PadSide, fill: char) -> &mut Self {
+        str_map!(self, |a| a.str_pad(width, side, fill))
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(feature = "rolling")]
+use crate::AutoExprRegTs;
+use lazy::{Data, Expr};
+use teapy_core::prelude::*;
+
+#[ext_trait]
+impl<'a> ExprStructExt for Expr<'a> {
+    /// Pack the output and `others` into a struct array, each array is a field
+    /// named by `names`. The output can also be an array vector, e.g. the
+    /// output of `svd`, and each array of the vector is a field.
+    fn to_struct(&mut self, others: Vec<Expr<'a>>, names: Vec<String>) -> &mut Self {
+        self.chain_f_ctx(move |(data, ctx)| {
+            let mut fields: Vec<ArrOk<'a>> = if let Data::ArrVec(_) = &data {
+                data.view_arr_vec(ctx.as_ref())?
+                    .into_iter()
+                    .map(|a| a.view().into_owned())
+                    .collect()
+            } else {
+                vec![data.view_raw_arr(ctx.as_ref())?.view().into_owned()]
+            };
+            for e in &others {
+                fields.push(e.view_raw_arr(ctx.as_ref())?.view().into_owned());
+            }
+            let out = StructArray::new(names.clone().into(), fields)?;
+            Ok((ArrOk::from(out).into(), ctx))
+        });
+        self
+    }
+
+    /// The field `name` of a struct array.
+    fn struct_field(&mut self, name: String) -> &mut Self {
+        self.chain_f_ctx(move |(data, ctx)| {
+            let out = match &*data.view_arr(ctx.as_ref())? {
+                ArrOk::Struct(a) => a.field(&name)?.view().into_owned(),
+                arr => tbail!("Expect a struct array, got dtype {}", arr.dtype_name()),
+            };
+            Ok((out.into(), ctx))
+        });
+        self
+    }
+
+    /// The slope, the intercept and the mean of the residuals of the rolling
+    /// regression on the index, packed into a struct array.
+    #[cfg(feature = "rolling")]
+    fn ts_reg_params(
+        &mut self,
+        window: usize,
+        min_periods: Option<usize>,
+        axis: i32,
+        par: bool,
+    ) -> &mut Self {
+        let mut intercept = self.clone();
+        intercept.ts_reg_intercept(window, min_periods, axis, par);
+        let mut resid_mean = self.clone();
+        resid_mean.ts_reg_resid_mean(window, min_periods, axis, par);
+        self.ts_reg_slope(window, min_periods, axis, par).to_struct(
+            vec![intercept, resid_mean],
+            vec!["slope".into(), "intercept".into(), "resid_mean".into()],
+        )
+    }
+}
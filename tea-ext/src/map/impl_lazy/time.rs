@@ -1,4 +1,4 @@
-use super::super::{StringExt, TimeExt};
+use super::super::{DateExt, StringExt, TimeExt};
 use lazy::Expr;
 use teapy_core::prelude::*;
 
@@ -7,7 +7,7 @@ impl<'a> ExprTimeExt for Expr<'a> {
     fn strptime(&mut self, fmt: Option<String>) -> &mut Self {
         self.cast_string().chain_f_ctx(move |(data, ctx)| {
             let arr = data.view_arr(ctx.as_ref())?;
-            let out: ArrOk<'a> = match_arrok!(arr; String(a) => {
+            let out: ArrOk<'a> = match_arrok!(&*arr; String(a) => {
                 let out = a.view().strptime(fmt.as_deref());
                 Ok(out.into())
             },)
@@ -17,17 +17,102 @@ impl<'a> ExprTimeExt for Expr<'a> {
         self
     }
 
+    fn strptime_date(&mut self, fmt: Option<String>) -> &mut Self {
+        self.cast_string().chain_f_ctx(move |(data, ctx)| {
+            let arr = &*data.view_arr(ctx.as_ref())?;
+            let out: ArrOk<'a> = match_arrok!(arr; String(a) => {
+                let out = a.view().strptime_date(fmt.as_deref());
+                Ok(out.into())
+            },)
+            .unwrap();
+            Ok((out.into(), ctx))
+        });
+        self
+    }
+
+    #[teapy(type = "(Time | Date | DateTimeTz)", dtype = "String")]
     fn strftime(&mut self, fmt: Option<String>) -> &mut Self {
-        self.cast_datetime(None).chain_f_ctx(move |(data, ctx)| {
+        self.chain_f_ctx(move |(data, ctx)| {
+            let arr = &*data.view_arr(ctx.as_ref())?;
+            // dates are formatted without the time part and timezone-aware
+            // datetimes are formatted in their local time
+            let out: ArrOk<'a> = if let ArrOk::Date(a) = arr {
+                a.view().strftime(fmt.as_deref()).into()
+            } else if let ArrOk::DateTimeTz(a) = arr {
+                a.strftime(fmt.as_deref()).into()
+            } else {
+                match_arrok!(
+                    arr.deref().cast_datetime(None);
+                    Time(a) =>
+                    {
+                        let out = a.view().strftime(fmt.as_deref());
+                        Ok(out.into())
+                    },
+                )
+                .unwrap()
+            };
+            Ok((out.into(), ctx))
+        });
+        self
+    }
+
+    /// Convert to the time zone keeping the instants, naive datetimes are read as utc.
+    fn convert_tz(&mut self, tz: TimeZone) -> &mut Self {
+        self.chain_f_ctx(move |(data, ctx)| {
+            let arr = data.into_arr(ctx.clone())?;
+            Ok((ArrOk::from(arr.convert_tz(tz)).into(), ctx))
+        });
+        self
+    }
+
+    /// Set the time zone keeping the local wall times, the time zone is removed
+    /// if `tz` is `None`.
+    fn replace_tz(&mut self, tz: Option<TimeZone>) -> &mut Self {
+        self.chain_f_ctx(move |(data, ctx)| {
+            let arr = data.into_arr(ctx.clone())?;
+            Ok((arr.replace_tz(tz).into(), ctx))
+        });
+        self
+    }
+
+    /// Truncate the datetimes to the duration, timezone-aware datetimes are
+    /// truncated in their local time. The start of a bucket is shifted by `offset`
+    /// if given, e.g. `9h30m` for daily buckets starting at the session open.
+    fn duration_trunc(&mut self, duration: TimeDelta, offset: Option<TimeDelta>) -> &mut Self {
+        self.chain_f_ctx(move |(data, ctx)| {
+            let arr = &*data.view_arr(ctx.as_ref())?;
+            let out: ArrOk<'a> = if let ArrOk::DateTimeTz(a) = arr {
+                a.duration_trunc(duration, offset).into()
+            } else {
+                match_arrok!(
+                    arr.deref().cast_datetime(None);
+                    Time(a) => { Ok(a.view().duration_trunc(duration, offset).into()) },
+                )
+                .unwrap()
+            };
+            Ok((out.into(), ctx))
+        });
+        self
+    }
+
+    fn offset_business_days(&mut self, n: i32) -> &mut Self {
+        self.cast_date().chain_f_ctx(move |(data, ctx)| {
             let arr = data.view_arr(ctx.as_ref())?;
-            let out: ArrOk<'a> = match_arrok!(
-                arr;
-                Time(a) =>
-                {
-                    let out = a.view().strftime(fmt.as_deref());
-                    Ok(out.into())
-                },
-            )
+            let out: ArrOk<'a> = match_arrok!(&*arr; Date(a) => {
+                Ok(a.view().offset_business_days(n).into())
+            },)
+            .unwrap();
+            Ok((out.into(), ctx))
+        });
+        self
+    }
+
+    fn is_business_day(&mut self) -> &mut Self {
+        self.cast_date().chain_f_ctx(move |(data, ctx)| {
+            let arr = data.view_arr(ctx.as_ref())?;
+            let out: ArrOk<'a> = match_arrok!(&*arr; Date(a) => {
+                Ok(a.view().is_business_day().into())
+            },)
             .unwrap();
             Ok((out.into(), ctx))
         });
@@ -0,0 +1,27 @@
+use teapy_core::match_masked;
+use teapy_core::prelude::*;
+
+/// The map functions of `MapExt` on masked arrays, `None` is returned for the
+/// dtypes that are not supported.
+#[ext_trait]
+impl MaskedMapExt for MaskedArray<'_> {
+    #[allow(unreachable_patterns)]
+    fn is_nan<'b>(&self) -> TResult<Option<ArrOk<'b>>> {
+        match_masked!(self, Ok(None);
+            (PureNumeric | Bool | U8 | Time | #[cfg(feature="time")] Date)(v) => {
+                let out = v.titer().map(|v| v.is_none()).collect_trusted_to_vec();
+                Ok(Some(Arr1::from_vec(out).into_dyn().into()))
+            },
+        )
+    }
+
+    #[allow(unreachable_patterns)]
+    fn not_nan<'b>(&self) -> TResult<Option<ArrOk<'b>>> {
+        match_masked!(self, Ok(None);
+            (PureNumeric | Bool | U8 | Time | #[cfg(feature="time")] Date)(v) => {
+                let out = v.titer().map(|v| v.not_none()).collect_trusted_to_vec();
+                Ok(Some(Arr1::from_vec(out).into_dyn().into()))
+            },
+        )
+    }
+}
@@ -1,6 +1,69 @@
 use ndarray::{Data, Dimension, Zip};
+use regex::Regex;
+use std::str::FromStr;
 use teapy_core::prelude::*;
 
+/// The side to pad a string on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PadSide {
+    /// Pad on the left, i.e. right-justify the string.
+    #[default]
+    Left,
+    Right,
+    Both,
+}
+
+impl FromStr for PadSide {
+    type Err = TError;
+
+    fn from_str(s: &str) -> TResult<Self> {
+        match s.to_lowercase().as_str() {
+            "left" => Ok(PadSide::Left),
+            "right" => Ok(PadSide::Right),
+            "both" => Ok(PadSide::Both),
+            _ => tbail!(ParseError: "Unknown pad side: {}, expect left, right or both", s),
+        }
+    }
+}
+
+#[inline]
+fn compile_regex(pat: &str) -> TResult<Regex> {
+    Regex::new(pat).map_err(|e| terr!("Invalid regex {}: {}", pat, e))
+}
+
+/// The chars of `s` from `start` with at most `length` chars, a negative
+/// `start` counts from the end.
+fn slice_chars(s: &str, start: i64, length: Option<usize>) -> String {
+    let start = if start < 0 {
+        let n = s.chars().count();
+        n.saturating_sub(start.unsigned_abs() as usize)
+    } else {
+        start as usize
+    };
+    let chars = s.chars().skip(start);
+    match length {
+        Some(length) => chars.take(length).collect(),
+        None => chars.collect(),
+    }
+}
+
+fn pad_str(s: &str, width: usize, side: PadSide, fill: char) -> String {
+    let n = s.chars().count();
+    if n >= width {
+        return s.to_owned();
+    }
+    let (left, right) = match side {
+        PadSide::Left => (width - n, 0),
+        PadSide::Right => (0, width - n),
+        PadSide::Both => ((width - n) / 2, width - n - (width - n) / 2),
+    };
+    let mut out = String::with_capacity(s.len() + (left + right) * fill.len_utf8());
+    out.extend(std::iter::repeat_n(fill, left));
+    out.push_str(s);
+    out.extend(std::iter::repeat_n(fill, right));
+    out
+}
+
 #[ext_trait]
 impl<S, D> StringExt for ArrBase<S, D>
 where
@@ -24,8 +87,145 @@ where
             .wrap()
     }
 
+    /// Whether each string contains the pattern, the pattern is a regex
+    /// unless `literal` is true.
+    fn str_contains(&self, pat: &str, literal: bool) -> TResult<Arr<bool, D>> {
+        if literal {
+            return Ok(Zip::from(&self.0)
+                .par_map_collect(|s| s.contains(pat))
+                .wrap());
+        }
+        let re = compile_regex(pat)?;
+        Ok(Zip::from(&self.0)
+            .par_map_collect(|s| re.is_match(s))
+            .wrap())
+    }
+
+    fn str_starts_with(&self, prefix: &str) -> Arr<bool, D> {
+        Zip::from(&self.0)
+            .par_map_collect(|s| s.starts_with(prefix))
+            .wrap()
+    }
+
+    fn str_ends_with(&self, suffix: &str) -> Arr<bool, D> {
+        Zip::from(&self.0)
+            .par_map_collect(|s| s.ends_with(suffix))
+            .wrap()
+    }
+
+    /// Replace the first `n` matches of the pattern by `value`, all the
+    /// matches are replaced if `n` is `None` and none if `n` is 0. `$1` in
+    /// `value` refers to a capture group unless `literal` is true.
+    fn str_replace(
+        &self,
+        pat: &str,
+        value: &str,
+        literal: bool,
+        n: Option<usize>,
+    ) -> TResult<Arr<String, D>> {
+        if n == Some(0) {
+            return Ok(self.0.to_owned().wrap());
+        }
+        if literal {
+            return Ok(Zip::from(&self.0)
+                .par_map_collect(|s| match n {
+                    Some(n) => s.replacen(pat, value, n),
+                    None => s.replace(pat, value),
+                })
+                .wrap());
+        }
+        let re = compile_regex(pat)?;
+        Ok(Zip::from(&self.0)
+            // the regex replaces all the matches if the limit is 0
+            .par_map_collect(|s| re.replacen(s, n.unwrap_or(0), value).into_owned())
+            .wrap())
+    }
+
+    /// The `group`-th capture group of the first match of the regex, the
+    /// whole match is group 0. An empty string is returned when nothing matches.
+    fn str_extract(&self, pat: &str, group: usize) -> TResult<Arr<String, D>> {
+        let re = compile_regex(pat)?;
+        if group >= re.captures_len() {
+            tbail!(
+                "The regex {} has only {} groups, got group {}",
+                pat,
+                re.captures_len() - 1,
+                group
+            )
+        }
+        Ok(Zip::from(&self.0)
+            .par_map_collect(|s| {
+                re.captures(s)
+                    .and_then(|c| c.get(group))
+                    .map_or_else(String::new, |m| m.as_str().to_owned())
+            })
+            .wrap())
+    }
+
+    /// Split each string by `sep` into a list array, the array should be 1-d.
+    fn str_split<'b>(&self, sep: &str) -> TResult<ListArray<'b>> {
+        let arr = self.view().to_dim1()?;
+        let mut offsets = Vec::with_capacity(arr.len() + 1);
+        offsets.push(0);
+        let mut values = Vec::new();
+        for s in arr.0.iter() {
+            values.extend(s.split(sep).map(str::to_owned));
+            offsets.push(values.len());
+        }
+        ListArray::new(offsets.into(), Arr1::from_vec(values).into_dyn().into())
+    }
+
+    /// The substring of `length` chars from the `start`-th char, a negative
+    /// `start` counts from the end.
+    fn str_slice(&self, start: i64, length: Option<usize>) -> Arr<String, D> {
+        Zip::from(&self.0)
+            .par_map_collect(|s| slice_chars(s, start, length))
+            .wrap()
+    }
+
+    fn str_to_upper(&self) -> Arr<String, D> {
+        Zip::from(&self.0)
+            .par_map_collect(|s| s.to_uppercase())
+            .wrap()
+    }
+
+    fn str_to_lower(&self) -> Arr<String, D> {
+        Zip::from(&self.0)
+            .par_map_collect(|s| s.to_lowercase())
+            .wrap()
+    }
+
+    /// Strip the whitespaces, or the chars in `chars`, on both sides.
+    fn str_strip(&self, chars: Option<&str>) -> Arr<String, D> {
+        Zip::from(&self.0)
+            .par_map_collect(|s| match chars {
+                Some(chars) => s.trim_matches(|c| chars.contains(c)).to_owned(),
+                None => s.trim().to_owned(),
+            })
+            .wrap()
+    }
+
+    /// The number of chars of each string.
+    fn str_len(&self) -> Arr<usize, D> {
+        Zip::from(&self.0)
+            .par_map_collect(|s| s.chars().count())
+            .wrap()
+    }
+
+    /// Pad the strings shorter than `width` chars with `fill`.
+    fn str_pad(&self, width: usize, side: PadSide, fill: char) -> Arr<String, D> {
+        Zip::from(&self.0)
+            .par_map_collect(|s| pad_str(s, width, side, fill))
+            .wrap()
+    }
+
     #[cfg(feature = "time")]
     fn strptime(&self, fmt: Option<&str>) -> Arr<DateTime, D> {
         self.map(|s| DateTime::parse(s, fmt).unwrap_or_default())
     }
+
+    #[cfg(feature = "time")]
+    fn strptime_date(&self, fmt: Option<&str>) -> Arr<Date, D> {
+        self.map(|s| Date::parse(s, fmt).unwrap_or_default())
+    }
 }
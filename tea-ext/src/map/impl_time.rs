@@ -16,6 +16,19 @@ where
         self.map(|dt| dt.strftime(fmt))
     }
 
+    /// Truncate the datetimes, the start of a bucket is shifted by `offset` if given.
+    #[inline]
+    fn duration_trunc(&self, duration: TimeDelta, offset: Option<TimeDelta>) -> Arr<DateTime<U>, D>
+    where
+        DateTime<U>: TryInto<CrDateTime<Utc>> + From<CrDateTime<Utc>>,
+    {
+        if let Some(offset) = offset {
+            self.map(|dt| (*dt - offset).duration_trunc(duration) + offset)
+        } else {
+            self.map(|dt| dt.duration_trunc(duration))
+        }
+    }
+
     // fn sub_datetime<S2>(&self, other: &ArrBase<S2, D>, par: bool) -> Arr<TimeDelta, D>
     // where
     //     S2: Data<Elem = DateTime>,
@@ -33,3 +46,25 @@ where
     //     }
     // }
 }
+
+#[ext_trait]
+impl<S, D> DateExt for ArrBase<S, D>
+where
+    S: Data<Elem = Date>,
+    D: Dimension,
+{
+    #[inline]
+    fn strftime(&self, fmt: Option<&str>) -> Arr<String, D> {
+        self.map(|d| d.strftime(fmt))
+    }
+
+    #[inline]
+    fn offset_business_days(&self, n: i32) -> Arr<Date, D> {
+        self.map(|d| d.offset_business_days(n))
+    }
+
+    #[inline]
+    fn is_business_day(&self) -> Arr<bool, D> {
+        self.map(|d| d.is_business_day())
+    }
+}
@@ -1,6 +1,7 @@
 mod impl_1d;
 mod impl_arrok;
 mod impl_inplace;
+mod impl_masked;
 mod impl_string;
 
 #[cfg(feature = "lazy")]
@@ -11,12 +12,13 @@ mod impl_time;
 pub use impl_1d::MapExt1d;
 pub use impl_arrok::ArrOkExt;
 pub use impl_inplace::*;
-pub use impl_string::StringExt;
+pub use impl_masked::MaskedMapExt;
+pub use impl_string::{PadSide, StringExt};
 
 #[cfg(feature = "lazy")]
 pub use impl_lazy::*;
 #[cfg(feature = "time")]
-pub use impl_time::TimeExt;
+pub use impl_time::{DateExt, TimeExt};
 
 use ndarray::{Data, DataMut, Dimension, Ix1, ShapeBuilder, Zip};
 use std::{fmt::Debug, mem::MaybeUninit};
@@ -34,6 +36,7 @@ use lazy::Expr;
 
 #[ext_trait(lazy = "view")]
 impl<T, S: Data<Elem = T>, D: Dimension> MapExt for ArrBase<S, D> {
+    #[teapy(lazy = "masked_view")]
     fn is_nan(&self) -> ArrD<bool>
     where
         T: IsNone,
@@ -41,6 +44,7 @@ impl<T, S: Data<Elem = T>, D: Dimension> MapExt for ArrBase<S, D> {
         self.map(|v| v.is_none()).into_dyn()
     }
 
+    #[teapy(lazy = "masked_view")]
     fn not_nan(&self) -> ArrD<bool>
     where
         T: IsNone,
@@ -9,11 +9,25 @@ use ndarray::{s, Axis};
 
 #[allow(dead_code)]
 #[cfg(feature = "time")]
+#[derive(Debug)]
 pub enum RollingTimeStartBy {
     Full,
     DurationStart,
 }
 
+#[cfg(feature = "time")]
+impl std::str::FromStr for RollingTimeStartBy {
+    type Err = TError;
+
+    fn from_str(s: &str) -> TResult<Self> {
+        match s.to_lowercase().as_str() {
+            "full" => Ok(RollingTimeStartBy::Full),
+            "duration_start" | "durationstart" | "ds" => Ok(RollingTimeStartBy::DurationStart),
+            _ => tbail!("Not supported rolling by time start_by method: {s}"),
+        }
+    }
+}
+
 #[ext_trait]
 impl<'a> RollingExt for Expr<'a> {
     #[cfg(feature = "concat")]
@@ -31,13 +45,15 @@ impl<'a> RollingExt for Expr<'a> {
         use tea_lazy::DataDict;
         let name = self.name().unwrap();
         self.chain_f_ctx(move |(data, ctx)| {
-            let arr = data.view_arr(ctx.as_ref())?.deref();
+            let arr = data.view_arr(ctx.as_ref())?;
+            let arr = arr.deref();
             let others_ref = others
                 .par_iter()
-                .map(|e| e.view_arr(ctx.as_ref()).unwrap().deref())
+                .map(|e| e.view_arr(ctx.as_ref()).unwrap())
                 .collect::<Vec<_>>();
             let others_name = others.iter().map(|e| e.name().unwrap()).collect_trusted();
-            let roll_start = roll_start.view_arr(ctx.as_ref())?.deref().cast_usize();
+            let roll_start = roll_start.view_arr(ctx.as_ref())?;
+            let roll_start = ArrOk::deref(&roll_start).cast_usize();
             let roll_start_arr = roll_start.view().to_dim1()?;
             let len = arr.len();
             if len != roll_start_arr.len() {
@@ -165,7 +181,8 @@ impl<'a> RollingExt for Expr<'a> {
     ) -> &mut Self {
         let duration: TimeDelta = duration.into();
         self.chain_f_ctx(move |(arr, ctx)| {
-            let arr = arr.view_arr(ctx.as_ref())?.deref().cast_datetime(None);
+            let arr = arr.view_arr(ctx.as_ref())?;
+            let arr = arr.deref().cast_datetime(None);
             match_arrok!(arr; Time(arr) => {
                 let view = arr.view().to_dim1()?;
                 if view.is_empty() {
@@ -226,7 +243,8 @@ impl<'a> RollingExt for Expr<'a> {
         use crate::map::MapExt1d;
         let duration: TimeDelta = duration.into();
         self.chain_f_ctx(move |(arr, ctx)| {
-            let arr = arr.view_arr(ctx.as_ref())?.deref().cast_datetime(None);
+            let arr = arr.view_arr(ctx.as_ref())?;
+            let arr = arr.deref().cast_datetime(None);
             match_arrok!(arr; Time(arr) => {
                 let view = arr.view().to_dim1()?;
             if view.is_empty() {
@@ -277,7 +295,8 @@ impl<'a> RollingExt for Expr<'a> {
         let offset: TimeDelta = offset.into();
         assert!(window >= offset);
         self.chain_f_ctx(move |(data, ctx)| {
-            let data = data.view_arr(ctx.as_ref())?.deref().cast_datetime(None);
+            let data = data.view_arr(ctx.as_ref())?;
+            let data = data.deref().cast_datetime(None);
             match_arrok!(data; Time(data) => {
                 let arr = data.view().to_dim1()?;
                 if arr.is_empty() {
@@ -8,13 +8,14 @@ pub use impl_lazy::*;
 use lazy::Expr;
 use ndarray::{Array1, Data, DataMut, DimMax, Dimension, Ix1, ShapeBuilder};
 use std::mem::MaybeUninit;
+use teapy_core::match_masked;
 use teapy_core::prelude::*;
 
 macro_rules! auto_define_rolling_funcs {
-    ($feature: ident:
+    ($feature: ident, $masked_feature: ident:
         $($func: ident ($($param: ident: $ty: ty),*) -> $out: ty {$tv_func: ident}),* $(,)?
     ) => {
-        #[arr_map_ext(lazy = "view", type = "Numeric")]
+        #[arr_map_ext(lazy = "masked_view", type = "Numeric")]
         impl<T: IsNone + Send + Sync, S: Data<Elem = T>, D: Dimension> $feature for ArrBase<S, D>
         {
             $(#[inline]
@@ -34,6 +35,26 @@ macro_rules! auto_define_rolling_funcs {
                     .$tv_func::<Array1<$out>, _>($($param,)* Some(out.0.view_mut()));
             })*
         }
+
+        /// The rolling functions on masked arrays, `None` is returned for the
+        /// dtypes that are not supported. Masked arrays are 1-d, so `axis` and
+        /// `par` are not used.
+        #[ext_trait]
+        impl $masked_feature for MaskedArray<'_> {
+            $(fn $func<'b>(
+                &self,
+                $( $param: $ty, )*
+                _axis: i32,
+                _par: bool,
+            ) -> TResult<Option<ArrOk<'b>>> {
+                match_masked!(self, Ok(None);
+                    PureNumeric(v) => {
+                        let out = v.$tv_func::<Vec<$out>, _>($($param,)* None).unwrap();
+                        Ok(Some(Arr1::from_vec(out).into_dyn().into()))
+                    },
+                )
+            })*
+        }
     };
 }
 
@@ -74,7 +95,7 @@ macro_rules! auto_define_rolling2_funcs {
 }
 
 auto_define_rolling_funcs!(
-    FeatureTs:
+    FeatureTs, MaskedFeatureTs:
     ts_sum(window: usize, min_periods: Option<usize>) -> f64 {ts_vsum_to},
     ts_mean(window: usize, min_periods: Option<usize>) -> f64 {ts_vmean_to},
     ts_ewm(window: usize, min_periods: Option<usize>) -> f64 {ts_vewm_to},
@@ -86,7 +107,7 @@ auto_define_rolling_funcs!(
 );
 
 auto_define_rolling_funcs!(
-    CmpTs:
+    CmpTs, MaskedCmpTs:
     ts_min(window: usize, min_periods: Option<usize>) -> f64 {ts_vmin_to},
     ts_max(window: usize, min_periods: Option<usize>) -> f64 {ts_vmax_to},
     ts_argmin(window: usize, min_periods: Option<usize>) -> f64 {ts_vargmin_to},
@@ -95,13 +116,13 @@ auto_define_rolling_funcs!(
 );
 
 auto_define_rolling_funcs!(
-    NormTs:
+    NormTs, MaskedNormTs:
     ts_zscore(window: usize, min_periods: Option<usize>) -> f64 {ts_vzscore_to},
     ts_minmaxnorm(window: usize, min_periods: Option<usize>) -> f64 {ts_vminmaxnorm_to}
 );
 
 auto_define_rolling_funcs!(
-    RegTs:
+    RegTs, MaskedRegTs:
     ts_reg(window: usize, min_periods: Option<usize>) -> f64 {ts_vreg_to},
     ts_tsf(window: usize, min_periods: Option<usize>) -> f64 {ts_vtsf_to},
     ts_reg_slope(window: usize, min_periods: Option<usize>) -> f64 {ts_vreg_slope_to},
@@ -19,16 +19,14 @@ fn produce_data() -> (ArrOk<'static>, ArrOk<'static>) {
     let sy2 = vec![1, 2, 3, 4, 5];
     let len2 = sy2.len();
     let data1: ArrD<_> = Arr1::from_vec(
-        std::iter::repeat(sy1)
-            .take(length / len1)
+        std::iter::repeat_n(sy1, length / len1)
             .flatten()
             .collect::<Vec<_>>(),
     )
     .into_dyn();
     let arr1: ArrOk = data1.into();
     let data2: ArrD<_> = Arr1::from_vec(
-        std::iter::repeat(sy2)
-            .take(length / len2)
+        std::iter::repeat_n(sy2, length / len2)
             .flatten()
             .collect::<Vec<_>>(),
     )
@@ -11,17 +11,35 @@ use teapy_core::utils::CollectTrustedToVec;
 #[ext_trait]
 impl<'a> GroupbyAggExt for Expr<'a> {
     /// This func should return an array indicates the start of each group
-    /// and a label array indicates the label of each group
+    /// and a label array indicates the label of each group.
+    ///
+    /// Timezone-aware datetimes are grouped by their local time and the start of
+    /// each group is shifted by `offset` if given, e.g. a session open.
     #[cfg(feature = "time")]
-    pub fn get_group_by_time_idx<TD>(&mut self, duration: TD, closed: String) -> &mut Self
+    pub fn get_group_by_time_idx<TD>(
+        &mut self,
+        duration: TD,
+        closed: String,
+        offset: Option<TimeDelta>,
+    ) -> &mut Self
     where
         TD: Into<TimeDelta>,
     {
         let duration: TimeDelta = duration.into();
         self.chain_f_ctx(move |(data, ctx)| {
             let closed = closed.clone();
-            let arr = data.view_arr(ctx.as_ref())?.deref().cast_datetime(None);
+            let arr = &*data.view_arr(ctx.as_ref())?;
+            let tz = if let ArrOk::DateTimeTz(a) = arr {
+                Some(a.tz)
+            } else {
+                None
+            };
+            let arr = arr.deref().cast_datetime(None);
             match_arrok!(arr; Time(arr) => {
+                let trunc = |t: DateTime<_>| match offset {
+                    Some(offset) => (t - offset).duration_trunc(duration) + offset,
+                    None => t.duration_trunc(duration),
+                };
                 let ts = arr.view().to_dim1()?;
                 if ts.is_empty() {
                     let label = Arr1::from_vec(Vec::<DateTime>::with_capacity(0))
@@ -37,7 +55,7 @@ impl<'a> GroupbyAggExt for Expr<'a> {
                 let mut start_vec = vec![];
                 match closed.to_lowercase().as_str() {
                     "left" => {
-                        let mut start = ts.first_1d().duration_trunc(duration);
+                        let mut start = trunc(ts.first_1d());
                         label.push(start);
                         start_vec.push(0);
                         for i in 0..ts.len() {
@@ -46,13 +64,13 @@ impl<'a> GroupbyAggExt for Expr<'a> {
                                 continue;
                             } else {
                                 start_vec.push(i);
-                                start = t.duration_trunc(duration);
+                                start = trunc(t);
                                 label.push(start);
                             }
                         }
                     }
                     "right" => {
-                        let mut start = ts.first_1d().duration_trunc(duration);
+                        let mut start = trunc(ts.first_1d());
                         if start == *ts.get(0).unwrap() {
                             start = start - duration;
                         }
@@ -64,7 +82,7 @@ impl<'a> GroupbyAggExt for Expr<'a> {
                                 continue;
                             } else {
                                 start_vec.push(i);
-                                start = t.duration_trunc(duration);
+                                start = trunc(t);
                                 if start == t {
                                     start = start - duration;
                                 }
@@ -76,6 +94,11 @@ impl<'a> GroupbyAggExt for Expr<'a> {
                 }
                 start_vec.push(ts.len()); // the end of the array, this element is not the start of the group
                 let label: ArrOk<'a> = Arr1::from_vec(label).into_dyn().into();
+                // the labels are local times, attach the time zone back
+                let label = match tz {
+                    Some(tz) => label.replace_tz(Some(tz)),
+                    None => label,
+                };
                 let start_vec: ArrOk<'a> = Arr1::from_vec(start_vec).into_dyn().into();
                 Ok((Data::ArrVec(vec![label, start_vec]), ctx))
             },)
@@ -92,17 +115,18 @@ impl<'a> GroupbyAggExt for Expr<'a> {
     ) -> &mut Self {
         let name = self.name().unwrap();
         self.chain_f_ctx(move |(data, ctx)| {
-            let arr = data.view_arr(ctx.as_ref())?.deref();
+            let arr = &*data.view_arr(ctx.as_ref())?;
             let others_ref = others
                 .iter()
-                .map(|e| e.view_arr(ctx.as_ref()).unwrap().deref())
+                .map(|e| e.view_arr(ctx.as_ref()).unwrap())
                 .collect_trusted();
             let others_name = others.iter().map(|e| e.name().unwrap()).collect_trusted();
             let group_start = if let Ok(mut start_idx) = start_idx.view_arr_vec(ctx.as_ref()) {
-                start_idx.pop().unwrap().deref().cast_usize()
+                start_idx.pop().unwrap()
             } else {
-                start_idx.view_arr(ctx.as_ref())?.deref().cast_usize()
+                start_idx.view_arr(ctx.as_ref())?
             };
+            let group_start = ArrOk::deref(&group_start).cast_usize();
             let group_start_view = group_start.view().to_dim1()?;
             let columns = std::iter::once(name.clone())
                 .chain(others_name)
@@ -16,7 +16,10 @@ impl<'a> ExprGroupByExt for Expr<'a> {
                 .par_iter()
                 .map(|e| e.view_arr(ctx.as_ref()).unwrap())
                 .collect::<Vec<_>>();
-            let keys = std::iter::once(arr).chain(others_ref).collect::<Vec<_>>();
+            let keys = std::iter::once(&arr)
+                .chain(&others_ref)
+                .map(|a| &**a)
+                .collect::<Vec<_>>();
             let group_idx = if par {
                 // groupby_par(&keys, sort)
                 unimplemented!()
@@ -39,13 +42,14 @@ impl<'a> ExprGroupByExt for Expr<'a> {
     ) -> &mut Self {
         let name = self.name().unwrap();
         self.chain_f_ctx(move |(data, ctx)| {
-            let arr = data.view_arr(ctx.as_ref())?.deref();
+            let arr = &*data.view_arr(ctx.as_ref())?;
             let others_ref = others
                 .par_iter()
-                .map(|e| e.view_arr(ctx.as_ref()).unwrap().deref())
+                .map(|e| e.view_arr(ctx.as_ref()).unwrap())
                 .collect::<Vec<_>>();
             let others_name = others.iter().map(|e| e.name().unwrap()).collect_trusted();
-            let idxs = idxs.view_arr(ctx.as_ref())?.deref().cast_vecusize();
+            let idxs = idxs.view_arr(ctx.as_ref())?;
+            let idxs = idxs.deref().cast_vecusize();
             let idxs_arr = idxs.view().to_dim1()?;
             let columns = std::iter::once(name.clone())
                 .chain(others_name)
@@ -68,7 +72,7 @@ impl<'a> ExprGroupByExt for Expr<'a> {
                         } else {
                             std::iter::once(arr.select_unchecked(Axis(0), idx).into_dyn().into())
                                 .chain(others_ref.iter().map(|arr| {
-                                    match_arrok!(arr; Dynamic(o) => {
+                                    match_arrok!(&**arr; Dynamic(o) => {
                                         let arr: ArrOk = o
                                             .view()
                                             .to_dim1()
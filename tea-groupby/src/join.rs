@@ -7,7 +7,10 @@ use tea_hash::{TpHash, TpHashMap, BUILD_HASHER};
 use teapy_core::prelude::*;
 use teapy_core::utils::CollectTrustedToVec;
 
+use std::borrow::Cow;
 use std::collections::hash_map::Entry;
+use std::iter::zip;
+use std::sync::Arc;
 
 use tea_ext::ArrOkExt;
 use tea_lazy::{Context, Data, Expr};
@@ -19,12 +22,13 @@ pub enum JoinType {
     Outer,
 }
 
+#[allow(clippy::type_complexity)]
 fn collect_left_right_keys<'a, 'r>(
     data: &'r Data<'a>,
     ctx: Option<&'r Context<'a>>,
     left_other: &'r Option<Vec<Expr<'a>>>,
     right: &'r Vec<Expr<'a>>,
-) -> TResult<(Vec<&'r ArrOk<'a>>, Vec<&'r ArrOk<'a>>)> {
+) -> TResult<(Vec<Cow<'r, ArrOk<'a>>>, Vec<Cow<'r, ArrOk<'a>>>)> {
     let left_len = left_other.as_ref().map(|a| a.len()).unwrap_or(0);
     let all_keys = if let Some(left_other) = left_other.as_ref() {
         left_other
@@ -41,11 +45,10 @@ fn collect_left_right_keys<'a, 'r>(
     };
 
     let arr = data.view_arr(ctx)?;
-    let left_keys = std::iter::once(&arr)
-        .chain(all_keys.iter().take(left_len))
-        .cloned()
+    let mut right_keys = all_keys;
+    let left_keys = std::iter::once(arr)
+        .chain(right_keys.drain(..left_len))
         .collect::<Vec<_>>();
-    let right_keys = all_keys.into_iter().skip(left_len).collect::<Vec<_>>();
     Ok((left_keys, right_keys))
 }
 
@@ -59,6 +62,8 @@ impl<'a> ExprJoinExt for Expr<'a> {
         self.chain_f_ctx(move |(data, ctx)| {
             let (left_keys, right_keys) =
                 collect_left_right_keys(&data, ctx.as_ref(), &left_other, &right)?;
+            let left_keys = left_keys.iter().map(|a| &**a).collect::<Vec<_>>();
+            let right_keys = right_keys.iter().map(|a| &**a).collect::<Vec<_>>();
             let idx = join_left(&left_keys, &right_keys);
             Ok((Arr1::from_vec(idx).into_dyn().into(), ctx))
         });
@@ -76,6 +81,8 @@ impl<'a> ExprJoinExt for Expr<'a> {
         self.chain_f_ctx(move |(data, ctx)| {
             let (left_keys, right_keys) =
                 collect_left_right_keys(&data, ctx.as_ref(), &left_other, &right)?;
+            let left_keys = left_keys.iter().map(|a| &**a).collect::<Vec<_>>();
+            let right_keys = right_keys.iter().map(|a| &**a).collect::<Vec<_>>();
             let key_len = left_keys.len();
             let (mut outer_keys, left_idx, right_idx) = join_outer(&left_keys, &right_keys);
             let output = if sort {
@@ -111,6 +118,55 @@ impl<'a> ExprJoinExt for Expr<'a> {
     }
 }
 
+/// Categorical keys are joined on their codes, the right keys are recoded with
+/// the categories of the left keys. A categorical key joined with a key of
+/// another dtype is decoded to string, timezone-aware keys are joined on their
+/// instants and compact integer keys are widened.
+///
+/// Return the keys and the merged categories of each categorical key.
+#[allow(clippy::type_complexity)]
+fn prepare_join_keys<'r>(
+    left_keys: &[&'r ArrOk<'_>],
+    right_keys: &[&'r ArrOk<'_>],
+) -> (
+    Vec<ArrOk<'r>>,
+    Vec<ArrOk<'r>>,
+    Vec<Option<Arc<Vec<String>>>>,
+) {
+    let mut categories = Vec::with_capacity(left_keys.len());
+    let (left, right) = zip(left_keys, right_keys)
+        .map(|(lk, rk)| match (lk, rk) {
+            (ArrOk::Categorical(la), ArrOk::Categorical(ra)) => {
+                let ra = la.recode(ra);
+                categories.push(Some(ra.categories.clone()));
+                (la.codes_usize().into(), ra.codes_usize().into())
+            }
+            (ArrOk::Categorical(la), rk) => {
+                categories.push(None);
+                (ArrOk::from(la.decode()), rk.view())
+            }
+            (lk, ArrOk::Categorical(ra)) => {
+                categories.push(None);
+                (lk.view(), ArrOk::from(ra.decode()))
+            }
+            (lk, rk) => {
+                categories.push(None);
+                (join_key(lk), join_key(rk))
+            }
+        })
+        .unzip();
+    (left, right, categories)
+}
+
+#[inline]
+fn join_key<'r>(key: &'r ArrOk<'_>) -> ArrOk<'r> {
+    match key {
+        #[cfg(feature = "time")]
+        ArrOk::DateTimeTz(a) => a.utc.view().into(),
+        key => key.view().widen(),
+    }
+}
+
 #[allow(suspicious_double_ref_op, clippy::clone_on_copy)]
 pub fn join_left<'a>(left_keys: &[&ArrOk<'a>], right_keys: &[&ArrOk<'a>]) -> Vec<Option<usize>> {
     assert_eq!(
@@ -138,6 +194,9 @@ pub fn join_left<'a>(left_keys: &[&ArrOk<'a>], right_keys: &[&ArrOk<'a>]) -> Vec
             panic!("the length of right keys should be equal, but the length of right key is different")
         }
     }
+    let (left_keys, right_keys, _) = prepare_join_keys(left_keys, right_keys);
+    let left_keys = left_keys.iter().collect::<Vec<_>>();
+    let right_keys = right_keys.iter().collect::<Vec<_>>();
     let mut output: Vec<Option<usize>> = Vec::with_capacity(len);
     // fast path for only one key
     if key_len == 1 {
@@ -187,8 +246,8 @@ pub fn join_left<'a>(left_keys: &[&ArrOk<'a>], right_keys: &[&ArrOk<'a>]) -> Vec
             },)
         },).unwrap();
     } else {
-        let (len, hashed_left_keys) = prepare_groupby(left_keys, false);
-        let (right_len, hashed_right_keys) = prepare_groupby(right_keys, false);
+        let (len, hashed_left_keys) = prepare_groupby(&left_keys, false);
+        let (right_len, hashed_right_keys) = prepare_groupby(&right_keys, false);
         let mut group_dict_right =
             collect_hashmap_keys(right_len, &hashed_right_keys, Some(right_len));
         for i in 0..len {
@@ -246,6 +305,17 @@ pub fn join_outer<'a>(
             panic!("the length of right keys should be equal, but the length of right key is different")
         }
     }
+    #[cfg(feature = "time")]
+    let tzs = left_keys
+        .iter()
+        .map(|k| match k {
+            ArrOk::DateTimeTz(a) => Some(a.tz),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let (left_keys, right_keys, categories) = prepare_join_keys(left_keys, right_keys);
+    let left_keys = left_keys.iter().collect::<Vec<_>>();
+    let right_keys = right_keys.iter().collect::<Vec<_>>();
     let outer_capatiy = len.max(right_len);
     let mut outer_keys = Vec::<ArrOk<'a>>::with_capacity(key_len);
 
@@ -307,8 +377,8 @@ pub fn join_outer<'a>(
             },)
         },).unwrap()
     } else {
-        let (len, hashed_left_keys) = prepare_groupby(left_keys, false);
-        let (right_len, hashed_right_keys) = prepare_groupby(right_keys, false);
+        let (len, hashed_left_keys) = prepare_groupby(&left_keys, false);
+        let (right_len, hashed_right_keys) = prepare_groupby(&right_keys, false);
         let mut key_idx = Vec::<(usize, bool, _)>::with_capacity(outer_capatiy);
         let mut outer_dict =
             TpHashMap::<_, (Option<usize>, Option<usize>)>::with_capacity_and_hasher(
@@ -353,8 +423,8 @@ pub fn join_outer<'a>(
         }
         for i in 0..key_len {
             outer_keys.push(
-                match_arrok!(left_keys[i]; Cast(larr) => {
-                match_arrok!(right_keys[i]; Cast(rarr) => {
+                match_arrok!(left_keys[i]; (Cast | #[cfg(feature="time")] Date)(larr) => {
+                match_arrok!(right_keys[i]; (Cast | #[cfg(feature="time")] Date)(rarr) => {
                     let arr_left = larr.view().to_dim1().unwrap();
                     let arr_right = rarr.view().to_dim1().unwrap();
                     let a = key_idx.iter().map(|(idx, is_left, _hash)| {
@@ -362,7 +432,7 @@ pub fn join_outer<'a>(
                             if *is_left {
                                 arr_left.uget(*idx).clone()
                             } else {
-                                arr_right.uget(*idx).clone().cast()
+                                Cast::cast(arr_right.uget(*idx).clone())
                             }
                         }
                     }).collect_trusted();
@@ -377,6 +447,24 @@ pub fn join_outer<'a>(
             .map(|(_idx, _is_left, value)| outer_dict.get(value).unwrap().clone())
             .unzip()
     };
+    // the outer keys of categorical keys are the codes in the merged categories
+    let outer_keys = zip(outer_keys, categories)
+        .map(|(key, categories)| match categories {
+            Some(categories) => {
+                let codes = key.cast_usize().view().map(|c| *c as u32);
+                CatArray::new(codes.into(), categories).into()
+            }
+            None => key,
+        })
+        .collect::<Vec<_>>();
+    // timezone-aware outer keys are the instants, attach the time zone back
+    #[cfg(feature = "time")]
+    let outer_keys = zip(outer_keys, tzs)
+        .map(|(key, tz)| match tz {
+            Some(tz) => key.convert_tz(tz).into(),
+            None => key,
+        })
+        .collect();
     (outer_keys, left_idx, right_idx)
 }
 
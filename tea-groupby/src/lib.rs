@@ -20,6 +20,18 @@ pub use join::{join_left, join_outer, ExprJoinExt, JoinType};
 #[cfg(feature = "lazy")]
 pub use unique::ExprUniqueExt;
 
+/// Register the lazy methods of the crate, so that the expressions using them
+/// can be rebuilt from their definitions, see `tea_lazy::ExprDef`.
+#[cfg(feature = "lazy")]
+pub fn register_ops() {
+    use tea_lazy::Expr;
+    <Expr as GroupbyAggExt>::register_ops();
+    <Expr as AutoExprGroupbyAggExt>::register_ops();
+    <Expr as ExprGroupByExt>::register_ops();
+    <Expr as ExprJoinExt>::register_ops();
+    <Expr as ExprUniqueExt>::register_ops();
+}
+
 use ndarray::{Data, Ix1};
 // use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
@@ -85,12 +97,26 @@ impl<T, S: Data<Elem = T>> HashExt1d for ArrBase<S, Ix1> {
     }
 }
 
+/// Categorical keys are grouped on their codes, timezone-aware keys on their
+/// instants and compact integer keys are widened, other keys are viewed.
+fn keys_with_codes<'r>(keys: &[&'r ArrOk<'_>]) -> Vec<ArrOk<'r>> {
+    keys.iter()
+        .map(|key| match key {
+            ArrOk::Categorical(a) => a.codes_usize().into(),
+            #[cfg(feature = "time")]
+            ArrOk::DateTimeTz(a) => a.utc.view().into(),
+            key => key.view().widen(),
+        })
+        .collect()
+}
+
 pub fn prepare_groupby(
     keys: &[&ArrOk<'_>],
     // hasher: Option<TpBuildHasher>,
     _par: bool,
 ) -> (usize, Vec<Arr1<u64>>) {
     // let hasher = hasher.unwrap_or(TpBuildHasher::default());
+    let keys = keys_with_codes(keys);
     let hashed_keys = keys
         .iter()
         .map(|arr| {
@@ -212,6 +238,8 @@ pub fn groupby(keys: &[&ArrOk<'_>], sort: bool) -> Vec<(usize, Vec<usize>)> {
     }
     let init_size = (len / 2).min(1);
     let by_len = keys.len();
+    let keys = keys_with_codes(keys);
+    let keys = keys.iter().collect::<Vec<_>>();
     let mut vec = if by_len == 1 {
         let key = keys[0];
         match_arrok!(key; Hash(key_arr) => {
@@ -299,7 +327,7 @@ pub fn groupby(keys: &[&ArrOk<'_>], sort: bool) -> Vec<(usize, Vec<usize>)> {
             },)
         },).unwrap()
     } else {
-        let (len, hashed_keys) = prepare_groupby(keys, false);
+        let (len, hashed_keys) = prepare_groupby(&keys, false);
         let group_dict = collect_hashmap_keys(len, &hashed_keys, None);
         group_dict.into_values().collect_trusted()
     };
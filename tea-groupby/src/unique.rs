@@ -47,18 +47,22 @@ impl<'a> ExprUniqueExt for Expr<'a> {
                 .as_ref()
                 .map(|vecs| {
                     vecs.into_par_iter()
-                        .map(|e| e.view_arr(ctx.as_ref()).unwrap().deref())
+                        .map(|e| e.view_arr(ctx.as_ref()).unwrap())
                         .collect::<Vec<_>>()
                 })
                 .unwrap_or_default();
-            let others_ref = others.iter().collect::<Vec<_>>();
+            let others_ref = others.iter().map(|a| &**a).collect::<Vec<_>>();
             let arr = data.view_arr(ctx.as_ref()).unwrap();
             let len = arr.len();
             let out_idx = if others_ref.is_empty() {
-                let arr: ArrOk = if arr.is_float() {
-                    match_arrok!(arr; PureFloat(a) => {Ok(a.view().to_dim1()?.tphash_1d().into_dyn().into())},).unwrap()
-                } else {
-                    arr.deref()
+                let arr: ArrOk = match &*arr {
+                    arr if arr.is_float() => {
+                        match_arrok!(arr; PureFloat(a) => {Ok(a.view().to_dim1()?.tphash_1d().into_dyn().into())},).unwrap()
+                    }
+                    ArrOk::Categorical(a) => a.codes_usize().into(),
+                    #[cfg(feature = "time")]
+                    ArrOk::DateTimeTz(a) => a.utc.view().into(),
+                    arr => arr.deref().widen(),
                 };
                 if &keep == "first" {
                     let mut out_idx = Vec::with_capacity(len);
@@ -112,8 +116,12 @@ impl<'a> ExprUniqueExt for Expr<'a> {
                 }
             } else {
                 let (len, hashed_keys) = super::prepare_groupby(&others_ref, false);
-                let arr = data.view_arr(ctx.as_ref())?;
-                let arr_key = match_arrok!(arr; TpHash(a) => {Ok(a.view().to_dim1()?.tphash_1d())},).unwrap();
+                let arr = &*data.view_arr(ctx.as_ref())?;
+                let arr_key = match_arrok!(arr;
+                    TpHash(a) => {Ok(a.view().to_dim1()?.tphash_1d())},
+                    Categorical(a) => {Ok(a.codes_usize().to_dim1()?.tphash_1d())},
+                    #[cfg(feature="time")] DateTimeTz(a) => {Ok(a.utc.view().to_dim1()?.tphash_1d())},
+                ).unwrap();
                 let mut out_idx = Vec::with_capacity(len);
                 if &keep == "first" {
                     let mut map = TpHashMap::<Vec<u64>, u8>::with_capacity_and_hasher(len, BUILD_HASHER.clone());
@@ -87,6 +87,18 @@ macro_rules! impl_tphash {
         )*
     };
 
+    // small ints are hashed the same as the widened int
+    (small_int $($ty: ty),*) => {
+        $(
+            impl TpHash for $ty {
+                #[inline]
+                fn tphash(&self) -> u64 {
+                    i64::cast_unsigned(*self as i64)
+                }
+            }
+        )*
+    };
+
     (default $($ty: ty),*) => {
         $(
             impl TpHash for $ty {
@@ -101,6 +113,7 @@ macro_rules! impl_tphash {
 
 impl_tphash!(uint u8, u16, u32, u64, usize);
 impl_tphash!(int i32, i64, isize);
+impl_tphash!(small_int i8, i16);
 impl_tphash!(default String, &str, Vec<u64>, [u64]);
 #[cfg(feature = "time")]
 impl_tphash!(int DateTime<unit::Millisecond>, DateTime<unit::Microsecond>, DateTime<unit::Nanosecond>);
@@ -0,0 +1,223 @@
+use super::{read_ipc, read_ipc_schema, scan::ipc_num_rows};
+use crate::ColSelect;
+use arrow::datatypes::Schema;
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use std::path::{Path, PathBuf};
+use teapy_core::prelude::*;
+
+/// A set of ipc files with compatible schemas, discovered by a glob pattern.
+///
+/// Hive style directories (`key=value`) in the paths are parsed as partition columns.
+pub struct IpcDataset {
+    pub files: Vec<PathBuf>,
+    /// The selected columns stored in the files.
+    pub schema: Schema,
+    /// The selected partition keys and their values of each file.
+    pub partitions: Vec<(String, Vec<String>)>,
+    /// The number of rows of each file.
+    pub num_rows: Vec<usize>,
+}
+
+/// Parse the `key=value` directories of a path.
+fn parse_partitions(path: &Path) -> Vec<(String, String)> {
+    path.parent()
+        .map(|p| {
+            p.components()
+                .filter_map(|c| {
+                    let (key, value) = c.as_os_str().to_str()?.split_once('=')?;
+                    Some((key.to_owned(), value.to_owned()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Nullability may differ between files, cast the arrays to the nullable dtype if needed.
+fn unify_nullable(arrs: Vec<ArrOk<'_>>) -> Vec<ArrOk<'_>> {
+    // compare the names, as `DataType` can't express `OptU8` and `OptU64`
+    let dtypes = arrs.iter().map(|a| a.dtype_name()).collect::<Vec<_>>();
+    if Iterator::all(&mut dtypes.iter(), |dt| *dt == dtypes[0]) {
+        return arrs;
+    }
+    let nullable = <[_]>::iter(&dtypes).find(|dt| {
+        matches!(
+            dt.as_str(),
+            "OptBool" | "OptI32" | "OptI64" | "OptUsize" | "OptF32" | "OptF64" | "OptU8" | "OptU64"
+        )
+    });
+    macro_rules! cast_all {
+        ($cast: ident) => {
+            arrs.into_iter().map(|a| a.$cast().into()).collect()
+        };
+    }
+    match nullable.map(String::as_str) {
+        Some("OptBool") => cast_all!(cast_opt_bool),
+        Some("OptI32") => cast_all!(cast_opt_i32),
+        Some("OptI64") => cast_all!(cast_opt_i64),
+        Some("OptUsize") => cast_all!(cast_optusize),
+        Some("OptF32") => cast_all!(cast_opt_f32),
+        Some("OptF64") => cast_all!(cast_opt_f64),
+        Some("OptU8") => cast_all!(cast_opt_u8),
+        Some("OptU64") => cast_all!(cast_opt_u64),
+        _ => arrs,
+    }
+}
+
+impl IpcDataset {
+    /// Discover the files matching `pattern` and check that their schemas are compatible.
+    pub fn try_new(pattern: &str, columns: ColSelect<'_>) -> TResult<Self> {
+        let mut files = glob::glob(pattern)
+            .map_err(|e| terr!("invalid glob pattern {}: {}", pattern, e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| terr!("{}", e))?;
+        files.sort();
+        let Some(first) = <[_]>::first(&files) else {
+            tbail!("no file matches the pattern: {}", pattern)
+        };
+        let keys = parse_partitions(first)
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>();
+        let mut values = vec![Vec::with_capacity(files.len()); keys.len()];
+        for file in &files {
+            let partitions = parse_partitions(file);
+            if !Iterator::eq(partitions.iter().map(|(k, _)| k), keys.iter()) {
+                tbail!("partition keys of {:?} don't match {:?}", file, keys)
+            }
+            values
+                .iter_mut()
+                .zip(partitions)
+                .for_each(|(v, (_, value))| v.push(value));
+        }
+        let mut partitions: Vec<_> = keys.into_iter().zip(values).collect();
+
+        // partition keys can be selected by name along with the columns in the files
+        let schema = read_ipc_schema(first)?;
+        let columns = match columns {
+            ColSelect::Name(names) => {
+                partitions.retain(|(k, _)| names.contains(&k.as_str()));
+                let names = names
+                    .into_iter()
+                    .filter(|n| !Iterator::any(&mut partitions.iter(), |(k, _)| k == n))
+                    .map(|n| n.to_owned())
+                    .collect();
+                ColSelect::NameOwned(names)
+            }
+            ColSelect::NameOwned(names) => {
+                partitions.retain(|(k, _)| names.contains(k));
+                let names = names
+                    .into_iter()
+                    .filter(|n| !Iterator::any(&mut partitions.iter(), |(k, _)| k == n))
+                    .collect();
+                ColSelect::NameOwned(names)
+            }
+            columns => columns,
+        };
+        let schema = match columns.into_proj(&schema)? {
+            Some(proj) => schema.filter(|i, _f| proj.contains(&i)),
+            None => schema,
+        };
+        // a column in the files takes precedence over the partition key with the same name
+        partitions.retain(|(k, _)| !Iterator::any(&mut schema.fields.iter(), |f| &f.name == k));
+
+        let num_rows = files
+            .par_iter()
+            .map(|file| {
+                let file_schema = read_ipc_schema(file)?;
+                for field in &schema.fields {
+                    let Some(f) = <[_]>::iter(&file_schema.fields).find(|f| f.name == field.name)
+                    else {
+                        tbail!("column {} doesn't exist in {:?}", field.name, file)
+                    };
+                    if f.data_type != field.data_type {
+                        tbail!(
+                            "dtype of column {} in {:?} is {:?}, expect {:?}",
+                            field.name,
+                            file,
+                            f.data_type,
+                            field.data_type
+                        )
+                    }
+                }
+                ipc_num_rows(file)
+            })
+            .collect::<TResult<Vec<_>>>()?;
+        Ok(IpcDataset {
+            files,
+            schema,
+            partitions,
+            num_rows,
+        })
+    }
+
+    /// Names of the columns in the files followed by the partition keys.
+    pub fn columns(&self) -> Vec<String> {
+        self.schema
+            .fields
+            .iter()
+            .map(|f| f.name.clone())
+            .chain(self.partitions.iter().map(|(k, _)| k.clone()))
+            .collect()
+    }
+
+    /// Read a column from all the files in parallel and concatenate them.
+    pub fn read_column<'a>(&self, name: &str) -> TResult<ArrOk<'a>> {
+        if let Some((_, values)) = <[_]>::iter(&self.partitions).find(|(k, _)| k == name) {
+            return Ok(self.partition_column(values));
+        }
+        let arrs = self
+            .files
+            .par_iter()
+            .map(|file| {
+                let (_schema, mut arr) = read_ipc(file, ColSelect::Name(vec![name]))?;
+                arr.pop()
+                    .ok_or_else(|| terr!("column {} doesn't exist in {:?}", name, file))
+            })
+            .collect::<TResult<Vec<_>>>()?;
+        Ok(ArrOk::same_dtype_concat_1d(unify_nullable(arrs)))
+    }
+
+    /// Repeat the partition value of each file by its number of rows,
+    /// the values are parsed as `i64` or datetime if possible.
+    fn partition_column<'a>(&self, values: &[String]) -> ArrOk<'a> {
+        fn repeat<T: Clone>(values: Vec<T>, num_rows: &[usize]) -> Vec<T> {
+            values
+                .into_iter()
+                .zip(num_rows)
+                .flat_map(|(v, n)| std::iter::repeat_n(v, *n))
+                .collect()
+        }
+        if let Ok(v) = values.iter().map(|v| v.parse::<i64>()).collect() {
+            let out: ArbArray<'a, i64> =
+                Arr1::from_vec(repeat(v, &self.num_rows)).into_dyn().into();
+            return out.into();
+        }
+        let dt = values
+            .iter()
+            .map(|v| DateTime::<unit::Nanosecond>::parse(v, None))
+            .collect::<TResult<Vec<_>>>();
+        if let Ok(v) = dt {
+            let out: ArbArray<'a, DateTime<unit::Nanosecond>> =
+                Arr1::from_vec(repeat(v, &self.num_rows)).into_dyn().into();
+            return out.into();
+        }
+        let out: ArbArray<'a, String> = Arr1::from_vec(repeat(values.to_vec(), &self.num_rows))
+            .into_dyn()
+            .into();
+        out.into()
+    }
+}
+
+/// Read all the selected columns of the files matching `pattern`.
+pub fn read_dataset<'a>(
+    pattern: &str,
+    columns: ColSelect<'_>,
+) -> TResult<(Vec<String>, Vec<ArrOk<'a>>)> {
+    let dataset = IpcDataset::try_new(pattern, columns)?;
+    let names = dataset.columns();
+    let arrs = names
+        .par_iter()
+        .map(|name| dataset.read_column(name))
+        .collect::<TResult<Vec<_>>>()?;
+    Ok((names, arrs))
+}
@@ -0,0 +1,144 @@
+use arrow::array::{Array, StructArray};
+use arrow::compute::concatenate::concatenate;
+use arrow::datatypes::{DataType as ArrowDT, Field};
+use arrow::ffi;
+use rayon::prelude::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+};
+#[cfg(feature = "lazy")]
+use tea_lazy::Expr;
+use teapy_core::prelude::*;
+
+pub use arrow::ffi::{ArrowArray, ArrowArrayStream, ArrowSchema};
+
+/// Export a 1-d array through the arrow C data interface, the buffers are shared
+/// with the array rather than copied, see [`ArrOk::share_arrow`].
+pub fn export_array(arr: &mut ArrOk<'_>, name: &str) -> TResult<(ArrowSchema, ArrowArray)> {
+    let arr = arr.share_arrow()?;
+    let field = Field::new(name, arr.data_type().clone(), arr.validity().is_some());
+    Ok((ffi::export_field_to_c(&field), ffi::export_array_to_c(arr)))
+}
+
+/// Convert the evaluated array of an expression into an arrow array for export.
+///
+/// An array owned by the expression is moved into the arrow array and the
+/// expression keeps a view of it, so the buffers are shared rather than copied.
+#[cfg(feature = "lazy")]
+pub fn expr_to_arrow(e: &Expr<'_>) -> TResult<Box<dyn Array>> {
+    if let Some(arr) = e.lock().arr_mut() {
+        return arr.share_arrow();
+    }
+    e.view_raw_arr(None)?.to_arrow()
+}
+
+/// Export the columns as a struct array, which is how a record batch is
+/// represented in the arrow C data interface.
+fn columns_to_struct(
+    arrow_arrs: Vec<Box<dyn Array>>,
+    names: &[&str],
+) -> TResult<(Field, Box<dyn Array>)> {
+    if arrow_arrs.len() != names.len() {
+        tbail!(
+            "the number of arrays ({}) doesn't match the number of names ({})",
+            arrow_arrs.len(),
+            names.len()
+        )
+    }
+    let fields = arrow_arrs
+        .iter()
+        .zip(names)
+        .map(|(a, name)| Field::new(*name, a.data_type().clone(), a.validity().is_some()))
+        .collect::<Vec<_>>();
+    let dtype = ArrowDT::Struct(fields);
+    let arr =
+        StructArray::try_new(dtype.clone(), arrow_arrs, None).map_err(|e| terr!("{:?}", e))?;
+    Ok((Field::new("", dtype, false), arr.boxed()))
+}
+
+/// Export the arrow arrays of the columns as a struct array through the arrow C data interface.
+pub fn export_columns(
+    arrs: Vec<Box<dyn Array>>,
+    names: &[&str],
+) -> TResult<(ArrowSchema, ArrowArray)> {
+    let (field, arr) = columns_to_struct(arrs, names)?;
+    Ok((ffi::export_field_to_c(&field), ffi::export_array_to_c(arr)))
+}
+
+/// Export the arrow arrays of the columns as an arrow C stream with a single record batch.
+pub fn export_stream(arrs: Vec<Box<dyn Array>>, names: &[&str]) -> TResult<ArrowArrayStream> {
+    let (field, arr) = columns_to_struct(arrs, names)?;
+    let iter = Box::new(std::iter::once(Ok(arr)));
+    Ok(ffi::export_iterator(iter, field))
+}
+
+/// Split struct arrays into columns, other arrays are treated as a single column.
+fn split_columns<'a>(
+    field: Field,
+    chunks: Vec<Box<dyn Array>>,
+) -> TResult<(Vec<String>, Vec<ArrOk<'a>>)> {
+    let (fields, columns) = if let ArrowDT::Struct(fields) = field.data_type {
+        let mut columns = vec![Vec::with_capacity(chunks.len()); fields.len()];
+        for chunk in chunks {
+            let chunk = chunk
+                .as_any()
+                .downcast_ref::<StructArray>()
+                .ok_or_else(|| terr!("expect a struct array, got {:?}", chunk.data_type()))?;
+            columns
+                .iter_mut()
+                .zip(chunk.values())
+                .for_each(|(col, arr)| col.push(arr.clone()));
+        }
+        (fields, columns)
+    } else {
+        (vec![field], vec![chunks])
+    };
+    let arrs = columns
+        .into_par_iter()
+        .zip(fields.par_iter())
+        .map(|(mut arrs, field)| {
+            // a single chunk is used as is so that the buffers are not copied
+            let arr = match arrs.len() {
+                0 => arrow::array::new_empty_array(field.data_type.clone()),
+                1 => arrs.pop().unwrap(),
+                _ => {
+                    let arrs = arrs.iter().map(|a| a.as_ref()).collect::<Vec<_>>();
+                    concatenate(&arrs).map_err(|e| terr!("{:?}", e))?
+                }
+            };
+            ArrOk::try_from_arrow(arr)
+        })
+        .collect::<TResult<Vec<_>>>()?;
+    let names = fields.into_iter().map(|f| f.name).collect();
+    Ok((names, arrs))
+}
+
+/// Import an array through the arrow C data interface, a struct array is
+/// imported as multiple columns.
+///
+/// # Safety
+/// `schema` and `array` should be valid according to the arrow C data interface.
+pub unsafe fn import_array<'a>(
+    schema: &ArrowSchema,
+    array: ArrowArray,
+) -> TResult<(Vec<String>, Vec<ArrOk<'a>>)> {
+    let field = ffi::import_field_from_c(schema).map_err(|e| terr!("{:?}", e))?;
+    let arr =
+        ffi::import_array_from_c(array, field.data_type.clone()).map_err(|e| terr!("{:?}", e))?;
+    split_columns(field, vec![arr])
+}
+
+/// Import all the arrays of an arrow C stream, a stream of struct arrays is
+/// imported as multiple columns.
+///
+/// # Safety
+/// `stream` should be valid according to the arrow C stream interface.
+pub unsafe fn import_stream<'a>(
+    stream: Box<ArrowArrayStream>,
+) -> TResult<(Vec<String>, Vec<ArrOk<'a>>)> {
+    let mut reader = ffi::ArrowArrayStreamReader::try_new(stream).map_err(|e| terr!("{:?}", e))?;
+    let mut chunks = vec![];
+    while let Some(chunk) = reader.next() {
+        chunks.push(chunk.map_err(|e| terr!("{:?}", e))?);
+    }
+    split_columns(reader.field().clone(), chunks)
+}
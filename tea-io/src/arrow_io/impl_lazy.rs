@@ -1,7 +1,13 @@
+use super::scan::read_filtered;
+use super::{IpcBatchReader, IpcBatchWriter, IpcCompression, IpcDataset, IpcScanOptions};
 use crate::{ColSelect, SingleCol};
+use arrow::array::Array;
+use arrow::io::ipc::read::read_file_metadata;
+use std::fs::File;
 use std::path::Path;
-use tea_lazy::{DataDict, Expr};
-use teapy_core::prelude::TResult;
+use std::sync::{Arc, OnceLock};
+use tea_lazy::{ColumnSelector, DataDict, Expr, Op};
+use teapy_core::prelude::{terr, ArrOk, TResult};
 use teapy_core::utils::CollectTrustedToVec;
 
 #[ext_trait]
@@ -19,32 +25,163 @@ impl<'a> ExprIPCExt for Expr<'a> {
             None
         };
         e.set_name(name);
-        e.chain_f_ctx(move |(_data, _ctx)| {
+        // a source node doesn't reference the context
+        let op = Op::new("read_ipc").param("path", p.as_ref().to_string_lossy().as_ref());
+        e.chain_op(op, move |(_data, _ctx)| {
             let (_schema, mut arr) = super::read_ipc(p.clone(), col.clone().into())?;
             let arr = arr.pop().unwrap();
             Ok((arr.into(), None))
         });
         e
     }
+
+    /// Evaluate the expression and write it into an ipc file,
+    /// the name of the column will be `column_0` if the expression has no name.
+    pub fn write_ipc<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        compression: Option<IpcCompression>,
+    ) -> TResult<()> {
+        self.eval_inplace(None)?;
+        self.prepare();
+        let name = self.name().unwrap_or_else(|| "column_0".to_owned());
+        let arr = self.view_raw_arr(None)?;
+        super::write_ipc(path, &[arr], &[name.as_str()], compression)
+    }
 }
 
-pub fn scan_ipc_lazy<'a, P>(path: P, columns: ColSelect<'_>) -> TResult<Vec<Expr<'a>>>
+/// The columns read by a filtered scan, shared by the expressions of the columns.
+type SharedArrays = Arc<OnceLock<Result<Vec<Box<dyn Array>>, String>>>;
+
+/// Scan the columns of an ipc file lazily.
+///
+/// If a row range or a predicate is given, the selected record batches are read once
+/// with all the columns when the first column is evaluated, and shared by all the columns.
+pub fn scan_ipc_lazy<'a, 'b, P, O>(path: P, options: O) -> TResult<Vec<Expr<'a>>>
 where
     P: AsRef<Path> + Send + Sync + Clone + 'a,
+    O: Into<IpcScanOptions<'b>>,
 {
-    let mut schema = super::read_ipc_schema(path.clone())?;
-    let proj = columns.into_proj(&schema)?;
-    if let Some(proj) = proj {
-        schema = schema.filter(|i, _f| proj.contains(&i));
+    let options = options.into();
+    let metadata =
+        read_file_metadata(&mut File::open(path.as_ref())?).map_err(|e| terr!("{:?}", e))?;
+    let proj = options.columns.into_proj(&metadata.schema)?;
+    let proj: Vec<usize> = match proj {
+        Some(mut proj) => {
+            proj.sort_unstable();
+            proj.dedup();
+            proj
+        }
+        None => (0..metadata.schema.fields.len()).collect(),
+    };
+    if options.offset == 0 && options.length.is_none() && options.predicate.is_none() {
+        let out = proj
+            .into_iter()
+            .map(|i| Expr::read_ipc(path.clone(), metadata.schema.fields[i].name.clone().into()))
+            .collect_trusted();
+        return Ok(out);
     }
-    let out = schema
-        .fields
+    let metadata = Arc::new(metadata);
+    let arrs: SharedArrays = Default::default();
+    let predicate = Arc::new(options.predicate);
+    let (offset, length) = (options.offset, options.length);
+    let proj = Arc::new(proj);
+    let out = (0..proj.len())
+        .map(|i| {
+            let mut e: Expr<'a> = 0_i32.into();
+            e.set_name(Some(metadata.schema.fields[proj[i]].name.clone()));
+            let (path, metadata, proj) = (path.clone(), metadata.clone(), proj.clone());
+            let (arrs, predicate) = (arrs.clone(), predicate.clone());
+            let op = Op::new("scan_ipc").param("path", path.as_ref().to_string_lossy().as_ref());
+            e.chain_op(op, move |(_data, _ctx)| {
+                let path = path.as_ref();
+                let arrs = arrs
+                    .get_or_init(|| {
+                        let predicate = predicate.as_ref().as_ref();
+                        read_filtered(path, &metadata, &proj, offset, length, predicate)
+                            .map_err(|e| e.to_string())
+                    })
+                    .as_ref()
+                    .map_err(|e| terr!("{}", e))?;
+                Ok((ArrOk::try_from_arrow(arrs[i].clone())?.into(), None))
+            });
+            e
+        })
+        .collect_trusted();
+    Ok(out)
+}
+
+/// Scan the ipc files matching a glob pattern lazily, each column is read from
+/// all the files in parallel and concatenated when it is evaluated.
+pub fn scan_dataset_lazy<'a>(pattern: &str, columns: ColSelect<'_>) -> TResult<Vec<Expr<'a>>> {
+    let dataset = Arc::new(IpcDataset::try_new(pattern, columns)?);
+    let out = dataset
+        .columns()
         .into_iter()
-        .map(|f| Expr::read_ipc(path.clone(), f.name.into()))
+        .map(|name| {
+            let mut e: Expr<'a> = 0_i32.into();
+            e.set_name(Some(name.clone()));
+            let dataset = dataset.clone();
+            let op = Op::new("scan_dataset").param("pattern", pattern);
+            e.chain_op(op, move |(_data, _ctx)| {
+                Ok((dataset.read_column(&name)?.into(), None))
+            });
+            e
+        })
         .collect_trusted();
     Ok(out)
 }
 
+/// Iterate an ipc file as `DataDict`s, see [`IpcBatchReader`].
+pub struct IpcDataDictReader(IpcBatchReader);
+
+impl IpcDataDictReader {
+    #[inline]
+    pub fn try_new<P: AsRef<Path>>(
+        path: P,
+        columns: ColSelect<'_>,
+        batch_rows: Option<usize>,
+    ) -> TResult<Self> {
+        Ok(IpcDataDictReader(IpcBatchReader::try_new(
+            path, columns, batch_rows,
+        )?))
+    }
+}
+
+impl Iterator for IpcDataDictReader {
+    type Item = TResult<DataDict<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let arrs = match self.0.next()? {
+            Ok(arrs) => arrs,
+            Err(e) => return Some(Err(e)),
+        };
+        let data = self
+            .0
+            .columns()
+            .into_iter()
+            .zip(arrs)
+            .map(|(name, a)| Expr::new_from_arr(a, Some(name.to_owned())))
+            .collect();
+        Some(Ok(DataDict::new(data, None)))
+    }
+}
+
+impl IpcBatchWriter {
+    /// Evaluate all the columns of the `DataDict` and append them as a record batch.
+    pub fn write_dict(&mut self, dd: &mut DataDict<'_>) -> TResult<()> {
+        dd.eval_inplace(ColumnSelector::All, true)?;
+        dd.data.iter().for_each(|e| e.prepare());
+        let arrs = dd
+            .data
+            .iter()
+            .map(|e| e.view_raw_arr(None))
+            .collect::<TResult<Vec<_>>>()?;
+        let columns = dd.columns();
+        self.write(&arrs, &columns)
+    }
+}
+
 #[ext_trait]
 impl<'a> DataDictIPCExt for DataDict<'a> {
     pub fn read_ipc<P: AsRef<Path>>(path: P, columns: ColSelect<'_>) -> TResult<DataDict<'a>> {
@@ -58,11 +195,47 @@ impl<'a> DataDictIPCExt for DataDict<'a> {
         Ok(DataDict::new(data, None))
     }
 
-    pub fn scan_ipc<P>(path: P, columns: ColSelect<'_>) -> TResult<DataDict<'a>>
+    /// Scan an ipc file lazily, `options` can be a `ColSelect` or an `IpcScanOptions`
+    /// with a row range and a predicate pushed down.
+    pub fn scan_ipc<'b, P, O>(path: P, options: O) -> TResult<DataDict<'a>>
     where
         P: AsRef<Path> + Send + Sync + Clone + 'a,
+        O: Into<IpcScanOptions<'b>>,
     {
-        let out = scan_ipc_lazy(path, columns)?;
+        let out = scan_ipc_lazy(path, options)?;
         Ok(Self::new(out, None))
     }
+
+    /// Read an ipc file lazily as a `DataDict` per record batch, or per `batch_rows` rows.
+    pub fn iter_ipc<P: AsRef<Path>>(
+        path: P,
+        columns: ColSelect<'_>,
+        batch_rows: Option<usize>,
+    ) -> TResult<IpcDataDictReader> {
+        IpcDataDictReader::try_new(path, columns, batch_rows)
+    }
+
+    /// Scan the ipc files matching a glob pattern, the partition keys of Hive style
+    /// directories (`date=2024-01-02/part.ipc`) are added as extra columns.
+    pub fn scan_dataset(pattern: &str, columns: ColSelect<'_>) -> TResult<DataDict<'a>> {
+        let out = scan_dataset_lazy(pattern, columns)?;
+        Ok(Self::new(out, None))
+    }
+
+    /// Evaluate all the columns of the `DataDict` and write them into an ipc file.
+    pub fn write_ipc<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        compression: Option<IpcCompression>,
+    ) -> TResult<()> {
+        self.eval_inplace(ColumnSelector::All, true)?;
+        self.data.iter().for_each(|e| e.prepare());
+        let arrs = self
+            .data
+            .iter()
+            .map(|e| e.view_raw_arr(None))
+            .collect::<TResult<Vec<_>>>()?;
+        let columns = self.columns();
+        super::write_ipc(path, &arrs, &columns, compression)
+    }
 }
@@ -1,13 +1,25 @@
-// use arrow::array::Array;
 use crate::ColSelect;
-use arrow::datatypes::Schema;
+use arrow::array::{new_empty_array, Array};
+use arrow::chunk::Chunk;
+use arrow::compute::concatenate::concatenate;
+use arrow::datatypes::{Field, Schema};
+use arrow::error::Error;
+use arrow::io::ipc::read::{self, FileMetadata};
+use arrow::io::ipc::write::{FileWriter, WriteOptions};
 use arrow::mmap::{mmap_dictionaries_unchecked, mmap_unchecked};
-use arrow::{error::Error, io::ipc::read};
 use memmap::Mmap;
-use rayon::prelude::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
-use std::{fs::File, path::Path, sync::Arc};
+use rayon::prelude::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+};
+use std::{fs::File, io::Cursor, path::Path, sync::Arc};
 use teapy_core::prelude::*;
 
+pub use arrow::io::ipc::write::Compression as IpcCompression;
+
+/// The maximum number of rows in a record batch when writing ipc files,
+/// so that a scan with predicate can skip the batches it doesn't need.
+const RECORD_BATCH_SIZE: usize = 512 * 512;
+
 #[inline]
 pub fn read_ipc_schema<P: AsRef<Path>>(path: P) -> TResult<Schema> {
     let mut file = File::open(path)?;
@@ -15,13 +27,75 @@ pub fn read_ipc_schema<P: AsRef<Path>>(path: P) -> TResult<Schema> {
     Ok(metadata.schema)
 }
 
-// #[inline]
-// pub fn filter_arr_by_proj(arrs: Vec<Box<dyn Array>>, schema: Schema, proj: &[usize]) -> (Vec<Box<dyn Array>>, Schema) {
-//     let schema = schema.filter(|i, _f| proj.contains(&i));
-//     let arrs = arrs.into_iter().enumerate().filter(|(i, _)| proj.contains(i)).map(|(_, a)| a).collect::<Vec<_>>();
-//     (arrs, schema)
-// }
+/// Concatenate the chunks of a column in arrow, so that chunks with and
+/// without nulls are converted to the same dtype.
+fn concat_arrow<'a>(arrs: Vec<Box<dyn Array>>) -> TResult<ArrOk<'a>> {
+    let arrs = arrs.iter().map(|a| a.as_ref()).collect::<Vec<_>>();
+    let arr = concatenate(&arrs).map_err(|e| terr!("{:?}", e))?;
+    ArrOk::try_from_arrow(arr)
+}
+
+#[inline]
+fn select_arrays(arrs: Vec<Box<dyn Array>>, proj: Option<&[usize]>) -> Vec<Box<dyn Array>> {
+    if let Some(proj) = proj {
+        arrs.into_iter()
+            .enumerate()
+            .filter(|(i, _)| proj.contains(i))
+            .map(|(_, a)| a)
+            .collect()
+    } else {
+        arrs
+    }
+}
+
+/// Memory-map all the record batches of an uncompressed ipc file.
+unsafe fn mmap_batches(
+    metadata: &FileMetadata,
+    mmap: Arc<Mmap>,
+    proj: Option<&[usize]>,
+) -> Result<Vec<Vec<Box<dyn Array>>>, Error> {
+    let dictionaries = mmap_dictionaries_unchecked(metadata, mmap.clone())?;
+    (0..metadata.blocks.len())
+        .into_par_iter()
+        .map(|i| {
+            let chunk = mmap_unchecked(metadata, &dictionaries, mmap.clone(), i)?;
+            Ok(select_arrays(chunk.into_arrays(), proj))
+        })
+        .collect()
+}
+
+/// Decompress the record batches of a compressed ipc file, one batch per task.
+fn decompress_batches(
+    metadata: &FileMetadata,
+    data: &[u8],
+    proj: Option<&[usize]>,
+) -> TResult<Vec<Vec<Box<dyn Array>>>> {
+    let dictionaries = read::read_file_dictionaries(&mut Cursor::new(data), metadata, &mut vec![])
+        .map_err(|e| terr!("{:?}", e))?;
+    (0..metadata.blocks.len())
+        .into_par_iter()
+        .map_init(
+            || (vec![], vec![]),
+            |(message_scratch, data_scratch), i| {
+                let chunk = read::read_batch(
+                    &mut Cursor::new(data),
+                    &dictionaries,
+                    metadata,
+                    proj,
+                    None,
+                    i,
+                    message_scratch,
+                    data_scratch,
+                )
+                .map_err(|e| terr!("{:?}", e))?;
+                Ok(chunk.into_arrays())
+            },
+        )
+        .collect()
+}
 
+/// Read an ipc file, uncompressed files are memory-mapped and compressed
+/// files are decompressed in parallel.
 pub fn read_ipc<'a, P: AsRef<Path>>(
     path: P,
     columns: ColSelect<'_>,
@@ -31,107 +105,92 @@ pub fn read_ipc<'a, P: AsRef<Path>>(
 
     // read the files' metadata. At this point, we can distribute the read whatever we like.
     let metadata = read::read_file_metadata(&mut file).map_err(|e| terr!("{:?}", e))?;
-    let blocks_num = metadata.blocks.len();
-    let mut schema = metadata.schema.clone();
-    let proj = columns.into_proj(&schema)?;
-
-    let dictionaries = unsafe {
-        mmap_dictionaries_unchecked(&metadata, mmap.clone()).map_err(|e| terr!("{:?}", e))?
+    // the columns are read in the order of the file
+    let proj = columns.into_proj(&metadata.schema)?.map(|mut proj| {
+        proj.sort_unstable();
+        proj.dedup();
+        proj
+    });
+    let schema = if let Some(proj) = &proj {
+        metadata.schema.clone().filter(|i, _f| proj.contains(&i))
+    } else {
+        metadata.schema.clone()
     };
-    let chunk = unsafe { mmap_unchecked(&metadata, &dictionaries, mmap.clone(), 0) };
-    let chunk = match chunk {
-        Ok(chunk) => chunk,
-        Err(e) => {
-            if let Error::NotYetImplemented(e) = &e {
-                if e == "mmap can only be done on uncompressed IPC files" {
-                    // unimplemented!("mmap can only be done on uncompressed IPC files")
-                    let mut reader = read::FileReader::new(file, metadata, proj, None);
-                    let schema = reader.schema().clone();
-                    let out: Vec<ArrOk<'a>> = if blocks_num > 1 {
-                        let mut arrs = reader
-                            .map(|batch| batch.unwrap().into_arrays())
-                            .collect::<Vec<_>>();
-                        let arrs = (0..arrs.get(0).unwrap().len())
-                            .map(|_| {
-                                let mut out = Vec::with_capacity(arrs.len());
-                                // out.push(arrs.pop().unwrap());
-                                arrs.iter_mut().for_each(|a| out.push(a.pop().unwrap()));
-                                out
-                            })
-                            .collect::<Vec<_>>();
-                        arrs.into_par_iter()
-                            .rev()
-                            .map(|a| {
-                                let a = a.into_par_iter().map(ArrOk::from_arrow).collect();
-                                ArrOk::same_dtype_concat_1d(a)
-                            })
-                            .collect()
-                    } else {
-                        let chunk = reader.next().unwrap().unwrap();
-                        chunk
-                            .into_arrays()
-                            .into_par_iter()
-                            .map(ArrOk::from_arrow)
-                            .collect()
-                    };
-                    return Ok((schema, out));
-                } else {
-                    tbail!("{:?}", e);
-                }
-            } else {
-                tbail!("{:?}", e);
-            }
+
+    let batches = match unsafe { mmap_batches(&metadata, mmap.clone(), proj.as_deref()) } {
+        Ok(batches) => batches,
+        Err(Error::NotYetImplemented(e))
+            if e == "mmap can only be done on uncompressed IPC files" =>
+        {
+            decompress_batches(&metadata, &mmap, proj.as_deref())?
         }
+        Err(e) => tbail!("{:?}", e),
     };
 
-    let arrs = chunk.into_arrays();
-    let mut arrs = if let Some(proj) = &proj {
-        schema = schema.filter(|i, _f| proj.contains(&i));
-        arrs.into_iter()
-            .enumerate()
-            .filter(|(i, _)| proj.contains(i))
-            .map(|(_, a)| a)
-            .collect::<Vec<_>>()
-    } else {
-        arrs
-    };
-    let mut other_arrs = (1..blocks_num)
-        .map(|i| {
-            let chunk =
-                unsafe { mmap_unchecked(&metadata, &dictionaries, mmap.clone(), i).unwrap() };
-            let arrs = chunk.into_arrays();
-            if let Some(proj) = &proj {
-                arrs.into_iter()
-                    .enumerate()
-                    .filter(|(i, _)| proj.contains(i))
-                    .map(|(_, a)| a)
-                    .collect::<Vec<_>>()
-            } else {
-                arrs
-            }
+    let mut columns = vec![Vec::with_capacity(batches.len()); schema.fields.len()];
+    for batch in batches {
+        columns
+            .iter_mut()
+            .zip(batch)
+            .for_each(|(col, arr)| col.push(arr));
+    }
+    let out = columns
+        .into_par_iter()
+        .zip(schema.fields.par_iter())
+        .map(|(mut arrs, field)| match arrs.len() {
+            0 => ArrOk::try_from_arrow(new_empty_array(field.data_type.clone())),
+            1 => ArrOk::try_from_arrow(arrs.pop().unwrap()),
+            _ => concat_arrow(arrs),
         })
-        .collect::<Vec<_>>();
+        .collect::<TResult<_>>()?;
+    Ok((schema, out))
+}
 
-    let out: Vec<ArrOk<'a>> = if !other_arrs.is_empty() {
-        let arrs = (0..arrs.len())
-            .map(|_| {
-                let mut out = Vec::with_capacity(other_arrs.len() + 1);
-                out.push(arrs.pop().unwrap());
-                other_arrs
-                    .iter_mut()
-                    .for_each(|a| out.push(a.pop().unwrap()));
-                out
-            })
+/// Write 1-d arrays into an arrow ipc file, `names` should have the same length as `arrs`.
+pub fn write_ipc<P: AsRef<Path>>(
+    path: P,
+    arrs: &[&ArrOk<'_>],
+    names: &[&str],
+    compression: Option<IpcCompression>,
+) -> TResult<()> {
+    if arrs.len() != names.len() {
+        tbail!(
+            "the number of arrays ({}) doesn't match the number of names ({})",
+            arrs.len(),
+            names.len()
+        )
+    }
+    let arrow_arrs = arrs
+        .par_iter()
+        .map(|a| a.to_arrow())
+        .collect::<TResult<Vec<_>>>()?;
+    let len = <[_]>::first(&arrow_arrs).map_or(0, |a| a.len());
+    if let Some((a, name)) = arrow_arrs.iter().zip(names).find(|(a, _)| a.len() != len) {
+        tbail!(
+            "all the columns should have the same length, column {} has length {} but expected {}",
+            name,
+            a.len(),
+            len
+        )
+    }
+    let fields = arrow_arrs
+        .iter()
+        .zip(names)
+        .map(|(a, name)| Field::new(*name, a.data_type().clone(), a.validity().is_some()))
+        .collect::<Vec<_>>();
+    let schema = Schema::from(fields);
+    let file = File::create(path)?;
+    let mut writer = FileWriter::try_new(file, schema, None, WriteOptions { compression })
+        .map_err(|e| terr!("{:?}", e))?;
+    for offset in (0..len.max(1)).step_by(RECORD_BATCH_SIZE) {
+        let length = RECORD_BATCH_SIZE.min(len - offset);
+        let arrs = arrow_arrs
+            .iter()
+            .map(|a| a.sliced(offset, length))
             .collect::<Vec<_>>();
-        arrs.into_par_iter()
-            .rev()
-            .map(|a| {
-                let a = a.into_par_iter().map(ArrOk::from_arrow).collect();
-                ArrOk::same_dtype_concat_1d(a)
-            })
-            .collect()
-    } else {
-        arrs.into_par_iter().map(ArrOk::from_arrow).collect()
-    };
-    Ok((schema, out))
+        let chunk = Chunk::try_new(arrs).map_err(|e| terr!("{:?}", e))?;
+        writer.write(&chunk, None).map_err(|e| terr!("{:?}", e))?;
+    }
+    writer.finish().map_err(|e| terr!("{:?}", e))?;
+    Ok(())
 }
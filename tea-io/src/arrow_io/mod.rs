@@ -1,8 +1,17 @@
+mod dataset;
+pub mod ffi;
 #[cfg(feature = "lazy")]
 mod impl_lazy;
 mod ipc;
+mod scan;
+mod stream;
 pub(crate) mod utils;
 
+pub use dataset::{read_dataset, IpcDataset};
 #[cfg(feature = "lazy")]
-pub use impl_lazy::{scan_ipc_lazy, DataDictIPCExt, ExprIPCExt};
-pub use ipc::{read_ipc, read_ipc_schema};
+pub use impl_lazy::{
+    scan_dataset_lazy, scan_ipc_lazy, DataDictIPCExt, ExprIPCExt, IpcDataDictReader,
+};
+pub use ipc::{read_ipc, read_ipc_schema, write_ipc, IpcCompression};
+pub use scan::{scan_ipc_filtered, CmpOp, IpcScanOptions, ScanPredicate, ScanValue};
+pub use stream::{IpcBatchReader, IpcBatchWriter};
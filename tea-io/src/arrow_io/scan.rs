@@ -0,0 +1,519 @@
+use crate::ColSelect;
+use arrow::array::{Array, BooleanArray};
+use arrow::compute::{concatenate::concatenate, filter::filter};
+use arrow::datatypes::{DataType, Schema};
+use arrow::io::ipc::read::{self, FileMetadata};
+use arrow_format::ipc::{planus::ReadAsRoot, Block, MessageHeaderRef, MessageRef};
+use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use std::cmp::Ordering;
+use std::io::{Read, Seek, SeekFrom};
+use std::{fs::File, path::Path};
+use teapy_core::prelude::*;
+
+/// Comparison operator used by [`ScanPredicate::Cmp`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// A literal value compared against the key column of a [`ScanPredicate`].
+#[derive(Clone, Debug)]
+pub enum ScanValue {
+    Bool(bool),
+    I64(i64),
+    F64(f64),
+    String(String),
+    DateTime(DateTime<unit::Nanosecond>),
+}
+
+/// A predicate pushed down into the ipc scan, rows that don't satisfy it are dropped
+/// and record batches without any satisfied row are not loaded at all.
+#[derive(Clone, Debug)]
+pub enum ScanPredicate {
+    /// `column <op> value`, null values never satisfy the comparison.
+    Cmp {
+        column: String,
+        op: CmpOp,
+        value: ScanValue,
+    },
+    /// `start <= column < end` on a datetime column, a missing bound is unbounded.
+    DateTimeRange {
+        column: String,
+        start: Option<DateTime<unit::Nanosecond>>,
+        end: Option<DateTime<unit::Nanosecond>>,
+    },
+}
+
+impl ScanPredicate {
+    #[inline]
+    pub fn column(&self) -> &str {
+        match self {
+            ScanPredicate::Cmp { column, .. } | ScanPredicate::DateTimeRange { column, .. } => {
+                column
+            }
+        }
+    }
+
+    fn eval(&self, key: &ArrOk<'_>) -> TResult<Vec<bool>> {
+        match self {
+            ScanPredicate::Cmp { op, value, .. } => {
+                let value = value.to_key(key)?;
+                eval_key(key, |k| {
+                    k.cmp_value(&value).is_some_and(|ord| match op {
+                        CmpOp::Eq => ord == Ordering::Equal,
+                        CmpOp::Ne => ord != Ordering::Equal,
+                        CmpOp::Gt => ord == Ordering::Greater,
+                        CmpOp::Ge => ord != Ordering::Less,
+                        CmpOp::Lt => ord == Ordering::Less,
+                        CmpOp::Le => ord != Ordering::Greater,
+                    })
+                })
+            }
+            ScanPredicate::DateTimeRange { start, end, .. } => {
+                if !is_datetime_key(key) {
+                    tbail!(
+                        "datetime range predicate can not be used on column of dtype {}",
+                        key.dtype_name()
+                    )
+                }
+                let start = start.and_then(opt_i64);
+                let end = end.and_then(opt_i64);
+                eval_key(key, |k| {
+                    let ScanKey::DateTime(v) = k else {
+                        return false;
+                    };
+                    start.is_none_or(|s| v >= s) && end.is_none_or(|e| v < e)
+                })
+            }
+        }
+    }
+}
+
+/// Options of [`scan_ipc_filtered`], the row range is applied before the predicate.
+pub struct IpcScanOptions<'a> {
+    pub columns: ColSelect<'a>,
+    pub offset: usize,
+    pub length: Option<usize>,
+    pub predicate: Option<ScanPredicate>,
+}
+
+impl Default for IpcScanOptions<'_> {
+    fn default() -> Self {
+        IpcScanOptions {
+            columns: ColSelect::Null,
+            offset: 0,
+            length: None,
+            predicate: None,
+        }
+    }
+}
+
+impl<'a> From<ColSelect<'a>> for IpcScanOptions<'a> {
+    fn from(columns: ColSelect<'a>) -> Self {
+        IpcScanOptions {
+            columns,
+            ..Default::default()
+        }
+    }
+}
+
+/// A value of the key column, normalized so that it can be compared with a [`ScanValue`].
+enum ScanKey<'s> {
+    Bool(bool),
+    I64(i64),
+    F64(f64),
+    Str(&'s str),
+    /// Nanoseconds since epoch.
+    DateTime(i64),
+}
+
+impl ScanValue {
+    /// Coerce the literal to the dtype of the key column, string literals are parsed
+    /// for datetime keys. A literal of an incompatible type is an error rather than
+    /// a predicate that no row satisfies. `None` if the literal is `NaT`.
+    fn to_key(&self, key: &ArrOk<'_>) -> TResult<Option<ScanKey<'_>>> {
+        let values = match key {
+            ArrOk::Masked(a) => a.values.as_ref(),
+            key => key,
+        };
+        let out = match (self, values) {
+            (ScanValue::Bool(v), ArrOk::Bool(_) | ArrOk::OptBool(_)) => Some(ScanKey::Bool(*v)),
+            (ScanValue::String(v), ArrOk::String(_)) => Some(ScanKey::Str(v.as_str())),
+            (ScanValue::DateTime(v), key) if is_datetime_key(key) => {
+                opt_i64(*v).map(ScanKey::DateTime)
+            }
+            (ScanValue::String(v), key) if is_datetime_key(key) => {
+                opt_i64(DateTime::<unit::Nanosecond>::parse(v, None)?).map(ScanKey::DateTime)
+            }
+            (ScanValue::I64(v), key) if is_numeric_key(key) => Some(ScanKey::I64(*v)),
+            (ScanValue::F64(v), key) if is_numeric_key(key) => Some(ScanKey::F64(*v)),
+            (value, _) => tbail!(
+                "literal {:?} can not be compared with column of dtype {}",
+                value,
+                key.dtype_name()
+            ),
+        };
+        Ok(out)
+    }
+}
+
+#[inline]
+fn is_numeric_key(key: &ArrOk<'_>) -> bool {
+    matches!(
+        key,
+        ArrOk::U8(_)
+            | ArrOk::U64(_)
+            | ArrOk::Usize(_)
+            | ArrOk::I8(_)
+            | ArrOk::I16(_)
+            | ArrOk::U16(_)
+            | ArrOk::U32(_)
+            | ArrOk::I32(_)
+            | ArrOk::I64(_)
+            | ArrOk::OptU8(_)
+            | ArrOk::OptU64(_)
+            | ArrOk::OptUsize(_)
+            | ArrOk::OptI32(_)
+            | ArrOk::OptI64(_)
+            | ArrOk::F32(_)
+            | ArrOk::F64(_)
+            | ArrOk::OptF32(_)
+            | ArrOk::OptF64(_)
+    )
+}
+
+#[inline]
+fn is_datetime_key(key: &ArrOk<'_>) -> bool {
+    match key {
+        ArrOk::Masked(a) => is_datetime_key(&a.values),
+        key => matches!(
+            key,
+            ArrOk::DateTimeMs(_)
+                | ArrOk::DateTimeUs(_)
+                | ArrOk::DateTimeNs(_)
+                | ArrOk::Date(_)
+                | ArrOk::DateTimeTz(_)
+        ),
+    }
+}
+
+impl<'s> ScanKey<'s> {
+    fn cmp_value(&self, value: &Option<ScanKey<'_>>) -> Option<Ordering> {
+        use ScanKey::*;
+        match (self, value.as_ref()?) {
+            (Bool(a), Bool(b)) => Some(a.cmp(b)),
+            (I64(a), I64(b)) | (DateTime(a), DateTime(b)) => Some(a.cmp(b)),
+            (I64(a), F64(b)) => (*a as f64).partial_cmp(b),
+            (F64(a), I64(b)) => a.partial_cmp(&(*b as f64)),
+            (F64(a), F64(b)) => a.partial_cmp(b),
+            (Str(a), Str(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+}
+
+#[inline]
+fn opt_i64<U: TimeUnitTrait>(dt: DateTime<U>) -> Option<i64> {
+    dt.is_not_nat().then(|| dt.into_i64())
+}
+
+/// Evaluate `f` on each value of the key column, null values are always `false`.
+fn eval_key<F: Fn(ScanKey<'_>) -> bool>(key: &ArrOk<'_>, f: F) -> TResult<Vec<bool>> {
+    macro_rules! eval {
+        ($a: expr, $v: ident => $key: expr) => {
+            $a.view()
+                .0
+                .iter()
+                .map(|$v| $key.map_or(false, &f))
+                .collect()
+        };
+    }
+    let out = match key {
+        ArrOk::Bool(a) => eval!(a, v => Some(ScanKey::Bool(*v))),
+        ArrOk::OptBool(a) => eval!(a, v => v.map(ScanKey::Bool)),
+        ArrOk::U8(a) => eval!(a, v => Some(ScanKey::I64(*v as i64))),
+        ArrOk::U64(a) => eval!(a, v => Some(ScanKey::I64(*v as i64))),
+        ArrOk::Usize(a) => eval!(a, v => Some(ScanKey::I64(*v as i64))),
+        ArrOk::I8(a) => eval!(a, v => Some(ScanKey::I64(*v as i64))),
+        ArrOk::I16(a) => eval!(a, v => Some(ScanKey::I64(*v as i64))),
+        ArrOk::U16(a) => eval!(a, v => Some(ScanKey::I64(*v as i64))),
+        ArrOk::U32(a) => eval!(a, v => Some(ScanKey::I64(*v as i64))),
+        ArrOk::OptU8(a) => eval!(a, v => v.map(|v| ScanKey::I64(v as i64))),
+        ArrOk::OptU64(a) => eval!(a, v => v.map(|v| ScanKey::I64(v as i64))),
+        ArrOk::I32(a) => eval!(a, v => Some(ScanKey::I64(*v as i64))),
+        ArrOk::I64(a) => eval!(a, v => Some(ScanKey::I64(*v))),
+        ArrOk::OptUsize(a) => eval!(a, v => v.map(|v| ScanKey::I64(v as i64))),
+        ArrOk::OptI32(a) => eval!(a, v => v.map(|v| ScanKey::I64(v as i64))),
+        ArrOk::OptI64(a) => eval!(a, v => v.map(ScanKey::I64)),
+        ArrOk::F32(a) => eval!(a, v => v.not_none().then_some(ScanKey::F64(*v as f64))),
+        ArrOk::F64(a) => eval!(a, v => v.not_none().then_some(ScanKey::F64(*v))),
+        ArrOk::OptF32(a) => eval!(a, v => v.map(|v| ScanKey::F64(v as f64))),
+        ArrOk::OptF64(a) => eval!(a, v => v.map(ScanKey::F64)),
+        ArrOk::String(a) => eval!(a, v => Some(ScanKey::Str(v.as_str()))),
+        ArrOk::DateTimeMs(a) => eval!(a, v => opt_i64(*v)
+            .map(|v| ScanKey::DateTime(v * 1_000_000))),
+        ArrOk::DateTimeUs(a) => eval!(a, v => opt_i64(*v)
+            .map(|v| ScanKey::DateTime(v * 1_000))),
+        ArrOk::DateTimeNs(a) => eval!(a, v => opt_i64(*v)
+            .map(ScanKey::DateTime)),
+        // dates are compared as the datetime at their midnight
+        ArrOk::Date(a) => eval!(a, v => opt_i64(v.to_datetime::<unit::Nanosecond>())
+            .map(ScanKey::DateTime)),
+        // timezone-aware datetimes are compared as their utc instants
+        ArrOk::DateTimeTz(a) => eval!(a.utc, v => opt_i64(*v)
+            .map(ScanKey::DateTime)),
+        ArrOk::Masked(a) => return eval_key(&a.to_dense(), f),
+        key => tbail!(
+            "dtype {} is not supported as a predicate key",
+            key.dtype_name()
+        ),
+    };
+    Ok(out)
+}
+
+/// Rows of a record batch selected by the row range.
+struct BatchRange {
+    index: usize,
+    offset: usize,
+    len: usize,
+}
+
+/// The statistics of a record batch in its message header.
+struct BatchHeader {
+    num_rows: usize,
+    /// The null count of each field node, in the depth-first order of the fields.
+    null_counts: Vec<usize>,
+}
+
+/// Read the message header of a record batch without reading its body.
+fn read_batch_header<R: Read + Seek>(reader: &mut R, block: &Block) -> TResult<BatchHeader> {
+    reader.seek(SeekFrom::Start(block.offset as u64))?;
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    if buf == [0xff; 4] {
+        // continuation marker
+        reader.read_exact(&mut buf)?;
+    }
+    let mut message = vec![0; i32::from_le_bytes(buf) as usize];
+    reader.read_exact(&mut message)?;
+    let message = MessageRef::read_as_root(&message).map_err(|e| terr!("{:?}", e))?;
+    match message.header().map_err(|e| terr!("{:?}", e))? {
+        Some(MessageHeaderRef::RecordBatch(batch)) => {
+            let null_counts = match batch.nodes().map_err(|e| terr!("{:?}", e))? {
+                Some(nodes) => nodes.iter().map(|n| n.null_count() as usize).collect(),
+                None => Vec::new(),
+            };
+            Ok(BatchHeader {
+                num_rows: batch.length().map_err(|e| terr!("{:?}", e))? as usize,
+                null_counts,
+            })
+        }
+        _ => tbail!("invalid record batch message in ipc file"),
+    }
+}
+
+/// The number of field nodes of a column in the message header of a record batch.
+fn node_count(dtype: &DataType) -> usize {
+    match dtype.to_logical_type() {
+        DataType::List(f)
+        | DataType::LargeList(f)
+        | DataType::FixedSizeList(f, _)
+        | DataType::Map(f, _) => 1 + node_count(&f.data_type),
+        DataType::Struct(fields) | DataType::Union(fields, _, _) => {
+            1 + Iterator::sum::<usize>(fields.iter().map(|f| node_count(&f.data_type)))
+        }
+        _ => 1,
+    }
+}
+
+/// Read the number of rows of an ipc file without reading the record batches.
+pub(crate) fn ipc_num_rows<P: AsRef<Path>>(path: P) -> TResult<usize> {
+    let mut file = File::open(path)?;
+    let metadata = read::read_file_metadata(&mut file).map_err(|e| terr!("{:?}", e))?;
+    let mut num_rows = 0;
+    for block in &metadata.blocks {
+        num_rows += read_batch_header(&mut file, block)?.num_rows;
+    }
+    Ok(num_rows)
+}
+
+/// Find the rows of the record batches selected by the row range. The batches
+/// whose `key` column is all null are skipped by their header as a null never
+/// satisfies the predicate.
+fn select_batches<R: Read + Seek>(
+    reader: &mut R,
+    metadata: &FileMetadata,
+    offset: usize,
+    length: Option<usize>,
+    key: Option<usize>,
+) -> TResult<Vec<BatchRange>> {
+    let end = length.map_or(usize::MAX, |l| offset.saturating_add(l));
+    let key_node = key.map(|key| {
+        Iterator::sum::<usize>(
+            metadata.schema.fields[..key]
+                .iter()
+                .map(|f| node_count(&f.data_type)),
+        )
+    });
+    let mut batch_start = 0;
+    let mut out = Vec::new();
+    for (index, block) in metadata.blocks.iter().enumerate() {
+        let header = read_batch_header(reader, block)?;
+        let batch_end = batch_start + header.num_rows;
+        let (start, stop) = (offset.max(batch_start), end.min(batch_end));
+        let all_null = key_node
+            .and_then(|n| <[_]>::get(&header.null_counts, n))
+            .is_some_and(|nulls| *nulls == header.num_rows);
+        if start < stop && !all_null {
+            out.push(BatchRange {
+                index,
+                offset: start - batch_start,
+                len: stop - start,
+            });
+        }
+        batch_start = batch_end;
+        if batch_start >= end {
+            break;
+        }
+    }
+    Ok(out)
+}
+
+/// Read the `proj` columns of an ipc file with a row range and a predicate pushed
+/// down, the arrays of the columns are returned in the order of `proj`.
+///
+/// Only the key column of the predicate is read for each selected record batch at
+/// first, the other columns are read for the batches with any satisfied row.
+pub(crate) fn read_filtered(
+    path: &Path,
+    metadata: &FileMetadata,
+    proj: &[usize],
+    offset: usize,
+    length: Option<usize>,
+    predicate: Option<&ScanPredicate>,
+) -> TResult<Vec<Box<dyn Array>>> {
+    let key = match predicate {
+        Some(predicate) => {
+            let key = crate::arrow_io::utils::columns_to_projection(
+                &[predicate.column()],
+                &metadata.schema,
+            )?;
+            let Some(&key) = <[_]>::first(&key) else {
+                tbail!("predicate column {} doesn't exist", predicate.column())
+            };
+            Some(key)
+        }
+        None => None,
+    };
+    let mut file = File::open(path)?;
+    let ranges = select_batches(&mut file, metadata, offset, length, key)?;
+    // the projection of `read_batch` should be sorted, the key column is read separately
+    let mut read_proj = proj.to_vec();
+    read_proj.sort_unstable();
+    read_proj.dedup();
+    read_proj.retain(|column| Some(*column) != key);
+    let key_in_proj = key.is_some_and(|key| proj.contains(&key));
+    let position = |column: usize| read_proj.binary_search(&column).unwrap_or(read_proj.len());
+    let dictionaries = read::read_file_dictionaries(&mut file, metadata, &mut vec![])
+        .map_err(|e| terr!("{:?}", e))?;
+    let read_range = |range: &BatchRange, proj: &[usize]| -> TResult<Vec<Box<dyn Array>>> {
+        if proj.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut file = File::open(path)?;
+        Ok(read::read_batch(
+            &mut file,
+            &dictionaries,
+            metadata,
+            Some(proj),
+            Some(range.offset + range.len),
+            range.index,
+            &mut vec![],
+            &mut vec![],
+        )
+        .map_err(|e| terr!("{:?}", e))?
+        .into_arrays()
+        .into_iter()
+        .map(|a| a.sliced(range.offset, range.len))
+        .collect())
+    };
+    let batches = ranges
+        .into_par_iter()
+        .map(|range| {
+            let (Some(predicate), Some(key)) = (predicate, key) else {
+                return read_range(&range, &read_proj).map(Some);
+            };
+            let key_arr = read_range(&range, &[key])?.pop().unwrap();
+            let mask = predicate.eval(&ArrOk::try_from_arrow(key_arr.clone())?)?;
+            if !Iterator::any(&mut mask.iter(), |v| *v) {
+                return Ok(None);
+            }
+            let mut arrs = read_range(&range, &read_proj)?;
+            if key_in_proj {
+                arrs.push(key_arr);
+            }
+            if Iterator::all(&mut mask.iter(), |v| *v) {
+                return Ok(Some(arrs));
+            }
+            let mask = BooleanArray::from_slice(mask);
+            let arrs = arrs
+                .iter()
+                .map(|a| filter(a.as_ref(), &mask).map_err(|e| terr!("{:?}", e)))
+                .collect::<TResult<Vec<_>>>()?;
+            Ok(Some(arrs))
+        })
+        .collect::<TResult<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    proj.par_iter()
+        .map(|&column| {
+            let i = position(column);
+            match batches.len() {
+                0 => Ok(arrow::array::new_empty_array(
+                    metadata.schema.fields[column].data_type.clone(),
+                )),
+                1 => Ok(batches[0][i].clone()),
+                _ => {
+                    let arrs = batches.iter().map(|b| b[i].as_ref()).collect::<Vec<_>>();
+                    concatenate(&arrs).map_err(|e| terr!("{:?}", e))
+                }
+            }
+        })
+        .collect()
+}
+
+/// Read an ipc file with a row range and a predicate pushed down,
+/// record batches ruled out by them are skipped.
+pub fn scan_ipc_filtered<'a, P: AsRef<Path>>(
+    path: P,
+    options: IpcScanOptions<'_>,
+) -> TResult<(Schema, Vec<ArrOk<'a>>)> {
+    let path = path.as_ref();
+    let metadata =
+        read::read_file_metadata(&mut File::open(path)?).map_err(|e| terr!("{:?}", e))?;
+    let mut proj = match options.columns.into_proj(&metadata.schema)? {
+        Some(proj) => proj,
+        None => (0..metadata.schema.fields.len()).collect(),
+    };
+    proj.sort_unstable();
+    proj.dedup();
+    let schema = metadata.schema.clone().filter(|i, _f| proj.contains(&i));
+    let arrs = read_filtered(
+        path,
+        &metadata,
+        &proj,
+        options.offset,
+        options.length,
+        options.predicate.as_ref(),
+    )?
+    .into_par_iter()
+    .map(ArrOk::try_from_arrow)
+    .collect::<TResult<Vec<_>>>()?;
+    Ok((schema, arrs))
+}
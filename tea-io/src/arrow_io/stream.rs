@@ -0,0 +1,192 @@
+use super::IpcCompression;
+use crate::ColSelect;
+use arrow::array::Array;
+use arrow::chunk::Chunk;
+use arrow::compute::concatenate::concatenate;
+use arrow::datatypes::{Field, Schema};
+use arrow::io::ipc::{read, write};
+use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use std::{fs::File, path::Path};
+use teapy_core::prelude::*;
+
+/// Read an ipc file batch by batch so that files larger than memory can be processed.
+///
+/// Each item is a record batch of the file, or `batch_rows` rows if it is given
+/// (the last one may be shorter).
+pub struct IpcBatchReader {
+    reader: read::FileReader<File>,
+    schema: Schema,
+    batch_rows: Option<usize>,
+    /// Arrays read from the file but not yielded yet, one `Vec` per column.
+    buffer: Vec<Vec<Box<dyn Array>>>,
+    buffered: usize,
+}
+
+impl IpcBatchReader {
+    pub fn try_new<P: AsRef<Path>>(
+        path: P,
+        columns: ColSelect<'_>,
+        batch_rows: Option<usize>,
+    ) -> TResult<Self> {
+        if batch_rows == Some(0) {
+            tbail!("batch_rows should be greater than 0")
+        }
+        let mut file = File::open(path)?;
+        let metadata = read::read_file_metadata(&mut file).map_err(|e| terr!("{:?}", e))?;
+        let proj = columns.into_proj(&metadata.schema)?.map(|mut proj| {
+            proj.sort_unstable();
+            proj.dedup();
+            proj
+        });
+        let reader = read::FileReader::new(file, metadata, proj, None);
+        let schema = reader.schema().clone();
+        Ok(IpcBatchReader {
+            buffer: vec![vec![]; schema.fields.len()],
+            reader,
+            schema,
+            batch_rows,
+            buffered: 0,
+        })
+    }
+
+    /// The schema of the selected columns.
+    #[inline]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    #[inline]
+    pub fn columns(&self) -> Vec<&str> {
+        self.schema.fields.iter().map(|f| f.name.as_str()).collect()
+    }
+
+    /// Take the first `n` buffered rows of each column.
+    fn take_buffer<'a>(&mut self, n: usize) -> TResult<Vec<ArrOk<'a>>> {
+        let arrs = std::mem::take(&mut self.buffer)
+            .into_par_iter()
+            .map(|arrs| {
+                let arr = if arrs.len() == 1 {
+                    arrs.into_iter().next().unwrap()
+                } else {
+                    let arrs = arrs.iter().map(|a| a.as_ref()).collect::<Vec<_>>();
+                    concatenate(&arrs).map_err(|e| terr!("{:?}", e))?
+                };
+                let rest = arr.sliced(n, arr.len() - n);
+                Ok((arr.sliced(0, n), rest))
+            })
+            .collect::<TResult<Vec<_>>>()?;
+        self.buffered -= n;
+        let (out, rest): (Vec<_>, Vec<_>) = arrs.into_iter().unzip();
+        self.buffer = rest
+            .into_iter()
+            .map(|a| if a.is_empty() { vec![] } else { vec![a] })
+            .collect();
+        out.into_par_iter().map(ArrOk::try_from_arrow).collect()
+    }
+}
+
+impl Iterator for IpcBatchReader {
+    type Item = TResult<Vec<ArrOk<'static>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(n) = self.batch_rows {
+                if self.buffered >= n {
+                    return Some(self.take_buffer(n));
+                }
+            }
+            match self.reader.next() {
+                Some(Ok(chunk)) => {
+                    let len = chunk.len();
+                    let arrs = chunk.into_arrays();
+                    if self.batch_rows.is_none() {
+                        return Some(arrs.into_par_iter().map(ArrOk::try_from_arrow).collect());
+                    }
+                    self.buffer
+                        .iter_mut()
+                        .zip(arrs)
+                        .for_each(|(buf, a)| buf.push(a));
+                    self.buffered += len;
+                }
+                Some(Err(e)) => return Some(Err(terr!("{:?}", e))),
+                None if self.buffered > 0 => return Some(self.take_buffer(self.buffered)),
+                None => return None,
+            }
+        }
+    }
+}
+
+/// Append 1-d arrays to an ipc file batch by batch, the schema is
+/// determined by the first batch. `finish` must be called to write the footer.
+pub struct IpcBatchWriter {
+    file: Option<File>,
+    writer: Option<(write::FileWriter<File>, Schema)>,
+    compression: Option<IpcCompression>,
+}
+
+impl IpcBatchWriter {
+    pub fn try_new<P: AsRef<Path>>(path: P, compression: Option<IpcCompression>) -> TResult<Self> {
+        Ok(IpcBatchWriter {
+            file: Some(File::create(path)?),
+            writer: None,
+            compression,
+        })
+    }
+
+    fn writer(&mut self, schema: &Schema) -> TResult<&mut write::FileWriter<File>> {
+        if self.writer.is_none() {
+            let Some(file) = self.file.take() else {
+                tbail!("failed to create the ipc writer")
+            };
+            let options = write::WriteOptions {
+                compression: self.compression,
+            };
+            let writer = write::FileWriter::try_new(file, schema.clone(), None, options)
+                .map_err(|e| terr!("{:?}", e))?;
+            self.writer = Some((writer, schema.clone()));
+        }
+        let (writer, file_schema) = self.writer.as_mut().unwrap();
+        if file_schema.fields != schema.fields {
+            tbail!(
+                "schema of the batch {:?} doesn't match the file {:?}",
+                schema.fields,
+                file_schema.fields
+            )
+        }
+        Ok(writer)
+    }
+
+    /// Write a record batch, the names and dtypes should be the same as the first batch.
+    pub fn write(&mut self, arrs: &[&ArrOk<'_>], names: &[&str]) -> TResult<()> {
+        if arrs.len() != names.len() {
+            tbail!(
+                "the number of arrays ({}) doesn't match the number of names ({})",
+                arrs.len(),
+                names.len()
+            )
+        }
+        let arrow_arrs = arrs
+            .par_iter()
+            .map(|a| a.to_arrow())
+            .collect::<TResult<Vec<_>>>()?;
+        // later batches may contain nulls even if the first one doesn't
+        let fields = arrow_arrs
+            .iter()
+            .zip(names)
+            .map(|(a, name)| Field::new(*name, a.data_type().clone(), true))
+            .collect::<Vec<_>>();
+        let schema = Schema::from(fields);
+        let writer = self.writer(&schema)?;
+        let chunk = Chunk::try_new(arrow_arrs).map_err(|e| terr!("{:?}", e))?;
+        writer.write(&chunk, None).map_err(|e| terr!("{:?}", e))
+    }
+
+    /// Write the footer of the file, an empty file is written if no batch is written.
+    pub fn finish(mut self) -> TResult<()> {
+        if self.writer.is_none() {
+            self.writer(&Schema::default())?;
+        }
+        let (writer, _schema) = self.writer.as_mut().unwrap();
+        writer.finish().map_err(|e| terr!("{:?}", e))
+    }
+}
@@ -1,4 +1,4 @@
-use teapy_core::prelude::TResult;
+use teapy_core::prelude::*;
 use teapy_core::utils::CollectTrustedToVec;
 // use tea_lazy::SingleCol;
 #[cfg(feature = "arw")]
@@ -47,6 +47,31 @@ impl<T> From<Option<T>> for ColSelect<'_> {
 }
 
 impl<'a> ColSelect<'a> {
+    /// Get the indices of the selected columns in `names`.
+    pub fn into_indices(self, names: &[String]) -> TResult<Vec<usize>> {
+        let find = |name: &str| {
+            names
+                .iter()
+                .position(|n| n == name)
+                .ok_or_else(|| terr!("column {} doesn't exist", name))
+        };
+        match self {
+            ColSelect::Idx(idx) => idx
+                .into_iter()
+                .map(|i| {
+                    let i = if i < 0 { names.len() as i32 + i } else { i };
+                    if i < 0 || i as usize >= names.len() {
+                        tbail!("column index out of bound")
+                    }
+                    Ok(i as usize)
+                })
+                .collect(),
+            ColSelect::Name(cols) => cols.into_iter().map(find).collect(),
+            ColSelect::NameOwned(cols) => cols.iter().map(|s| find(s)).collect(),
+            ColSelect::Null => Ok((0..names.len()).collect()),
+        }
+    }
+
     #[cfg(feature = "arw")]
     pub fn into_proj(self, schema: &Schema) -> TResult<Option<Vec<usize>>> {
         use super::arrow_io::utils::columns_to_projection;
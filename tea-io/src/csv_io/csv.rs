@@ -0,0 +1,438 @@
+use crate::ColSelect;
+use csv::{ReaderBuilder, StringRecord, WriterBuilder};
+use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use std::iter::zip;
+use std::path::Path;
+use teapy_core::prelude::*;
+
+/// The minimum number of bytes parsed by a thread when reading csv files.
+const MIN_CHUNK_BYTES: usize = 1 << 16;
+
+/// Options of [`read_csv`].
+pub struct CsvReadOptions<'a> {
+    pub delimiter: u8,
+    pub has_header: bool,
+    /// Tokens that should be treated as null, empty field is always null.
+    pub null_values: Vec<String>,
+    /// Dtype overrides by column name, other columns are inferred.
+    pub dtypes: Vec<(String, DataType)>,
+    /// Whether to try parsing string columns as `DateTime<Nanosecond>`.
+    pub parse_dates: bool,
+    /// Format used to parse datetime, common formats are tried if it is `None`.
+    pub datetime_fmt: Option<String>,
+    pub columns: ColSelect<'a>,
+}
+
+impl Default for CsvReadOptions<'_> {
+    fn default() -> Self {
+        CsvReadOptions {
+            delimiter: b',',
+            has_header: true,
+            null_values: vec![],
+            dtypes: vec![],
+            parse_dates: false,
+            datetime_fmt: None,
+            columns: ColSelect::Null,
+        }
+    }
+}
+
+/// Options of [`write_csv`].
+#[derive(Clone, Debug)]
+pub struct CsvWriteOptions {
+    pub delimiter: u8,
+    pub has_header: bool,
+    /// The string written for null values (`None`, `NaN` and `NaT`).
+    pub null_value: String,
+    /// Format used by `strftime` to write datetime columns.
+    pub datetime_fmt: Option<String>,
+}
+
+impl Default for CsvWriteOptions {
+    fn default() -> Self {
+        CsvWriteOptions {
+            delimiter: b',',
+            has_header: true,
+            null_value: String::new(),
+            datetime_fmt: None,
+        }
+    }
+}
+
+struct CsvColumn<'r> {
+    records: &'r [StringRecord],
+    idx: usize,
+    null_values: &'r [String],
+}
+
+impl<'r> CsvColumn<'r> {
+    #[inline]
+    fn is_null(&self, s: &str) -> bool {
+        s.is_empty() || Iterator::any(&mut self.null_values.iter(), |n| n == s)
+    }
+
+    /// Iterate the values of the column, null values are `None`.
+    #[inline]
+    fn values(&self) -> impl Iterator<Item = Option<&'r str>> + '_ {
+        self.records.iter().map(move |r| {
+            let s = r.get(self.idx).unwrap_or_default();
+            (!self.is_null(s)).then_some(s)
+        })
+    }
+
+    fn parse<'a, T, F>(&self, f: F) -> TResult<ArrOk<'a>>
+    where
+        T: Send + 'a,
+        F: Fn(Option<&'r str>) -> TResult<T> + Send + Sync,
+        ArbArray<'a, T>: Into<ArrOk<'a>>,
+    {
+        let out = self
+            .records
+            .par_iter()
+            .map(|r| {
+                let s = r.get(self.idx).unwrap_or_default();
+                f((!self.is_null(s)).then_some(s))
+            })
+            .collect::<TResult<Vec<T>>>()?;
+        let arb: ArbArray<'a, T> = Arr1::from_vec(out).into_dyn().into();
+        Ok(arb.into())
+    }
+
+    #[inline]
+    fn has_null(&self) -> bool {
+        self.records
+            .par_iter()
+            .any(|r| self.is_null(r.get(self.idx).unwrap_or_default()))
+    }
+
+    /// Read a string column with nulls as a masked array, the values of the
+    /// nulls are empty strings.
+    fn parse_masked_str<'a>(&self) -> TResult<ArrOk<'a>> {
+        let (values, validity): (Vec<_>, Vec<_>) = self
+            .records
+            .par_iter()
+            .map(|r| {
+                let s = r.get(self.idx).unwrap_or_default();
+                if self.is_null(s) {
+                    (String::new(), false)
+                } else {
+                    (s.to_owned(), true)
+                }
+            })
+            .unzip();
+        let values = Arr1::from_vec(values).into_dyn().into();
+        Ok(MaskedArray::new(values, validity.into_iter().collect())?.into())
+    }
+
+    fn infer_dtype(&self, parse_dates: bool, datetime_fmt: Option<&str>) -> DataType {
+        let mut has_null = false;
+        let (mut is_bool, mut is_int, mut is_float, mut is_dt) = (true, true, true, parse_dates);
+        let mut all_null = true;
+        for s in self.values() {
+            let Some(s) = s else {
+                has_null = true;
+                continue;
+            };
+            all_null = false;
+            is_bool = is_bool && parse_bool(s).is_some();
+            is_int = is_int && s.parse::<i64>().is_ok();
+            is_float = is_float && s.parse::<f64>().is_ok();
+            is_dt = is_dt && DateTime::<unit::Nanosecond>::parse(s, datetime_fmt).is_ok();
+            if !(is_bool || is_int || is_float || is_dt) {
+                return DataType::String;
+            }
+        }
+        match (all_null, has_null) {
+            (true, _) => DataType::F64,
+            _ if is_bool => {
+                if has_null {
+                    DataType::OptBool
+                } else {
+                    DataType::Bool
+                }
+            }
+            _ if is_int => {
+                if has_null {
+                    DataType::OptI64
+                } else {
+                    DataType::I64
+                }
+            }
+            _ if is_float => DataType::F64,
+            _ if is_dt => DataType::DateTime(TimeUnit::Nanosecond),
+            _ => DataType::String,
+        }
+    }
+
+    fn parse_as<'a>(&self, dtype: &DataType, datetime_fmt: Option<&str>) -> TResult<ArrOk<'a>> {
+        fn not_null<T>(v: Option<T>) -> TResult<T> {
+            v.ok_or_else(|| terr!("null value in a non-nullable csv column"))
+        }
+        fn num<T: std::str::FromStr>(s: &str) -> TResult<T> {
+            s.trim()
+                .parse::<T>()
+                .map_err(|_| terr!(ParseError: "can not parse {:?} as a number", s))
+        }
+        fn bool_(s: &str) -> TResult<bool> {
+            parse_bool(s).ok_or_else(|| terr!(ParseError: "can not parse {:?} as bool", s))
+        }
+        macro_rules! parse_dt {
+            ($unit: ident) => {
+                self.parse(|s| {
+                    s.map_or(Ok(DateTime::<unit::$unit>::nat()), |s| {
+                        DateTime::parse(s, datetime_fmt)
+                    })
+                })
+            };
+        }
+        match dtype {
+            DataType::Bool => self.parse(|s| bool_(not_null(s)?)),
+            DataType::OptBool => self.parse(|s| s.map(bool_).transpose()),
+            DataType::I32 => self.parse(|s| num::<i32>(not_null(s)?)),
+            DataType::I64 => self.parse(|s| num::<i64>(not_null(s)?)),
+            DataType::OptI32 => self.parse(|s| s.map(num::<i32>).transpose()),
+            DataType::OptI64 => self.parse(|s| s.map(num::<i64>).transpose()),
+            DataType::F32 => self.parse(|s| s.map_or(Ok(f32::NAN), num::<f32>)),
+            DataType::F64 => self.parse(|s| s.map_or(Ok(f64::NAN), num::<f64>)),
+            DataType::OptF64 => self.parse(|s| s.map(num::<f64>).transpose()),
+            DataType::String => self.parse(|s| Ok(s.unwrap_or_default().to_owned())),
+            DataType::DateTime(TimeUnit::Millisecond) => parse_dt!(Millisecond),
+            DataType::DateTime(TimeUnit::Microsecond) => parse_dt!(Microsecond),
+            DataType::DateTime(TimeUnit::Nanosecond) => parse_dt!(Nanosecond),
+            dtype => tbail!("dtype {:?} is not supported when reading csv", dtype),
+        }
+    }
+}
+
+#[inline]
+fn parse_bool(s: &str) -> Option<bool> {
+    if s.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if s.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Split the records after `start` into chunks of lines, so that they can be
+/// parsed in parallel.
+///
+/// The data is not split if it has quoted fields, as a quoted field may
+/// contain line breaks.
+fn split_lines(data: &[u8], start: usize) -> Vec<&[u8]> {
+    let data = &data[start..];
+    if data.is_empty() {
+        return vec![];
+    }
+    if data.contains(&b'"') {
+        return vec![data];
+    }
+    let chunk_size = (data.len() / rayon::current_num_threads()).max(MIN_CHUNK_BYTES);
+    let mut out = vec![];
+    let mut lo = 0;
+    while lo < data.len() {
+        let hi = match data.get(lo + chunk_size..) {
+            Some(rest) => rest
+                .iter()
+                .position(|b| *b == b'\n')
+                .map_or(data.len(), |i| lo + chunk_size + i + 1),
+            None => data.len(),
+        };
+        out.push(&data[lo..hi]);
+        lo = hi;
+    }
+    out
+}
+
+/// Read a csv file into 1-d arrays, returns the column names and the arrays.
+///
+/// The file is split into chunks at line boundaries and the chunks are parsed
+/// in parallel, then the columns are inferred and parsed in parallel.
+/// String columns with nulls are read as masked `String` columns, which keep
+/// the nulls in a validity bitmap.
+pub fn read_csv<'a, P: AsRef<Path>>(
+    path: P,
+    options: CsvReadOptions<'_>,
+) -> TResult<(Vec<String>, Vec<ArrOk<'a>>)> {
+    let data = std::fs::read(path)?;
+    let (headers, start) = if options.has_header {
+        let mut reader = ReaderBuilder::new()
+            .delimiter(options.delimiter)
+            .from_reader(data.as_slice());
+        let headers = reader.headers().map_err(|e| terr!("{}", e))?.clone();
+        (Some(headers), reader.position().byte() as usize)
+    } else {
+        (None, 0)
+    };
+    let chunks = split_lines(&data, start)
+        .into_par_iter()
+        .map(|chunk| {
+            ReaderBuilder::new()
+                .delimiter(options.delimiter)
+                .has_headers(false)
+                .from_reader(chunk)
+                .records()
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| terr!("{}", e))
+        })
+        .collect::<TResult<Vec<_>>>()?;
+    let width = headers
+        .as_ref()
+        .map(|h| h.len())
+        .or_else(|| chunks.iter().flatten().next().map(|r| r.len()))
+        .unwrap_or(0);
+    // each chunk is checked by its reader, so only the first record of a chunk is checked here
+    if let Some(r) = chunks
+        .iter()
+        .filter_map(|c| c.first())
+        .find(|r| r.len() != width)
+    {
+        tbail!(
+            "found record with {} fields, but the previous record has {} fields",
+            r.len(),
+            width
+        )
+    }
+    let records = chunks.into_iter().flatten().collect::<Vec<_>>();
+    let names: Vec<String> = match headers {
+        Some(headers) => headers.iter().map(|s| s.to_owned()).collect(),
+        None => (0..width).map(|i| format!("column_{i}")).collect(),
+    };
+
+    let selected = options.columns.into_indices(&names)?;
+    let datetime_fmt = options.datetime_fmt.as_deref();
+    let arrs = selected
+        .into_par_iter()
+        .map(|idx| {
+            let col = CsvColumn {
+                records: &records,
+                idx,
+                null_values: &options.null_values,
+            };
+            let inferred;
+            let dtype = match options.dtypes.iter().find(|(name, _)| name == &names[idx]) {
+                Some((_, dtype)) => dtype,
+                None => {
+                    inferred = col.infer_dtype(options.parse_dates, datetime_fmt);
+                    &inferred
+                }
+            };
+            let arr = match dtype {
+                DataType::String | DataType::Object if col.has_null() => col.parse_masked_str()?,
+                DataType::Object => col.parse_as(&DataType::String, datetime_fmt)?,
+                dtype => col.parse_as(dtype, datetime_fmt)?,
+            };
+            Ok((names[idx].clone(), arr))
+        })
+        .collect::<TResult<Vec<_>>>()?;
+    Ok(arrs.into_iter().unzip())
+}
+
+#[inline]
+fn format_opt<T: ToString>(v: Option<T>, null_value: &str) -> String {
+    v.map_or_else(|| null_value.to_owned(), |v| v.to_string())
+}
+
+fn format_column(arr: &ArrOk<'_>, options: &CsvWriteOptions) -> TResult<Vec<String>> {
+    let null = options.null_value.as_str();
+    let fmt = options.datetime_fmt.as_deref();
+    macro_rules! format_arr {
+        ($a: expr, $f: expr) => {
+            $a.view().0.iter().map($f).collect::<Vec<_>>()
+        };
+    }
+    let out = match arr {
+        ArrOk::Bool(a) => format_arr!(a, |v| v.to_string()),
+        ArrOk::U8(a) => format_arr!(a, |v| v.to_string()),
+        ArrOk::U64(a) => format_arr!(a, |v| v.to_string()),
+        ArrOk::Usize(a) => format_arr!(a, |v| v.to_string()),
+        ArrOk::I32(a) => format_arr!(a, |v| v.to_string()),
+        ArrOk::I64(a) => format_arr!(a, |v| v.to_string()),
+        ArrOk::F32(a) => format_arr!(a, |v| format_opt(v.not_none().then_some(v), null)),
+        ArrOk::F64(a) => format_arr!(a, |v| format_opt(v.not_none().then_some(v), null)),
+        ArrOk::OptBool(a) => format_arr!(a, |v| format_opt(*v, null)),
+        ArrOk::OptUsize(a) => format_arr!(a, |v| format_opt(*v, null)),
+        ArrOk::OptI32(a) => format_arr!(a, |v| format_opt(*v, null)),
+        ArrOk::OptI64(a) => format_arr!(a, |v| format_opt(*v, null)),
+        ArrOk::OptF32(a) => format_arr!(a, |v| format_opt(*v, null)),
+        ArrOk::OptF64(a) => format_arr!(a, |v| format_opt(*v, null)),
+        ArrOk::String(a) => format_arr!(a, |v| v.clone()),
+        // masked strings are formatted without converting the nulls to python objects
+        ArrOk::Masked(a) => match a.values.as_ref() {
+            ArrOk::String(values) => zip(values.view().0.iter(), a.validity.iter())
+                .map(|(v, valid)| if valid { v.clone() } else { null.to_owned() })
+                .collect(),
+            _ => format_column(&a.to_dense(), options)?,
+        },
+        ArrOk::Object(a) => format_arr!(a, |v| if v.is_none() {
+            null.to_owned()
+        } else {
+            v.to_string()
+        }),
+        ArrOk::DateTimeMs(a) => format_arr!(a, |v| if v.is_nat() {
+            null.to_owned()
+        } else {
+            v.strftime(fmt)
+        }),
+        ArrOk::DateTimeUs(a) => format_arr!(a, |v| if v.is_nat() {
+            null.to_owned()
+        } else {
+            v.strftime(fmt)
+        }),
+        ArrOk::DateTimeNs(a) => format_arr!(a, |v| if v.is_nat() {
+            null.to_owned()
+        } else {
+            v.strftime(fmt)
+        }),
+        arr => tbail!("dtype {:?} can not be written to csv", arr.dtype()),
+    };
+    Ok(out)
+}
+
+/// Write 1-d arrays into a csv file, `names` should have the same length as `arrs`.
+pub fn write_csv<P: AsRef<Path>>(
+    path: P,
+    arrs: &[&ArrOk<'_>],
+    names: &[&str],
+    options: CsvWriteOptions,
+) -> TResult<()> {
+    if arrs.len() != names.len() {
+        tbail!(
+            "the number of arrays ({}) doesn't match the number of names ({})",
+            arrs.len(),
+            names.len()
+        )
+    }
+    if Iterator::any(&mut arrs.iter(), |a| a.ndim() > 1) {
+        tbail!("Only 1-d array can be written to csv")
+    }
+    // python objects are formatted by the calling thread, which may hold the gil
+    let columns = if Iterator::any(&mut arrs.iter(), |a| matches!(a, ArrOk::Object(_))) {
+        arrs.iter()
+            .map(|a| format_column(a, &options))
+            .collect::<TResult<Vec<_>>>()?
+    } else {
+        arrs.par_iter()
+            .map(|a| format_column(a, &options))
+            .collect::<TResult<Vec<_>>>()?
+    };
+    let len = <[_]>::first(&columns).map_or(0, |c| c.len());
+    if Iterator::any(&mut columns.iter(), |c| c.len() != len) {
+        tbail!("all the columns written to csv should have the same length")
+    }
+    let mut writer = WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .from_path(path)
+        .map_err(|e| terr!("{}", e))?;
+    if options.has_header {
+        writer.write_record(names).map_err(|e| terr!("{}", e))?;
+    }
+    for i in 0..len {
+        writer
+            .write_record(columns.iter().map(|c| c[i].as_str()))
+            .map_err(|e| terr!("{}", e))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
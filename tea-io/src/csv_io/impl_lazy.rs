@@ -0,0 +1,30 @@
+use super::{CsvReadOptions, CsvWriteOptions};
+use std::path::Path;
+use tea_lazy::{ColumnSelector, DataDict, Expr};
+use teapy_core::prelude::TResult;
+
+#[ext_trait]
+impl<'a> DataDictCsvExt for DataDict<'a> {
+    pub fn read_csv<P: AsRef<Path>>(path: P, options: CsvReadOptions<'_>) -> TResult<DataDict<'a>> {
+        let (names, arr_vec) = super::read_csv(path, options)?;
+        let data: Vec<Expr<'a>> = names
+            .into_iter()
+            .zip(arr_vec)
+            .map(|(name, a)| Expr::new_from_arr(a, Some(name)))
+            .collect();
+        Ok(DataDict::new(data, None))
+    }
+
+    /// Evaluate all the columns of the `DataDict` and write them into a csv file.
+    pub fn write_csv<P: AsRef<Path>>(&mut self, path: P, options: CsvWriteOptions) -> TResult<()> {
+        self.eval_inplace(ColumnSelector::All, true)?;
+        self.data.iter().for_each(|e| e.prepare());
+        let arrs = self
+            .data
+            .iter()
+            .map(|e| e.view_raw_arr(None))
+            .collect::<TResult<Vec<_>>>()?;
+        let columns = self.columns();
+        super::write_csv(path, &arrs, &columns, options)
+    }
+}
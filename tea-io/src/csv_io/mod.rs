@@ -0,0 +1,7 @@
+mod csv;
+#[cfg(feature = "lazy")]
+mod impl_lazy;
+
+pub use csv::{read_csv, write_csv, CsvReadOptions, CsvWriteOptions};
+#[cfg(feature = "lazy")]
+pub use impl_lazy::DataDictCsvExt;
@@ -4,7 +4,32 @@ extern crate tea_macros;
 
 mod arrow_io;
 mod colselect;
+#[cfg(feature = "csv")]
+mod csv_io;
+#[cfg(feature = "npy")]
+mod npy_io;
+#[cfg(feature = "parquet")]
+mod parquet_io;
 
 #[cfg(feature = "arw")]
-pub use arrow_io::{read_ipc, read_ipc_schema, scan_ipc_lazy, DataDictIPCExt, ExprIPCExt};
+pub use arrow_io::ffi;
+#[cfg(feature = "arw")]
+pub use arrow_io::{
+    read_dataset, read_ipc, read_ipc_schema, scan_dataset_lazy, scan_ipc_filtered, scan_ipc_lazy,
+    write_ipc, CmpOp, DataDictIPCExt, ExprIPCExt, IpcBatchReader, IpcBatchWriter, IpcCompression,
+    IpcDataDictReader, IpcDataset, IpcScanOptions, ScanPredicate, ScanValue,
+};
 pub use colselect::{ColSelect, SingleCol};
+#[cfg(all(feature = "csv", feature = "lazy"))]
+pub use csv_io::DataDictCsvExt;
+#[cfg(feature = "csv")]
+pub use csv_io::{read_csv, write_csv, CsvReadOptions, CsvWriteOptions};
+#[cfg(all(feature = "npy", feature = "lazy"))]
+pub use npy_io::{DataDictNpyExt, ExprNpyExt};
+#[cfg(feature = "npy")]
+pub use npy_io::{read_npy, read_npz, write_npy, write_npz};
+#[cfg(feature = "parquet")]
+pub use parquet_io::{
+    read_parquet, read_parquet_schema, scan_parquet_lazy, write_parquet, DataDictParquetExt,
+    ExprParquetExt, ParquetCompression,
+};
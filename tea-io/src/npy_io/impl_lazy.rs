@@ -0,0 +1,40 @@
+use crate::ColSelect;
+use std::path::Path;
+use tea_lazy::{ColumnSelector, DataDict, Expr};
+use teapy_core::prelude::TResult;
+
+#[ext_trait]
+impl<'a> ExprNpyExt for Expr<'a> {
+    pub fn read_npy<P: AsRef<Path>>(path: P) -> TResult<Expr<'a>> {
+        let arr = super::read_npy(path)?;
+        Ok(Expr::new_from_arr(arr, None))
+    }
+}
+
+#[ext_trait]
+impl<'a> DataDictNpyExt for DataDict<'a> {
+    /// Read the arrays of a npz file, each entry is read as a column.
+    pub fn read_npz<P: AsRef<Path>>(path: P, columns: ColSelect<'_>) -> TResult<DataDict<'a>> {
+        let (names, arr_vec) = super::read_npz(path, columns)?;
+        let data: Vec<Expr<'a>> = names
+            .into_iter()
+            .zip(arr_vec)
+            .map(|(name, a)| Expr::new_from_arr(a, Some(name)))
+            .collect();
+        Ok(DataDict::new(data, None))
+    }
+
+    /// Evaluate all the columns of the `DataDict` and write them into a npz file.
+    pub fn write_npz<P: AsRef<Path>>(&mut self, path: P, compress: bool) -> TResult<()> {
+        self.eval_inplace(ColumnSelector::All, true)?;
+        self.data.iter().for_each(|e| e.prepare());
+        let arrs = self
+            .data
+            .iter()
+            .map(|e| e.view_arr(None))
+            .collect::<TResult<Vec<_>>>()?;
+        let arrs = arrs.iter().map(|a| &**a).collect::<Vec<_>>();
+        let columns = self.columns();
+        super::write_npz(path, &arrs, &columns, compress)
+    }
+}
@@ -0,0 +1,7 @@
+#[cfg(feature = "lazy")]
+mod impl_lazy;
+mod npy;
+
+#[cfg(feature = "lazy")]
+pub use impl_lazy::{DataDictNpyExt, ExprNpyExt};
+pub use npy::{read_npy, read_npz, write_npy, write_npz};
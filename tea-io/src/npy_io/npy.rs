@@ -0,0 +1,357 @@
+use crate::ColSelect;
+use memmap::Mmap;
+use ndarray::{ArrayD, ArrayViewD, IxDyn, ShapeBuilder};
+use std::any::Any;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use teapy_core::prelude::*;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Dtypes that can be read from and written to npy files.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum NpyDtype {
+    Bool,
+    U8,
+    U64,
+    I32,
+    I64,
+    F32,
+    F64,
+    DateTimeMs,
+    DateTimeUs,
+    DateTimeNs,
+}
+
+impl NpyDtype {
+    fn from_descr(descr: &str) -> TResult<Self> {
+        if descr.starts_with('>') {
+            tbail!("big endian npy dtype {} is not supported", descr)
+        }
+        // `=` is the native byte order, which is little endian on supported platforms
+        let dtype = match descr.trim_start_matches(['<', '|', '=']) {
+            "b1" => NpyDtype::Bool,
+            "u1" => NpyDtype::U8,
+            "u8" => NpyDtype::U64,
+            "i4" => NpyDtype::I32,
+            "i8" => NpyDtype::I64,
+            "f4" => NpyDtype::F32,
+            "f8" => NpyDtype::F64,
+            "M8[ms]" => NpyDtype::DateTimeMs,
+            "M8[us]" => NpyDtype::DateTimeUs,
+            "M8[ns]" => NpyDtype::DateTimeNs,
+            _ => tbail!("npy dtype {} is not supported", descr),
+        };
+        Ok(dtype)
+    }
+
+    fn descr(&self) -> &'static str {
+        match self {
+            NpyDtype::Bool => "|b1",
+            NpyDtype::U8 => "|u1",
+            NpyDtype::U64 => "<u8",
+            NpyDtype::I32 => "<i4",
+            NpyDtype::I64 => "<i8",
+            NpyDtype::F32 => "<f4",
+            NpyDtype::F64 => "<f8",
+            NpyDtype::DateTimeMs => "<M8[ms]",
+            NpyDtype::DateTimeUs => "<M8[us]",
+            NpyDtype::DateTimeNs => "<M8[ns]",
+        }
+    }
+}
+
+struct NpyHeader {
+    dtype: NpyDtype,
+    fortran_order: bool,
+    shape: Vec<usize>,
+}
+
+/// Find the value of `key` in the header dict, the rest of the header is returned.
+fn header_value<'h>(header: &'h str, key: &str) -> TResult<&'h str> {
+    let pat = format!("'{key}':");
+    let start = header
+        .find(&pat)
+        .ok_or_else(|| terr!("key {} is missing in the npy header", key))?;
+    Ok(header[start + pat.len()..].trim_start())
+}
+
+/// Parse the header of a npy file, returns the header and the offset of the data.
+fn parse_header(bytes: &[u8]) -> TResult<(NpyHeader, usize)> {
+    if bytes.len() < 10 || !bytes.starts_with(MAGIC) {
+        tbail!("not a npy file")
+    }
+    let (len, start) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 if bytes.len() >= 12 => (
+            u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize,
+            12,
+        ),
+        v => tbail!("npy format version {} is not supported", v),
+    };
+    let header = bytes
+        .get(start..start + len)
+        .ok_or_else(|| terr!("npy header is truncated"))?;
+    let header = std::str::from_utf8(header).map_err(|e| terr!("{}", e))?;
+
+    let descr = header_value(header, "descr")?;
+    let Some(descr) = descr.strip_prefix('\'').and_then(|d| d.split('\'').next()) else {
+        tbail!("npy dtype {} is not supported", descr)
+    };
+    let fortran_order = header_value(header, "fortran_order")?.starts_with("True");
+    let shape = header_value(header, "shape")?;
+    let shape = shape
+        .strip_prefix('(')
+        .and_then(|s| s.split(')').next())
+        .ok_or_else(|| terr!("invalid shape in the npy header"))?
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<usize>().map_err(|e| terr!("{}", e)))
+        .collect::<TResult<Vec<_>>>()?;
+    let header = NpyHeader {
+        dtype: NpyDtype::from_descr(descr)?,
+        fortran_order,
+        shape,
+    };
+    Ok((header, start + len))
+}
+
+/// Create an array on the data of a npy file, the data is viewed without copying
+/// if it is aligned, and `owner` is kept alive by the array.
+///
+/// # Safety
+///
+/// Any bit pattern of the data should be a valid `T`.
+unsafe fn view_or_copy<'a, T, O>(owner: O, offset: usize, header: &NpyHeader) -> TResult<ArrOk<'a>>
+where
+    T: Copy + 'a,
+    O: AsRef<[u8]> + Any + Send + Sync,
+    ArbArray<'a, T>: Into<ArrOk<'a>>,
+{
+    let len: usize = header.shape.iter().product();
+    let data = &owner.as_ref()[offset..];
+    let nbytes = len * std::mem::size_of::<T>();
+    if data.len() < nbytes {
+        tbail!(
+            "npy data is truncated, expect {} bytes, got {}",
+            nbytes,
+            data.len()
+        )
+    }
+    let shape = IxDyn(&header.shape).set_f(header.fortran_order);
+    let ptr = data.as_ptr() as *const T;
+    let arb: ArbArray<'a, T> = if ptr.align_offset(std::mem::align_of::<T>()) == 0 {
+        let view: ArrayViewD<'a, T> = ArrayViewD::from_shape_ptr(shape, ptr);
+        ViewOnBase::new_from_owner(owner, view.wrap()).into()
+    } else {
+        let vec = (0..len).map(|i| ptr.add(i).read_unaligned()).collect();
+        ArrayD::from_shape_vec(shape, vec)
+            .map_err(|e| terr!("{}", e))?
+            .wrap()
+            .into()
+    };
+    Ok(arb.into())
+}
+
+/// Parse a npy file in memory.
+fn npy_from_bytes<'a, O: AsRef<[u8]> + Any + Send + Sync>(owner: O) -> TResult<ArrOk<'a>> {
+    let (header, offset) = parse_header(owner.as_ref())?;
+    // safety: numpy only writes 0 and 1 for bool, which is checked,
+    // and any bit pattern is valid for the other dtypes
+    unsafe {
+        match header.dtype {
+            NpyDtype::Bool => {
+                let len = header.shape.iter().product::<usize>();
+                let data = owner.as_ref().get(offset..offset + len).unwrap_or_default();
+                if Iterator::any(&mut data.iter(), |b| *b > 1) {
+                    tbail!("invalid bool value in the npy data")
+                }
+                view_or_copy::<bool, _>(owner, offset, &header)
+            }
+            NpyDtype::U8 => view_or_copy::<u8, _>(owner, offset, &header),
+            NpyDtype::U64 => view_or_copy::<u64, _>(owner, offset, &header),
+            NpyDtype::I32 => view_or_copy::<i32, _>(owner, offset, &header),
+            NpyDtype::I64 => view_or_copy::<i64, _>(owner, offset, &header),
+            NpyDtype::F32 => view_or_copy::<f32, _>(owner, offset, &header),
+            NpyDtype::F64 => view_or_copy::<f64, _>(owner, offset, &header),
+            NpyDtype::DateTimeMs => {
+                view_or_copy::<DateTime<unit::Millisecond>, _>(owner, offset, &header)
+            }
+            NpyDtype::DateTimeUs => {
+                view_or_copy::<DateTime<unit::Microsecond>, _>(owner, offset, &header)
+            }
+            NpyDtype::DateTimeNs => {
+                view_or_copy::<DateTime<unit::Nanosecond>, _>(owner, offset, &header)
+            }
+        }
+    }
+}
+
+/// Read a npy file, the file is memory-mapped so the data is only loaded when it's used.
+pub fn read_npy<'a, P: AsRef<Path>>(path: P) -> TResult<ArrOk<'a>> {
+    let file = File::open(path)?;
+    let mmap = unsafe { Mmap::map(&file)? };
+    npy_from_bytes(mmap)
+}
+
+fn write_header<W: Write>(writer: &mut W, dtype: NpyDtype, shape: &[usize]) -> TResult<()> {
+    let shape = match shape {
+        [n] => format!("({n},)"),
+        shape => {
+            let shape = shape.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+            format!("({})", shape.join(", "))
+        }
+    };
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        dtype.descr(),
+        shape
+    );
+    // the data is aligned to 64 bytes
+    let len = MAGIC.len() + 4 + header.len() + 1;
+    header.push_str(&" ".repeat(len.next_multiple_of(64) - len));
+    header.push('\n');
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[1, 0])?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+    Ok(())
+}
+
+fn write_view<W: Write, T: Copy>(
+    writer: &mut W,
+    view: ArrViewD<'_, T>,
+    dtype: NpyDtype,
+) -> TResult<()> {
+    fn as_bytes<T>(s: &[T]) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(s.as_ptr() as *const u8, std::mem::size_of_val(s)) }
+    }
+    write_header(writer, dtype, view.0.shape())?;
+    // the data is written in c order
+    if let Some(s) = view.0.as_slice() {
+        writer.write_all(as_bytes(s))?;
+    } else {
+        let data = view.0.iter().copied().collect::<Vec<_>>();
+        writer.write_all(as_bytes(&data))?;
+    }
+    Ok(())
+}
+
+fn write_npy_to<W: Write>(writer: &mut W, arr: &ArrOk<'_>) -> TResult<()> {
+    match arr {
+        ArrOk::Bool(a) => write_view(writer, a.view(), NpyDtype::Bool),
+        ArrOk::U8(a) => write_view(writer, a.view(), NpyDtype::U8),
+        ArrOk::U64(a) => write_view(writer, a.view(), NpyDtype::U64),
+        ArrOk::Usize(a) => write_view(writer, a.view().mapv(|v| v as u64).view(), NpyDtype::U64),
+        ArrOk::I32(a) => write_view(writer, a.view(), NpyDtype::I32),
+        ArrOk::I64(a) => write_view(writer, a.view(), NpyDtype::I64),
+        ArrOk::F32(a) => write_view(writer, a.view(), NpyDtype::F32),
+        ArrOk::F64(a) => write_view(writer, a.view(), NpyDtype::F64),
+        ArrOk::DateTimeMs(a) => write_view(writer, a.view(), NpyDtype::DateTimeMs),
+        ArrOk::DateTimeUs(a) => write_view(writer, a.view(), NpyDtype::DateTimeUs),
+        ArrOk::DateTimeNs(a) => write_view(writer, a.view(), NpyDtype::DateTimeNs),
+        arr => tbail!("dtype {:?} can not be written to npy", arr.dtype()),
+    }
+}
+
+/// Write an array of any dimension into a npy file.
+pub fn write_npy<P: AsRef<Path>>(path: P, arr: &ArrOk<'_>) -> TResult<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_npy_to(&mut writer, arr)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// A stored entry of a memory-mapped npz file.
+struct MmapEntry {
+    mmap: Arc<Mmap>,
+    start: usize,
+    end: usize,
+}
+
+impl AsRef<[u8]> for MmapEntry {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.mmap[self.start..self.end]
+    }
+}
+
+/// Read the arrays of a npz file, the names of the entries without `.npy` are
+/// returned as the names of the arrays.
+///
+/// Uncompressed entries (`numpy.savez`) are memory-mapped, compressed entries
+/// (`numpy.savez_compressed`) are decompressed into memory.
+pub fn read_npz<'a, P: AsRef<Path>>(
+    path: P,
+    columns: ColSelect<'_>,
+) -> TResult<(Vec<String>, Vec<ArrOk<'a>>)> {
+    let file = File::open(path)?;
+    let mmap = Arc::new(unsafe { Mmap::map(&file)? });
+    let mut archive = ZipArchive::new(file).map_err(|e| terr!("{}", e))?;
+    let names = (0..archive.len())
+        .map(|i| {
+            let entry = archive.by_index_raw(i).map_err(|e| terr!("{}", e))?;
+            Ok(entry.name().trim_end_matches(".npy").to_owned())
+        })
+        .collect::<TResult<Vec<_>>>()?;
+    let selected = columns.into_indices(&names)?;
+    let arrs = selected
+        .iter()
+        .map(|&i| {
+            let mut entry = archive.by_index(i).map_err(|e| terr!("{}", e))?;
+            if entry.compression() == CompressionMethod::Stored {
+                let start = entry.data_start() as usize;
+                let owner = MmapEntry {
+                    mmap: mmap.clone(),
+                    start,
+                    end: start + entry.size() as usize,
+                };
+                npy_from_bytes(owner)
+            } else {
+                let mut buf = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut buf)?;
+                npy_from_bytes(buf)
+            }
+        })
+        .collect::<TResult<Vec<_>>>()?;
+    let names = selected.into_iter().map(|i| names[i].clone()).collect();
+    Ok((names, arrs))
+}
+
+/// Write arrays into a npz file, each array is an entry named `{name}.npy`.
+pub fn write_npz<P: AsRef<Path>>(
+    path: P,
+    arrs: &[&ArrOk<'_>],
+    names: &[&str],
+    compress: bool,
+) -> TResult<()> {
+    if arrs.len() != names.len() {
+        tbail!(
+            "the number of arrays ({}) doesn't match the number of names ({})",
+            arrs.len(),
+            names.len()
+        )
+    }
+    let method = if compress {
+        CompressionMethod::Deflated
+    } else {
+        CompressionMethod::Stored
+    };
+    let mut writer = ZipWriter::new(File::create(path)?);
+    for (arr, name) in arrs.iter().zip(names) {
+        // entries larger than 4GB need the zip64 extension
+        let options = SimpleFileOptions::default()
+            .compression_method(method)
+            .large_file(arr.len() * 8 >= u32::MAX as usize);
+        writer
+            .start_file(format!("{name}.npy"), options)
+            .map_err(|e| terr!("{}", e))?;
+        write_npy_to(&mut writer, arr)?;
+    }
+    writer.finish().map_err(|e| terr!("{}", e))?;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use super::ParquetCompression;
+use crate::{ColSelect, SingleCol};
+use std::path::Path;
+use tea_lazy::{ColumnSelector, DataDict, Expr, Op};
+use teapy_core::prelude::TResult;
+use teapy_core::utils::CollectTrustedToVec;
+
+#[ext_trait]
+impl<'a> ExprParquetExt for Expr<'a> {
+    pub fn read_parquet<P>(p: P, col: SingleCol<'a>) -> Self
+    where
+        P: AsRef<Path> + Send + Sync + Clone + 'a,
+    {
+        let mut e: Expr<'a> = 0_i32.into();
+        let name = if let SingleCol::Name(s) = col {
+            Some(s.to_owned())
+        } else if let SingleCol::NameOwned(s) = &col {
+            Some(s.clone())
+        } else {
+            None
+        };
+        e.set_name(name);
+        // a source node doesn't reference the context
+        let op = Op::new("read_parquet").param("path", p.as_ref().to_string_lossy().as_ref());
+        e.chain_op(op, move |(_data, _ctx)| {
+            let (_schema, mut arr) = super::read_parquet(p.clone(), col.clone().into())?;
+            let arr = arr.pop().unwrap();
+            Ok((arr.into(), None))
+        });
+        e
+    }
+
+    /// Evaluate the expression and write it into a parquet file,
+    /// the name of the column will be `column_0` if the expression has no name.
+    pub fn write_parquet<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        compression: Option<ParquetCompression>,
+    ) -> TResult<()> {
+        self.eval_inplace(None)?;
+        self.prepare();
+        let name = self.name().unwrap_or_else(|| "column_0".to_owned());
+        let arr = self.view_raw_arr(None)?;
+        super::write_parquet(path, &[arr], &[name.as_str()], compression)
+    }
+}
+
+pub fn scan_parquet_lazy<'a, P>(path: P, columns: ColSelect<'_>) -> TResult<Vec<Expr<'a>>>
+where
+    P: AsRef<Path> + Send + Sync + Clone + 'a,
+{
+    let mut schema = super::read_parquet_schema(path.clone())?;
+    let proj = columns.into_proj(&schema)?;
+    if let Some(proj) = proj {
+        schema = schema.filter(|i, _f| proj.contains(&i));
+    }
+    let out = schema
+        .fields
+        .into_iter()
+        .map(|f| Expr::read_parquet(path.clone(), f.name.into()))
+        .collect_trusted();
+    Ok(out)
+}
+
+#[ext_trait]
+impl<'a> DataDictParquetExt for DataDict<'a> {
+    pub fn read_parquet<P: AsRef<Path>>(path: P, columns: ColSelect<'_>) -> TResult<DataDict<'a>> {
+        let (schema, arr_vec) = super::read_parquet(path, columns)?;
+        let data: Vec<Expr<'a>> = schema
+            .fields
+            .into_iter()
+            .zip(arr_vec)
+            .map(|(s, a)| Expr::new_from_arr(a, Some(s.name)))
+            .collect();
+        Ok(DataDict::new(data, None))
+    }
+
+    pub fn scan_parquet<P>(path: P, columns: ColSelect<'_>) -> TResult<DataDict<'a>>
+    where
+        P: AsRef<Path> + Send + Sync + Clone + 'a,
+    {
+        let out = scan_parquet_lazy(path, columns)?;
+        Ok(Self::new(out, None))
+    }
+
+    /// Evaluate all the columns of the `DataDict` and write them into a parquet file.
+    pub fn write_parquet<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        compression: Option<ParquetCompression>,
+    ) -> TResult<()> {
+        self.eval_inplace(ColumnSelector::All, true)?;
+        self.data.iter().for_each(|e| e.prepare());
+        let arrs = self
+            .data
+            .iter()
+            .map(|e| e.view_raw_arr(None))
+            .collect::<TResult<Vec<_>>>()?;
+        let columns = self.columns();
+        super::write_parquet(path, &arrs, &columns, compression)
+    }
+}
@@ -0,0 +1,7 @@
+#[cfg(feature = "lazy")]
+mod impl_lazy;
+mod parquet;
+
+#[cfg(feature = "lazy")]
+pub use impl_lazy::{scan_parquet_lazy, DataDictParquetExt, ExprParquetExt};
+pub use parquet::{read_parquet, read_parquet_schema, write_parquet, ParquetCompression};
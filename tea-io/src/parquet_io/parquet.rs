@@ -0,0 +1,153 @@
+use crate::ColSelect;
+use arrow::array::Array;
+use arrow::chunk::Chunk;
+use arrow::compute::concatenate::concatenate;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::io::parquet::read::{self, RowGroupDeserializer};
+use arrow::io::parquet::write::{
+    transverse, Encoding, FileWriter, RowGroupIterator, Version, WriteOptions,
+};
+use rayon::prelude::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+};
+use std::{fs::File, path::Path};
+use teapy_core::prelude::*;
+
+pub use arrow::io::parquet::write::CompressionOptions as ParquetCompression;
+
+/// The maximum number of rows in a row group when writing parquet files.
+const ROW_GROUP_SIZE: usize = 512 * 512;
+
+#[inline]
+pub fn read_parquet_schema<P: AsRef<Path>>(path: P) -> TResult<Schema> {
+    let mut file = File::open(path)?;
+    let metadata = read::read_metadata(&mut file).map_err(|e| terr!("{:?}", e))?;
+    read::infer_schema(&metadata).map_err(|e| terr!("{:?}", e))
+}
+
+/// Read a parquet file, the row groups are deserialized in parallel.
+pub fn read_parquet<'a, P: AsRef<Path>>(
+    path: P,
+    columns: ColSelect<'_>,
+) -> TResult<(Schema, Vec<ArrOk<'a>>)> {
+    let path = path.as_ref();
+    let mut file = File::open(path)?;
+    let metadata = read::read_metadata(&mut file).map_err(|e| terr!("{:?}", e))?;
+    let mut schema = read::infer_schema(&metadata).map_err(|e| terr!("{:?}", e))?;
+    if let Some(proj) = columns.into_proj(&schema)? {
+        schema = schema.filter(|i, _f| proj.contains(&i));
+    }
+    let fields = schema.fields.clone();
+
+    // each row group is read by its own file handle so that they can be deserialized in parallel
+    let row_groups = metadata
+        .row_groups
+        .par_iter()
+        .map(|row_group| {
+            let mut file = File::open(path)?;
+            let column_iters =
+                read::read_columns_many(&mut file, row_group, fields.clone(), None, None, None)
+                    .map_err(|e| terr!("{:?}", e))?;
+            RowGroupDeserializer::new(column_iters, row_group.num_rows(), None)
+                .map(|chunk| chunk.map(Chunk::into_arrays))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| terr!("{:?}", e))
+        })
+        .collect::<TResult<Vec<_>>>()?;
+
+    let mut columns: Vec<Vec<Box<dyn Array>>> = vec![vec![]; fields.len()];
+    for chunk in row_groups.into_iter().flatten() {
+        columns
+            .iter_mut()
+            .zip(chunk)
+            .for_each(|(col, arr)| col.push(arr));
+    }
+    let out = columns
+        .into_par_iter()
+        .zip(fields.into_par_iter())
+        .map(|(mut arrs, field)| {
+            // concatenate in arrow so that chunks with and without nulls get the same dtype
+            let arr = match arrs.len() {
+                0 => arrow::array::new_empty_array(field.data_type),
+                1 => arrs.pop().unwrap(),
+                _ => {
+                    let arrs = arrs.iter().map(|a| a.as_ref()).collect::<Vec<_>>();
+                    concatenate(&arrs).map_err(|e| terr!("{:?}", e))?
+                }
+            };
+            ArrOk::try_from_arrow(arr)
+        })
+        .collect::<TResult<Vec<_>>>()?;
+    Ok((schema, out))
+}
+
+/// Write 1-d arrays into a parquet file, `names` should have the same length as `arrs`.
+pub fn write_parquet<P: AsRef<Path>>(
+    path: P,
+    arrs: &[&ArrOk<'_>],
+    names: &[&str],
+    compression: Option<ParquetCompression>,
+) -> TResult<()> {
+    if arrs.len() != names.len() {
+        tbail!(
+            "the number of arrays ({}) doesn't match the number of names ({})",
+            arrs.len(),
+            names.len()
+        )
+    }
+    let arrow_arrs = arrs
+        .par_iter()
+        .map(|a| a.to_arrow())
+        .collect::<TResult<Vec<_>>>()?;
+    let fields = arrow_arrs
+        .iter()
+        .zip(names)
+        .map(|(a, name)| Field::new(*name, a.data_type().clone(), a.validity().is_some()))
+        .collect::<Vec<_>>();
+    let schema = Schema::from(fields);
+    let options = WriteOptions {
+        write_statistics: true,
+        compression: compression.unwrap_or(ParquetCompression::Uncompressed),
+        version: Version::V2,
+        data_pagesize_limit: None,
+    };
+    let encodings = schema
+        .fields
+        .iter()
+        .map(|f| {
+            // dictionary arrays (categoricals) only support the dictionary encoding
+            transverse(&f.data_type, |dtype| match dtype {
+                DataType::Dictionary(..) => Encoding::RleDictionary,
+                _ => Encoding::Plain,
+            })
+        })
+        .collect();
+    let len = <[_]>::first(&arrow_arrs).map_or(0, |a| a.len());
+    if let Some((a, name)) = arrow_arrs.iter().zip(names).find(|(a, _)| a.len() != len) {
+        tbail!(
+            "all the columns should have the same length, column {} has length {} but expected {}",
+            name,
+            a.len(),
+            len
+        )
+    }
+    let chunks = (0..len.max(1)).step_by(ROW_GROUP_SIZE).map(|offset| {
+        let length = ROW_GROUP_SIZE.min(len - offset);
+        let arrs = arrow_arrs
+            .iter()
+            .map(|a| a.sliced(offset, length))
+            .collect::<Vec<_>>();
+        Chunk::try_new(arrs)
+    });
+    let row_groups = RowGroupIterator::try_new(chunks, &schema, options, encodings)
+        .map_err(|e| terr!("{:?}", e))?;
+    let file = File::create(path)?;
+    let mut writer = FileWriter::try_new(file, schema, options).map_err(|e| terr!("{:?}", e))?;
+    for group in row_groups {
+        writer
+            .write(group.map_err(|e| terr!("{:?}", e))?)
+            .map_err(|e| terr!("{:?}", e))?;
+    }
+    writer.end(None).map_err(|e| terr!("{:?}", e))?;
+    Ok(())
+}
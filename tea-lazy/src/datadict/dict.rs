@@ -6,7 +6,7 @@ use std::sync::Arc;
 use teapy_core::prelude::{tbail, terr};
 // use serde::Serialize;
 
-use crate::{Context, Expr};
+use crate::{CommonSubexprs, Context, Expr, ExprType};
 use teapy_core::{prelude::TResult, utils::CollectTrustedToVec};
 
 // #[cfg(feature = "agg")]
@@ -241,7 +241,7 @@ impl<'a> DataDict<'a> {
                 Ok(self
                     .data
                     .get(col_idx)
-                    .unwrap_or_else(|| panic!("Select index: {:?} of ot bound", &col_idx))
+                    .unwrap_or_else(|| panic!("Select index: {:?} of ot bound", col_idx))
                     .into())
             }
             ColumnSelector::NameOwned(col_name) => {
@@ -507,22 +507,28 @@ impl<'a> DataDict<'a> {
     }
 
     pub fn eval_inplace(&mut self, col: ColumnSelector, context: bool) -> TResult<()> {
-        // is there a good way to avoid clone at all cases? Currently we can not get a immutable reference
-        // of self and a mutable reference of self at the same time.
-        let context: Option<Context<'a>> = if context { self.clone().into() } else { None };
+        if context {
+            // is there a good way to avoid clone at all cases? Currently we can not get a immutable reference
+            // of self and a mutable reference of self at the same time.
+            let ctx: Option<Context<'a>> = self.clone().into();
+            for wave in self.context_waves(col)? {
+                self.eval_columns(&wave, ctx.clone())?;
+            }
+            return Ok(());
+        }
         let expr = self.get_mut(col)?;
         match expr {
             GetMutOutput::Expr(e) => {
                 // we should update_column_map if the name of the expr has changed after evaluation.
                 let ori_name = e.name().unwrap();
-                e.eval_inplace_freeze(context)?;
+                e.eval_inplace_freeze(None)?;
                 let new_name = e.name().unwrap();
                 self.update_column_map(ori_name, new_name)?
             }
             GetMutOutput::Exprs(mut es) => {
                 let ori_name_vec = es.iter().map(|e| e.name().unwrap()).collect_trusted();
                 es.par_iter_mut()
-                    .try_for_each(|e| e.eval_inplace_freeze(context.clone()).map(|_| {}))?;
+                    .try_for_each(|e| e.eval_inplace_freeze(None).map(|_| {}))?;
                 let new_name_vec = es.iter().map(|e| e.name().unwrap()).collect_trusted();
                 zip(ori_name_vec, new_name_vec).try_for_each(|(ori_name, new_name)| {
                     self.update_column_map(ori_name, new_name)
@@ -532,6 +538,160 @@ impl<'a> DataDict<'a> {
         Ok(())
     }
 
+    /// Evaluate the columns like `eval_inplace`, but the sub-expressions shared
+    /// by the columns of a wave are evaluated only once. Return the number of
+    /// deduplicated nodes.
+    pub fn eval_inplace_cse(&mut self, col: ColumnSelector, context: bool) -> TResult<usize> {
+        let (waves, ctx) = if context {
+            (self.context_waves(col)?, self.clone().into())
+        } else {
+            (vec![self.column_idx(col)?], None)
+        };
+        let mut dedup = 0;
+        for wave in waves {
+            let exprs = wave.iter().map(|i| &self.data[*i]).collect_trusted();
+            let mut cse = CommonSubexprs::new(&exprs);
+            cse.eval(ctx.clone())?;
+            dedup += cse.dedup;
+            self.eval_columns(&wave, ctx.clone())?;
+        }
+        Ok(dedup)
+    }
+
+    /// The indices of the selected columns.
+    fn column_idx(&self, col: ColumnSelector) -> TResult<Vec<usize>> {
+        self.get_selector_out_name(col)
+            .into_iter()
+            .map(|name| {
+                self.map
+                    .get(&name)
+                    .copied()
+                    .ok_or_else(|| terr!("Column {name} doesn't exist!"))
+            })
+            .collect()
+    }
+
+    /// Group the selected columns and the columns they reference through the
+    /// context into waves, a column only depends on the columns of the
+    /// previous waves. A column referencing all the columns (e.g. through a
+    /// python function) is evaluated alone, after the columns it doesn't reach.
+    pub fn context_waves(&self, col: ColumnSelector) -> TResult<Vec<Vec<usize>>> {
+        let deps = (0..self.len())
+            .map(|i| self.context_deps(i))
+            .collect_trusted();
+        let mut visited = vec![false; self.len()];
+        let mut path = Vec::new();
+        for i in self.column_idx(col)? {
+            self.visit_context_deps(i, &deps, &mut visited, &mut path)?;
+        }
+        if zip(&visited, &deps).any(|(visited, (_, all))| *visited && *all) {
+            for i in 0..self.len() {
+                self.visit_context_deps(i, &deps, &mut visited, &mut path)?;
+            }
+        }
+        let mut pending = (0..self.len()).filter(|i| visited[*i]).collect::<Vec<_>>();
+        let mut done = vec![false; self.len()];
+        let mut waves = Vec::new();
+        while !pending.is_empty() {
+            let ready = |i: &&usize| deps[**i].0.iter().all(|j| done[*j]);
+            let mut wave = pending
+                .iter()
+                .filter(|i| ready(i) && !deps[**i].1)
+                .copied()
+                .collect::<Vec<_>>();
+            if wave.is_empty() {
+                // the other columns wait for the columns referencing all the columns,
+                // there is always a ready one as the references have no cycle.
+                wave.push(*pending.iter().find(ready).unwrap());
+            }
+            wave.iter().for_each(|i| done[*i] = true);
+            pending.retain(|i| !done[*i]);
+            waves.push(wave);
+        }
+        Ok(waves)
+    }
+
+    /// The types of the columns inferred without evaluation, a column
+    /// referenced through the context is typed before the columns using it.
+    pub fn schema(&self) -> TResult<Vec<Option<ExprType>>> {
+        let mut types: Vec<Option<ExprType>> = vec![None; self.len()];
+        for wave in self.context_waves(ColumnSelector::All)? {
+            for i in wave {
+                types[i] = self.data[i].infer_type_with(&|col| match self
+                    .get_selector_out_name(col.clone())
+                    .as_slice()
+                {
+                    [name] => self.map.get(name).and_then(|j| types[*j].clone()),
+                    _ => None,
+                })?;
+            }
+        }
+        Ok(types)
+    }
+
+    /// The indices of the columns the column `i` references through the context,
+    /// and whether it references all the columns.
+    fn context_deps(&self, i: usize) -> (Vec<usize>, bool) {
+        let mut all = false;
+        let deps = self.data[i]
+            .context_refs()
+            .into_iter()
+            .filter(|cs| {
+                let is_all = matches!(cs, ColumnSelector::All);
+                all |= is_all;
+                !is_all
+            })
+            .flat_map(|cs| self.get_selector_out_name(cs))
+            .filter_map(|name| self.map.get(&name).copied())
+            .collect();
+        (deps, all)
+    }
+
+    fn visit_context_deps(
+        &self,
+        i: usize,
+        deps: &[(Vec<usize>, bool)],
+        visited: &mut [bool],
+        path: &mut Vec<usize>,
+    ) -> TResult<()> {
+        if visited[i] {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|j| *j == i) {
+            let cycle = path[start..]
+                .iter()
+                .chain(std::iter::once(&i))
+                .map(|j| self.data[*j].name().unwrap())
+                .collect::<Vec<_>>();
+            tbail!(
+                "Cycle in the context references of the columns: {}",
+                cycle.join(" -> ")
+            );
+        }
+        path.push(i);
+        for j in &deps[i].0 {
+            self.visit_context_deps(*j, deps, visited, path)?;
+        }
+        path.pop();
+        visited[i] = true;
+        Ok(())
+    }
+
+    /// Evaluate the columns of the indices in parallel.
+    fn eval_columns(&mut self, idx: &[usize], ctx: Option<Context<'a>>) -> TResult<()> {
+        let mut selected = vec![false; self.len()];
+        idx.iter().for_each(|i| selected[*i] = true);
+        let mut es = zip(&mut self.data, selected)
+            .filter_map(|(e, selected)| selected.then_some(e))
+            .collect::<Vec<_>>();
+        let ori_name_vec = es.iter().map(|e| e.name().unwrap()).collect_trusted();
+        es.par_iter_mut()
+            .try_for_each(|e| e.eval_inplace_freeze(ctx.clone()).map(|_| {}))?;
+        let new_name_vec = es.iter().map(|e| e.name().unwrap()).collect_trusted();
+        zip(ori_name_vec, new_name_vec)
+            .try_for_each(|(ori_name, new_name)| self.update_column_map(ori_name, new_name))
+    }
+
     /// Insert a new expr or update the old value,
     /// the column name will be the name of the value expression.
     /// The caller must ensure that the name of the value is not None;
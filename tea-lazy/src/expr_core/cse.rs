@@ -0,0 +1,274 @@
+use super::expr_inner::ExprInner;
+use super::op::{node_group_ends, OpParam, SCOPED_INPUTS};
+use super::{Data, Expr, SelectorDef};
+use crate::{Context, TpHashMap};
+use rayon::prelude::*;
+use std::sync::Arc;
+use teapy_core::prelude::*;
+
+/// The key of a sub-expression, the `usize` fields are the ids of the keys
+/// interned before.
+#[derive(Hash, PartialEq, Eq)]
+enum Key {
+    Column(String),
+    Scalar(String),
+    /// Data that is only equal to itself, e.g. an array.
+    Unique(usize),
+    Op {
+        name: &'static str,
+        params: String,
+        inputs: Vec<(&'static str, usize)>,
+        base: usize,
+    },
+    /// A node that can not be compared, e.g. a lambda.
+    Func {
+        func: usize,
+        base: usize,
+    },
+}
+
+#[inline]
+fn ptr(e: &Expr<'_>) -> usize {
+    Arc::as_ptr(e) as usize
+}
+
+fn scalar_key(arr: &ArrOk<'_>) -> Option<String> {
+    if arr.ndim() != 0 {
+        return None;
+    }
+    match_arrok!(arr; Dynamic(a) => {
+        Ok(format!("{}: {:?}", arr.dtype_name(), a.view().0.iter().next().unwrap()))
+    },)
+    .ok()
+}
+
+/// The sub-expressions shared by a group of expressions.
+///
+/// `CommonSubexprs::new` finds the identical sub-expressions by the operations
+/// recorded on the nodes and rewrites the expressions to reference a single
+/// shared expression for each of them, so that it is evaluated only once.
+pub struct CommonSubexprs<'a> {
+    /// The shared sub-expressions, each one only depends on the shared
+    /// sub-expressions of the previous waves.
+    pub waves: Vec<Vec<Expr<'a>>>,
+    /// The number of nodes that are no longer evaluated more than once.
+    pub dedup: usize,
+}
+
+#[derive(Default)]
+struct Cse<'a> {
+    ids: TpHashMap<Key, usize>,
+    /// the number of node chains each id is a prefix of
+    uses: Vec<usize>,
+    /// the id and the prefixes (end of the nodes, id) of each expression
+    chains: TpHashMap<usize, (usize, Vec<(usize, usize)>)>,
+    nodes: usize,
+    handles: TpHashMap<usize, Expr<'a>>,
+    shared: Vec<Expr<'a>>,
+    // keep the visited expressions alive so that their addresses are not reused
+    pinned: Vec<Expr<'a>>,
+}
+
+impl<'a> Cse<'a> {
+    fn intern(&mut self, key: Key) -> usize {
+        let len = self.ids.len();
+        let id = *self.ids.entry(key).or_insert(len);
+        if id == len {
+            self.uses.push(0);
+        }
+        id
+    }
+
+    fn leaf(&mut self, data: &Data<'a>, p: usize) -> usize {
+        let key = match data {
+            Data::Context(selector) => {
+                Some(Key::Column(format!("{:?}", SelectorDef::from(selector))))
+            }
+            Data::Arr(arr) => scalar_key(arr).map(Key::Scalar),
+            Data::ArcArr(arr) => scalar_key(arr).map(Key::Scalar),
+            _ => None,
+        };
+        self.intern(key.unwrap_or(Key::Unique(p)))
+    }
+
+    /// Intern the prefixes of the node chain of `e`, return the id of `e`.
+    fn visit(&mut self, e: &Expr<'a>) -> usize {
+        let p = ptr(e);
+        if let Some((id, _)) = self.chains.get(&p) {
+            return *id;
+        }
+        self.pinned.push(e.share());
+        let inner = e.lock();
+        let mut id = match &inner.base {
+            Data::Expr(base) => self.visit(base),
+            data => self.leaf(data, p),
+        };
+        let mut chain = Vec::new();
+        for (end, op) in node_group_ends(&inner.nodes) {
+            let func = Key::Func {
+                func: Arc::as_ptr(&inner.nodes[end - 1].func) as *const () as usize,
+                base: id,
+            };
+            let key = if let Some(op) = op {
+                let inputs = op
+                    .inputs
+                    .iter()
+                    .map(|(name, input)| {
+                        let id = if SCOPED_INPUTS.contains(name) {
+                            self.intern(Key::Unique(ptr(input)))
+                        } else {
+                            self.visit(input)
+                        };
+                        (*name, id)
+                    })
+                    .collect();
+                // a parameter only described by its type can not be compared
+                let comparable = !Iterator::any(
+                    &mut op.params.iter(),
+                    |(_, p)| matches!(p, OpParam::Other(s) if s.contains('<')),
+                );
+                if comparable {
+                    let params = op
+                        .params
+                        .iter()
+                        .filter(|(name, _)| !matches!(*name, "par" | "_par"))
+                        .collect::<Vec<_>>();
+                    Key::Op {
+                        name: op.name,
+                        params: format!("{params:?}"),
+                        inputs,
+                        base: id,
+                    }
+                } else {
+                    func
+                }
+            } else {
+                func
+            };
+            id = self.intern(key);
+            self.uses[id] += 1;
+            chain.push((end, id));
+        }
+        self.nodes += chain.len();
+        drop(inner);
+        self.chains.insert(p, (id, chain));
+        id
+    }
+
+    /// Replace the prefixes of the node chain of `e` with the shared expressions,
+    /// the prefixes used by other expressions are split out to be shared.
+    fn rewrite(&mut self, e: &Expr<'a>) {
+        let Some((_, chain)) = self.chains.remove(&ptr(e)) else {
+            return;
+        };
+        let mut inner = e.lock();
+        // the number of nodes moved out of `inner`
+        let mut moved = 0;
+        let start = match chain
+            .iter()
+            .rposition(|(_, id)| self.handles.contains_key(id))
+        {
+            Some(k) => {
+                let (end, id) = chain[k];
+                inner.nodes.drain(..end);
+                inner.base = Data::Expr(self.handles[&id].share());
+                moved = end;
+                k + 1
+            }
+            None => {
+                if let Data::Expr(base) = &inner.base {
+                    self.rewrite(base);
+                }
+                0
+            }
+        };
+        for j in start..chain.len() {
+            let (end, id) = chain[j];
+            if let Some(op) = inner.nodes[end - moved - 1].op.clone() {
+                for (name, input) in &op.inputs {
+                    if !SCOPED_INPUTS.contains(name) {
+                        self.rewrite(input);
+                    }
+                }
+            }
+            // no need to split here if all the chains continue with the same node
+            let next_uses = chain.get(j + 1).map_or(0, |(_, next)| self.uses[next]);
+            if self.uses[id] > 1 && self.uses[id] > next_uses {
+                let mut shared = ExprInner::new(std::mem::take(&mut inner.base), None);
+                shared.nodes = inner.nodes.drain(..end - moved).collect();
+                let shared: Expr<'a> = shared.into();
+                inner.base = Data::Expr(shared.share());
+                self.handles.insert(id, shared.share());
+                self.shared.push(shared);
+                moved = end;
+            }
+        }
+    }
+
+    /// The number of waves needed to evaluate the shared expressions `e` depends on.
+    fn level(
+        &self,
+        e: &Expr<'a>,
+        shared: &TpHashMap<usize, ()>,
+        memo: &mut TpHashMap<usize, usize>,
+    ) -> usize {
+        let p = ptr(e);
+        if let Some(level) = memo.get(&p) {
+            return *level;
+        }
+        let inner = e.lock();
+        let mut level = match &inner.base {
+            Data::Expr(base) => self.level(base, shared, memo),
+            _ => 0,
+        };
+        for (_, op) in node_group_ends(&inner.nodes) {
+            for (name, input) in op.iter().flat_map(|op| op.inputs.iter()) {
+                if !SCOPED_INPUTS.contains(name) {
+                    level = level.max(self.level(input, shared, memo));
+                }
+            }
+        }
+        if shared.contains_key(&p) {
+            level += 1;
+        }
+        memo.insert(p, level);
+        level
+    }
+}
+
+impl<'a> CommonSubexprs<'a> {
+    /// Share the identical sub-expressions of `exprs`, the expressions are
+    /// rewritten in place.
+    pub fn new(exprs: &[&Expr<'a>]) -> Self {
+        let mut cse = Cse::default();
+        for e in exprs {
+            cse.visit(e);
+        }
+        let dedup = cse.nodes - cse.uses.iter().filter(|u| **u > 0).count();
+        if dedup > 0 {
+            for e in exprs {
+                cse.rewrite(e);
+            }
+        }
+        let shared = cse.shared.iter().map(|e| (ptr(e), ())).collect();
+        let mut memo = TpHashMap::new();
+        let mut waves: Vec<Vec<Expr<'a>>> = Vec::new();
+        for e in &cse.shared {
+            let level = cse.level(e, &shared, &mut memo);
+            if waves.len() < level {
+                waves.resize_with(level, Vec::new);
+            }
+            waves[level - 1].push(e.share());
+        }
+        CommonSubexprs { waves, dedup }
+    }
+
+    /// Evaluate the shared sub-expressions wave by wave.
+    pub fn eval(&mut self, ctx: Option<Context<'a>>) -> TResult<()> {
+        for wave in &mut self.waves {
+            wave.par_iter_mut()
+                .try_for_each(|e| e.eval_inplace_freeze(ctx.clone()).map(|_| {}))?;
+        }
+        Ok(())
+    }
+}
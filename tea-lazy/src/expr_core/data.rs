@@ -6,7 +6,7 @@ use crate::OlsResult;
 
 use crate::{ColumnSelector, Context};
 use derive_more::From;
-use std::{fmt::Debug, sync::Arc};
+use std::{borrow::Cow, fmt::Debug, sync::Arc};
 use teapy_core::prelude::*;
 
 #[derive(From, Clone)]
@@ -102,7 +102,7 @@ impl<'a> Data<'a> {
     pub fn dtype(&self) -> String {
         match self {
             Data::Expr(e) => e.dtype(),
-            Data::Arr(arr) => format!("{:?}", arr.dtype()),
+            Data::Arr(arr) => arr.dtype_name(),
             _ => "Unknown".to_string(),
         }
     }
@@ -203,7 +203,8 @@ impl<'a> Data<'a> {
     #[inline]
     pub fn into_arr(self, ctx: Option<Context<'a>>) -> TResult<ArrOk<'a>> {
         match self {
-            Data::Arr(arr) => Ok(arr),
+            // the kernels don't support the compact integer dtypes
+            Data::Arr(arr) => Ok(arr.into_dense().widen()),
             Data::Expr(e) => e.into_arr(ctx),
 
             Data::Context(col) => {
@@ -212,7 +213,12 @@ impl<'a> Data<'a> {
                     .ok_or_else(|| terr!("The context is not provided"))?;
                 let out = ctx1.get(col.clone())?;
                 // need clone here
-                Ok(out.into_expr()?.view_arr(None)?.deref().into_owned())
+                Ok(out
+                    .into_expr()?
+                    .view_arr(None)?
+                    .deref()
+                    .into_owned()
+                    .widen())
             }
             // #[cfg(feature = "arw")]
             // Data::Arrow(arr) => Ok(ArrOk::from_arrow(arr)),
@@ -280,27 +286,44 @@ impl<'a> Data<'a> {
         }
     }
 
+    /// View the array the kernels compute on, masked arrays are filled and
+    /// the compact integer dtypes are widened.
+    #[inline]
+    pub fn view_arr<'b>(&'b self, ctx: Option<&'b Context<'a>>) -> TResult<Cow<'b, ArrOk<'a>>> {
+        let arr = self.view_raw_arr(ctx)?.to_dense();
+        if arr.is_compact() {
+            Ok(Cow::Owned(arr.view().widen().into_owned()))
+        } else {
+            Ok(arr)
+        }
+    }
+
+    /// View the array without filling masked arrays, used when the array is
+    /// exported and the validity bitmap should be kept.
     #[inline]
-    pub fn view_arr<'b>(&'b self, ctx: Option<&'b Context<'a>>) -> TResult<&'b ArrOk<'a>> {
+    pub fn view_raw_arr<'b>(&'b self, ctx: Option<&'b Context<'a>>) -> TResult<&'b ArrOk<'a>> {
         match self {
             Data::Arr(arr) => Ok(arr),
-            Data::Expr(e) => e.view_arr(ctx),
+            Data::Expr(e) => e.view_raw_arr(ctx),
 
             Data::Context(col) => {
                 let out = ctx
                     .ok_or_else(|| terr!("The context is not provided"))?
                     .get(col.clone())?
                     .into_expr()?;
-                out.view_arr(None)
+                out.view_raw_arr(None)
             }
             _ => tbail!("The output of the expression is not an array, {:?}", self),
         }
     }
 
     #[inline]
-    pub fn view_arr_vec<'b>(&'b self, ctx: Option<&'b Context<'a>>) -> TResult<Vec<&'b ArrOk<'a>>> {
+    pub fn view_arr_vec<'b>(
+        &'b self,
+        ctx: Option<&'b Context<'a>>,
+    ) -> TResult<Vec<Cow<'b, ArrOk<'a>>>> {
         match self {
-            Data::ArrVec(arr_vec) => Ok(arr_vec.iter().collect::<Vec<_>>()),
+            Data::ArrVec(arr_vec) => Ok(arr_vec.iter().map(ArrOk::to_dense).collect::<Vec<_>>()),
             Data::Expr(e) => e.view_arr_vec(ctx),
 
             Data::Context(col) => {
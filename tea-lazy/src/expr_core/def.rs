@@ -0,0 +1,561 @@
+use super::expr_inner::ExprInner;
+use super::op::node_groups;
+use super::{Data, Expr, OpParam};
+use crate::ColumnSelector;
+use parking_lot::RwLock;
+#[cfg(feature = "srd")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::sync::LazyLock;
+use teapy_core::prelude::*;
+
+/// The definition of an expression: the data it is based on and the
+/// operations chained on it. An expression can be rebuilt from its definition
+/// as long as the operations are registered, see `register_op`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "srd", derive(Serialize, Deserialize))]
+pub struct ExprDef {
+    pub name: Option<String>,
+    pub base: BaseDef,
+    pub ops: Vec<OpDef>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "srd", derive(Serialize, Deserialize))]
+pub enum BaseDef {
+    Expr(Box<ExprDef>),
+    /// Columns of the context, e.g. `context("close")`.
+    Column(SelectorDef),
+    Array {
+        dtype: String,
+        shape: Vec<usize>,
+        values: Vec<OpParam>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "srd", derive(Serialize, Deserialize))]
+pub enum SelectorDef {
+    Index(i32),
+    VecIndex(Vec<i32>),
+    Name(String),
+    VecName(Vec<String>),
+    Regex(String),
+    All,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "srd", derive(Serialize, Deserialize))]
+pub struct OpDef {
+    pub name: String,
+    pub params: Vec<(String, OpParam)>,
+    pub inputs: Vec<(String, ExprDef)>,
+}
+
+/// Values that can be parsed from an `OpParam`.
+pub trait FromOpParam: Sized {
+    fn from_op_param(param: &OpParam) -> TResult<Self>;
+}
+
+macro_rules! impl_from_op_param_int {
+    ($($ty: ty),*) => {
+        $(impl FromOpParam for $ty {
+            #[inline]
+            fn from_op_param(param: &OpParam) -> TResult<Self> {
+                match param {
+                    OpParam::Int(v) => (*v)
+                        .try_into()
+                        .map_err(|_| terr!("{v} is out of the range of {}", stringify!($ty))),
+                    _ => tbail!("Expect an integer, got {param}"),
+                }
+            }
+        })*
+    };
+}
+
+impl_from_op_param_int!(i8, i16, i32, i64, u8, u16, u32, u64, usize, isize);
+
+macro_rules! impl_from_op_param_float {
+    ($($ty: ty),*) => {
+        $(impl FromOpParam for $ty {
+            #[inline]
+            fn from_op_param(param: &OpParam) -> TResult<Self> {
+                match param {
+                    OpParam::Float(v) => Ok(*v as $ty),
+                    OpParam::Int(v) => Ok(*v as $ty),
+                    _ => tbail!("Expect a float, got {param}"),
+                }
+            }
+        })*
+    };
+}
+
+impl_from_op_param_float!(f32, f64);
+
+impl FromOpParam for bool {
+    #[inline]
+    fn from_op_param(param: &OpParam) -> TResult<Self> {
+        match param {
+            OpParam::Bool(v) => Ok(*v),
+            _ => tbail!("Expect a bool, got {param}"),
+        }
+    }
+}
+
+impl FromOpParam for String {
+    #[inline]
+    fn from_op_param(param: &OpParam) -> TResult<Self> {
+        match param {
+            OpParam::Str(v) => Ok(v.clone()),
+            _ => tbail!("Expect a string, got {param}"),
+        }
+    }
+}
+
+impl FromOpParam for char {
+    #[inline]
+    fn from_op_param(param: &OpParam) -> TResult<Self> {
+        let s = String::from_op_param(param)?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => tbail!("Expect a char, got {param}"),
+        }
+    }
+}
+
+impl<T: FromOpParam> FromOpParam for Option<T> {
+    #[inline]
+    fn from_op_param(param: &OpParam) -> TResult<Self> {
+        match param {
+            OpParam::None => Ok(None),
+            _ => T::from_op_param(param).map(Some),
+        }
+    }
+}
+
+impl<T: FromOpParam> FromOpParam for Vec<T> {
+    #[inline]
+    fn from_op_param(param: &OpParam) -> TResult<Self> {
+        match param {
+            OpParam::List(v) => v.iter().map(T::from_op_param).collect(),
+            _ => tbail!("Expect a list, got {param}"),
+        }
+    }
+}
+
+/// The arguments of an operation to rebuild, expressions are taken from the
+/// inputs by name and the other arguments are parsed from the parameters.
+pub struct OpArgs<'a> {
+    pub params: Vec<(String, OpParam)>,
+    pub inputs: Vec<(String, Expr<'a>)>,
+}
+
+impl<'a> OpArgs<'a> {
+    pub fn param(&self, name: &str) -> TResult<&OpParam> {
+        self.params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, p)| p)
+            .ok_or_else(|| terr!("Parameter {name} is not found"))
+    }
+
+    pub fn take_inputs(&mut self, name: &str) -> Vec<Expr<'a>> {
+        let (out, rest) = std::mem::take(&mut self.inputs)
+            .into_iter()
+            .partition(|(n, _)| n == name);
+        self.inputs = rest;
+        out.into_iter().map(|(_, e)| e).collect()
+    }
+}
+
+/// Arguments of the lazy methods that can be rebuilt from `OpArgs`, the
+/// counterpart of `OpArg`.
+pub trait FromOpArg<'a>: Sized {
+    fn from_op_args(name: &str, args: &mut OpArgs<'a>) -> TResult<Self>;
+}
+
+impl<'a> FromOpArg<'a> for Expr<'a> {
+    #[inline]
+    fn from_op_args(name: &str, args: &mut OpArgs<'a>) -> TResult<Self> {
+        let mut inputs = args.take_inputs(name);
+        if inputs.len() != 1 {
+            tbail!("Expect one input {name}, got {}", inputs.len())
+        }
+        Ok(inputs.pop().unwrap())
+    }
+}
+
+impl<'a> FromOpArg<'a> for Vec<Expr<'a>> {
+    #[inline]
+    fn from_op_args(name: &str, args: &mut OpArgs<'a>) -> TResult<Self> {
+        Ok(args.take_inputs(name))
+    }
+}
+
+impl<'a> FromOpArg<'a> for Option<Expr<'a>> {
+    #[inline]
+    fn from_op_args(name: &str, args: &mut OpArgs<'a>) -> TResult<Self> {
+        let mut inputs = args.take_inputs(name);
+        if inputs.len() > 1 {
+            tbail!("Expect at most one input {name}, got {}", inputs.len())
+        }
+        Ok(inputs.pop())
+    }
+}
+
+impl<'a, T: FromOpParam> FromOpArg<'a> for T {
+    #[inline]
+    fn from_op_args(name: &str, args: &mut OpArgs<'a>) -> TResult<Self> {
+        T::from_op_param(args.param(name)?)
+    }
+}
+
+// The arguments of the builders generated by `ext_trait` are parsed by
+// autoref specialization: `FromOpArg` is preferred, then `FromStr` for the
+// parameters recorded by `Debug`, otherwise the argument can't be rebuilt.
+#[doc(hidden)]
+pub struct OpArgParser<T>(pub PhantomData<T>);
+
+#[doc(hidden)]
+pub trait ParseOpArg<'a, T> {
+    fn parse_op_arg(&self, name: &str, args: &mut OpArgs<'a>) -> TResult<T>;
+}
+
+impl<'a, T: FromOpArg<'a>> ParseOpArg<'a, T> for &&OpArgParser<T> {
+    #[inline]
+    fn parse_op_arg(&self, name: &str, args: &mut OpArgs<'a>) -> TResult<T> {
+        T::from_op_args(name, args)
+    }
+}
+
+#[doc(hidden)]
+pub trait ParseOpArgStr<'a, T> {
+    fn parse_op_arg(&self, name: &str, args: &mut OpArgs<'a>) -> TResult<T>;
+}
+
+impl<'a, T: FromStr> ParseOpArgStr<'a, T> for &OpArgParser<T> {
+    fn parse_op_arg(&self, name: &str, args: &mut OpArgs<'a>) -> TResult<T> {
+        match args.param(name)? {
+            OpParam::Str(s) | OpParam::Other(s) => s
+                .parse()
+                .or_else(|_| s.to_lowercase().parse())
+                .map_err(|_| terr!("Can not parse parameter {name} from {s}")),
+            p => tbail!("Expect a string for parameter {name}, got {p}"),
+        }
+    }
+}
+
+#[doc(hidden)]
+pub trait ParseOpArgAny<'a, T> {
+    fn parse_op_arg(&self, name: &str, args: &mut OpArgs<'a>) -> TResult<T>;
+}
+
+impl<'a, T> ParseOpArgAny<'a, T> for OpArgParser<T> {
+    fn parse_op_arg(&self, name: &str, _args: &mut OpArgs<'a>) -> TResult<T> {
+        tbail!(
+            "Argument {name} of type {} can not be rebuilt",
+            std::any::type_name::<T>()
+        )
+    }
+}
+
+/// Apply the operation with the arguments to the expression.
+pub type OpBuilder = for<'a> fn(&mut Expr<'a>, &mut OpArgs<'a>) -> TResult<()>;
+
+static OP_BUILDERS: LazyLock<RwLock<HashMap<&'static str, OpBuilder>>> =
+    LazyLock::new(|| RwLock::new(builtin_ops()));
+
+/// Register the builder of the operation `name`, the lazy methods generated
+/// by `ext_trait` are registered by the `register_ops` of the crates.
+pub fn register_op(name: &'static str, builder: OpBuilder) {
+    OP_BUILDERS.write().insert(name, builder);
+}
+
+fn builtin_ops() -> HashMap<&'static str, OpBuilder> {
+    let mut ops: HashMap<&'static str, OpBuilder> = HashMap::new();
+    macro_rules! register {
+        ($($(#[$meta: meta])? $name: ident),* $(,)?) => {
+            $(
+                $(#[$meta])?
+                ops.insert(stringify!($name), |e, _| {
+                    e.$name();
+                    Ok(())
+                });
+            )*
+        };
+    }
+    register!(
+        cast_f32,
+        cast_f64,
+        cast_i32,
+        cast_i64,
+        cast_u64,
+        cast_usize,
+        cast_string,
+        cast_bool,
+        cast_object,
+        #[cfg(feature = "time")]
+        cast_datetime_ms,
+        #[cfg(feature = "time")]
+        cast_datetime_us,
+        #[cfg(feature = "time")]
+        cast_datetime_ns,
+        #[cfg(feature = "time")]
+        cast_date,
+        #[cfg(feature = "time")]
+        cast_timedelta,
+        cast_optusize,
+        cast_vecusize,
+        cast_i8,
+        cast_i16,
+        cast_u16,
+        cast_u32,
+        cast_opt_u8,
+        cast_opt_u64,
+        cast_float,
+        cast_int,
+        cast_categorical,
+    );
+    ops.insert("cast_decimal", |e, args| {
+        let precision = u8::from_op_args("precision", args)?;
+        let scale = u8::from_op_args("scale", args)?;
+        e.cast_decimal(precision, scale);
+        Ok(())
+    });
+    #[cfg(feature = "time")]
+    ops.insert("cast_datetime", |e, args| {
+        let unit = match Option::<String>::from_op_args("unit", args)?.as_deref() {
+            None => None,
+            Some("Nanosecond") => Some(TimeUnit::Nanosecond),
+            Some("Microsecond") => Some(TimeUnit::Microsecond),
+            Some("Millisecond") => Some(TimeUnit::Millisecond),
+            Some("Second") => Some(TimeUnit::Second),
+            Some(unit) => tbail!("Unsupported time unit {unit}"),
+        };
+        e.cast_datetime(unit);
+        Ok(())
+    });
+    #[cfg(feature = "ops")]
+    {
+        macro_rules! register_binary {
+            ($($name: literal: $op: tt),*) => {
+                $(ops.insert($name, |e, args| {
+                    let rhs = Expr::from_op_args("rhs", args)?;
+                    *e = e.clone() $op rhs;
+                    Ok(())
+                });)*
+            };
+        }
+        register_binary!("add": +, "sub": -, "mul": *, "div": /, "and": &, "or": |);
+        macro_rules! register_cmp {
+            ($($name: ident),*) => {
+                $(ops.insert(stringify!($name), |e, args| {
+                    let rhs = Expr::from_op_args("rhs", args)?;
+                    let par = bool::from_op_args("par", args)?;
+                    e.$name(rhs, par);
+                    Ok(())
+                });)*
+            };
+        }
+        register_cmp!(eq, ne, gt, ge, lt, le);
+        ops.insert("dot", |e, args| {
+            e.dot(Expr::from_op_args("rhs", args)?);
+            Ok(())
+        });
+        ops.insert("neg", |e, _| {
+            *e = -e.clone();
+            Ok(())
+        });
+        ops.insert("not", |e, _| {
+            *e = !e.clone();
+            Ok(())
+        });
+    }
+    ops
+}
+
+fn arr_to_def(arr: &ArrOk<'_>) -> TResult<BaseDef> {
+    macro_rules! values {
+        ($a: expr, $variant: ident) => {
+            $a.view()
+                .0
+                .iter()
+                .map(|v| OpParam::$variant(v.clone().into()))
+                .collect()
+        };
+    }
+    let values = match arr {
+        ArrOk::F64(a) => values!(a, Float),
+        ArrOk::F32(a) => values!(a, Float),
+        ArrOk::I64(a) => values!(a, Int),
+        ArrOk::I32(a) => values!(a, Int),
+        ArrOk::Usize(a) => a.view().0.iter().map(|v| OpParam::Int(*v as i64)).collect(),
+        ArrOk::Bool(a) => values!(a, Bool),
+        ArrOk::String(a) => values!(a, Str),
+        _ => tbail!("Array of dtype {} can not be serialized", arr.dtype_name()),
+    };
+    Ok(BaseDef::Array {
+        dtype: arr.dtype_name(),
+        shape: arr.shape().to_vec(),
+        values,
+    })
+}
+
+fn arr_from_def<'a>(dtype: &str, shape: &[usize], values: &[OpParam]) -> TResult<ArrOk<'a>> {
+    fn build<T: FromOpParam>(shape: &[usize], values: &[OpParam]) -> TResult<ArrD<T>> {
+        let values = values
+            .iter()
+            .map(T::from_op_param)
+            .collect::<TResult<Vec<T>>>()?;
+        let arr = ndarray::ArrayD::from_shape_vec(shape, values).map_err(|e| terr!("{e}"))?;
+        Ok(arr.wrap())
+    }
+    let arr = match dtype {
+        "F64" => build::<f64>(shape, values)?.into(),
+        "F32" => build::<f32>(shape, values)?.into(),
+        "I64" => build::<i64>(shape, values)?.into(),
+        "I32" => build::<i32>(shape, values)?.into(),
+        "Usize" => build::<usize>(shape, values)?.into(),
+        "Bool" => build::<bool>(shape, values)?.into(),
+        "String" => build::<String>(shape, values)?.into(),
+        _ => tbail!("Array of dtype {dtype} can not be deserialized"),
+    };
+    Ok(arr)
+}
+
+impl From<&ColumnSelector<'_>> for SelectorDef {
+    fn from(selector: &ColumnSelector<'_>) -> Self {
+        match selector {
+            ColumnSelector::Index(i) => SelectorDef::Index(*i),
+            ColumnSelector::VecIndex(v) => SelectorDef::VecIndex(v.clone()),
+            ColumnSelector::Name(name) => SelectorDef::Name(name.to_string()),
+            ColumnSelector::NameOwned(name) => SelectorDef::Name(name.clone()),
+            ColumnSelector::VecName(v) => {
+                SelectorDef::VecName(v.iter().map(|s| s.to_string()).collect())
+            }
+            ColumnSelector::VecNameOwned(v) => SelectorDef::VecName(v.clone()),
+            ColumnSelector::Regex(re) => SelectorDef::Regex(re.as_str().to_string()),
+            ColumnSelector::All => SelectorDef::All,
+        }
+    }
+}
+
+impl SelectorDef {
+    fn to_selector<'a>(&self) -> TResult<ColumnSelector<'a>> {
+        Ok(match self {
+            SelectorDef::Index(i) => ColumnSelector::Index(*i),
+            SelectorDef::VecIndex(v) => ColumnSelector::VecIndex(v.clone()),
+            SelectorDef::Name(name) => ColumnSelector::NameOwned(name.clone()),
+            SelectorDef::VecName(v) => ColumnSelector::VecNameOwned(v.clone()),
+            SelectorDef::Regex(re) => {
+                ColumnSelector::Regex(regex::Regex::new(re).map_err(|e| terr!("{e}"))?)
+            }
+            SelectorDef::All => ColumnSelector::All,
+        })
+    }
+}
+
+impl ExprInner<'_> {
+    /// The definition of the expression, fails if an operation is not
+    /// recorded, e.g. a closure chained by `chain_f_ctx`.
+    pub fn to_def(&self) -> TResult<ExprDef> {
+        let base = match &self.base {
+            Data::Expr(e) => BaseDef::Expr(Box::new(e.lock().to_def()?)),
+            Data::Arr(arr) => arr_to_def(arr)?,
+            Data::ArcArr(arr) => arr_to_def(arr)?,
+            Data::Context(selector) => BaseDef::Column(selector.into()),
+            data => tbail!("Data of type {} can not be serialized", data.get_type()),
+        };
+        let ops = node_groups(&self.nodes)
+            .into_iter()
+            .map(|op| {
+                let op = op.ok_or_else(|| {
+                    terr!("Expression contains a function that is not recorded as an operation")
+                })?;
+                Ok(OpDef {
+                    name: op.name.to_string(),
+                    params: op
+                        .params
+                        .iter()
+                        .map(|(n, p)| (n.to_string(), p.clone()))
+                        .collect(),
+                    inputs: op
+                        .inputs
+                        .iter()
+                        .map(|(n, e)| Ok((n.to_string(), e.lock().to_def()?)))
+                        .collect::<TResult<_>>()?,
+                })
+            })
+            .collect::<TResult<_>>()?;
+        Ok(ExprDef {
+            name: self.name_owned(),
+            base,
+            ops,
+        })
+    }
+}
+
+impl Expr<'_> {
+    /// The definition of the expression, see `ExprInner::to_def`.
+    #[inline]
+    pub fn to_def(&self) -> TResult<ExprDef> {
+        self.lock().to_def()
+    }
+}
+
+impl ExprDef {
+    /// Rebuild an executable expression from the definition.
+    pub fn build<'a>(&self) -> TResult<Expr<'a>> {
+        let mut e: Expr<'a> = match &self.base {
+            BaseDef::Expr(def) => def.build()?,
+            BaseDef::Column(selector) => Data::Context(selector.to_selector()?).into(),
+            BaseDef::Array {
+                dtype,
+                shape,
+                values,
+            } => arr_from_def(dtype, shape, values)?.into(),
+        };
+        for op in &self.ops {
+            let builder = *OP_BUILDERS
+                .read()
+                .get(op.name.as_str())
+                .ok_or_else(|| terr!("Operation {} is not registered", op.name))?;
+            let mut args = OpArgs {
+                params: op.params.clone(),
+                inputs: op
+                    .inputs
+                    .iter()
+                    .map(|(n, def)| Ok((n.clone(), def.build()?)))
+                    .collect::<TResult<_>>()?,
+            };
+            builder(&mut e, &mut args)?;
+        }
+        e.set_name(self.name.clone());
+        Ok(e)
+    }
+
+    /// Whether the definition contains arrays other than scalars.
+    pub fn has_array(&self) -> bool {
+        let base = match &self.base {
+            BaseDef::Expr(def) => def.has_array(),
+            BaseDef::Column(_) => false,
+            BaseDef::Array { values, .. } => values.len() > 1,
+        };
+        base || Iterator::any(&mut self.ops.iter(), |op| {
+            Iterator::any(&mut op.inputs.iter(), |(_, def)| def.has_array())
+        })
+    }
+
+    #[cfg(feature = "srd")]
+    pub fn to_json(&self) -> TResult<String> {
+        serde_json::to_string(self).map_err(|e| terr!("{e}"))
+    }
+
+    #[cfg(feature = "srd")]
+    pub fn from_json(s: &str) -> TResult<Self> {
+        serde_json::from_str(s).map_err(|e| terr!("{e}"))
+    }
+}
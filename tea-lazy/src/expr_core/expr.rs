@@ -1,11 +1,13 @@
 use super::data::Data;
 use super::expr_element::ExprElement;
 use super::expr_inner::{ExprInner, FuncOut};
-use super::FuncNode;
-use crate::Context;
+use super::infer::{self, ExprType};
+use super::{op, FuncNode, Op};
 #[cfg(feature = "blas")]
 use crate::OlsResult;
+use crate::{ColumnSelector, Context};
 use parking_lot::Mutex;
+use std::borrow::Cow;
 use std::fmt::Debug;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -112,7 +114,7 @@ impl<'a> Deref for Expr<'a> {
 impl<'a> Debug for Expr<'a> {
     #[inline]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", &self.lock())
+        write!(f, "{:?}", self.lock())
     }
 }
 
@@ -173,7 +175,11 @@ impl<'a> Expr<'a> {
 
     #[inline(always)]
     pub fn dtype(&self) -> String {
-        self.lock().dtype()
+        if self.step() == 0 {
+            return self.lock().dtype();
+        }
+        self.infer_type()
+            .map_or_else(|| "Unknown".to_string(), |ty| ty.dtype())
     }
 
     #[inline(always)]
@@ -181,6 +187,13 @@ impl<'a> Expr<'a> {
         Arc::strong_count(&self.0)
     }
 
+    /// Another handle of the same expression, unlike `clone` the evaluation
+    /// of one handle is seen by the others.
+    #[inline(always)]
+    pub(crate) fn share(&self) -> Self {
+        Expr(self.0.clone())
+    }
+
     #[inline(always)]
     pub fn name(&self) -> Option<String> {
         self.lock().name_owned()
@@ -232,6 +245,81 @@ impl<'a> Expr<'a> {
         }
     }
 
+    /// Chain `f` as a node described by `op`.
+    #[inline]
+    pub fn chain_op<F>(&mut self, op: Op<'a>, f: F) -> &mut Self
+    where
+        F: Fn(FuncOut<'a>) -> TResult<FuncOut<'a>> + Send + Sync + 'a,
+    {
+        let mark = self.op_mark();
+        self.chain_f_ctx(f);
+        self.set_op(mark, op);
+        self
+    }
+
+    /// Mark the current end of the function chain, see `set_op`.
+    #[inline]
+    pub fn op_mark(&self) -> (usize, usize) {
+        (Arc::as_ptr(&self.0) as usize, self.step())
+    }
+
+    /// Describe the nodes chained after `mark` with `op`. If the expression
+    /// was cloned while chaining, all the nodes are chained after `mark`.
+    #[inline]
+    pub fn set_op(&mut self, mark: (usize, usize), op: Op<'a>) {
+        let start = if Arc::as_ptr(&self.0) as usize == mark.0 {
+            mark.1
+        } else {
+            0
+        };
+        self.lock().set_op(start, op);
+    }
+
+    /// The formula of the expression, e.g. `ts_mean(close, 20) - close`.
+    #[inline]
+    pub fn formula(&self) -> String {
+        self.lock().formula()
+    }
+
+    /// A tree of the operations of the expression.
+    #[inline]
+    pub fn explain(&self) -> String {
+        self.lock().explain()
+    }
+
+    /// The columns of the context referenced by the expression, a node
+    /// without an operation (e.g. a python function) is reported as
+    /// `ColumnSelector::All` as it may reference any column.
+    #[inline]
+    pub fn context_refs(&self) -> Vec<ColumnSelector<'a>> {
+        op::context_refs(self)
+    }
+
+    /// The dtype and the dimensionality of the output inferred without evaluation
+    /// when the operations are chained, `None` if they depend on the context or
+    /// on an operation without a dtype rule.
+    #[inline]
+    pub fn infer_type(&self) -> Option<ExprType> {
+        infer::cached_type(&self.lock(), None)
+    }
+
+    /// Infer the type like `infer_type`, the columns of the context are typed
+    /// by `columns`. An error is returned if an operation doesn't accept the
+    /// dtype of its input.
+    #[inline]
+    pub fn infer_type_with(
+        &self,
+        columns: &dyn Fn(&ColumnSelector<'a>) -> Option<ExprType>,
+    ) -> TResult<Option<ExprType>> {
+        infer::infer_type(self, columns, &mut Default::default())
+    }
+
+    /// Check that the last operation accepts the dtypes of its inputs.
+    #[inline]
+    pub fn check_type(&self) -> TResult<()> {
+        infer::infer_last_op(&self.lock()).map(|_| {})
+    }
+
     #[inline]
     pub fn simplify(&mut self) {
         if let Some(e) = Arc::get_mut(&mut self.0) {
@@ -274,7 +362,7 @@ impl<'a> Expr<'a> {
                     e.eval_inplace(ctx.clone(), false)?;
                     let arr = e.view_arr(ctx.as_ref())?;
                     // need clone here
-                    match_arrok!(arr; Dynamic(a) => { Ok(a.view().to_owned().into()) },).unwrap()
+                    arr.view().into_owned()
                 };
                 Ok(out)
             }
@@ -309,7 +397,8 @@ impl<'a> Expr<'a> {
                 let arr_vec = arr_vec
                     .into_iter()
                     .map(|a| {
-                        match_arrok!(a; Dynamic(a) => { Ok(a.view().to_owned().into()) },).unwrap()
+                        match_arrok!(&*a; Dynamic(a) => { Ok(a.view().to_owned().into()) },)
+                            .unwrap()
                     })
                     .collect_trusted_to_vec();
                 Ok(arr_vec)
@@ -336,23 +425,35 @@ impl<'a> Expr<'a> {
     }
 
     #[inline]
-    pub fn view_arr(&self, ctx: Option<&Context<'a>>) -> TResult<&ArrOk<'a>> {
+    pub fn view_arr(&self, ctx: Option<&Context<'a>>) -> TResult<Cow<'_, ArrOk<'a>>> {
+        self.view_raw_arr(ctx).map(ArrOk::to_dense)
+    }
+
+    /// View the evaluated array, masked arrays are not filled so that the
+    /// validity bitmap can be exported without copying.
+    #[inline]
+    pub fn view_raw_arr(&self, ctx: Option<&Context<'a>>) -> TResult<&ArrOk<'a>> {
         let mut e = self.lock();
         e.eval_inplace(ctx.cloned(), false)?;
-        let arr = e.view_arr(ctx)?;
+        let arr = e.view_raw_arr(ctx)?;
         // safety: the array can only be read when the expression is already evaluated
         // so the data of the array should not be changed
         unsafe { Ok(std::mem::transmute::<&ArrOk<'_>, &ArrOk<'a>>(arr)) }
     }
 
     #[inline]
-    pub fn view_arr_vec(&self, ctx: Option<&Context<'a>>) -> TResult<Vec<&ArrOk<'a>>> {
+    pub fn view_arr_vec(&self, ctx: Option<&Context<'a>>) -> TResult<Vec<Cow<'_, ArrOk<'a>>>> {
         let mut e = self.lock();
         e.eval_inplace(ctx.cloned(), false)?;
         let arr = e.view_arr_vec(ctx)?;
         // safety: the array can only be read when the expression is already evaluated
         // so the data of the array should not be changed
-        unsafe { Ok(std::mem::transmute::<Vec<&ArrOk<'_>>, Vec<&ArrOk<'a>>>(arr)) }
+        unsafe {
+            Ok(std::mem::transmute::<
+                Vec<Cow<'_, ArrOk<'_>>>,
+                Vec<Cow<'_, ArrOk<'a>>>,
+            >(arr))
+        }
     }
 
     #[inline(always)]
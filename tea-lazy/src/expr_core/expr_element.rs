@@ -27,4 +27,6 @@ where
     <Self as TryInto<CrDateTime<Utc>>>::Error: std::fmt::Debug,
 {
 }
+#[cfg(feature = "time")]
+impl ExprElement for Date {}
 impl ExprElement for TimeDelta {}
@@ -1,8 +1,9 @@
-use super::Data;
+use super::infer::{self, ExprType};
+use super::{Data, Op};
 #[cfg(feature = "blas")]
 use crate::OlsResult;
 use crate::{Context, ExprElement};
-use std::{fmt::Debug, sync::Arc};
+use std::{borrow::Cow, fmt::Debug, ops::Deref, sync::Arc};
 use teapy_core::prelude::*;
 
 #[derive(Default)]
@@ -66,7 +67,25 @@ where
 
 // pub type ExprBase<'a> = Arc<Mutex<Data<'a>>>;
 pub type FuncOut<'a> = (Data<'a>, Option<Context<'a>>);
-pub type FuncNode<'a> = Arc<dyn Fn(FuncOut<'a>) -> TResult<FuncOut<'a>> + Send + Sync + 'a>;
+pub type Func<'a> = dyn Fn(FuncOut<'a>) -> TResult<FuncOut<'a>> + Send + Sync + 'a;
+
+/// A node of the function chain, `op` describes what the function does.
+#[derive(Clone)]
+pub struct FuncNode<'a> {
+    pub func: Arc<Func<'a>>,
+    pub op: Option<Arc<Op<'a>>>,
+    /// The type of the output inferred when `op` is set, only the last node
+    /// of an operation has it.
+    pub ty: Option<ExprType>,
+}
+
+impl<'a> Deref for FuncNode<'a> {
+    type Target = Func<'a>;
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.func.as_ref()
+    }
+}
 
 impl<'a> Debug for ExprInner<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -78,7 +97,9 @@ impl<'a> Debug for ExprInner<'a> {
             if let Some(name) = &self.name {
                 f.field("name", name);
             }
-            f.field("step", &self.step_acc()).finish()
+            f.field("formula", &self.formula())
+                .field("step", &self.step_acc())
+                .finish()
         }
     }
 }
@@ -172,7 +193,27 @@ impl<'a> ExprInner<'a> {
     where
         F: Fn(FuncOut<'a>) -> TResult<FuncOut<'a>> + Send + Sync + 'a,
     {
-        self.nodes.push(Arc::new(f));
+        self.nodes.push(FuncNode {
+            func: Arc::new(f),
+            op: None,
+            ty: None,
+        });
+    }
+
+    /// Describe the nodes from `start` with `op`.
+    #[inline]
+    pub fn set_op(&mut self, start: usize, op: Op<'a>) {
+        let op = Arc::new(op);
+        let start = start.min(self.nodes.len());
+        for node in &mut self.nodes[start..] {
+            node.op = Some(op.clone());
+            node.ty = None;
+        }
+        if start < self.nodes.len() {
+            // the type is unknown if the operation doesn't accept its input
+            let ty = infer::infer_last_op(self).ok().flatten();
+            self.nodes.last_mut().unwrap().ty = ty;
+        }
     }
 
     pub fn eval_inplace(
@@ -250,18 +291,35 @@ impl<'a> ExprInner<'a> {
             .map(|data| data.into_ols_res(ctx).unwrap())
     }
 
-    pub fn view_arr<'b>(&'b self, ctx: Option<&'b Context<'a>>) -> TResult<&'b ArrOk<'a>> {
+    /// The evaluated array if the expression is evaluated without a context and
+    /// owns its array, so that the array can be modified in place.
+    #[inline]
+    pub fn arr_mut(&mut self) -> Option<&mut ArrOk<'a>> {
+        match &mut self.base {
+            Data::Arr(arr) if self.nodes.is_empty() => Some(arr),
+            _ => None,
+        }
+    }
+
+    pub fn view_arr<'b>(&'b self, ctx: Option<&'b Context<'a>>) -> TResult<Cow<'b, ArrOk<'a>>> {
+        self.view_raw_arr(ctx).map(ArrOk::to_dense)
+    }
+
+    pub fn view_raw_arr<'b>(&'b self, ctx: Option<&'b Context<'a>>) -> TResult<&'b ArrOk<'a>> {
         if (self.step() > 0) & ctx.is_none() {
             tbail!("Can not view array before evaluate the expression");
         }
         if (ctx.is_some() || matches!(&self.base, Data::Context(_))) && self.step() != 0 {
-            self.ctx_ref.as_ref().unwrap().view_arr(ctx)
+            self.ctx_ref.as_ref().unwrap().view_raw_arr(ctx)
         } else {
-            self.base.view_arr(ctx)
+            self.base.view_raw_arr(ctx)
         }
     }
 
-    pub fn view_arr_vec<'b>(&'b self, ctx: Option<&'b Context<'a>>) -> TResult<Vec<&'b ArrOk<'a>>> {
+    pub fn view_arr_vec<'b>(
+        &'b self,
+        ctx: Option<&'b Context<'a>>,
+    ) -> TResult<Vec<Cow<'b, ArrOk<'a>>>> {
         if (self.step() > 0) & ctx.is_none() {
             tbail!("Can not view array before evaluate the expression");
         }
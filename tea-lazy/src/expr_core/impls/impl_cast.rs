@@ -1,21 +1,30 @@
-use crate::Expr;
+use crate::{Expr, Op, OutDtype, OutNdim, TypeCheck, TypeRule};
 use teapy_core::prelude::*;
 
+/// The rule of a cast to the arrays of `T`.
+#[inline]
+fn cast_rule<T: 'static>() -> TypeRule
+where
+    ArrD<T>: Into<ArrOk<'static>>,
+{
+    TypeRule::new(TypeCheck::ANY, OutDtype::of::<T>(), OutNdim::Same)
+}
+
 macro_rules! impl_cast {
-    ($($(#[$meta: meta])? $func: ident: $dtype: ident $(($inner: path))?),* $(,)?) => {
+    ($($(#[$meta: meta])? $func: ident: $dtype: ident $(($inner: path))? => $ty: ty),* $(,)?) => {
         impl<'a> Expr<'a> {
             $(
                 $(#[$meta])?
                 pub fn $func(&mut self) -> &mut Self {
-                    self.chain_f_ctx(|(arr, ctx)| {
+                    let op = Op::new(stringify!($func)).rule(cast_rule::<$ty>());
+                    self.chain_op(op, |(arr, ctx)| {
                         let dtype = arr.view_arr(ctx.as_ref())?.dtype();
                         if dtype == DataType::$dtype $(($inner))? {
                             return Ok((arr, ctx));
                         } else {
                             Ok((arr.into_arr(ctx.clone())?.$func().into(), ctx))
                         }
-                    });
-                    self
+                    })
                 }
             )*
         }
@@ -23,54 +32,98 @@ macro_rules! impl_cast {
 }
 
 impl_cast!(
-    cast_f32: F32,
-    cast_f64: F64,
-    cast_i32: I32,
-    cast_i64: I64,
-    cast_u64: U64,
-    cast_usize: Usize,
-    cast_string: String,
-    cast_bool: Bool,
-    cast_object: Object,
-    #[cfg(feature="time")] cast_datetime_ms: DateTime(TimeUnit::Millisecond),
-    #[cfg(feature="time")] cast_datetime_us: DateTime(TimeUnit::Microsecond),
-    #[cfg(feature="time")] cast_datetime_ns: DateTime(TimeUnit::Nanosecond),
-    #[cfg(feature="time")] cast_timedelta: TimeDelta,
-    cast_optusize: OptUsize,
-    cast_vecusize: VecUsize,
+    cast_f32: F32 => f32,
+    cast_f64: F64 => f64,
+    cast_i32: I32 => i32,
+    cast_i64: I64 => i64,
+    cast_u64: U64 => u64,
+    cast_usize: Usize => usize,
+    cast_string: String => String,
+    cast_bool: Bool => bool,
+    cast_object: Object => Object,
+    #[cfg(feature="time")] cast_datetime_ms: DateTime(TimeUnit::Millisecond) => DateTime<unit::Millisecond>,
+    #[cfg(feature="time")] cast_datetime_us: DateTime(TimeUnit::Microsecond) => DateTime<unit::Microsecond>,
+    #[cfg(feature="time")] cast_datetime_ns: DateTime(TimeUnit::Nanosecond) => DateTime<unit::Nanosecond>,
+    #[cfg(feature="time")] cast_date: DateTime(TimeUnit::Day) => Date,
+    #[cfg(feature="time")] cast_timedelta: TimeDelta => TimeDelta,
+    cast_optusize: OptUsize => Option<usize>,
+    cast_vecusize: VecUsize => Vec<usize>,
+);
+
+// the compact dtypes can't be expressed by `DataType`, the array is returned
+// directly by `ArrOk` if the dtype is already correct
+macro_rules! impl_compact_cast {
+    ($($func: ident => $ty: ty),* $(,)?) => {
+        impl<'a> Expr<'a> {
+            $(
+                pub fn $func(&mut self) -> &mut Self {
+                    let op = Op::new(stringify!($func)).rule(cast_rule::<$ty>());
+                    self.chain_op(op, |(arr, ctx)| {
+                        let arr = arr.into_arr(ctx.clone())?;
+                        Ok((ArrOk::from(arr.$func()).into(), ctx))
+                    })
+                }
+            )*
+        }
+    };
+}
+
+impl_compact_cast!(
+    cast_i8 => i8,
+    cast_i16 => i16,
+    cast_u16 => u16,
+    cast_u32 => u32,
+    cast_opt_u8 => Option<u8>,
+    cast_opt_u64 => Option<u64>,
 );
 
 impl<'a> Expr<'a> {
     pub fn cast_float(&mut self) -> &mut Self {
-        self.chain_f_ctx(|(arr, ctx)| {
+        self.chain_op(Op::new("cast_float"), |(arr, ctx)| {
             let dtype = arr.view_arr(ctx.as_ref())?.dtype();
             if dtype.is_float() {
                 Ok((arr, ctx))
             } else {
                 Ok((arr.into_arr(ctx.clone())?.cast_float().into(), ctx))
             }
-        });
-        self
+        })
     }
 
     pub fn cast_int(&mut self) -> &mut Self {
-        self.chain_f_ctx(|(arr, ctx)| {
+        self.chain_op(Op::new("cast_int"), |(arr, ctx)| {
             let dtype = arr.view_arr(ctx.as_ref())?.dtype();
             if dtype.is_int() {
                 Ok((arr, ctx))
             } else {
                 Ok((arr.into_arr(ctx.clone())?.cast_int().into(), ctx))
             }
-        });
-        self
+        })
+    }
+
+    pub fn cast_categorical(&mut self) -> &mut Self {
+        self.chain_op(Op::new("cast_categorical"), |(arr, ctx)| {
+            let arr = arr.into_arr(ctx.clone())?;
+            Ok((ArrOk::from(arr.cast_categorical()).into(), ctx))
+        })
+    }
+
+    /// Cast to `Decimal(precision, scale)`, see `ArrOk::cast_decimal`.
+    pub fn cast_decimal(&mut self, precision: u8, scale: u8) -> &mut Self {
+        let op = Op::new("cast_decimal")
+            .param("precision", precision)
+            .param("scale", scale);
+        self.chain_op(op, move |(arr, ctx)| {
+            let arr = arr.into_arr(ctx.clone())?;
+            Ok((ArrOk::from(arr.cast_decimal(precision, scale)?).into(), ctx))
+        })
     }
 
     #[cfg(feature = "time")]
     pub fn cast_datetime(&mut self, unit: Option<TimeUnit>) -> &mut Self {
-        self.chain_f_ctx(move |(arr, ctx)| {
+        let op = Op::new("cast_datetime").param("unit", unit.map(|u| format!("{u:?}")));
+        self.chain_op(op, move |(arr, ctx)| {
             let arr = arr.into_arr(ctx.clone())?;
             Ok((arr.cast_datetime(unit).into(), ctx))
-        });
-        self
+        })
     }
 }
@@ -1,4 +1,4 @@
-use crate::Expr;
+use crate::{Expr, Op};
 use std::ops::{Add, BitAnd, BitOr, Div, Mul, Neg, Not, Sub};
 use teapy_core::prelude::*;
 
@@ -6,25 +6,32 @@ macro_rules! impl_cmp {
     ($func: ident $(, $(#[$meta: meta])? $dtype: ident)*) => {
         impl<'a> Expr<'a> {
             pub fn $func(&mut self, rhs: Expr<'a>, par: bool) {
-                self.chain_f_ctx(move |(data, ctx)| {
+                let op = Op::new(stringify!($func))
+                    .input("rhs", &rhs)
+                    .param("par", par);
+                self.chain_op(op, move |(data, ctx)| {
                     let ldtype = data.view_arr(ctx.as_ref())?.dtype();
                     let rdtype = rhs.view_arr(ctx.as_ref())?.dtype();
                     let out = if ldtype.is_float() | rdtype.is_float() {
-                        let arr = data.view_arr(ctx.as_ref())?.as_float();
+                        let arr = data.view_arr(ctx.as_ref())?;
+                        let arr = arr.as_float();
                         match_arrok!(arr; Float(a) => {
-                            let rhs_arr: ArbArray<_> = rhs.view_arr(ctx.as_ref())?.deref().cast();
+                            let rhs_arr = rhs.view_arr(ctx.as_ref())?;
+                            let rhs_arr: ArbArray<_> = rhs_arr.deref().cast();
                             Ok(a.view().$func(&rhs_arr.view(), par))
                         },).unwrap()
                     } else if ldtype.is_int() | rdtype.is_int() {
-                        let arr = data.view_arr(ctx.as_ref())?.as_int();
+                        let arr = data.view_arr(ctx.as_ref())?;
+                        let arr = arr.as_int();
                         match_arrok!(arr; Int(a) => {
-                            let rhs_arr: ArbArray<_> = rhs.view_arr(ctx.as_ref())?.deref().cast();
+                            let rhs_arr = rhs.view_arr(ctx.as_ref())?;
+                            let rhs_arr: ArbArray<_> = rhs_arr.deref().cast();
                             Ok(a.view().$func(&rhs_arr.view(), par))
                         },).unwrap()
                     } else {
                         let arr = data.view_arr(ctx.as_ref())?;
                         let rhs_arr = rhs.view_arr(ctx.as_ref())?;
-                        match (arr, rhs_arr) {
+                        match (&*arr, &*rhs_arr) {
                             $(
                                 $(#[$meta])? (ArrOk::$dtype(a), ArrOk::$dtype(r)) => {
                                     a.view().$func(&r.view(), par)
@@ -44,25 +51,30 @@ macro_rules! impl_dot {
     ($func: ident $(, $(#[$meta: meta])? $dtype: ident)*) => {
         impl<'a> Expr<'a> {
             pub fn $func(&mut self, rhs: Expr<'a>) {
-                self.chain_f_ctx(move |(data, ctx)| {
+                let op = Op::new(stringify!($func)).input("rhs", &rhs);
+                self.chain_op(op, move |(data, ctx)| {
                     let ldtype = data.view_arr(ctx.as_ref())?.dtype();
                     let rdtype = rhs.view_arr(ctx.as_ref())?.dtype();
                     let out: ArrOk = if ldtype.is_float() | rdtype.is_float() {
-                        let arr = data.view_arr(ctx.as_ref())?.as_float();
+                        let arr = data.view_arr(ctx.as_ref())?;
+                        let arr = arr.as_float();
                         match_arrok!(arr; PureFloat(a) => {
-                            let rhs_arr: ArbArray<_> = rhs.view_arr(ctx.as_ref())?.deref().cast();
+                            let rhs_arr = rhs.view_arr(ctx.as_ref())?;
+                            let rhs_arr: ArbArray<_> = rhs_arr.deref().cast();
                             Ok(a.view().$func(&rhs_arr.view())?.into())
                         },).unwrap()
                     } else if ldtype.is_int() | rdtype.is_int() {
-                        let arr = data.view_arr(ctx.as_ref())?.as_int();
+                        let arr = data.view_arr(ctx.as_ref())?;
+                        let arr = arr.as_int();
                         match_arrok!(arr; PureInt(a) => {
-                            let rhs_arr: ArbArray<_> = rhs.view_arr(ctx.as_ref())?.deref().cast();
+                            let rhs_arr = rhs.view_arr(ctx.as_ref())?;
+                            let rhs_arr: ArbArray<_> = rhs_arr.deref().cast();
                             Ok(a.view().$func(&rhs_arr.view())?.into())
                         },).unwrap()
                     } else {
                         let arr = data.view_arr(ctx.as_ref())?;
                         let rhs_arr = rhs.view_arr(ctx.as_ref())?;
-                        match (arr, rhs_arr) {
+                        match (&*arr, &*rhs_arr) {
                             $(
                                 $(#[$meta])? (ArrOk::$dtype(a), ArrOk::$dtype(r)) => {
                                     a.view().$func(&r.view())?.into()
@@ -72,7 +84,7 @@ macro_rules! impl_dot {
                         }
                     };
                     Ok((out.into(), ctx))
-                })
+                });
             }
         }
     };
@@ -157,14 +169,35 @@ impl_cmp!(
 
 impl_dot!(dot);
 
+/// Whether the array can be an operand of exact decimal arithmetic.
+#[inline]
+fn is_decimal_operand(arr: &ArrOk) -> bool {
+    matches!(arr, ArrOk::Decimal(_)) || arr.is_int()
+}
+
+/// View a decimal operand, integers are cast to decimals of scale 0.
+fn as_decimal<'b>(arr: &'b ArrOk<'_>) -> TResult<DecArray<'b>> {
+    match arr {
+        ArrOk::Decimal(a) => Ok(a.view()),
+        // 19 digits are enough for any i64
+        arr => arr.deref().cast_decimal(19, 0),
+    }
+}
+
 impl<'a> Add for Expr<'a> {
     type Output = Expr<'a>;
     fn add(mut self, rhs: Self) -> Self::Output {
-        self.chain_f_ctx(move |(data, ctx)| {
+        let op = Op::new("add").input("rhs", &rhs);
+        self.chain_op(op, move |(data, ctx)| {
             let arr = data.into_arr(ctx.clone())?;
-            let rhs_arr = rhs.view_arr(ctx.as_ref())?;
+            let rhs_arr = &*rhs.view_arr(ctx.as_ref())?;
             use ArrOk::*;
             let out = match (&arr, &rhs_arr) {
+                (Decimal(_), _) | (_, Decimal(_))
+                    if is_decimal_operand(&arr) && is_decimal_operand(rhs_arr) =>
+                {
+                    ArrOk::from(as_decimal(&arr)?.add(&as_decimal(rhs_arr)?)?).into()
+                }
                 (F64(_), _) | (_, F64(_)) => (arr.cast_f64().into_owned().0
                     + rhs_arr.deref().cast_f64().view().0)
                     .wrap()
@@ -260,12 +293,18 @@ impl<'a> Add for Expr<'a> {
 impl<'a> Sub for Expr<'a> {
     type Output = Expr<'a>;
     fn sub(mut self, rhs: Self) -> Self::Output {
-        self.chain_f_ctx(move |(data, ctx)| {
+        let op = Op::new("sub").input("rhs", &rhs);
+        self.chain_op(op, move |(data, ctx)| {
             let arr = data.into_arr(ctx.clone())?;
             // rhs.eval_inplace(ctx.clone())?;
-            let rhs_arr = rhs.view_arr(ctx.as_ref())?;
+            let rhs_arr = &*rhs.view_arr(ctx.as_ref())?;
             use ArrOk::*;
             let out = match (&arr, &rhs_arr) {
+                (Decimal(_), _) | (_, Decimal(_))
+                    if is_decimal_operand(&arr) && is_decimal_operand(rhs_arr) =>
+                {
+                    ArrOk::from(as_decimal(&arr)?.sub(&as_decimal(rhs_arr)?)?).into()
+                }
                 (F64(_), _) | (_, F64(_)) => (arr.cast_f64().into_owned().0
                     - rhs_arr.deref().cast_f64().view().0)
                     .wrap()
@@ -348,12 +387,19 @@ impl<'a> Sub for Expr<'a> {
 impl<'a> Mul for Expr<'a> {
     type Output = Expr<'a>;
     fn mul(mut self, rhs: Self) -> Self::Output {
-        self.chain_f_ctx(move |(data, ctx)| {
+        let op = Op::new("mul").input("rhs", &rhs);
+        self.chain_op(op, move |(data, ctx)| {
             let arr = data.into_arr(ctx.clone())?;
             // rhs.eval_inplace(ctx.clone())?;
-            let rhs_arr = rhs.view_arr(ctx.as_ref())?;
+            let rhs_arr = &*rhs.view_arr(ctx.as_ref())?;
             use ArrOk::*;
             let out = match (&arr, &rhs_arr) {
+                (Decimal(a), r) if r.is_int() => {
+                    ArrOk::from(a.mul_int(r.deref().cast_i64().view())?).into()
+                }
+                (l, Decimal(b)) if l.is_int() => {
+                    ArrOk::from(b.mul_int(l.deref().cast_i64().view())?).into()
+                }
                 (F64(_), _) | (_, F64(_)) => (arr.cast_f64().into_owned().0
                     * rhs_arr.deref().cast_f64().view().0)
                     .wrap()
@@ -377,6 +423,10 @@ impl<'a> Mul for Expr<'a> {
                 // (TimeDelta(_), I32(_)) => {
                 //     arr.cast_timedelta().to_owned().0.mul(rhs_arr.cast_i32().view().0).wrap().into()
                 // },
+                (Decimal(_), _) | (_, Decimal(_)) => (arr.cast_f64().into_owned().0
+                    * rhs_arr.deref().cast_f64().view().0)
+                    .wrap()
+                    .into(),
                 _ => todo!(),
             };
             Ok((out, ctx))
@@ -388,10 +438,11 @@ impl<'a> Mul for Expr<'a> {
 impl<'a> Div for Expr<'a> {
     type Output = Expr<'a>;
     fn div(mut self, rhs: Self) -> Self::Output {
-        self.chain_f_ctx(move |(data, ctx)| {
+        let op = Op::new("div").input("rhs", &rhs);
+        self.chain_op(op, move |(data, ctx)| {
             let arr = data.into_arr(ctx.clone())?;
             // rhs.eval_inplace(ctx.clone())?;
-            let rhs_arr = rhs.view_arr(ctx.as_ref())?;
+            let rhs_arr = &*rhs.view_arr(ctx.as_ref())?;
             use ArrOk::*;
             let out = match (&arr, &rhs_arr) {
                 (F64(_), _) | (_, F64(_)) => (arr.cast_f64().into_owned().0
@@ -407,7 +458,10 @@ impl<'a> Div for Expr<'a> {
                 | (I32(_), _)
                 | (_, I32(_))
                 | (Usize(_), _)
-                | (_, Usize(_)) => (arr.cast_f64().into_owned().0
+                | (_, Usize(_))
+                // exact decimal division needs a scale and a round mode, see `dec_div`
+                | (Decimal(_), _)
+                | (_, Decimal(_)) => (arr.cast_f64().into_owned().0
                     / rhs_arr.deref().cast_f64().view().0)
                     .wrap()
                     .into(),
@@ -422,11 +476,12 @@ impl<'a> Div for Expr<'a> {
 impl<'a> BitAnd for Expr<'a> {
     type Output = Expr<'a>;
     fn bitand(mut self, rhs: Self) -> Self::Output {
+        let (op, mark) = (Op::new("and").input("rhs", &rhs), self.op_mark());
         self.cast_bool().chain_f_ctx(move |(data, ctx)| {
             let arr = data.into_arr(ctx.clone())?;
             let mut rhs = rhs.clone();
             rhs.cast_bool().eval_inplace(ctx.clone())?;
-            let rhs_arr = rhs.view_arr(ctx.as_ref())?;
+            let rhs_arr = &*rhs.view_arr(ctx.as_ref())?;
             let out = match_arrok!((arr, a, Bool), (rhs_arr, b, Bool), {
                 Ok(a.cast_bool()
                     .into_owned()
@@ -438,6 +493,7 @@ impl<'a> BitAnd for Expr<'a> {
             .unwrap();
             Ok((out, ctx))
         });
+        self.set_op(mark, op);
         self
     }
 }
@@ -445,11 +501,12 @@ impl<'a> BitAnd for Expr<'a> {
 impl<'a> BitOr for Expr<'a> {
     type Output = Expr<'a>;
     fn bitor(mut self, rhs: Self) -> Self::Output {
+        let (op, mark) = (Op::new("or").input("rhs", &rhs), self.op_mark());
         self.cast_bool().chain_f_ctx(move |(data, ctx)| {
             let arr = data.into_arr(ctx.clone())?;
             let mut rhs = rhs.clone();
             rhs.cast_bool().eval_inplace(ctx.clone())?;
-            let rhs_arr = rhs.view_arr(ctx.as_ref())?;
+            let rhs_arr = &*rhs.view_arr(ctx.as_ref())?;
             let out = match_arrok!((arr, a, Bool), (rhs_arr, b, Bool), {
                 Ok(a.cast_bool()
                     .into_owned()
@@ -461,6 +518,7 @@ impl<'a> BitOr for Expr<'a> {
             .unwrap();
             Ok((out, ctx))
         });
+        self.set_op(mark, op);
         self
     }
 }
@@ -468,7 +526,7 @@ impl<'a> BitOr for Expr<'a> {
 impl<'a> Neg for Expr<'a> {
     type Output = Expr<'a>;
     fn neg(mut self) -> Self::Output {
-        self.chain_f_ctx(move |(data, ctx)| {
+        self.chain_op(Op::new("neg"), move |(data, ctx)| {
             let arr = data.into_arr(ctx.clone())?;
             let out = match_arrok!(
                 arr;
@@ -485,12 +543,14 @@ impl<'a> Neg for Expr<'a> {
 impl<'a> Not for Expr<'a> {
     type Output = Expr<'a>;
     fn not(mut self) -> Self::Output {
+        let mark = self.op_mark();
         self.cast_bool().chain_f_ctx(move |(data, ctx)| {
             let arr = data.into_arr(ctx.clone())?;
             let out =
                 match_arrok!(arr; Bool(a) => { Ok((!a.into_owned().0).wrap().into()) },).unwrap();
             Ok((out, ctx))
         });
+        self.set_op(mark, Op::new("not"));
         self
     }
 }
@@ -5,7 +5,7 @@ pub use utils::adjust_slice;
 #[cfg(feature = "ops")]
 mod impl_ops;
 
-use super::super::Expr;
+use super::super::{Expr, Op};
 use teapy_core::prelude::*;
 use teapy_core::utils::CollectTrustedToVec;
 
@@ -18,13 +18,17 @@ impl<'a> Expr<'a> {
         // several Exprs without clone?
         let mut out = (0..len).map(|_| self.clone()).collect_trusted();
         out.iter_mut().enumerate().for_each(|(i, e)| {
-            e.chain_f_ctx(move |(data, ctx)| {
-                let arr = data.view_arr_vec(ctx.as_ref())?.remove(i);
-                Ok((
-                    match_arrok!(arr; Dynamic(a) => { Ok(a.view().to_owned().into()) },).unwrap(),
-                    ctx,
-                ))
-            });
+            e.chain_op(
+                Op::new("split_vec_base").param("index", i),
+                move |(data, ctx)| {
+                    let arr = data.view_arr_vec(ctx.as_ref())?.remove(i);
+                    Ok((
+                        match_arrok!(&*arr; Dynamic(a) => { Ok(a.view().to_owned().into()) },)
+                            .unwrap(),
+                        ctx,
+                    ))
+                },
+            );
         });
         out
     }
@@ -0,0 +1,248 @@
+use super::expr_inner::ExprInner;
+use super::op::{node_group_ends, SCOPED_INPUTS};
+use super::{Data, Expr, Op};
+use crate::{ColumnSelector, TpHashMap};
+use ndarray::SliceInfoElem;
+use std::sync::Arc;
+use teapy_core::prelude::*;
+
+/// The dtypes accepted by an input of an operation.
+#[derive(Clone, Copy)]
+pub struct TypeCheck {
+    /// The name of the accepted dtypes, e.g. `Numeric`.
+    pub expect: &'static str,
+    pub accepts: fn(&ArrOk<'_>) -> bool,
+}
+
+impl TypeCheck {
+    pub const ANY: TypeCheck = TypeCheck {
+        expect: "any",
+        accepts: |_| true,
+    };
+
+    fn check(&self, op: &str, ty: &ExprType) -> TResult<()> {
+        if !(self.accepts)(&ty.kernel_proto()) {
+            tbail!("{op}: expect a {} dtype, found {}", self.expect, ty.dtype());
+        }
+        Ok(())
+    }
+}
+
+/// The dtype of the output of an operation.
+#[derive(Clone, Copy)]
+pub enum OutDtype {
+    /// Same as the dtype of the input.
+    Same,
+    /// The dtype of the array returned by the function.
+    Of(fn() -> ArrOk<'static>),
+    Unknown,
+}
+
+impl OutDtype {
+    /// The dtype of the arrays of `T`.
+    #[inline]
+    pub fn of<T: 'static>() -> Self
+    where
+        ArrD<T>: Into<ArrOk<'static>>,
+    {
+        OutDtype::Of(|| Arr1::<T>::from_vec(Vec::new()).into_dyn().into())
+    }
+}
+
+/// The dimensionality of the output of an operation.
+#[derive(Clone, Copy)]
+pub enum OutNdim {
+    Same,
+    /// An axis of the input is reduced.
+    Reduce,
+    Unknown,
+}
+
+/// The dtype rule of an operation: the dtypes of the inputs it accepts and
+/// the dtype and the dimensionality of its output.
+#[derive(Clone, Copy)]
+pub struct TypeRule {
+    pub input: TypeCheck,
+    /// The check of the input named `other`.
+    pub other: Option<TypeCheck>,
+    pub dtype: OutDtype,
+    pub ndim: OutNdim,
+}
+
+impl TypeRule {
+    #[inline]
+    pub fn new(input: TypeCheck, dtype: OutDtype, ndim: OutNdim) -> Self {
+        TypeRule {
+            input,
+            other: None,
+            dtype,
+            ndim,
+        }
+    }
+
+    #[inline]
+    pub fn other(mut self, other: TypeCheck) -> Self {
+        self.other = Some(other);
+        self
+    }
+}
+
+/// The dtype and the dimensionality of the output of an expression, inferred
+/// without evaluating the expression.
+#[derive(Clone)]
+pub struct ExprType {
+    /// An empty array of the dtype, or the array itself if it is a scalar.
+    proto: ArrOk<'static>,
+    pub ndim: Option<usize>,
+}
+
+impl ExprType {
+    pub fn new(arr: &ArrOk<'_>) -> Self {
+        let ndim = arr.ndim();
+        let proto = if ndim == 0 {
+            arr.clone().into_owned()
+        } else {
+            let mut info = vec![SliceInfoElem::from(..); ndim];
+            info[0] = SliceInfoElem::from(..0);
+            match arr {
+                // slicing a masked array gives the dense array, whose dtype
+                // differs for masked strings
+                ArrOk::Masked(a) => {
+                    let values = a.values.slice(info.as_slice()).into_owned();
+                    MaskedArray::new(values, Bitmap::new_valid(0))
+                        .unwrap()
+                        .into()
+                }
+                arr => arr.slice(info.as_slice()).into_owned(),
+            }
+        };
+        ExprType {
+            proto,
+            ndim: Some(ndim),
+        }
+    }
+
+    #[inline]
+    pub fn dtype(&self) -> String {
+        self.proto.dtype_name()
+    }
+
+    /// An array of the dtype, its shape is meaningless.
+    #[inline]
+    pub fn proto(&self) -> &ArrOk<'static> {
+        &self.proto
+    }
+
+    /// An array of the dtype the kernels compute on, see `Data::view_arr`.
+    #[inline]
+    fn kernel_proto(&self) -> ArrOk<'static> {
+        self.proto.to_dense().into_owned().widen()
+    }
+}
+
+/// The type of the output of `op` applied to an input of type `ty`, the
+/// inputs of the operation are typed by `input_type`. An error is returned
+/// if the operation doesn't accept the dtype of its input.
+fn infer_op<'a>(
+    op: &Op<'a>,
+    ty: Option<ExprType>,
+    mut input_type: impl FnMut(&Expr<'a>) -> TResult<Option<ExprType>>,
+) -> TResult<Option<ExprType>> {
+    let mut other = None;
+    for (name, input) in &op.inputs {
+        if !SCOPED_INPUTS.contains(name) {
+            let input_ty = input_type(input)?;
+            if *name == "other" {
+                other = input_ty;
+            }
+        }
+    }
+    let Some(rule) = &op.rule else {
+        return Ok(None);
+    };
+    if let Some(ty) = &ty {
+        rule.input.check(op.name, ty)?;
+    }
+    if let (Some(check), Some(other)) = (&rule.other, &other) {
+        check.check(op.name, other)?;
+    }
+    let ndim = ty.as_ref().and_then(|ty| ty.ndim);
+    let ndim = match rule.ndim {
+        OutNdim::Same => ndim,
+        OutNdim::Reduce => ndim.map(|n| n.saturating_sub(1)),
+        OutNdim::Unknown => None,
+    };
+    let proto = match rule.dtype {
+        OutDtype::Same => ty.map(|ty| ty.kernel_proto()),
+        OutDtype::Of(f) => Some(f()),
+        OutDtype::Unknown => None,
+    };
+    Ok(proto.map(|proto| ExprType { proto, ndim }))
+}
+
+/// Infer the type of `e`, the context columns are typed by `columns`.
+/// An error is returned if an operation doesn't accept the dtype of its input.
+pub(super) fn infer_type<'a>(
+    e: &Expr<'a>,
+    columns: &dyn Fn(&ColumnSelector<'a>) -> Option<ExprType>,
+    memo: &mut TpHashMap<usize, Option<ExprType>>,
+) -> TResult<Option<ExprType>> {
+    let p = Arc::as_ptr(e) as usize;
+    if let Some(ty) = memo.get(&p) {
+        return Ok(ty.clone());
+    }
+    let inner = e.lock();
+    let mut ty = match &inner.base {
+        Data::Expr(base) => infer_type(base, columns, memo)?,
+        Data::Context(col) => columns(col),
+        base => base_type(base),
+    };
+    for (_, op) in node_group_ends(&inner.nodes) {
+        ty = match op {
+            Some(op) => infer_op(op, ty, |input| infer_type(input, columns, memo))?,
+            None => None,
+        };
+    }
+    drop(inner);
+    memo.insert(p, ty.clone());
+    Ok(ty)
+}
+
+/// The type of the base of an expression without the context.
+fn base_type(base: &Data<'_>) -> Option<ExprType> {
+    match base {
+        Data::Expr(e) => cached_type(&e.lock(), None),
+        Data::Arr(arr) => Some(ExprType::new(arr)),
+        Data::ArcArr(arr) => Some(ExprType::new(arr)),
+        _ => None,
+    }
+}
+
+/// The type of the output of the first `end` nodes (all the nodes if `None`)
+/// cached on the nodes, `end` must be the end of an operation.
+pub(super) fn cached_type(e: &ExprInner<'_>, end: Option<usize>) -> Option<ExprType> {
+    match end.unwrap_or(e.nodes.len()) {
+        0 => base_type(&e.base),
+        end => e.nodes[end - 1].ty.clone(),
+    }
+}
+
+/// Infer the type of the output of the last operation from the types cached
+/// on the nodes before it and on its inputs.
+pub(super) fn infer_last_op(e: &ExprInner<'_>) -> TResult<Option<ExprType>> {
+    let Some((last, _)) = e.nodes.split_last() else {
+        return Ok(base_type(&e.base));
+    };
+    let Some(op) = &last.op else {
+        return Ok(None);
+    };
+    // the nodes of the operation are at the end
+    let start = e
+        .nodes
+        .iter()
+        .rposition(|node| !node.op.as_ref().is_some_and(|o| Arc::ptr_eq(o, op)))
+        .map_or(0, |i| i + 1);
+    infer_op(op, cached_type(e, Some(start)), |input| {
+        Ok(cached_type(&input.lock(), None))
+    })
+}
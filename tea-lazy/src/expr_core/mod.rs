@@ -1,13 +1,27 @@
+mod cse;
 mod data;
+mod def;
 mod expr;
 mod expr_element;
 mod expr_inner;
 mod impls;
+mod infer;
+mod op;
 
+pub use cse::CommonSubexprs;
 pub use data::Data;
+pub use def::{
+    register_op, BaseDef, ExprDef, FromOpArg, FromOpParam, OpArgs, OpBuilder, OpDef, SelectorDef,
+};
+#[doc(hidden)]
+pub use def::{OpArgParser, ParseOpArg, ParseOpArgAny, ParseOpArgStr};
 pub use expr::Expr;
 pub use expr_element::ExprElement;
-pub use expr_inner::{FuncNode, FuncOut};
+pub use expr_inner::{Func, FuncNode, FuncOut};
+pub use infer::{ExprType, OutDtype, OutNdim, TypeCheck, TypeRule};
+pub use op::{Op, OpArg, OpParam, ToOpParam};
+#[doc(hidden)]
+pub use op::{OpArgProbe, RecordOpArg, RecordOpArgAny, RecordOpArgDebug};
 
 pub use impls::adjust_slice;
 // #[cfg(feature = "agg")]
@@ -0,0 +1,487 @@
+use super::expr_inner::{ExprInner, FuncNode};
+use super::infer::TypeRule;
+use super::{Data, Expr};
+use crate::{ColumnSelector, TpHashMap};
+use std::fmt::{self, Debug, Display, Write};
+use std::sync::Arc;
+use teapy_core::prelude::*;
+
+/// The inputs evaluated in their own context (the windows of rolling and the
+/// groups of groupby) rather than the context of the expression.
+pub(super) const SCOPED_INPUTS: &[&str] = &["agg_expr"];
+
+/// A parameter of an operation.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "srd", derive(serde::Serialize, serde::Deserialize))]
+pub enum OpParam {
+    None,
+    Bool(bool),
+    Int(i64),
+    Float(#[cfg_attr(feature = "srd", serde(with = "float_param"))] f64),
+    Str(String),
+    List(Vec<OpParam>),
+    /// A parameter that can only be described, e.g. an enum or a closure.
+    Other(String),
+}
+
+/// serde_json writes the non-finite floats as `null`, which can't be read
+/// back, so they are written as strings, e.g. `"NaN"` and `"-inf"`.
+#[cfg(feature = "srd")]
+mod float_param {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(v: &f64, s: S) -> Result<S::Ok, S::Error> {
+        if v.is_finite() {
+            s.serialize_f64(*v)
+        } else {
+            s.serialize_str(&v.to_string())
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Float {
+        Num(f64),
+        Str(String),
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+        match Float::deserialize(d)? {
+            Float::Num(v) => Ok(v),
+            Float::Str(v) => v.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+impl Display for OpParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpParam::None => write!(f, "None"),
+            OpParam::Bool(v) => write!(f, "{}", if *v { "True" } else { "False" }),
+            OpParam::Int(v) => write!(f, "{v}"),
+            OpParam::Float(v) => write!(f, "{v:?}"),
+            OpParam::Str(v) => write!(f, "{v:?}"),
+            OpParam::List(v) => {
+                write!(f, "[")?;
+                for (i, p) in v.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{p}")?;
+                }
+                write!(f, "]")
+            }
+            OpParam::Other(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+/// Values that can be recorded as an `OpParam`.
+pub trait ToOpParam {
+    fn to_op_param(&self) -> OpParam;
+}
+
+macro_rules! impl_to_op_param {
+    ($variant: ident: $($ty: ty),*) => {
+        $(impl ToOpParam for $ty {
+            #[inline]
+            fn to_op_param(&self) -> OpParam {
+                OpParam::$variant((*self).into())
+            }
+        })*
+    };
+}
+
+impl_to_op_param!(Bool: bool);
+impl_to_op_param!(Int: i8, i16, i32, i64, u8, u16, u32);
+impl_to_op_param!(Float: f32, f64);
+
+macro_rules! impl_to_op_param_as_i64 {
+    ($($ty: ty),*) => {
+        $(impl ToOpParam for $ty {
+            #[inline]
+            fn to_op_param(&self) -> OpParam {
+                OpParam::Int(*self as i64)
+            }
+        })*
+    };
+}
+
+impl_to_op_param_as_i64!(u64, usize, isize);
+
+impl ToOpParam for char {
+    #[inline]
+    fn to_op_param(&self) -> OpParam {
+        OpParam::Str(self.to_string())
+    }
+}
+
+impl ToOpParam for str {
+    #[inline]
+    fn to_op_param(&self) -> OpParam {
+        OpParam::Str(self.to_string())
+    }
+}
+
+impl ToOpParam for String {
+    #[inline]
+    fn to_op_param(&self) -> OpParam {
+        OpParam::Str(self.clone())
+    }
+}
+
+impl<T: ToOpParam + ?Sized> ToOpParam for &T {
+    #[inline]
+    fn to_op_param(&self) -> OpParam {
+        (**self).to_op_param()
+    }
+}
+
+impl<T: ToOpParam> ToOpParam for Option<T> {
+    #[inline]
+    fn to_op_param(&self) -> OpParam {
+        self.as_ref().map_or(OpParam::None, ToOpParam::to_op_param)
+    }
+}
+
+impl<T: ToOpParam> ToOpParam for Vec<T> {
+    #[inline]
+    fn to_op_param(&self) -> OpParam {
+        OpParam::List(self.iter().map(ToOpParam::to_op_param).collect())
+    }
+}
+
+/// The operation of the nodes of an expression: the function name, the
+/// parameters and the input expressions other than the expression itself.
+#[derive(Clone)]
+pub struct Op<'a> {
+    pub name: &'static str,
+    pub params: Vec<(&'static str, OpParam)>,
+    pub inputs: Vec<(&'static str, Expr<'a>)>,
+    /// The dtypes accepted by the operation and the dtype of its output.
+    pub rule: Option<TypeRule>,
+}
+
+impl<'a> Op<'a> {
+    #[inline]
+    pub fn new(name: &'static str) -> Self {
+        Op {
+            name,
+            params: Vec::new(),
+            inputs: Vec::new(),
+            rule: None,
+        }
+    }
+
+    #[inline]
+    pub fn param<P: ToOpParam>(mut self, name: &'static str, param: P) -> Self {
+        self.params.push((name, param.to_op_param()));
+        self
+    }
+
+    #[inline]
+    pub fn input(mut self, name: &'static str, expr: &Expr<'a>) -> Self {
+        self.inputs.push((name, expr.clone()));
+        self
+    }
+
+    #[inline]
+    pub fn rule(mut self, rule: TypeRule) -> Self {
+        self.rule = Some(rule);
+        self
+    }
+
+    /// The symbol of the operator if the operation is an infix operator.
+    fn infix(&self) -> Option<&'static str> {
+        let symbol = match self.name {
+            "add" => "+",
+            "sub" => "-",
+            "mul" => "*",
+            "div" => "/",
+            "and" => "&",
+            "or" => "|",
+            "eq" => "==",
+            "ne" => "!=",
+            "gt" => ">",
+            "ge" => ">=",
+            "lt" => "<",
+            "le" => "<=",
+            _ => return None,
+        };
+        (self.inputs.len() == 1).then_some(symbol)
+    }
+}
+
+/// Arguments of the lazy methods, expressions are recorded as the inputs of
+/// the operation and the other values as parameters.
+pub trait OpArg<'a> {
+    fn record(&self, name: &'static str, op: &mut Op<'a>);
+}
+
+impl<'a> OpArg<'a> for Expr<'a> {
+    #[inline]
+    fn record(&self, name: &'static str, op: &mut Op<'a>) {
+        op.inputs.push((name, self.clone()));
+    }
+}
+
+impl<'a> OpArg<'a> for Vec<Expr<'a>> {
+    #[inline]
+    fn record(&self, name: &'static str, op: &mut Op<'a>) {
+        op.inputs.extend(self.iter().map(|e| (name, e.clone())));
+    }
+}
+
+impl<'a> OpArg<'a> for Option<Expr<'a>> {
+    #[inline]
+    fn record(&self, name: &'static str, op: &mut Op<'a>) {
+        if let Some(e) = self {
+            op.inputs.push((name, e.clone()))
+        }
+    }
+}
+
+impl<'a, T: ToOpParam + ?Sized> OpArg<'a> for T {
+    #[inline]
+    fn record(&self, name: &'static str, op: &mut Op<'a>) {
+        op.params.push((name, self.to_op_param()));
+    }
+}
+
+// The arguments of the methods generated by `ext_trait` are recorded by
+// autoref specialization: `OpArg` is preferred, then `Debug`, then the type name.
+#[doc(hidden)]
+pub struct OpArgProbe<'r, T: ?Sized>(pub &'r T);
+
+#[doc(hidden)]
+pub trait RecordOpArg<'a> {
+    fn record_op_arg(&self, name: &'static str, op: &mut Op<'a>);
+}
+
+impl<'a, T: OpArg<'a> + ?Sized> RecordOpArg<'a> for &&OpArgProbe<'_, T> {
+    #[inline]
+    fn record_op_arg(&self, name: &'static str, op: &mut Op<'a>) {
+        self.0.record(name, op)
+    }
+}
+
+#[doc(hidden)]
+pub trait RecordOpArgDebug<'a> {
+    fn record_op_arg(&self, name: &'static str, op: &mut Op<'a>);
+}
+
+impl<'a, T: Debug + ?Sized> RecordOpArgDebug<'a> for &OpArgProbe<'_, T> {
+    #[inline]
+    fn record_op_arg(&self, name: &'static str, op: &mut Op<'a>) {
+        op.params
+            .push((name, OpParam::Other(format!("{:?}", self.0))))
+    }
+}
+
+#[doc(hidden)]
+pub trait RecordOpArgAny<'a> {
+    fn record_op_arg(&self, name: &'static str, op: &mut Op<'a>);
+}
+
+impl<'a, T: ?Sized> RecordOpArgAny<'a> for OpArgProbe<'_, T> {
+    #[inline]
+    fn record_op_arg(&self, name: &'static str, op: &mut Op<'a>) {
+        let ty = std::any::type_name::<T>();
+        op.params.push((name, OpParam::Other(format!("<{ty}>"))))
+    }
+}
+
+/// The operations of the nodes, consecutive nodes chained by the same method
+/// share the operation and are grouped together.
+pub(super) fn node_groups<'b, 'a>(nodes: &'b [FuncNode<'a>]) -> Vec<Option<&'b Arc<Op<'a>>>> {
+    node_group_ends(nodes)
+        .into_iter()
+        .map(|(_, op)| op)
+        .collect()
+}
+
+/// Same as `node_groups`, but also return the end index of each group.
+pub(super) fn node_group_ends<'b, 'a>(
+    nodes: &'b [FuncNode<'a>],
+) -> Vec<(usize, Option<&'b Arc<Op<'a>>>)> {
+    let mut out: Vec<(usize, Option<&Arc<Op<'a>>>)> = Vec::new();
+    for (i, node) in nodes.iter().enumerate() {
+        let op = node.op.as_ref();
+        match (<[_]>::last_mut(&mut out), op) {
+            (Some((end, Some(last))), Some(op)) if Arc::ptr_eq(last, op) => *end = i + 1,
+            _ => out.push((i + 1, op)),
+        }
+    }
+    out
+}
+
+/// Describe the data the expression is based on.
+fn fmt_leaf(data: &Data<'_>, name: Option<&str>) -> String {
+    if let Some(name) = name {
+        return name.to_string();
+    }
+    let fmt_arr = |arr: &ArrOk<'_>| {
+        if arr.ndim() == 0 {
+            match_arrok!(arr; Dynamic(a) => {
+                Ok(format!("{:?}", a.view().0.iter().next().unwrap()))
+            },)
+            .unwrap_or_else(|_| format!("<{}>", arr.dtype_name()))
+        } else {
+            format!("<{} array of shape {:?}>", arr.dtype_name(), arr.shape())
+        }
+    };
+    match data {
+        Data::Expr(e) => fmt_leaf(&e.lock().base, None),
+        Data::Arr(arr) => fmt_arr(arr),
+        Data::ArcArr(arr) => fmt_arr(arr),
+        Data::ArrVec(arr_vec) => format!("<{} arrays>", arr_vec.len()),
+        Data::Context(selector) => selector
+            .name()
+            .unwrap_or_else(|| format!("context({selector:?})")),
+        #[cfg(feature = "blas")]
+        Data::OlsRes(_) => "<ols result>".to_string(),
+    }
+}
+
+/// The formula of the expression and whether it ends with an infix operator.
+fn fmt_formula(e: &ExprInner<'_>) -> (String, bool) {
+    if e.step_acc() == 0 {
+        return (fmt_leaf(&e.base, e.name()), false);
+    }
+    let (mut out, mut infix) = match &e.base {
+        Data::Expr(base) => fmt_formula(&base.lock()),
+        data => (fmt_leaf(data, e.name()), false),
+    };
+    let paren = |s: String, infix: bool| if infix { format!("({s})") } else { s };
+    for op in node_groups(&e.nodes) {
+        let Some(op) = op else {
+            out = format!("<lambda>({out})");
+            infix = false;
+            continue;
+        };
+        let inputs = op
+            .inputs
+            .iter()
+            .map(|(_, i)| fmt_formula(&i.lock()))
+            .collect::<Vec<_>>();
+        if let Some(symbol) = op.infix() {
+            let (rhs, rhs_infix) = inputs.into_iter().next().unwrap();
+            out = format!("{} {symbol} {}", paren(out, infix), paren(rhs, rhs_infix));
+            infix = true;
+            continue;
+        }
+        out = match op.name {
+            "neg" => format!("-{}", paren(out, infix)),
+            "not" => format!("~{}", paren(out, infix)),
+            name => {
+                let mut args = vec![out];
+                args.extend(inputs.into_iter().map(|(s, _)| s));
+                args.extend(
+                    op.params
+                        .iter()
+                        .filter(|(name, _)| !matches!(*name, "par" | "_par"))
+                        .map(|(_, p)| p.to_string()),
+                );
+                format!("{name}({})", args.join(", "))
+            }
+        };
+        infix = false;
+    }
+    (out, infix)
+}
+
+fn explain_into(e: &ExprInner<'_>, depth: usize, out: &mut String) {
+    let groups = node_groups(&e.nodes);
+    explain_groups(e, &groups, depth, out)
+}
+
+fn explain_groups(
+    e: &ExprInner<'_>,
+    groups: &[Option<&Arc<Op<'_>>>],
+    depth: usize,
+    out: &mut String,
+) {
+    let indent = "  ".repeat(depth);
+    match groups.split_last() {
+        None => match &e.base {
+            Data::Expr(base) if e.step_acc() > 0 => explain_into(&base.lock(), depth, out),
+            data => writeln!(out, "{indent}{}", fmt_leaf(data, e.name())).unwrap(),
+        },
+        Some((None, rest)) => {
+            writeln!(out, "{indent}<lambda>").unwrap();
+            explain_groups(e, rest, depth + 1, out);
+        }
+        Some((Some(op), rest)) => {
+            let params = op
+                .params
+                .iter()
+                .map(|(name, p)| format!("{name}={p}"))
+                .collect::<Vec<_>>();
+            writeln!(out, "{indent}{}({})", op.name, params.join(", ")).unwrap();
+            explain_groups(e, rest, depth + 1, out);
+            for (_, input) in &op.inputs {
+                explain_into(&input.lock(), depth + 1, out);
+            }
+        }
+    }
+}
+
+fn context_refs_into<'a>(
+    e: &Expr<'a>,
+    seen: &mut TpHashMap<usize, ()>,
+    out: &mut Vec<ColumnSelector<'a>>,
+) {
+    if seen.insert(Arc::as_ptr(e) as usize, ()).is_some() {
+        return;
+    }
+    let inner = e.lock();
+    match &inner.base {
+        Data::Expr(base) => context_refs_into(base, seen, out),
+        Data::Context(selector) => out.push(selector.clone()),
+        _ => {}
+    }
+    for op in node_groups(&inner.nodes) {
+        let Some(op) = op else {
+            // the function of the node may reference any column
+            out.push(ColumnSelector::All);
+            continue;
+        };
+        for (name, input) in &op.inputs {
+            if !SCOPED_INPUTS.contains(name) {
+                context_refs_into(input, seen, out);
+            }
+        }
+    }
+}
+
+/// The columns of the context referenced by the expression and its inputs,
+/// a node without an operation references all the columns.
+pub(super) fn context_refs<'a>(e: &Expr<'a>) -> Vec<ColumnSelector<'a>> {
+    let mut out = Vec::new();
+    context_refs_into(e, &mut TpHashMap::new(), &mut out);
+    out
+}
+
+impl<'a> ExprInner<'a> {
+    /// The formula of the expression, e.g. `ts_mean(close, 20) - close`.
+    #[inline]
+    pub fn formula(&self) -> String {
+        fmt_formula(self).0
+    }
+
+    /// A tree of the operations of the expression, each operation is
+    /// followed by its operands indented.
+    #[inline]
+    pub fn explain(&self) -> String {
+        let mut out = String::new();
+        explain_into(self, 0, &mut out);
+        out
+    }
+}
+
+impl Display for Expr<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lock().formula())
+    }
+}
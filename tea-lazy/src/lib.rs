@@ -9,6 +9,15 @@ mod linalg;
 pub use datadict::{ColumnSelector, Context, DataDict, GetMutOutput, GetOutput, SetInput};
 pub use tea_hash::TpHashMap;
 
-pub use expr_core::{adjust_slice, Data, Expr, ExprElement, FuncNode, FuncOut};
+pub use expr_core::{
+    adjust_slice, register_op, BaseDef, CommonSubexprs, Data, Expr, ExprDef, ExprElement, ExprType,
+    FromOpArg, FromOpParam, Func, FuncNode, FuncOut, Op, OpArg, OpArgs, OpBuilder, OpDef, OpParam,
+    OutDtype, OutNdim, SelectorDef, ToOpParam, TypeCheck, TypeRule,
+};
+#[doc(hidden)]
+pub use expr_core::{
+    OpArgParser, OpArgProbe, ParseOpArg, ParseOpArgAny, ParseOpArgStr, RecordOpArg, RecordOpArgAny,
+    RecordOpArgDebug,
+};
 #[cfg(feature = "blas")]
 pub use linalg::OlsResult;
@@ -21,7 +21,39 @@ pub(crate) fn impl_view(
             self.chain_f_ctx(move |(data, ctx)| {
                 let arr = data.view_arr(ctx.as_ref())?;
                 // match_arrok!(#arr_type arr, a, { Ok((a.view().#fn_name(#(#params),*).into(), ctx)) })
-                match_arrok!(arr; #arr_type(a) => { Ok((a.view().#fn_name(#(#params),*).into(), ctx)) },)
+                match_arrok!(&*arr; #arr_type(a) => { Ok((a.view().#fn_name(#(#params),*).into(), ctx)) },)
+            });
+            self
+        }
+    }
+}
+
+/// Like `impl_view`, but masked arrays are first passed to the method of the
+/// same name on `MaskedArray`, which returns `None` for the dtypes it doesn't
+/// support so that the dense array is used instead.
+pub(crate) fn impl_masked_view(
+    lazy_sig: &syn::Signature,
+    arr_type: Option<TokenStream2>,
+    _other_type: Option<TokenStream2>,
+) -> TokenStream2 {
+    let fn_name = &lazy_sig.ident;
+    let params = parse_params(lazy_sig);
+    let arr_type = if let Some(arr_type) = arr_type {
+        arr_type
+    } else {
+        quote! {Dynamic}
+    };
+    quote! {
+        #lazy_sig
+        {
+            self.chain_f_ctx(move |(data, ctx)| {
+                if let ArrOk::Masked(m) = data.view_raw_arr(ctx.as_ref())? {
+                    if let Some(out) = m.#fn_name(#(#params),*)? {
+                        return Ok((out.into(), ctx));
+                    }
+                }
+                let arr = data.view_arr(ctx.as_ref())?;
+                match_arrok!(&*arr; #arr_type(a) => { Ok((a.view().#fn_name(#(#params),*).into(), ctx)) },)
             });
             self
         }
@@ -52,8 +84,8 @@ pub(crate) fn impl_view2(
             self.chain_f_ctx(move |(data, ctx)| {
                 let arr = data.view_arr(ctx.as_ref())?;
                 let other_arr = #other.view_arr(ctx.as_ref())?;
-                match_arrok!(arr; #arr_type(a) => {
-                    match_arrok!(other_arr; #other_type(b) => {
+                match_arrok!(&*arr; #arr_type(a) => {
+                    match_arrok!(&*other_arr; #other_type(b) => {
                         Ok((a.view().#fn_name(&b.view(), #(#params),*).into(), ctx))
                     },)
                 },)
@@ -85,7 +117,7 @@ pub(crate) fn impl_f64func(
         {
             self.chain_f_ctx(move |(data, ctx)| {
                 let arr = data.view_arr(ctx.as_ref())?;
-                match_arrok!(arr; #arr_type(a) => { Ok((a.view().map(|v| v.f64().#fn_name(#(#params),*)).into(), ctx)) },)
+                match_arrok!(&*arr; #arr_type(a) => { Ok((a.view().map(|v| v.f64().#fn_name(#(#params),*)).into(), ctx)) },)
                 // match_arrok!(#arr_type arr, a, { Ok((a.view().map(|v| v.f64().#fn_name(#(#params),*)).into(), ctx)) })
             });
             self
@@ -145,8 +177,9 @@ pub(crate) fn impl_rolling_by_startidx_agg(
         {
             self.chain_f_ctx(
                 move |(data, ctx)| {
-                    let arr = data.view_arr(ctx.as_ref())?.deref();
-                    let roll_start = #roll_start.view_arr(ctx.as_ref())?.deref().cast_usize();
+                    let arr = &*data.view_arr(ctx.as_ref())?;
+                    let roll_start = #roll_start.view_arr(ctx.as_ref())?;
+                    let roll_start = ArrOk::deref(&roll_start).cast_usize();
                     let roll_start_arr = roll_start.view().to_dim1()?;
                     let len = arr.len();
                     if len != roll_start_arr.len() {
@@ -207,9 +240,10 @@ pub(crate) fn impl_rolling_by_startidx_agg2(
         {
             self.chain_f_ctx(
                 move |(data, ctx)| {
-                    let arr = data.view_arr(ctx.as_ref())?.deref();
-                    let other = #other.view_arr(ctx.as_ref())?.deref();
-                    let roll_start = #roll_start.view_arr(ctx.as_ref())?.deref().cast_usize();
+                    let arr = &*data.view_arr(ctx.as_ref())?;
+                    let other = &*#other.view_arr(ctx.as_ref())?;
+                    let roll_start = #roll_start.view_arr(ctx.as_ref())?;
+                    let roll_start = ArrOk::deref(&roll_start).cast_usize();
                     let roll_start_arr = roll_start.view().to_dim1()?;
                     let len = arr.len();
                     if len != roll_start_arr.len() {
@@ -228,7 +262,7 @@ pub(crate) fn impl_rolling_by_startidx_agg2(
                                 start = end;  // the start idx should be inbound
                             }
                             let current_arr = arr.slice(s![start..end + 1]).wrap();
-                            match_arrok!(&other; #other_type(other) => {
+                            match_arrok!(other; #other_type(other) => {
                                 let other = other.view().to_dim1().unwrap();
                                 let other_arr = other.slice(s![start..end + 1]).wrap();
                                 Ok(current_arr.#fn_name(&other_arr, #(#params),*))
@@ -268,8 +302,9 @@ pub(crate) fn impl_rolling_by_vecusize_agg(
         {
             self.chain_f_ctx(
                 move |(data, ctx)| {
-                    let arr = data.view_arr(ctx.as_ref())?.deref();
-                    let idxs = #idxs.view_arr(ctx.as_ref())?.deref().cast_vecusize();
+                    let arr = &*data.view_arr(ctx.as_ref())?;
+                    let idxs = #idxs.view_arr(ctx.as_ref())?;
+                    let idxs = ArrOk::deref(&idxs).cast_vecusize();
                     let idxs_arr = idxs.view().to_dim1()?;
                     let out: ArrOk<'a> = match_arrok!(arr; #arr_type(arr) => {
                         let arr = arr.view().to_dim1()?;
@@ -313,14 +348,15 @@ pub(crate) fn impl_group_by_startidx_agg(
         {
             self.chain_f_ctx(
                 move |(data, ctx)| {
-                    let arr = data.view_arr(ctx.as_ref())?.deref();
+                    let arr = &*data.view_arr(ctx.as_ref())?;
                     let group_start = if let Ok(mut group_idx) = #start_idx.view_arr_vec(ctx.as_ref()) {
                         // idx with time info or other info
-                        group_idx.pop().unwrap().deref().cast_usize()
+                        group_idx.pop().unwrap()
                     } else {
                         // only idx info
-                        group_idx.view_arr(ctx.as_ref())?.deref().cast_usize()
+                        group_idx.view_arr(ctx.as_ref())?
                     };
+                    let group_start = ArrOk::deref(&group_start).cast_usize();
                     let group_start_view = group_start.view().to_dim1()?;
                     let out: ArrOk<'a> = match_arrok!(arr; #arr_type(arr) => {
                         let arr = arr.view().to_dim1()?;
@@ -370,13 +406,14 @@ pub(crate) fn impl_group_by_startidx_agg2(
         {
             self.chain_f_ctx(
                 move |(data, ctx)| {
-                    let arr = data.view_arr(ctx.as_ref())?.deref();
-                    let other = #other.view_arr(ctx.as_ref())?.deref();
+                    let arr = &*data.view_arr(ctx.as_ref())?;
+                    let other = &*#other.view_arr(ctx.as_ref())?;
                     let group_start = if let Ok(mut group_idx) = #start_idx.view_arr_vec(ctx.as_ref()) {
-                        group_idx.pop().unwrap().deref().cast_usize()
+                        group_idx.pop().unwrap()
                     } else {
-                        group_idx.view_arr(ctx.as_ref())?.deref().cast_usize()
+                        group_idx.view_arr(ctx.as_ref())?
                     };
+                    let group_start = ArrOk::deref(&group_start).cast_usize();
                     let group_start_view = group_start.view().to_dim1()?;
 
                     let out: ArrOk<'a> = match_arrok!(arr; #arr_type(arr) => {
@@ -385,7 +422,7 @@ pub(crate) fn impl_group_by_startidx_agg2(
                         .map(|v| {
                             let (start, next_start) = (v[0], v[1]);
                             let current_arr = arr.slice(s![start..next_start]).wrap();
-                            match_arrok!(&other; #other_type(other) => {
+                            match_arrok!(other; #other_type(other) => {
                                 let other = other.view().to_dim1().unwrap();
                                 let other_arr = other.slice(s![start..next_start]).wrap();
                                 Ok(current_arr.#fn_name(&other_arr, #(#params),*))
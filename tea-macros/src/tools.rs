@@ -1,6 +1,6 @@
 use crate::MethodType;
 use proc_macro::TokenStream;
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{Group, TokenStream as TokenStream2, TokenTree};
 use quote::{format_ident, quote};
 use syn::{
     parse_macro_input, parse_quote, Attribute, FnArg, Generics, Ident, ImplItem, ItemImpl,
@@ -17,6 +17,11 @@ pub(crate) struct TpAttrParser {
     lazy_exclude: bool,
     arr_type: Option<TokenStream2>,
     other_type: Option<TokenStream2>,
+    /// whether the lazy impl dispatches on `arr_type`, so that the dtype rule
+    /// of the method can be derived from its signature
+    typed: bool,
+    /// the element type of the output of a hand-written lazy method
+    dtype: Option<TokenStream2>,
 }
 
 // impl Default for TpAttrParser {
@@ -49,6 +54,8 @@ impl TpAttrParser {
         let mut lazy_exclude = false;
         let mut arr_type = None;
         let mut other_type = None;
+        let mut typed = false;
+        let mut dtype = None;
         if attr.path().is_ident("lazy_exclude") {
             lazy_exclude = true;
             if let Some(res) = Self::parse_nested_meta(attr) {
@@ -62,6 +69,8 @@ impl TpAttrParser {
                 lazy_func = res.lazy_func;
                 arr_type = res.arr_type;
                 other_type = res.other_type;
+                typed = res.typed;
+                dtype = res.dtype;
             }
         } else if attr.path().is_ident("teapy") {
             if let Some(res) = Self::parse_nested_meta(attr) {
@@ -76,6 +85,8 @@ impl TpAttrParser {
             lazy_exclude,
             arr_type,
             other_type,
+            typed,
+            dtype,
         })
     }
 
@@ -86,13 +97,20 @@ impl TpAttrParser {
         let mut lazy_func: Option<LazyFunc> = None;
         let mut arr_type = None;
         let mut other_type = None;
+        let mut typed = false;
+        let mut dtype = None;
         let res = attr.parse_nested_meta(|meta| {
             if meta.path.is_ident("lazy") {
                 let value = meta.value()?;
                 let s: syn::LitStr = value.parse()?;
                 let s = s.value();
+                typed = matches!(
+                    s.as_str(),
+                    "view" | "masked_view" | "view2" | "view_mut" | "f64_func"
+                );
                 match s.as_str() {
                     "view" => lazy_func = Some(Box::new(impl_view)),
+                    "masked_view" => lazy_func = Some(Box::new(impl_masked_view)),
                     "view2" => lazy_func = Some(Box::new(impl_view2)),
                     "view_mut" => lazy_func = Some(Box::new(impl_viewmut)),
                     "f64_func" => lazy_func = Some(Box::new(impl_f64func)),
@@ -117,14 +135,20 @@ impl TpAttrParser {
                 let value = meta.value()?;
                 let s: syn::LitStr = value.parse()?;
                 let s = s.value();
-                let s: Ident = syn::parse_str(s.as_str())?;
-                arr_type = Some(quote! {#s});
+                // a group of dtypes, e.g. `Numeric` or `(Time | Date)`
+                let s: TokenStream2 = syn::parse_str(s.as_str())?;
+                arr_type = Some(s);
             } else if meta.path.is_ident("type2") {
                 let value = meta.value()?;
                 let s: syn::LitStr = value.parse()?;
                 let s = s.value();
                 let s: Ident = syn::parse_str(s.as_str())?;
                 other_type = Some(quote! {#s});
+            } else if meta.path.is_ident("dtype") {
+                let value = meta.value()?;
+                let s: syn::LitStr = value.parse()?;
+                let s: Type = syn::parse_str(s.value().as_str())?;
+                dtype = Some(quote! {#s});
             } else if meta.path.is_ident("lazy_only") {
                 lazy_only = true;
             } else if meta.path.is_ident("lazy_exclude") {
@@ -139,6 +163,8 @@ impl TpAttrParser {
                 lazy_exclude,
                 arr_type,
                 other_type,
+                typed,
+                dtype,
             })
         } else {
             None
@@ -197,12 +223,7 @@ pub(crate) fn remove_params(params: &mut Vec<Box<syn::Pat>>, names: Vec<&str>) {
 }
 
 fn remove_inline_attr(attrs: &mut Vec<syn::Attribute>) {
-    attrs.retain(|attr| {
-        if attr.path().is_ident("inline") {
-            return false;
-        }
-        true
-    });
+    attrs.retain(|attr| !attr.path().is_ident("inline"));
 }
 
 #[allow(clippy::single_match)]
@@ -313,6 +334,191 @@ pub(crate) fn sig_1d_to_nd(
     (fn_1d_sig, fn_1d_block, fn_sig, ty_1d)
 }
 
+/// Whether the method chains on the expression, i.e. takes `&mut self` and
+/// returns `&mut Self`.
+fn is_chain_method(sig: &syn::Signature) -> bool {
+    let returns_self = matches!(
+        &sig.output,
+        ReturnType::Type(_, ty) if quote!(#ty).to_string() == quote!(&mut Self).to_string()
+    );
+    let mut_self = matches!(
+        sig.inputs.first(),
+        Some(FnArg::Receiver(r)) if r.reference.is_some() && r.mutability.is_some()
+    );
+    returns_self && mut_self
+}
+
+/// Replace `Self` by `Expr<'_>` and elide the lifetimes so that the type can be
+/// used in the builder closure.
+fn erase_type(ty: TokenStream2) -> TokenStream2 {
+    let mut out = TokenStream2::new();
+    let mut tokens = ty.into_iter().peekable();
+    while let Some(tt) = tokens.next() {
+        match tt {
+            TokenTree::Ident(ident) if ident == "Self" => out.extend(quote! { Expr<'_> }),
+            TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                // skip the name of the lifetime
+                tokens.next();
+                out.extend(quote! { '_ });
+            }
+            TokenTree::Group(group) => {
+                let mut new = Group::new(group.delimiter(), erase_type(group.stream()));
+                new.set_span(group.span());
+                out.extend([TokenTree::Group(new)]);
+            }
+            tt => out.extend([tt]),
+        }
+    }
+    out
+}
+
+/// Register a builder of the method that rebuilds the operation recorded by
+/// `record_op` from its arguments, see `tea_lazy::ExprDef`.
+pub(crate) fn op_builder(
+    sig: &syn::Signature,
+    attrs: &[Attribute],
+    trait_name: &Ident,
+) -> Option<TokenStream2> {
+    if !is_chain_method(sig) || !sig.generics.params.is_empty() {
+        return None;
+    }
+    let mut idents = Vec::new();
+    let mut types = Vec::new();
+    for arg in sig.inputs.iter().skip(1) {
+        let FnArg::Typed(pat_type) = arg else {
+            return None;
+        };
+        let syn::Pat::Ident(pat_ident) = &*pat_type.pat else {
+            return None;
+        };
+        let ty = &pat_type.ty;
+        if matches!(&**ty, Type::ImplTrait(_)) {
+            return None;
+        }
+        idents.push(pat_ident.ident.clone());
+        types.push(erase_type(quote! { #ty }));
+    }
+    let names = idents.iter().map(|ident| ident.to_string());
+    let fn_name = &sig.ident;
+    let name = fn_name.to_string();
+    let cfgs = attrs.iter().filter(|attr| attr.path().is_ident("cfg"));
+    Some(quote! {
+        #(#cfgs)*
+        ::tea_lazy::register_op(#name, |__e, __args| {
+            #[allow(unused_imports)]
+            use ::tea_lazy::{ParseOpArg as _, ParseOpArgAny as _, ParseOpArgStr as _};
+            #(
+                let #idents = (&&&::tea_lazy::OpArgParser::<#types>(::std::marker::PhantomData))
+                    .parse_op_arg(#names, __args)?;
+            )*
+            #trait_name::#fn_name(__e, #(#idents),*);
+            Ok(())
+        });
+    })
+}
+
+/// The element types of the outputs whose dtype is known without the dtype
+/// of the input.
+const CONCRETE_TYPES: &[&str] = &[
+    "bool",
+    "u8",
+    "u64",
+    "usize",
+    "i32",
+    "i64",
+    "f32",
+    "f64",
+    "String",
+    "Option<bool>",
+    "Option<f32>",
+    "Option<f64>",
+    "Option<i32>",
+    "Option<i64>",
+    "Option<usize>",
+    "Vec<usize>",
+];
+
+/// Check the dtype of an input by the same match as the lazy impl.
+fn type_check(arr_type: &TokenStream2) -> TokenStream2 {
+    let expect = arr_type.to_string();
+    let expect = expect
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .replace(" | ", " or ");
+    quote! {
+        ::tea_lazy::TypeCheck {
+            expect: #expect,
+            accepts: |arr: &ArrOk<'_>| match_arrok!(arr; #arr_type(_a) => { Ok(()) },).is_ok(),
+        }
+    }
+}
+
+/// The dtype rule of a lazy method, see `tea_lazy::TypeRule`. `out` is the
+/// element type of the output, `T` means the dtype of the input and `None`
+/// an unknown dtype.
+fn type_rule(
+    arr_type: Option<&TokenStream2>,
+    other_type: Option<&TokenStream2>,
+    out: Option<TokenStream2>,
+    reduce: bool,
+) -> TokenStream2 {
+    let input = type_check(arr_type.unwrap_or(&quote! {Dynamic}));
+    let dtype = match out.as_ref().map(|ty| ty.to_string().replace(' ', "")) {
+        Some(ty) if ty == "T" => quote! { ::tea_lazy::OutDtype::Same },
+        Some(ty) if CONCRETE_TYPES.contains(&ty.as_str()) => {
+            quote! { ::tea_lazy::OutDtype::of::<#out>() }
+        }
+        _ => quote! { ::tea_lazy::OutDtype::Unknown },
+    };
+    let ndim = if reduce {
+        quote! { ::tea_lazy::OutNdim::Reduce }
+    } else {
+        quote! { ::tea_lazy::OutNdim::Same }
+    };
+    let other = other_type.map(|other_type| {
+        let other = type_check(other_type);
+        quote! { .other(#other) }
+    });
+    quote! { ::tea_lazy::TypeRule::new(#input, #dtype, #ndim) #other }
+}
+
+/// Record the name and the arguments of a lazy method as the operation of the
+/// nodes the method chains, so that the expression can be explained.
+pub(crate) fn record_op(method: TokenStream2, rule: Option<TokenStream2>) -> TokenStream2 {
+    let Ok(mut method) = syn::parse2::<syn::ImplItemFn>(method.clone()) else {
+        return method;
+    };
+    if !is_chain_method(&method.sig) {
+        return quote! { #method };
+    }
+    let name = method.sig.ident.to_string();
+    let args = parse_params(&method.sig)
+        .into_iter()
+        .filter_map(|pat| match *pat {
+            syn::Pat::Ident(pat_ident) => Some(pat_ident.ident),
+            _ => None,
+        })
+        .map(|arg| {
+            let arg_name = arg.to_string();
+            quote! { (&&&::tea_lazy::OpArgProbe(&#arg)).record_op_arg(#arg_name, &mut __op); }
+        });
+    let rule = rule.map(|rule| quote! { .rule(#rule) });
+    let block = &method.block;
+    method.block = parse_quote! {
+        {
+            #[allow(unused_imports)]
+            use ::tea_lazy::{RecordOpArg as _, RecordOpArgAny as _, RecordOpArgDebug as _};
+            let mut __op = ::tea_lazy::Op::new(#name)#rule;
+            #(#args)*
+            let __mark = self.op_mark();
+            let _: &mut Self = #block;
+            self.set_op(__mark, __op);
+            self
+        }
+    };
+    quote! { #method }
+}
+
 #[allow(clippy::manual_map)]
 pub(crate) fn ext_tool(attr: TokenStream, input: TokenStream) -> TokenStream {
     let parse_res = parse_input_attr(attr);
@@ -324,7 +530,15 @@ pub(crate) fn ext_tool(attr: TokenStream, input: TokenStream) -> TokenStream {
     };
 
     let self_type = item_impl.self_ty.clone();
+    // methods of the expression itself also record their operations
+    let is_expr = matches!(
+        &*self_type,
+        Type::Path(ty) if ty.path.segments.last().is_some_and(|seg| seg.ident == "Expr")
+    );
     let generics = item_impl.generics.clone();
+    let lazy_trait_name = format_ident!("AutoExpr{}", trait_name);
+    let mut builders = Vec::new();
+    let mut lazy_builders = Vec::new();
     let (lazy_trait_methods, lazy_impls, trait_methods, impl_methods): (
         Vec<_>,
         Vec<_>,
@@ -354,13 +568,36 @@ pub(crate) fn ext_tool(attr: TokenStream, input: TokenStream) -> TokenStream {
                 let method_block = &method.block;
                 let trait_method_sig = no_mut_arg(method_sig);
                 let trait_method = quote! { #(#attrs_notinline)* #trait_method_sig; };
-                let impl_method = quote! { #(#method_attrs)* #method_sig #method_block };
+                let impl_method = if is_expr {
+                    // hand-written methods declare the dtype of their output
+                    let rule = current_parse_res.dtype.clone().map(|dtype| {
+                        type_rule(current_arr_type.as_ref(), None, Some(dtype), false)
+                    });
+                    record_op(quote! { #method_sig #method_block }, rule)
+                } else {
+                    quote! { #method_sig #method_block }
+                };
+                let impl_method = quote! { #(#method_attrs)* #impl_method };
                 // impl lazy
                 let mut lazy_trait_fn_sig =
                     to_lazy_sig(&trait_method_sig, current_other_type.is_some());
                 lazy_trait_fn_sig.output = parse_quote! { -> &mut Self };
                 lazy_trait_fn_sig.generics = Default::default();
 
+                // the output dtype of the lazy impls can't be told from the signature
+                let typed = if current_parse_res.lazy_func.is_some() {
+                    current_parse_res.typed
+                } else {
+                    parse_res.typed
+                };
+                let rule = typed.then(|| {
+                    type_rule(
+                        current_arr_type.as_ref(),
+                        current_other_type.as_ref(),
+                        None,
+                        false,
+                    )
+                });
                 let lazy_impl = if let Some(lazy_func) = current_parse_res.lazy_func.as_ref() {
                     Some(lazy_func(
                         &lazy_trait_fn_sig,
@@ -376,10 +613,22 @@ pub(crate) fn ext_tool(attr: TokenStream, input: TokenStream) -> TokenStream {
                 } else {
                     None
                 };
-                let lazy_impl = lazy_impl.map(|lazy_impl| quote! {#(#method_attrs)* #lazy_impl});
+                let lazy_impl = lazy_impl
+                    .map(|lazy_impl| record_op(lazy_impl, rule))
+                    .map(|lazy_impl| quote! {#(#method_attrs)* #lazy_impl});
                 let lazy_trait_method = quote! { #(#attrs_notinline)* #lazy_trait_fn_sig; };
                 let lazy_only = parse_res.lazy_only | current_parse_res.lazy_only;
                 let lazy_exclude = parse_res.lazy_exclude | current_parse_res.lazy_exclude;
+                if is_expr && !lazy_only {
+                    builders.extend(op_builder(method_sig, &method_attrs, &trait_name));
+                }
+                if lazy_impl.is_some() && !lazy_exclude {
+                    lazy_builders.extend(op_builder(
+                        &lazy_trait_fn_sig,
+                        &method_attrs,
+                        &lazy_trait_name,
+                    ));
+                }
                 if lazy_only {
                     Some((lazy_trait_method, lazy_impl, None, None))
                 } else if lazy_exclude {
@@ -433,6 +682,8 @@ pub(crate) fn ext_tool(attr: TokenStream, input: TokenStream) -> TokenStream {
         // attr,
         lazy_trait_methods,
         lazy_impls,
+        is_expr.then_some(builders),
+        lazy_builders,
     )
 }
 
@@ -456,6 +707,8 @@ where
     let parse_res = parse_input_attr(attr);
     let self_type = item_impl.self_ty.clone();
     let generics = item_impl.generics.clone();
+    let lazy_trait_name = format_ident!("AutoExpr{}", trait_name);
+    let mut lazy_builders = Vec::new();
     let (lazy_trait_methods, lazy_impls, trait_methods, impl_methods): (Vec<_>, Vec<_>, Vec<_>, Vec<_>) = item_impl.items.into_iter().filter_map(|item| {
         if let ImplItem::Fn(fn_1d) = item {
             let mut fn_1d_attrs = fn_1d.attrs.clone();
@@ -474,7 +727,7 @@ where
             remove_inline_attr(&mut fn_attrs_notinline);
             let (fn_1d_sig, fn_1d_block, fn_sig, ty_1d) = sig_1d_to_nd(fn_1d, otype.clone(), method);
             let params = parse_params(&fn_1d_sig);
-            let fn_block = nd_impl_func(&fn_1d_sig, params, ty_1d);
+            let fn_block = nd_impl_func(&fn_1d_sig, params, ty_1d.clone());
             let trait_fn_sig = no_mut_arg(&fn_sig);
             let trait_fn_1d_sig = no_mut_arg(&fn_1d_sig);
             let trait_method = quote! { #(#fn_attrs_notinline)* #trait_fn_1d_sig; #(#fn_attrs_notinline)* #trait_fn_sig;};
@@ -483,6 +736,19 @@ where
             let mut lazy_trait_fn_sig = to_lazy_sig(&fn_sig, current_other_type.is_some());
             lazy_trait_fn_sig.output = lazy_output;
             lazy_trait_fn_sig.generics = Default::default();
+            let typed = if current_parse_res.lazy_func.is_some() {
+                current_parse_res.typed
+            } else {
+                parse_res.typed
+            };
+            let rule = typed.then(|| {
+                let out = match method {
+                    MethodType::Inplace => Some(quote! { T }),
+                    _ => ty_1d.as_ref().map(|ty| quote! { #ty }),
+                };
+                let reduce = matches!(method, MethodType::Reduce | MethodType::Reduce2);
+                type_rule(current_arr_type.as_ref(), current_other_type.as_ref(), out, reduce)
+            });
             let lazy_impl = if let Some(lazy_func) = current_parse_res.lazy_func.as_ref() {
                 Some(lazy_func(&lazy_trait_fn_sig, current_arr_type, current_other_type))
             } else if let Some(lazy_func) = parse_res.lazy_func.as_ref() {
@@ -490,10 +756,13 @@ where
             } else {
                 None
             };
-            let lazy_impl = lazy_impl.map(|lazy_impl| quote!{#(#fn_1d_attrs)* #lazy_impl});
+            let lazy_impl = lazy_impl.map(|lazy_impl| record_op(lazy_impl, rule)).map(|lazy_impl| quote!{#(#fn_1d_attrs)* #lazy_impl});
             let lazy_trait_method = quote! { #(#fn_attrs_notinline)* #lazy_trait_fn_sig; };
             let lazy_only = parse_res.lazy_only | current_parse_res.lazy_only;
             let lazy_exclude = parse_res.lazy_exclude | current_parse_res.lazy_exclude;
+            if lazy_impl.is_some() && !lazy_exclude {
+                lazy_builders.extend(op_builder(&lazy_trait_fn_sig, &fn_1d_attrs, &lazy_trait_name));
+            }
             if lazy_only {
                 Some((lazy_trait_method, lazy_impl, None, None))
             } else if lazy_exclude {
@@ -537,21 +806,39 @@ where
         // attr,
         lazy_trait_methods,
         lazy_impls,
+        None,
+        lazy_builders,
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn expand_trait_ext(
     trait_name: Ident,
     self_type: Box<Type>,
-    trait_methods: Vec<TokenStream2>,
-    impl_methods: Vec<TokenStream2>,
+    mut trait_methods: Vec<TokenStream2>,
+    mut impl_methods: Vec<TokenStream2>,
     generics: Generics,
     // _attr: TokenStream2,
-    lazy_trait_methods: Vec<TokenStream2>,
-    lazy_impls: Vec<TokenStream2>,
+    mut lazy_trait_methods: Vec<TokenStream2>,
+    mut lazy_impls: Vec<TokenStream2>,
+    builders: Option<Vec<TokenStream2>>,
+    lazy_builders: Vec<TokenStream2>,
 ) -> TokenStream {
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let lazy_trait_name = format_ident!("AutoExpr{}", trait_name);
+    let register_ops = quote! {
+        /// Register the builders of the methods, see `tea_lazy::register_op`.
+        #[doc(hidden)]
+        fn register_ops() where Self: Sized;
+    };
+    if let Some(builders) = builders.filter(|_| !trait_methods.is_empty()) {
+        trait_methods.push(register_ops.clone());
+        impl_methods.push(quote! { fn register_ops() { #(#builders)* } });
+    }
+    if !lazy_impls.is_empty() {
+        lazy_trait_methods.push(register_ops);
+        lazy_impls.push(quote! { fn register_ops() { #(#lazy_builders)* } });
+    }
     let expanded = if (!lazy_impls.is_empty()) && (!trait_methods.is_empty()) {
         quote! {
             // #attr
@@ -1,19 +1,36 @@
 from .array_func import *
 from .expr import Expr, register
 from .mod_func import *
-from .py_datadict import DataDict, from_dataframe, from_pd, from_pl, scan_ipc
+from .py_datadict import (
+    DataDict,
+    IpcWriter,
+    from_arrow,
+    from_dataframe,
+    from_pd,
+    from_pl,
+    iter_ipc,
+    read_csv,
+    read_npz,
+    scan_dataset,
+    scan_ipc,
+    scan_parquet,
+)
 from .selector import Selector
 from .tears import (
     arange,
     concat,
     context,
+    cse_exprs,
     eval_exprs,
     expr_register,
     get_version,
     nan,
+    read_npy,
+    schema_exprs,
     stack,
     timedelta,
     where_,
+    write_npy,
 )
 from .tears import calc_ret_single as _calc_ret_single
 from .tears import calc_ret_single_with_spread as _calc_ret_single_with_spread
@@ -115,10 +115,13 @@ def groupby(
     sort=True,
     par=False,
     type_=None,
+    offset=None,
 ) -> ExprGroupBy:
     """
     window: int | duration, such as 1y, 2mo, 3d, 4h, 5m, 6s, or combine them
     others: only available in rolling.apply, this can add other expressions into the rolling context
+    offset: shift the start of each time group, such as 9h30m for the session open,
+        timezone-aware time_expr is grouped by its local time
     """
     if by is None and idxs is None:
         raise ValueError("by or info must be specified")
@@ -136,6 +139,7 @@ def groupby(
         sort=sort,
         par=par,
         type_=type_,
+        offset=offset,
     )
 
 
@@ -282,9 +286,11 @@ class ExprGroupBy(ExprRollMixin):
         sort=True,
         par=False,
         type_=None,
+        offset=None,
     ) -> None:
         self.type = type_
         self.closed = closed
+        self.offset = offset
         self.info = idxs
         self.sort = sort
         self.par = par
@@ -309,7 +315,7 @@ class ExprGroupBy(ExprRollMixin):
             elif self.type == "time":
                 # groupby time
                 return self.time_expr._get_group_by_time_idx(
-                    self.window, closed=self.closed, split=True
+                    self.window, closed=self.closed, split=True, offset=self.offset
                 )
             elif self.type == "default":
                 # groupby keys
@@ -369,3 +375,84 @@ class ExprGroupBy(ExprRollMixin):
             raise NotImplementedError
         else:
             raise ValueError
+
+
+class ExprListNameSpace:
+    """methods of list arrays, available as `expr.list`"""
+
+    def __init__(self, expr):
+        self.expr = expr
+
+    def len(self):
+        return self.expr.list_len()
+
+    def get(self, i):
+        return self.expr.list_get(i)
+
+    def sum(self):
+        return self.expr.list_sum()
+
+    def mean(self, min_periods=1):
+        return self.expr.list_mean(min_periods)
+
+
+Expr.list = property(ExprListNameSpace)
+
+
+class ExprStructNameSpace:
+    """methods of struct arrays, available as `expr.struct`"""
+
+    def __init__(self, expr):
+        self.expr = expr
+
+    def field(self, name):
+        return self.expr.struct_field(name).alias(name)
+
+
+Expr.struct = property(ExprStructNameSpace)
+
+
+class ExprStrNameSpace:
+    """methods of string arrays, available as `expr.str`"""
+
+    def __init__(self, expr):
+        self.expr = expr
+
+    def contains(self, pat, literal=False):
+        return self.expr.str_contains(pat, literal)
+
+    def starts_with(self, prefix):
+        return self.expr.str_starts_with(prefix)
+
+    def ends_with(self, suffix):
+        return self.expr.str_ends_with(suffix)
+
+    def replace(self, pat, value, literal=False, n=None):
+        return self.expr.str_replace(pat, value, literal, n)
+
+    def extract(self, pat, group=1):
+        return self.expr.str_extract(pat, group)
+
+    def split(self, sep):
+        return self.expr.str_split(sep)
+
+    def slice(self, start, length=None):
+        return self.expr.str_slice(start, length)
+
+    def upper(self):
+        return self.expr.str_to_upper()
+
+    def lower(self):
+        return self.expr.str_to_lower()
+
+    def strip(self, chars=None):
+        return self.expr.str_strip(chars)
+
+    def len(self):
+        return self.expr.str_len()
+
+    def pad(self, width, side="left", fillchar=" "):
+        return self.expr.str_pad(width, side, fillchar)
+
+
+Expr.str = property(ExprStrNameSpace)
@@ -3,6 +3,7 @@ from functools import wraps
 from .selector import Selector
 from .tears import arange, stack
 from .tears import corr as _corr
+from .tears import parse_expr as _asexpr
 
 
 def select_wrapper(func):
@@ -51,6 +52,17 @@ def hsum(exprs, par=False):
     return stack(exprs, axis=-1).sum(axis=-1, par=par)
 
 
+def struct(*exprs, **named_exprs):
+    """pack the expressions into a struct, positional expressions are named
+    by their own names"""
+    exprs = [_asexpr(e) for e in exprs]
+    names = [e.name for e in exprs] + list(named_exprs)
+    if None in names:
+        raise ValueError("the fields of a struct should be named")
+    exprs += [_asexpr(e) for e in named_exprs.values()]
+    return exprs[0].to_struct(names, exprs[1:])
+
+
 def align_frames(dds, by, sort=True, rev=False, outer_df=False, with_by=True):
     suffix = "__align_by"
     if len(dds) <= 1:
@@ -1,31 +1,165 @@
 from .selector import selector_to_expr
-from .tears import Expr, eval_exprs, stack
+from .tears import Expr, cse_exprs, eval_exprs, schema_exprs, stack
 from .tears import context as ct
+from .tears import from_arrow as _from_arrow
+from .tears import from_dataframe as _from_dataframe
 from .tears import scan_ipc as _scan_ipc
+from .tears import IpcBatchReader as _IpcBatchReader
+from .tears import IpcBatchWriter as _IpcBatchWriter
+from .tears import read_csv as _read_csv
+from .tears import read_npz as _read_npz
+from .tears import scan_dataset as _scan_dataset
+from .tears import scan_parquet as _scan_parquet
+from .tears import to_arrow_c_array as _to_arrow_c_array
+from .tears import to_arrow_c_stream as _to_arrow_c_stream
+from .tears import write_csv as _write_csv
+from .tears import write_ipc as _write_ipc
+from .tears import write_npz as _write_npz
+from .tears import write_parquet as _write_parquet
 
 name_prefix = "column_"
 
 
-def scan_ipc(path, columns=None):
-    return DataDict(_scan_ipc(path, columns=columns))
+def scan_ipc(path, columns=None, offset=0, length=None, predicate=None):
+    """Scan an ipc file lazily.
+
+    offset, length: the row range to read, applied before the predicate
+    predicate: a tuple (column, op, value) where op is one of "==", "!=", ">", ">=",
+        "<", "<=", or (column, "between", (start, end)) for a datetime range,
+        record batches without any selected row are skipped, str and numpy
+        datetime64 values are parsed for datetime columns
+    """
+    return DataDict(
+        _scan_ipc(
+            str(path),
+            columns=columns,
+            offset=offset,
+            length=length,
+            predicate=predicate,
+        )
+    )
+
+
+def iter_ipc(path, columns=None, batch_rows=None):
+    """Read an ipc file as a DataDict per record batch, or per `batch_rows` rows."""
+    for exprs in _IpcBatchReader(str(path), columns=columns, batch_rows=batch_rows):
+        yield DataDict(exprs)
+
+
+class IpcWriter:
+    """Append DataDicts to an ipc file batch by batch.
+
+    The schema is determined by the first DataDict, `finish` must be called
+    to write the footer unless the writer is used as a context manager.
+    """
+
+    def __init__(self, path, compression=None):
+        self._writer = _IpcBatchWriter(str(path), compression=compression)
+
+    def write(self, dd):
+        self._writer.write(dd.exprs)
+
+    def finish(self):
+        self._writer.finish()
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *args):
+        self.finish()
+
+
+def scan_dataset(pattern, columns=None):
+    """Scan the ipc files matching a glob pattern as a single DataDict.
+
+    Partition keys of Hive style directories (`date=2024-01-02/part.ipc`)
+    are added as extra columns.
+    """
+    return DataDict(_scan_dataset(str(pattern), columns=columns))
+
+
+def scan_parquet(path, columns=None):
+    return DataDict(_scan_parquet(str(path), columns=columns))
+
+
+def read_csv(
+    path,
+    columns=None,
+    delimiter=",",
+    has_header=True,
+    null_values=None,
+    dtypes=None,
+    parse_dates=False,
+    datetime_fmt=None,
+):
+    """Read a csv file into a DataDict.
+
+    null_values: extra tokens treated as null, empty field is always null,
+        string columns with nulls are read as object columns of str and None
+    dtypes: a dict of column name to dtype name, other columns are inferred
+    parse_dates: whether to try parsing string columns as datetime
+    """
+    return DataDict(
+        _read_csv(
+            str(path),
+            columns=columns,
+            delimiter=delimiter,
+            has_header=has_header,
+            null_values=null_values,
+            dtypes=dtypes,
+            parse_dates=parse_dates,
+            datetime_fmt=datetime_fmt,
+        )
+    )
+
+
+def read_npz(path, columns=None):
+    """Read a npz file into a DataDict, each array in the archive is a column.
+
+    Arrays saved by ``numpy.savez`` are memory-mapped rather than loaded.
+    """
+    return DataDict(_read_npz(str(path), columns=columns))
+
+
+def from_arrow(obj):
+    """Create a DataDict from an object implementing the arrow PyCapsule
+    interface (``__arrow_c_stream__`` or ``__arrow_c_array__``), such as a
+    pyarrow Table or a polars DataFrame.
 
+    Primitive columns without nulls share the buffers of the arrow arrays.
+    """
+    return DataDict(_from_arrow(obj))
 
-def from_dataframe(df, copy=False):
-    return DataDict(df.to_dict(), copy=copy)
 
+def from_dataframe(df, copy=False, infer_object=True):
+    """Create a DataDict from a pandas or polars DataFrame.
 
-def from_pd(df, copy=False):
-    return from_dataframe(df, copy=copy)
+    Nullable integer and boolean columns are read as optional dtypes,
+    string columns without nulls as str and datetime columns keep their units.
+    String columns with nulls are read as OptString, which keeps the nulls.
 
+    infer_object: convert the object columns of str to String (OptString if
+        there are nulls), and the object columns of bool or int with nulls to
+        OptBool or OptI64,
+        see `DataDict.object_columns` for the columns kept as Object
+    """
+    return DataDict(_from_dataframe(df, copy=copy, infer_object=infer_object))
 
-def from_pl(df, copy=False):
-    return from_dataframe(df, copy=copy)
+
+def from_pd(df, copy=False, infer_object=True):
+    return from_dataframe(df, copy=copy, infer_object=infer_object)
+
+
+def from_pl(df, copy=False, infer_object=True):
+    return from_dataframe(df, copy=copy, infer_object=infer_object)
 
 
 class DataDict:
     default_name = name_prefix
 
-    def __init__(self, data=None, columns=None, copy=False, **kwargs):
+    def __init__(
+        self, data=None, columns=None, copy=False, infer_object=True, **kwargs
+    ):
         self.copy_flag = copy
         self.col_map = None
         self.auto_idx = 0
@@ -33,11 +167,14 @@ class DataDict:
             self.exprs = []
         elif isinstance(data, dict):
             if columns is None:
-                self.exprs = [Expr(v, copy=copy).alias(k) for k, v in data.items()]
+                self.exprs = [
+                    Expr(v, copy=copy, infer_object=infer_object).alias(k)
+                    for k, v in data.items()
+                ]
             else:  # columns has a higher priority
                 assert len(columns) == len(data)
                 self.exprs = [
-                    Expr(v, copy=copy).alias(name)
+                    Expr(v, copy=copy, infer_object=infer_object).alias(name)
                     for name, (k, v) in zip(columns, data.items())
                 ]
         else:
@@ -53,13 +190,16 @@ class DataDict:
                         for e in data
                     ]
                 self.exprs = [
-                    Expr(v, copy=copy).alias(k) for k, v in zip(columns, data)
+                    Expr(v, copy=copy, infer_object=infer_object).alias(k)
+                    for k, v in zip(columns, data)
                 ]
             else:
                 raise ValueError("data must be a dict, list or tuple")
         if len(kwargs):
             for k, v in kwargs.items():
-                self.exprs.append(Expr(v, copy=copy).alias(k))
+                self.exprs.append(
+                    Expr(v, copy=copy, infer_object=infer_object).alias(k)
+                )
 
     def _init_col_map(self, force_init=False):
         if self.col_map is None or force_init:
@@ -82,6 +222,16 @@ class DataDict:
     def dtypes(self):
         return {e.name: e.dtype for e in self.exprs}
 
+    def schema(self):
+        """The dtypes of the columns inferred without evaluation, the dtype is
+        `Unknown` if it can't be inferred, e.g. after a python function."""
+        return dict(zip(self.columns, schema_exprs(self.exprs)))
+
+    @property
+    def object_columns(self):
+        """The columns kept as Object, e.g. mixed typed columns."""
+        return [e.name for e in self.exprs if e.dtype == "Object"]
+
     @property
     def raw_data(self):
         from warnings import warn
@@ -181,11 +331,76 @@ class DataDict:
 
         return pl.DataFrame(self.to_dict())
 
+    def __arrow_c_array__(self, requested_schema=None):
+        return _to_arrow_c_array(self.exprs)
+
+    def __arrow_c_stream__(self, requested_schema=None):
+        return _to_arrow_c_stream(self.exprs)
+
+    def to_arrow(self):
+        """Convert the DataDict into a pyarrow Table through the arrow C stream interface."""
+        import pyarrow as pa
+
+        return pa.table(self)
+
+    def write_ipc(self, path, compression=None):
+        """Write the DataDict into an arrow ipc file.
+
+        compression: None, "lz4" or "zstd"
+        """
+        _write_ipc(self.exprs, str(path), compression=compression)
+
+    def write_parquet(self, path, compression=None):
+        """Write the DataDict into a parquet file.
+
+        compression: None, "snappy", "gzip", "lz4", "zstd" or "brotli"
+        """
+        _write_parquet(self.exprs, str(path), compression=compression)
+
+    def write_csv(
+        self, path, delimiter=",", has_header=True, null_value="", datetime_fmt=None
+    ):
+        """Write the DataDict into a csv file.
+
+        datetime_fmt: the strftime format used to write datetime columns
+        """
+        _write_csv(
+            self.exprs,
+            str(path),
+            delimiter=delimiter,
+            has_header=has_header,
+            null_value=null_value,
+            datetime_fmt=datetime_fmt,
+        )
+
+    def write_npz(self, path, compress=False):
+        """Write the DataDict into a npz file, which can be read by ``numpy.load``.
+
+        compress: whether to compress the arrays like ``numpy.savez_compressed``
+        """
+        _write_npz(self.exprs, str(path), compress=compress)
+
     def __repr__(self) -> str:
         return {e.name: e for e in self.exprs}.__repr__()
 
-    def eval(self, columns=None, inplace=False):
+    def cse(self, columns=None):
+        """Share the sub-expressions common to the columns so that they are
+        evaluated only once, return the number of deduplicated nodes."""
         if columns is None:
+            return cse_exprs(self.exprs)
+        exprs = self.get(columns)
+        return 0 if isinstance(exprs, Expr) else cse_exprs(exprs.exprs)
+
+    def eval(self, columns=None, inplace=False, cse=False, context=False):
+        """Evaluate the columns, if `context` is True, the `context` references
+        between the columns are resolved and only the selected columns and the
+        columns they reference are evaluated, a referenced column is evaluated
+        before the columns using it."""
+        if cse:
+            self.cse(columns)
+        if context:
+            self.exprs = eval_exprs(self.exprs, context=True, columns=columns)
+        elif columns is None:
             self.exprs = eval_exprs(self.exprs)
         else:
             exprs = self.get(columns)
@@ -449,8 +664,10 @@ class DataDict:
         dd.slice(idx, axis=0, inplace=True, check=False)
         return None if inplace else dd
 
-    def groupby(self, by=None, time_col=None, closed="left", group=True):
-        return GroupBy(self, by=by, time_col=time_col, closed=closed, group=group)
+    def groupby(self, by=None, time_col=None, closed="left", group=True, offset=None):
+        return GroupBy(
+            self, by=by, time_col=time_col, closed=closed, group=group, offset=offset
+        )
 
     def corr(self, columns=None, method="pearson", min_periods=1, stable=False):
         from .tears import corr
@@ -460,12 +677,15 @@ class DataDict:
 
 
 class GroupBy:
-    def __init__(self, dd, by=None, time_col=None, closed="left", group=True) -> None:
+    def __init__(
+        self, dd, by=None, time_col=None, closed="left", group=True, offset=None
+    ) -> None:
         self.dd = dd
         self.by = by
         self.time_col = time_col
         self.closed = closed
         self.group = group
+        self.offset = offset
 
     def agg(self, exprs=None, **kwargs):
         if self.dd.is_empty():
@@ -476,7 +696,11 @@ class GroupBy:
         others = self.dd[columns[1:]].exprs
         by = self.by if time_expr is not None else self.dd[self.by]
         groupby_obj = e.groupby(
-            by=by, time_expr=time_expr, closed=self.closed, others=others
+            by=by,
+            time_expr=time_expr,
+            closed=self.closed,
+            others=others,
+            offset=self.offset,
         )
         info, type_ = groupby_obj.info, groupby_obj.type
         self.info = info
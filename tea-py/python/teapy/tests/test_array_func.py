@@ -23,6 +23,29 @@ def test_cast_datetime():
     assert now1 == now2
 
 
+def test_cast_small_int():
+    dtypes = {"int8": "I8", "int16": "I16", "uint16": "U16", "uint32": "U32"}
+    for dtype, name in dtypes.items():
+        arr = np.array([1, 2, 3], dtype=dtype)
+        e = Expr(arr)
+        assert e.dtype == name
+        assert e.eview().dtype == arr.dtype
+        assert_allclose(e.cast("f64").eview(), arr.astype(np.float64))
+    res = Expr(np.array([1.0, 300.0])).cast("i16").eview()
+    assert res.dtype == np.int16
+    assert_allclose(res, np.array([1, 300]))
+
+
+def test_small_int_kernels():
+    arr = np.array([1, 2, 3, 4])
+    exprs = [Expr(arr.astype(dtype)) for dtype in ["int8", "int16", "uint16", "uint32"]]
+    exprs += [Expr(arr).cast("optu8"), Expr(arr).cast("optu64")]
+    for e in exprs:
+        # the compact dtypes are widened by the kernels
+        assert e.sum().eview() == 10
+        assert_allclose(e.ts_mean(2, min_periods=1).eview(), [1.0, 1.5, 2.5, 3.5])
+
+
 @given(make_arr(30, unique=True, nan_p=0))
 def test_argsort(arr):
     res1 = tp.argsort(arr)
@@ -1,5 +1,6 @@
 import numpy as np
 import pandas as pd
+import pytest
 import teapy as tp
 from numpy.testing import assert_array_equal
 from teapy import Expr, get_align_frames_idx, s
@@ -91,6 +92,354 @@ def test_to_dict():
     assert dd.to_dict() == {"a": 1, "b": 2, "c": 2}
 
 
+def test_write_ipc(tmp_path):
+    dt = np.array(
+        [np.datetime64("nat", "ms"), np.datetime64("2020-01-01 00:00:02", "ms")]
+    )
+    dd = DataDict(a=[1.0, np.nan], b=np.array([1, 2]), c=[True, False], d=dt)
+    for compression in [None, "lz4", "zstd"]:
+        path = tmp_path / f"test_{compression}.feather"
+        dd.write_ipc(path, compression=compression)
+        res = tp.scan_ipc(str(path)).eval()
+        assert res.columns == ["a", "b", "c", "d"]
+        assert_allclose(res["a"].view, np.array([1.0, np.nan]))
+        assert_array_equal(res["b"].view, np.array([1, 2]))
+        assert_array_equal(res["c"].view, np.array([True, False]))
+        assert_array_equal(res["d"].view, dt)
+
+
+def test_write_ipc_nullable_dtype(tmp_path):
+    dd = DataDict(a=np.array([1, 2]), b=np.array([1, 2]))
+    dd["a"] = dd["a"].cast("usize")
+    dd["b"] = dd["b"].cast("optusize")
+    path = tmp_path / "test.feather"
+    dd.write_ipc(path)
+    res = tp.scan_ipc(str(path)).eval()
+    # the dtypes round-trip even if there is no null
+    assert res["a"].dtype == "Usize"
+    assert res["b"].dtype == "OptUsize"
+
+
+def test_ipc_nullable_string(tmp_path):
+    pa = pytest.importorskip("pyarrow")
+    feather = pytest.importorskip("pyarrow.feather")
+    table = pa.table({"s": ["x", None, "z"], "u": ["x", "y", "z"]})
+    path = tmp_path / "test.feather"
+    feather.write_feather(table, path)
+    res = tp.scan_ipc(path).eval()
+    assert res.dtypes == {"s": "OptString", "u": "String"}
+    assert res["s"].view.tolist() == ["x", None, "z"]
+    # the nulls are kept when the strings are written back
+    res.write_ipc(tmp_path / "out.feather")
+    table = feather.read_table(tmp_path / "out.feather")
+    assert table["s"].to_pylist() == ["x", None, "z"]
+
+
+def test_scan_ipc_pushdown(tmp_path):
+    time = pd.date_range("2024-01-01", periods=10, freq="12h").values
+    dd = DataDict(a=np.arange(10), b=np.arange(10) * 0.5, time=time)
+    path = tmp_path / "test.feather"
+    dd.write_ipc(path)
+    res = tp.scan_ipc(path, offset=3, length=4).eval()
+    assert_array_equal(res["a"].view, np.arange(3, 7))
+    res = tp.scan_ipc(path, columns=["b"], predicate=("a", ">=", 8)).eval()
+    assert res.columns == ["b"]
+    assert_allclose(res["b"].view, np.array([4.0, 4.5]))
+    predicate = ("time", "between", ("2024-01-02", "2024-01-03"))
+    res = tp.scan_ipc(path, predicate=predicate).eval()
+    assert_array_equal(res["a"].view, np.array([2, 3]))
+    res = tp.scan_ipc(path, offset=3, predicate=("a", "<", 5)).eval()
+    assert_array_equal(res["a"].view, np.array([3, 4]))
+    # string and numpy datetime literals are coerced for a datetime key
+    res = tp.scan_ipc(path, predicate=("time", ">=", "2024-01-05")).eval()
+    assert_array_equal(res["a"].view, np.array([8, 9]))
+    predicate = ("time", "==", np.datetime64("2024-01-01 12:00:00", "s"))
+    res = tp.scan_ipc(path, predicate=predicate).eval()
+    assert_array_equal(res["a"].view, np.array([1]))
+    with pytest.raises(ValueError, match="can not be compared"):
+        tp.scan_ipc(path, predicate=("a", ">=", "x")).eval()
+
+
+def test_scan_ipc_pushdown_batches(tmp_path):
+    # more rows than a record batch, so the predicate spans several batches
+    n = 512 * 512 + 10
+    dd = DataDict(a=np.arange(n), b=np.arange(n) * 0.5)
+    path = tmp_path / "test.feather"
+    dd.write_ipc(path)
+    res = tp.scan_ipc(path, predicate=("a", ">=", n - 15)).eval()
+    assert_array_equal(res["a"].view, np.arange(n - 15, n))
+    assert_allclose(res["b"].view, np.arange(n - 15, n) * 0.5)
+    res = tp.scan_ipc(path, offset=10, predicate=("b", "<", 10)).eval()
+    assert_array_equal(res["a"].view, np.arange(10, 20))
+
+
+def test_scan_ipc_pushdown_small_int_key(tmp_path):
+    pa = pytest.importorskip("pyarrow")
+    feather = pytest.importorskip("pyarrow.feather")
+    table = pa.table(
+        {
+            "a": pa.array(np.arange(6), type=pa.int8()),
+            "b": pa.array([None, None, None, 4, None, 6], type=pa.uint8()),
+            "v": np.arange(6) * 0.5,
+        }
+    )
+    path = tmp_path / "test.feather"
+    # the key `b` of the first batch is all null, the batch is skipped by its header
+    feather.write_feather(table, path, compression="uncompressed", chunksize=3)
+    res = tp.scan_ipc(path, predicate=("a", ">=", 4)).eval()
+    assert_allclose(res["v"].view, [2.0, 2.5])
+    res = tp.scan_ipc(path, columns=["v"], predicate=("b", ">", 4)).eval()
+    assert_allclose(res["v"].view, [2.5])
+
+
+def test_write_ipc_unequal_length(tmp_path):
+    dd = DataDict(a=[1.0, 2.0, 3.0], b=[1, 2])
+    with pytest.raises(ValueError, match="same length"):
+        dd.write_ipc(tmp_path / "test.feather")
+
+
+def test_iter_ipc(tmp_path):
+    path = tmp_path / "test.feather"
+    DataDict(a=np.arange(10), b=np.arange(10) * 0.5).write_ipc(path)
+    out_path = tmp_path / "out.feather"
+    with tp.IpcWriter(out_path) as writer:
+        for dd in tp.iter_ipc(path, columns=["a"], batch_rows=4):
+            assert len(dd["a"].view) <= 4
+            dd["c"] = dd["a"] * 2
+            writer.write(dd)
+    res = tp.scan_ipc(out_path).eval()
+    assert res.columns == ["a", "c"]
+    assert_array_equal(res["c"].view, np.arange(10) * 2)
+
+
+def test_iter_ipc_unknown_tz(tmp_path):
+    pa = pytest.importorskip("pyarrow")
+    feather = pytest.importorskip("pyarrow.feather")
+    ts = pa.array([0, 1], type=pa.timestamp("s", tz="Unknown/Zone"))
+    path = tmp_path / "test.feather"
+    feather.write_feather(pa.table({"a": ts}), path)
+    # an error is raised rather than aborting the process
+    for batch_rows in [None, 1]:
+        with pytest.raises(ValueError, match="Unknown time zone"):
+            list(tp.iter_ipc(path, batch_rows=batch_rows))
+
+
+def test_from_dataframe():
+    df = pd.DataFrame(
+        {
+            "b": pd.array([1, None, 3], dtype="Int64"),
+            "a": pd.array([True, None, False], dtype="boolean"),
+            "s": pd.array(["x", None, "z"], dtype="string"),
+            "u": pd.array(["x", "y", "z"], dtype="string"),
+            "t": pd.to_datetime(["2024-01-01", None, "2024-01-03"]),
+            "f": [1.0, 2.0, 3.0],
+        }
+    )
+    dd = tp.from_pd(df)
+    assert dd.columns == ["b", "a", "s", "u", "t", "f"]
+    assert dd.dtypes == {
+        "b": "OptI64",
+        "a": "OptBool",
+        "s": "OptString",
+        "u": "String",
+        "t": "DateTime(Nanosecond)",
+        "f": "F64",
+    }
+    assert dd["b"].view.tolist() == [1, None, 3]
+    # the null of a string is kept rather than filled by ""
+    assert dd["s"].view.tolist() == ["x", None, "z"]
+    assert dd["u"].view.tolist() == ["x", "y", "z"]
+
+
+def test_infer_object():
+    df = pd.DataFrame(
+        {
+            "s": ["x", None, "z"],
+            "u": ["x", "y", "z"],
+            "b": [True, None, False],
+            "i": [1, None, 3],
+            "m": ["x", 1, None],
+        },
+        dtype=object,
+    )
+    dd = tp.from_pd(df)
+    assert dd.dtypes == {
+        "s": "OptString",
+        "u": "String",
+        "b": "OptBool",
+        "i": "OptI64",
+        "m": "Object",
+    }
+    # strings with nulls are masked rather than kept as object
+    assert dd.object_columns == ["m"]
+    assert dd["s"].view.tolist() == ["x", None, "z"]
+    assert dd["i"].view.tolist() == [1, None, 3]
+    assert tp.from_pd(df, infer_object=False).object_columns == ["s", "u", "b", "i", "m"]
+    dd = DataDict(a=np.array([1, np.nan], dtype=object), infer_object=False)
+    assert dd.object_columns == ["a"]
+    assert Expr(np.array([None, np.nan], dtype=object)).dtype == "Object"
+
+
+def test_arrow_c_interface():
+    pa = pytest.importorskip("pyarrow")
+    dd = DataDict(a=np.arange(5), b=np.arange(5) * 0.5)
+    table = dd.to_arrow()
+    assert table.column_names == ["a", "b"]
+    assert table["b"].to_pylist() == [0, 0.5, 1, 1.5, 2]
+    table = pa.table({"a": pa.chunked_array([[1, 2], [3]]), "b": [1.0, None, 3.0]})
+    res = tp.from_arrow(table)
+    assert res.columns == ["a", "b"]
+    assert_array_equal(res["a"].view, [1, 2, 3])
+    assert_allclose(res["b"].view, [1.0, np.nan, 3.0])
+
+
+def test_arrow_export_zero_copy():
+    pa = pytest.importorskip("pyarrow")
+    dd = DataDict(a=np.arange(5))
+    dd["b"] = dd["a"] * 2
+    dd.eval(inplace=True)
+    ptr = dd["b"].view.__array_interface__["data"][0]
+    # the owned array is moved into arrow, so exporting it again doesn't copy either
+    for _ in range(2):
+        table = dd.to_arrow()
+        assert table["b"].chunks[0].buffers()[1].address == ptr
+    assert_array_equal(dd["b"].view, np.arange(5) * 2)
+
+
+def test_arrow_nullable():
+    pa = pytest.importorskip("pyarrow")
+    table = pa.table({"a": pa.chunked_array([[1, None], [3]]), "b": [1, 2, 3]})
+    res = tp.from_arrow(table)
+    assert res["a"].dtype == "OptI64"
+    assert res["a"].view.tolist() == [1, None, 3]
+    assert res["a"].sum().eview() == 4
+    out = res.to_arrow()
+    assert out["a"].to_pylist() == [1, None, 3]
+    assert out["b"].null_count == 0
+
+
+def test_arrow_nullable_kernels():
+    pa = pytest.importorskip("pyarrow")
+    table = pa.table({"a": [1.0, None, 3.0, 4.0], "b": [1, None, 3, 4]})
+    res = tp.from_arrow(table)
+    dense = Expr(np.array([1.0, np.nan, 3.0, 4.0]))
+    assert res["a"].dtype == "F64"
+    assert res["a"].sum().eview() == 8.0
+    assert res["b"].max().eview() == 4
+    assert_allclose(res["a"].ts_sum(2).view, dense.ts_sum(2).view)
+    assert_allclose(res["b"].ts_mean(2).view, dense.ts_mean(2).view)
+    assert res["b"].is_nan().view.tolist() == [False, True, False, False]
+
+
+def test_arrow_timestamp():
+    pa = pytest.importorskip("pyarrow")
+    ts = pa.array([0, 86400, None], type=pa.timestamp("s"))
+    res = tp.from_arrow(pa.table({"a": ts}))
+    assert res["a"].view.astype(str).tolist() == [
+        "1970-01-01T00:00:00.000",
+        "1970-01-02T00:00:00.000",
+        "NaT",
+    ]
+    ts = pa.array([0], type=pa.timestamp("s", tz="Unknown/Zone"))
+    with pytest.raises(ValueError, match="Unknown time zone"):
+        tp.from_arrow(pa.table({"a": ts}))
+
+
+def test_scan_dataset(tmp_path):
+    for i, date in enumerate(["2024-01-02", "2024-01-03"]):
+        path = tmp_path / f"date={date}"
+        path.mkdir()
+        DataDict(a=np.arange(3) + i * 3).write_ipc(path / "part.ipc")
+    res = tp.scan_dataset(tmp_path / "*" / "*.ipc").eval()
+    assert res.columns == ["a", "date"]
+    assert_array_equal(res["a"].view, np.arange(6))
+    assert_array_equal(
+        res["date"].view,
+        np.array(["2024-01-02"] * 3 + ["2024-01-03"] * 3, dtype="datetime64[ns]"),
+    )
+    res = tp.scan_dataset(tmp_path / "*" / "*.ipc", columns=["date"]).eval()
+    assert res.columns == ["date"]
+
+
+def test_write_parquet(tmp_path):
+    dd = DataDict(a=[1.0, np.nan, 3.0], b=np.array([1, 2, 3]), c=["x", "y", "z"])
+    for compression in [None, "snappy", "zstd"]:
+        path = tmp_path / f"test_{compression}.parquet"
+        dd.write_parquet(path, compression=compression)
+        res = tp.scan_parquet(path, columns=["a", "c"]).eval()
+        assert res.columns == ["a", "c"]
+        assert_allclose(res["a"].view, np.array([1.0, np.nan, 3.0]))
+        assert res["c"].view.tolist() == ["x", "y", "z"]
+
+
+def test_write_parquet_unequal_length(tmp_path):
+    dd = DataDict(a=[1.0, 2.0, 3.0], b=[1, 2])
+    with pytest.raises(ValueError, match="same length"):
+        dd.write_parquet(tmp_path / "test.parquet")
+
+
+def test_csv(tmp_path):
+    path = tmp_path / "test.csv"
+    path.write_text(
+        "a;b;c;d;e\n1;1.5;true;2024-01-02;x\nNA;2.5;false;;\n3;;true;2024-01-03;z\n"
+    )
+    dd = tp.read_csv(path, delimiter=";", null_values=["NA"], parse_dates=True).eval()
+    assert dd.columns == ["a", "b", "c", "d", "e"]
+    assert dd["a"].view.tolist() == [1, None, 3]
+    assert_allclose(dd["b"].view, np.array([1.5, 2.5, np.nan]))
+    assert dd["c"].view.tolist() == [True, False, True]
+    assert dd["d"].view.astype("datetime64[D]").astype(str).tolist() == [
+        "2024-01-02",
+        "NaT",
+        "2024-01-03",
+    ]
+    # nullable strings are read as masked strings rather than python objects
+    assert dd["e"].dtype == "OptString"
+    assert dd["e"].view.tolist() == ["x", None, "z"]
+    dd.write_csv(tmp_path / "out.csv", datetime_fmt="%Y-%m-%d")
+    res = tp.read_csv(tmp_path / "out.csv", columns=["b", "a"], dtypes={"a": "f64"})
+    res = res.eval()
+    assert res.columns == ["b", "a"]
+    assert_allclose(res["a"].view, np.array([1.0, np.nan, 3.0]))
+    lines = (tmp_path / "out.csv").read_text().splitlines()
+    assert lines[1] == "1,1.5,true,2024-01-02,x"
+    assert lines[2] == ",2.5,false,,"
+
+
+def test_csv_chunks(tmp_path):
+    # large enough to be split into chunks parsed by different threads
+    n = 300_000
+    dd = DataDict(a=np.arange(n), b=np.arange(n) * 0.5)
+    dd.write_csv(tmp_path / "test.csv")
+    res = tp.read_csv(tmp_path / "test.csv").eval()
+    assert_array_equal(res["a"].view, np.arange(n))
+    assert_allclose(res["b"].view, np.arange(n) * 0.5)
+
+
+def test_npy(tmp_path):
+    arr = np.arange(12, dtype=np.float64).reshape(3, 4).T
+    np.save(tmp_path / "a.npy", arr)
+    res = tp.read_npy(str(tmp_path / "a.npy")).eval()
+    assert_allclose(res.view, arr)
+    tp.write_npy(tp.Expr(arr), str(tmp_path / "b.npy"))
+    assert_allclose(np.load(tmp_path / "b.npy"), arr)
+    dd = DataDict(
+        {
+            "a": np.array([1, 2, 3]),
+            "b": np.array([True, False, True]),
+            "c": np.array(["2024-01-01", "NaT", "2024-01-03"], dtype="datetime64[ns]"),
+        }
+    )
+    for compress in [False, True]:
+        dd.write_npz(tmp_path / "c.npz", compress=compress)
+        npz = np.load(tmp_path / "c.npz")
+        assert npz["b"].tolist() == [True, False, True]
+        res = tp.read_npz(tmp_path / "c.npz", columns=["c", "a"]).eval()
+        assert res.columns == ["c", "a"]
+        assert res["a"].view.tolist() == [1, 2, 3]
+        assert np.isnat(res["c"].view[1])
+
+
 def test_dropna():
     dd = DataDict(
         {
@@ -214,6 +563,50 @@ def test_join():
     assert_array_equal(by[0].eview(), np.array(["a", "b", "c", "d", "e"]))
 
 
+def test_categorical(tmp_path):
+    ldd = DataDict({"on": ["a", "b", "d", "c"], "va": [1, 2, 3, 4]})
+    rdd = DataDict({"on": ["b", "a", "e"], "vb": [10, 20, 30]})
+    ldd["on"] = ldd["on"].cast("category").eval()
+    rdd["on"] = rdd["on"].cast("category").eval()
+    assert ldd["on"].dtype == "Categorical"
+    assert_array_equal(ldd["on"].eview(), np.array(["a", "b", "d", "c"]))
+    assert_array_equal(ldd["on"].cast("str").eview(), np.array(["a", "b", "d", "c"]))
+    dd = ldd.join(rdd, how="outer", on="on").eval()
+    assert_allclose(dd["va"].eview(), np.array([1, 2, 4, 3, np.nan]))
+    assert_allclose(dd["vb"].eview(), np.array([20, 10, np.nan, np.nan, 30]))
+    assert_array_equal(dd["on"].eview(), np.array(["a", "b", "c", "d", "e"]))
+    path = tmp_path / "test_categorical.feather"
+    ldd.write_ipc(path)
+    res = tp.scan_ipc(str(path)).eval()
+    assert res["on"].dtype == "Categorical"
+    assert_array_equal(res["on"].eview(), np.array(["a", "b", "d", "c"]))
+    path = tmp_path / "test_categorical.parquet"
+    ldd.write_parquet(path)
+    res = tp.scan_parquet(path).eval()
+    assert res["on"].dtype == "Categorical"
+    assert_array_equal(res["on"].eview(), np.array(["a", "b", "d", "c"]))
+    assert_array_equal(res["va"].eview(), np.array([1, 2, 3, 4]))
+
+
+def test_categorical_null(tmp_path):
+    ldd = DataDict({"on": ["a", "b", "d", "c"], "va": [1, 2, 3, 4]})
+    rdd = DataDict({"on": ["b", "a", "e"], "vb": [10, 20, 30]})
+    _, idxs = get_align_frames_idx([ldd, rdd], by="on", return_by=True)
+    on = ldd["on"].cast("category").select(idxs[0]).eval()
+    assert on.dtype == "Categorical"
+    # a missing index is null rather than an empty category
+    assert on.view.tolist() == ["a", "b", "c", "d", None]
+    assert on.cast("object").view.tolist() == ["a", "b", "c", "d", None]
+    # null is placed last whatever the order
+    assert on.sort(on, rev=True).view.tolist() == ["d", "c", "b", "a", None]
+    dd = DataDict({"on": on})
+    path = tmp_path / "test_categorical_null.feather"
+    dd.write_ipc(path)
+    res = tp.scan_ipc(str(path)).eval()
+    assert res["on"].dtype == "Categorical"
+    assert res["on"].view.tolist() == ["a", "b", "c", "d", None]
+
+
 def test_groupby():
     n = 100
     dd = DataDict(
@@ -255,3 +648,61 @@ def test_unique():
     assert_allclose(dd.unique("a")["v"].eview(), [1, 3, 4, 5])
     assert_allclose(dd.unique(["a", "b"], keep="last")["v"].eview(), [1, 2, 4, 5, 6])
     assert_allclose(dd.unique(["a", "b"], keep="first")["v"].eview(), [1, 2, 3, 4, 5])
+
+
+def test_cse():
+    dd = DataDict(a=[1.0, 3, 2, 5, 4])
+    a = dd["a"]
+    dd = dd.with_columns(
+        (a.ts_std(3) * 2).alias("b"),
+        (a.ts_std(3) * 2).alias("c"),
+        (a - a.ts_std(3)).alias("d"),
+    )
+    assert dd.cse() == 3
+    expect = a.ts_std(3).eview()
+    dd.eval(cse=True)
+    assert_allclose(dd["b"].view, expect * 2)
+    assert_allclose(dd["c"].view, expect * 2)
+    assert_allclose(dd["d"].view, a.eview() - expect)
+
+
+def test_schema():
+    dd = DataDict(a=[1.0, 3, 2], s=np.array(["x", "y", "z"], dtype=object))
+    dd["b"] = tp.context("a").ts_mean(2)
+    dd["c"] = tp.context("b").argmax()
+    assert dd.schema() == {"a": "F64", "s": "String", "b": "F64", "c": "I32"}
+    dd["d"] = tp.context("s").ts_mean(2)
+    with pytest.raises(TypeError, match="ts_mean"):
+        dd.schema()
+
+
+def test_eval_context():
+    dd = DataDict(x=[1.0, 2, 3])
+    dd["a"] = dd["x"] * 2
+    dd["b"] = tp.context("a") + 1
+    # put_mask has no operation descriptor, it may reference any column
+    dd["c"] = dd["x"].put_mask(tp.context("x") > 1, tp.context("b"))
+    dd.eval(context=True)
+    assert_allclose(dd["a"].view, [2, 4, 6])
+    assert_allclose(dd["b"].view, [3, 5, 7])
+    assert_allclose(dd["c"].view, [1, 5, 7])
+    # only the selected columns and their references are evaluated
+    dd = DataDict(x=[1.0, 2, 3])
+    dd["a"] = dd["x"] * 2
+    dd["b"] = tp.context("a") + 1
+    dd["d"] = dd["x"] - 1
+    assert dd.eval("b", context=True, inplace=True) is None
+    assert_allclose(dd["b"].view, [3, 5, 7])
+    assert dd["a"].step == 0
+    assert dd["d"].step > 0
+
+
+def test_context_cycle():
+    dd = DataDict(x=[1.0, 2, 3])
+    dd["a"] = tp.context("b") + 1
+    dd["b"] = tp.context("c") + 1
+    dd["c"] = tp.context("a") + 1
+    with pytest.raises(ValueError, match="Cycle in the context references"):
+        dd.eval(context=True)
+    with pytest.raises(TypeError, match="a -> b -> c -> a"):
+        dd.schema()
@@ -1,5 +1,6 @@
 import numpy as np
 import pandas as pd
+import pytest
 import teapy as tp
 from teapy.testing import assert_allclose, assert_series_equal
 
@@ -61,6 +62,74 @@ def test_isin():
     assert e.filter(~e.is_in(["ab", "a"])).eview().tolist() == ["bd", "sdf", "bdfd"]
 
 
+def test_struct():
+    a = tp.Expr(np.array([1.0, 2, 3]), "a")
+    s = tp.struct(a, c=np.array([4, 5, 6]))
+    assert s.dtype == "Struct{a: F64, c: I64}"
+    assert s.struct.field("c").name == "c"
+    assert_allclose(s.struct.field("c").eview(), [4, 5, 6])
+    assert_allclose(s.select([2, 0]).struct.field("a").eview(), [3, 1])
+    assert list(s.eview()) == ["a", "c"]
+    arr = np.random.randn(20)
+    params = tp.Expr(arr).ts_reg_params(5)
+    assert_allclose(
+        params.struct.field("slope").eview(), tp.Expr(arr).ts_reg_slope(5).eview()
+    )
+
+
+def test_list_dtype():
+    e = tp.Expr([np.array([1, 2]), np.array([3]), np.array([], dtype=int)])
+    assert e.dtype == "List[I64]"
+    assert e.list.len().eview().tolist() == [2, 1, 0]
+    assert e.list.sum().eview().tolist() == [3, 3, 0]
+    assert e.list.get(0).eview().tolist() == [1, 3, None]
+    assert e.explode().eview().tolist() == [1, 2, 3]
+    assert [r.tolist() for r in e.eview()] == [[1, 2], [3], []]
+    pa = pytest.importorskip("pyarrow")
+    res = tp.from_arrow(pa.table({"a": [[1, 2], None, [3]]}))
+    # null lists are kept rather than read as empty lists
+    assert res["a"].list.len().eview().tolist() == [2, None, 1]
+    assert res["a"].list.get(0).eview().tolist() == [1, None, 3]
+    assert res["a"].explode().eview().tolist() == [1, 2, 3]
+    assert [r if r is None else r.tolist() for r in res["a"].eview()] == [[1, 2], None, [3]]
+    assert res.to_arrow()["a"].to_pylist() == [[1, 2], None, [3]]
+
+
+def test_decimal():
+    from decimal import Decimal
+
+    a = tp.Expr(np.array(["1.25", "-0.015", "", "3"], dtype=object)).cast(
+        "decimal(10, 2)"
+    )
+    assert a.dtype == "Decimal(10, 2)"
+    assert list(a.eview()) == [Decimal("1.25"), Decimal("-0.02"), None, Decimal("3.00")]
+    b = tp.Expr(np.array([0.1, 0.2, 0.3, 0.4])).cast("decimal(5, 1)")
+    assert list((a + b).eview()) == [Decimal("1.35"), Decimal("0.18"), None, Decimal("3.40")]
+    assert list((a * 3).eview())[0] == Decimal("3.75")
+    assert list(a.dec_div(b, 3).eview())[0] == Decimal("12.500")
+    assert list(a.dec_round(1, "half_up").eview())[0] == Decimal("1.3")
+    assert_allclose(a.cast("f64").eview(), [1.25, -0.02, np.nan, 3])
+
+
+def test_str():
+    s = tp.Expr(np.array([" Foo-12 ", "bar-345", "baz"], dtype=object))
+    assert list(s.str.contains(r"\d+").eview()) == [True, True, False]
+    assert list(s.str.contains("ba", literal=True).eview()) == [False, True, True]
+    assert list(s.str.strip().str.upper().eview()) == ["FOO-12", "BAR-345", "BAZ"]
+    assert list(s.str.extract(r"-(\d+)").eview()) == ["12", "345", ""]
+    assert list(s.str.replace(r"\d", "#").eview()) == [" Foo-## ", "bar-###", "baz"]
+    res = s.str.replace(r"\d", "#", n=1).eview()
+    assert list(res) == [" Foo-#2 ", "bar-#45", "baz"]
+    # n=0 replaces nothing in both modes
+    for literal in [True, False]:
+        res = s.str.replace("a", "#", literal=literal, n=0).eview()
+        assert list(res) == [" Foo-12 ", "bar-345", "baz"]
+    assert list(s.str.slice(0, 3).eview()) == [" Fo", "bar", "baz"]
+    assert list(s.str.pad(5, fillchar="*").eview()) == [" Foo-12 ", "bar-345", "**baz"]
+    assert_allclose(s.str.len().eview(), [8, 7, 3])
+    assert list(s.str.split("-").list.len().eview()) == [2, 2, 1]
+
+
 def test_rolling():
     time = tp.Expr(
         pd.date_range("2020-01-01 04:00:00", "2020-01-5 00:00:00", freq="4H").values
@@ -148,3 +217,74 @@ def test_group_by_time():
         v = v.eview()
         assert_allclose(v, df_pd["value"])
         assert_series_equal(pd.Series(label), df_pd.index.to_series())
+
+
+def test_group_by_time_tz():
+    # utc instants, grouped by the sessions opening at 09:30 in Shanghai
+    time = tp.Expr(
+        np.array(
+            [
+                "2024-01-01 00:00",
+                "2024-01-01 02:00",
+                "2024-01-01 20:00",
+                "2024-01-02 03:00",
+            ],
+            dtype="datetime64[ns]",
+        )
+    ).convert_tz("Asia/Shanghai")
+    value = tp.Expr([1, 2, 3, 4])
+    label, v = value.groupby("1d", time_expr=time, offset="9h30m").agg(
+        tp.s(0).sum()
+    )
+    assert label.strftime("%m-%d %H:%M").view.tolist() == [
+        "12-31 09:30",
+        "01-01 09:30",
+        "01-02 09:30",
+    ]
+    assert_allclose(v.eview(), [1, 5, 4])
+
+
+def test_repr_formula():
+    close = tp.Expr([1.0, 2, 3, 4], name="close")
+    e = close.ts_mean(2) - close.ts_mean(3)
+    assert repr(e).startswith("ts_mean(close, 2")
+    assert " - ts_mean(close, 3" in repr(e)
+    assert e.explain().splitlines()[0] == "sub()"
+
+
+@pytest.mark.skipif(
+    not hasattr(tp.Expr, "to_json"), reason="teapy is built without the srd feature"
+)
+def test_json_roundtrip():
+    import pickle
+
+    e = ((tp.context("close").ts_mean(3) - 1).abs() * 2).alias("res")
+    e2 = tp.Expr.from_json(e.to_json())
+    assert repr(e2) == repr(e)
+    assert repr(pickle.loads(pickle.dumps(e))) == repr(e)
+    dd = tp.DataDict(close=[1.0, 2, 3, 4])
+    assert_allclose(dd.with_columns(e2)["res"].view, dd.with_columns(e)["res"].view)
+    # non-finite float parameters are kept by the json definition
+    e = (tp.context("close") * np.inf + np.nan).alias("res")
+    e2 = pickle.loads(pickle.dumps(e))
+    assert repr(e2) == repr(e)
+    assert np.isnan(dd.with_columns(e2)["res"].view).all()
+
+
+def test_infer_dtype():
+    e = tp.Expr([1.0, 2, 3])
+    assert e.ts_mean(2).dtype == "F64"
+    assert e.ts_mean(2).cast("i32").dtype == "I32"
+    assert tp.context("a").ts_mean(2).dtype == "F64"
+    assert tp.context("a").dtype == "Unknown"
+    # the dtype is cached on the nodes when they are chained
+    e2 = e
+    for _ in range(1000):
+        e2 = e2.ts_mean(2)
+    assert e2.dtype == "F64"
+    s = tp.Expr(np.array(["x", "y"], dtype=object))
+    assert s.dtype == "String"
+    with pytest.raises(TypeError, match="ts_mean"):
+        s.ts_mean(2)
+    with pytest.raises(TypeError, match="strftime"):
+        e.strftime()
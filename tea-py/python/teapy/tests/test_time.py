@@ -11,3 +11,48 @@ def test_nat():
         assert np.isnat(b.view[0])
         assert b.view[1] == a[1]
         # assert (b[1] - b[0]).eview()
+
+
+def test_date():
+    a = np.array(["2024-01-05", "NaT", "2024-01-06"], dtype="datetime64[D]")
+    e = tp.Expr(a)
+    assert e.view.dtype == np.dtype("datetime64[D]")
+    assert np.isnat(e.view[1])
+    assert e.strftime().view.tolist() == ["2024-01-05", "NaT", "2024-01-06"]
+    assert e.offset_business_days(1).view.astype(str).tolist() == [
+        "2024-01-08",
+        "NaT",
+        "2024-01-08",
+    ]
+    s = tp.Expr(np.array(["20240105", "2024-01-06 12:00:00"], dtype=object))
+    assert s.strptime_date().view.astype(str).tolist() == ["2024-01-05", "2024-01-06"]
+    dt = tp.Expr(np.array(["2024-01-05 10:00"], dtype="datetime64[ns]"))
+    assert dt.cast("date").view.astype(str).tolist() == ["2024-01-05"]
+
+
+def test_timezone():
+    a = np.array(
+        ["2024-01-01 16:30", "2024-01-02 01:00", "2024-01-02 02:00"],
+        dtype="datetime64[ns]",
+    )
+    e = tp.Expr(a).convert_tz("Asia/Shanghai")
+    assert e.dtype == "DateTime(Nanosecond, Asia/Shanghai)"
+    # the utc instants are kept
+    assert (e.view == a).all()
+    assert e.strftime("%Y-%m-%d %H:%M").view.tolist() == [
+        "2024-01-02 00:30",
+        "2024-01-02 09:00",
+        "2024-01-02 10:00",
+    ]
+    # truncated at the local midnight
+    assert e.duration_trunc("1d").view.astype(str).tolist() == [
+        "2024-01-01T16:00:00.000000000"
+    ] * 3
+    session = e.duration_trunc("1d", offset="9h30m")
+    assert session.strftime("%d %H:%M").view.tolist() == [
+        "01 09:30",
+        "01 09:30",
+        "02 09:30",
+    ]
+    local = e.replace_tz(None)
+    assert local.view.astype(str).tolist()[0] == "2024-01-02T00:30:00.000000000"
@@ -56,11 +56,13 @@ pub unsafe fn calc_ret_single(
     };
     let mut out = pos.clone();
     out.e.cast_f64().chain_f_ctx(move |(data, ctx)| {
-        let arr = data.view_arr(ctx.as_ref())?;
+        let arr = &*data.view_arr(ctx.as_ref())?;
         let pos_arr = match_arrok!(arr; F64(a) => { a.view().to_dim1() },)?; // 当期仓位的1d array
-        let opening_cost = opening_cost.e.view_arr(ctx.as_ref())?.deref().cast_f64(); // 开仓成本的1d array
+        let opening_cost = opening_cost.e.view_arr(ctx.as_ref())?;
+        let opening_cost = opening_cost.deref().cast_f64(); // 开仓成本的1d array
         let opening_cost_arr = opening_cost.view().to_dim1()?; // 开仓成本的1d array
-        let closing_cost = closing_cost.e.view_arr(ctx.as_ref())?.deref().cast_f64(); // 平仓价格的1d array
+        let closing_cost = closing_cost.e.view_arr(ctx.as_ref())?;
+        let closing_cost = closing_cost.deref().cast_f64(); // 平仓价格的1d array
         let closing_cost_arr = closing_cost.view().to_dim1()?; // 平仓价格的1d array
         if pos_arr.is_empty() {
             return Ok((Arr1::from_vec(Vec::<f64>::new()).into_dyn().into(), ctx));
@@ -71,11 +73,8 @@ pub unsafe fn calc_ret_single(
         let mut last_lot_num = 0.;
         let mut last_close = closing_cost_arr[0];
         if let Some(contract_signal) = contract_signal.as_ref() {
-            let contract_signal = contract_signal
-                .e
-                .view_arr(ctx.as_ref())?
-                .deref()
-                .cast_bool();
+            let contract_signal = contract_signal.e.view_arr(ctx.as_ref())?;
+            let contract_signal = contract_signal.deref().cast_bool();
             let contract_signal_arr = contract_signal.view().to_dim1()?;
             Ok((
                 Zip::from(&pos_arr.0)
@@ -229,13 +228,16 @@ pub unsafe fn calc_ret_single_with_spread(
         (None, None)
     };
     out.e.cast_f64().chain_f_ctx(move |(data, ctx)| {
-        let arr = data.view_arr(ctx.as_ref())?;
+        let arr = &*data.view_arr(ctx.as_ref())?;
         let pos_arr = match_arrok!(arr; F64(a) => { a.view().to_dim1() },)?; // 当期仓位的1d array
-        let opening_cost = opening_cost.e.view_arr(ctx.as_ref())?.deref().cast_f64(); // 开仓成本的1d array
+        let opening_cost = opening_cost.e.view_arr(ctx.as_ref())?;
+        let opening_cost = opening_cost.deref().cast_f64(); // 开仓成本的1d array
         let opening_cost_arr = opening_cost.view().to_dim1()?; // 开仓成本的1d array
-        let closing_cost = closing_cost.e.view_arr(ctx.as_ref())?.deref().cast_f64(); // 平仓价格的1d array
+        let closing_cost = closing_cost.e.view_arr(ctx.as_ref())?;
+        let closing_cost = closing_cost.deref().cast_f64(); // 平仓价格的1d array
         let closing_cost_arr = closing_cost.view().to_dim1()?; // 平仓价格的1d array
-        let spread = spread.e.view_arr(ctx.as_ref())?.deref().cast_f64();
+        let spread = spread.e.view_arr(ctx.as_ref())?;
+        let spread = spread.deref().cast_f64();
         let spread_arr = spread.view().to_dim1()?;
         if pos_arr.is_empty() {
             return Ok((Arr1::from_vec(Vec::<f64>::new()).into_dyn().into(), ctx));
@@ -246,11 +248,8 @@ pub unsafe fn calc_ret_single_with_spread(
         let mut last_lot_num = 0.;
         let mut last_close = closing_cost_arr[0];
         if let Some(contract_signal) = contract_signal.as_ref() {
-            let contract_signal = contract_signal
-                .e
-                .view_arr(ctx.as_ref())?
-                .deref()
-                .cast_bool();
+            let contract_signal = contract_signal.e.view_arr(ctx.as_ref())?;
+            let contract_signal = contract_signal.deref().cast_bool();
             let contract_signal_arr = contract_signal.view().to_dim1()?;
             Ok((
                 Zip::from(&pos_arr.0)
@@ -10,6 +10,7 @@ use numpy::{
 };
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyFloat, PyInt, PyString};
 use pyo3::IntoPyObjectExt;
 
 #[cfg(feature = "arw")]
@@ -52,11 +53,16 @@ pub enum PyArrayOk<'py> {
     I32(Bound<'py, PyArrayDyn<i32>>),
     I64(Bound<'py, PyArrayDyn<i64>>),
     U64(Bound<'py, PyArrayDyn<u64>>),
+    I8(Bound<'py, PyArrayDyn<i8>>),
+    I16(Bound<'py, PyArrayDyn<i16>>),
+    U16(Bound<'py, PyArrayDyn<u16>>),
+    U32(Bound<'py, PyArrayDyn<u32>>),
     Usize(Bound<'py, PyArrayDyn<usize>>),
     Object(Bound<'py, PyArrayDyn<Object>>),
     DateTimeMs(Bound<'py, PyArrayDyn<Datetime<units::Milliseconds>>>),
     DateTimeUs(Bound<'py, PyArrayDyn<Datetime<units::Microseconds>>>),
     DateTimeNs(Bound<'py, PyArrayDyn<Datetime<units::Nanoseconds>>>),
+    Date(Bound<'py, PyArrayDyn<Datetime<units::Days>>>),
 }
 
 /// match the enum `PyArrayOk` to get the discrete dtype of `PyArray` so that we can
@@ -100,6 +106,123 @@ impl<'py> PyArrayOk<'py> {
     }
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum ObjectKind {
+    Str,
+    Bool,
+    Int,
+}
+
+#[inline]
+fn is_null_object(obj: &Bound<'_, PyAny>) -> bool {
+    obj.is_none() || obj.downcast::<PyFloat>().is_ok_and(|f| f.value().is_nan())
+}
+
+/// Strings with nulls as a masked `String` array, the values of the nulls are
+/// empty strings. Strings without null are read as a `String` array.
+pub fn masked_strings<'a>(data: Vec<Option<String>>) -> ArrOk<'a> {
+    if Iterator::all(&mut data.iter(), Option::is_some) {
+        let data = data.into_iter().map(Option::unwrap).collect::<Vec<_>>();
+        return Arr1::from_vec(data).into_dyn().into();
+    }
+    let validity = data.iter().map(Option::is_some).collect();
+    let values = data
+        .into_iter()
+        .map(Option::unwrap_or_default)
+        .collect::<Vec<_>>();
+    MaskedArray::new(Arr1::from_vec(values).into_dyn().into(), validity)
+        .unwrap()
+        .into()
+}
+
+/// Infer the dtype of an object array whose elements are all `str`, `bool`
+/// or `int` apart from nulls (`None` and `NaN`). Strings are read as `String`
+/// or as masked strings if there are nulls, booleans and integers as `OptBool`
+/// and `OptI64`.
+///
+/// Return `None` if the array is mixed typed, has no valid element or is a
+/// multi-dimensional array of strings with nulls (only 1-d arrays can be
+/// masked), so the caller can keep it as an `Object` array.
+pub fn infer_object_arr(arr: &Bound<'_, PyArrayDyn<Object>>) -> Option<ArrOk<'static>> {
+    let py = arr.py();
+    let arr = arr.readonly();
+    let view = arr.as_array();
+    let mut kind = None;
+    let mut has_null = false;
+    for obj in view.iter() {
+        let obj = obj.0.bind(py);
+        let k = if is_null_object(obj) {
+            has_null = true;
+            continue;
+        } else if obj.is_instance_of::<PyString>() {
+            ObjectKind::Str
+        } else if obj.is_instance_of::<PyBool>() {
+            ObjectKind::Bool
+        } else if obj.is_instance_of::<PyInt>() {
+            ObjectKind::Int
+        } else {
+            return None;
+        };
+        match kind {
+            None => kind = Some(k),
+            Some(kind) if kind != k => return None,
+            _ => {}
+        }
+    }
+    if has_null && kind == Some(ObjectKind::Str) {
+        if view.ndim() != 1 {
+            return None;
+        }
+        let data = view
+            .iter()
+            .map(|obj| {
+                let obj = obj.0.bind(py);
+                if is_null_object(obj) {
+                    Some(None)
+                } else {
+                    obj.extract::<String>().ok().map(Some)
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        return Some(masked_strings(data));
+    }
+    fn collect<T, F>(view: ndarray::ArrayViewD<'_, Object>, py: Python<'_>, f: F) -> Option<ArrD<T>>
+    where
+        F: Fn(&Bound<'_, PyAny>) -> Option<T>,
+    {
+        let data = view
+            .iter()
+            .map(|obj| f(obj.0.bind(py)))
+            .collect::<Option<Vec<_>>>()?;
+        Some(
+            ndarray::ArrayD::from_shape_vec(view.raw_dim(), data)
+                .ok()?
+                .wrap(),
+        )
+    }
+    let out = match kind? {
+        ObjectKind::Str => collect(view, py, |obj| obj.extract::<String>().ok())?.into(),
+        ObjectKind::Bool => collect(view, py, |obj| {
+            if is_null_object(obj) {
+                Some(None)
+            } else {
+                obj.extract::<bool>().ok().map(Some)
+            }
+        })?
+        .into(),
+        // integers out of the range of i64 keep the array as object
+        ObjectKind::Int => collect(view, py, |obj| {
+            if is_null_object(obj) {
+                Some(None)
+            } else {
+                obj.extract::<i64>().ok().map(Some)
+            }
+        })?
+        .into(),
+    };
+    Some(out)
+}
+
 // do not change the order of the variants
 #[derive(FromPyObject)]
 pub enum PyList {
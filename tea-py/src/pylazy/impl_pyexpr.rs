@@ -1,5 +1,5 @@
 use super::export::*;
-use super::pyfunc::{parse_expr, parse_expr_list, parse_expr_nocopy};
+use super::pyfunc::{parse_expr, parse_expr_list, parse_expr_nocopy, parse_expr_with};
 use crate::from_py::{NoDim0, PyContext};
 use ndarray::SliceInfoElem;
 #[cfg(feature = "time")]
@@ -17,9 +17,12 @@ use std::borrow::Cow;
 use tea_hash::TpHashMap;
 
 use teapy_core::prelude::*;
+use teapy_core::utils::CollectTrustedToVec;
 
 #[cfg(feature = "map")]
 use super::pyfunc::where_;
+#[cfg(feature = "srd")]
+use tea_lazy::ExprDef;
 use tea_lazy::{Data, Expr};
 
 #[cfg(feature = "agg")]
@@ -58,14 +61,15 @@ pub fn expr_register(name: String, f: PyObject) -> PyResult<()> {
 #[allow(clippy::missing_safety_doc)]
 impl PyExpr {
     #[new]
-    #[pyo3(signature=(expr=None, name=None, copy=false))]
+    #[pyo3(signature=(expr=None, name=None, copy=false, infer_object=true))]
     pub unsafe fn new(
         expr: Option<&Bound<'_, PyAny>>,
         name: Option<String>,
         copy: bool,
+        infer_object: bool,
     ) -> PyResult<Self> {
         let mut out = if let Some(expr) = expr {
-            parse_expr(expr, copy)?
+            parse_expr_with(expr, copy, infer_object)?
         } else {
             Default::default()
         };
@@ -109,7 +113,16 @@ impl PyExpr {
     }
 
     fn __repr__(&self) -> String {
-        format!("{:?}", &self.e)
+        if self.e.step_acc() == 0 {
+            format!("{:?}", self.e)
+        } else {
+            self.e.formula()
+        }
+    }
+
+    /// A tree of the operations of the expression.
+    pub fn explain(&self) -> String {
+        self.e.explain()
     }
 
     pub fn simplify(&mut self) {
@@ -268,7 +281,7 @@ impl PyExpr {
                     .iter()
                     .map(|arr| {
                         match_arrok!(arr;
-                            (PureNumeric | Bool | U8 | U64 | Object)(a) => {
+                            (PureNumeric | SmallInt | Bool | U8 | U64 | Object)(a) => {
                                 Ok(unsafe{
                                     PyArray::borrow_from_array(&a.view().0, container.clone())
                                     .no_dim0(py)
@@ -313,13 +326,24 @@ impl PyExpr {
                 return out.into_py_any(py);
             }
         }
-        let arr = data
-            .view_arr(ct_rs.as_ref())
-            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        // the compact integer dtypes are viewed as they are rather than widened
+        let arr = match data
+            .view_raw_arr(ct_rs.as_ref())
+            .map_err(|e| PyValueError::new_err(e.to_string()))?
+            .to_dense()
+        {
+            Cow::Borrowed(arr) => arr,
+            // the dense array of a masked array is dropped after the view, so it is copied
+            Cow::Owned(arr) => return arr_to_py(&arr, py),
+        };
+        // list, struct and decimal arrays can not be viewed by numpy, so they are copied
+        if let ArrOk::List(_) | ArrOk::Struct(_) | ArrOk::Decimal(_) = arr {
+            return arr_to_py(arr, py);
+        }
         unsafe {
             match_arrok!(
                 arr;
-                (PureNumeric | Bool | U8 | U64 | Object)(a) => {
+                (PureNumeric | SmallInt | Bool | U8 | U64 | Object)(a) => {
                     Ok(PyArray::borrow_from_array(&a.view().0, container)
                     .no_dim0(py)?)
                 },
@@ -341,7 +365,7 @@ impl PyExpr {
                     Ok(PyArray::borrow_from_array(&a.view().0, container)
                     .no_dim0(py)?)
                 },
-                // can not view string and timedelta dtype for technical reasons
+                // can not view string, timedelta and categorical dtype for technical reasons
                 String(a) => {
                     return PyArray::from_owned_array(
                         py,
@@ -349,6 +373,17 @@ impl PyExpr {
                     )
                     .no_dim0(py);
                 },
+                // numpy datetimes are naive, so the utc instants are viewed
+                #[cfg(feature = "time")]
+                DateTimeTz(a) => {
+                    let a: &ArbArray<'_, NPDatetime<units::Nanoseconds>> = std::mem::transmute(&a.utc);
+                    Ok(PyArray::borrow_from_array(&a.view().0, container)
+                    .no_dim0(py)?)
+                },
+                #[cfg(feature = "time")]
+                Date(a) => {
+                    return PyArray::from_owned_array(py, a.view().to_np_date().0).no_dim0(py);
+                },
                 #[cfg(feature = "time")]
                 TimeDelta(a) => {
                     return PyArray::from_owned_array(
@@ -357,6 +392,13 @@ impl PyExpr {
                     )
                     .no_dim0(py);
                 },
+                Categorical(a) => {
+                    return PyArray::from_owned_array(
+                        py,
+                        a.decode_opt().view().cast::<Object>().0,
+                    )
+                    .no_dim0(py);
+                },
             )
             .map_err(|e| PyValueError::new_err(e.to_string()))
         }
@@ -412,8 +454,8 @@ impl PyExpr {
                     .into_iter()
                     .map(|arr| {
                         match_arrok!(
-                            arr;
-                            (PureNumeric | Bool | U8 | U64 | Object)(a) => {
+                            &*arr;
+                            (PureNumeric | SmallInt | Bool | U8 | U64 | Object)(a) => {
                                 Ok(PyArray::from_owned_array(py, a.view().to_owned().0)
                                 .no_dim0(py)
                                 .unwrap())
@@ -447,61 +489,10 @@ impl PyExpr {
             }
         }
         let arr = data
-            .view_arr(ct_rs.as_ref())
-            .map_err(|e| PyValueError::new_err(e.to_string()))?;
-        Ok(match_arrok!(
-            arr;
-            (PureNumeric | Bool | U8 | U64 | Object)(a) => {
-                Ok(PyArray::from_owned_array(
-                    py,
-                    a.view().to_owned().0
-                )
-                .no_dim0(py)?)
-            },
-            #[cfg(feature = "time")]
-            DateTimeMs(a) => {
-                let a: &ArbArray<'_, NPDatetime<units::Milliseconds>> = unsafe{std::mem::transmute(a)};
-                Ok(PyArray::from_owned_array(
-                    py,
-                    a.view().to_owned().0
-                )
-                .no_dim0(py)?)
-            },
-            #[cfg(feature = "time")]
-            DateTimeUs(a) => {
-                let a: &ArbArray<'_, NPDatetime<units::Microseconds>> = unsafe{std::mem::transmute(a)};
-                Ok(PyArray::from_owned_array(
-                    py,
-                    a.view().to_owned().0
-                )
-                .no_dim0(py)?)
-            },
-            #[cfg(feature = "time")]
-            DateTimeNs(a) => {
-                let a: &ArbArray<'_, NPDatetime<units::Nanoseconds>> = unsafe{std::mem::transmute(a)};
-                Ok(PyArray::from_owned_array(
-                    py,
-                    a.view().to_owned().0
-                )
-                .no_dim0(py)?)
-            },
-            // can not view string and timedelta dtype for technical reasons
-            String(a) => {
-                return PyArray::from_owned_array(
-                    py,
-                    a.view().cast::<Object>().0,
-                )
-                .no_dim0(py);
-            },
-            #[cfg(feature = "time")]
-            TimeDelta(a) => {
-                return PyArray::from_owned_array(
-                    py,
-                    a.view().cast::<Object>().0,
-                )
-                .no_dim0(py);
-            },
-        ).unwrap())
+            .view_raw_arr(ct_rs.as_ref())
+            .map_err(|e| PyValueError::new_err(e.to_string()))?
+            .to_dense();
+        arr_to_py(&arr, py)
     }
 
     #[getter]
@@ -712,7 +703,7 @@ impl PyExpr {
     fn abs(&self) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.abs();
-        Ok(out)
+        out.checked()
     }
 
     #[cfg(feature = "map")]
@@ -804,9 +795,33 @@ impl PyExpr {
         }
     }
 
+    /// Serialize the definition of the expression to json, the expression
+    /// can be rebuilt by `Expr.from_json` without its inputs.
+    #[cfg(feature = "srd")]
+    pub fn to_json(&self) -> PyResult<String> {
+        self.e
+            .to_def()
+            .and_then(|def| def.to_json())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[cfg(feature = "srd")]
+    #[staticmethod]
+    pub fn from_json(s: &str) -> PyResult<Self> {
+        let e: Expr<'static> = ExprDef::from_json(s)
+            .and_then(|def| def.build())
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(e.to_py(None))
+    }
+
     // #[classmethod]
     pub fn __setstate__(&mut self, state: &Bound<'_, PyAny>) -> PyResult<()> {
         if let Ok(state) = state.downcast::<PyDict>() {
+            #[cfg(feature = "srd")]
+            if let Some(def) = state.get_item("def")? {
+                *self = Self::from_json(&def.extract::<String>()?)?;
+                return Ok(());
+            }
             let name = state
                 .get_item("name")?
                 .unwrap()
@@ -835,9 +850,21 @@ impl PyExpr {
 
     #[pyo3(signature=())]
     pub fn __getstate__<'py>(&'py mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let state = PyDict::new(py);
+        // pickle the definition rather than the result if the expression
+        // only depends on the context and scalars
+        #[cfg(feature = "srd")]
+        if let Ok(def) = self.e.to_def() {
+            if !def.has_array() {
+                let def = def
+                    .to_json()
+                    .map_err(|e| PyValueError::new_err(e.to_string()))?;
+                state.set_item("def", def)?;
+                return state.into_bound_py_any(py);
+            }
+        }
         let name = self.e.name();
         let arr = self.value(None, py)?;
-        let state = PyDict::new(py);
         state.set_item("name", name)?;
         state.set_item("arr", arr)?;
         state.into_bound_py_any(py)
@@ -857,180 +884,180 @@ impl PyExpr {
     /// Returns the square root of a number.
     ///
     /// Returns NaN if self is a negative number other than -0.0.
-    pub fn sqrt(&self) -> Self {
+    pub fn sqrt(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.sqrt();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns the cube root of each element.
-    pub fn cbrt(&self) -> Self {
+    pub fn cbrt(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.cbrt();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns the sign of each element.
-    fn sign(&self) -> Self {
+    fn sign(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.sign();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns the natural logarithm of each element.
-    pub fn ln(&self) -> Self {
+    pub fn ln(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.ln();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns ln(1+n) (natural logarithm) more accurately than if the operations were performed separately.
-    pub fn ln_1p(&self) -> Self {
+    pub fn ln_1p(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.ln_1p();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns the base 2 logarithm of each element.
-    pub fn log2(&self) -> Self {
+    pub fn log2(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.log2();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns the base 10 logarithm of each element.
-    pub fn log10(&self) -> Self {
+    pub fn log10(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.log10();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns e^(self) of each element, (the exponential function).
-    pub fn exp(&self) -> Self {
+    pub fn exp(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.exp();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns 2^(self) of each element.
-    pub fn exp2(&self) -> Self {
+    pub fn exp2(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.exp2();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns e^(self) - 1 in a way that is accurate even if the number is close to zero.
-    pub fn exp_m1(&self) -> Self {
+    pub fn exp_m1(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.exp_m1();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Computes the arccosine of each element. Return value is in radians in the range 0,
     /// pi or NaN if the number is outside the range -1, 1.
-    pub fn acos(&self) -> Self {
+    pub fn acos(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.acos();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Computes the arcsine of each element. Return value is in radians in the range -pi/2,
     /// pi/2 or NaN if the number is outside the range -1, 1.
-    pub fn asin(&self) -> Self {
+    pub fn asin(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.asin();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Computes the arctangent of each element. Return value is in radians in the range -pi/2, pi/2;
-    pub fn atan(&self) -> Self {
+    pub fn atan(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.atan();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Computes the sine of each element (in radians).
-    pub fn sin(&self) -> Self {
+    pub fn sin(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.sin();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Computes the cosine of each element (in radians).
-    pub fn cos(&self) -> Self {
+    pub fn cos(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.cos();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Computes the tangent of each element (in radians).
-    pub fn tan(&self) -> Self {
+    pub fn tan(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.tan();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns the smallest integer greater than or equal to `self`.
-    pub fn ceil(&self) -> Self {
+    pub fn ceil(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.ceil();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns the largest integer less than or equal to `self`.
-    pub fn floor(&self) -> Self {
+    pub fn floor(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.floor();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns the fractional part of each element.
-    pub fn fract(&self) -> Self {
+    pub fn fract(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.fract();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns the integer part of each element. This means that non-integer numbers are always truncated towards zero.
-    pub fn trunc(&self) -> Self {
+    pub fn trunc(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.trunc();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns true if this number is neither infinite nor NaN
-    pub fn is_finite(&self) -> Self {
+    pub fn is_finite(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.is_finite();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     /// Returns true if this value is positive infinity or negative infinity, and false otherwise.
-    pub fn is_inf(&self) -> Self {
+    pub fn is_inf(&self) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.is_infinite();
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
@@ -1039,42 +1066,42 @@ impl PyExpr {
     /// The result might not be correctly rounded owing to implementation details;
     /// `self.log2()` can produce more accurate results for base 2,
     /// and `self.log10()` can produce more accurate results for base 10.
-    pub fn log(&self, base: f64) -> Self {
+    pub fn log(&self, base: f64) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.log(base);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn first(&self, axis: i32, par: bool) -> Self {
+    pub fn first(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.first(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn last(&self, axis: i32, par: bool) -> Self {
+    pub fn last(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.last(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn valid_first(&self, axis: i32, par: bool) -> Self {
+    pub fn valid_first(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.valid_first(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn valid_last(&self, axis: i32, par: bool) -> Self {
+    pub fn valid_last(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.valid_last(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
@@ -1136,42 +1163,42 @@ impl PyExpr {
 
     #[cfg(feature = "map")]
     #[pyo3(signature=(n=1, axis=0, par=false))]
-    pub fn pct_change(&self, n: i32, axis: i32, par: bool) -> Self {
+    pub fn pct_change(&self, n: i32, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.pct_change(n, axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn count_nan(&self, axis: i32, par: bool) -> Self {
+    pub fn count_nan(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.count_none(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn count_none(&self, axis: i32, par: bool) -> Self {
+    pub fn count_none(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.count_none(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn count_notnan(&self, axis: i32, par: bool) -> Self {
+    pub fn count_notnan(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.count_valid(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn count_valid(&self, axis: i32, par: bool) -> Self {
+    pub fn count_valid(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.count_valid(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
@@ -1225,41 +1252,127 @@ impl PyExpr {
     }
 
     #[cfg(feature = "map")]
-    pub fn is_nan(&self) -> Self {
+    pub fn is_nan(&self) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.is_nan();
-        out
+        out.checked()
     }
 
     #[cfg(feature = "map")]
-    pub fn not_nan(&self) -> Self {
+    pub fn not_nan(&self) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.not_nan();
-        out
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    pub fn list_len(&self) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.list_len();
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    pub fn list_get(&self, i: i64) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.list_get(i);
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    pub fn list_sum(&self) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.list_sum();
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    #[pyo3(signature=(min_periods=1))]
+    pub fn list_mean(&self, min_periods: usize) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.list_mean(min_periods);
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    pub fn explode(&self) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.explode();
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    #[pyo3(signature=(other, scale, mode="half_even"))]
+    pub fn dec_div(&self, other: &Bound<'_, PyAny>, scale: u8, mode: &str) -> PyResult<Self> {
+        let mode = mode
+            .parse::<RoundMode>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let other = parse_expr_nocopy(other)?;
+        let obj = other.obj();
+        let mut out = self.clone();
+        out.e.dec_div(other.e, scale, mode);
+        Ok(out.add_obj_into(obj))
+    }
+
+    #[cfg(feature = "map")]
+    #[pyo3(signature=(scale, mode="half_even"))]
+    pub fn dec_round(&self, scale: u8, mode: &str) -> PyResult<Self> {
+        let mode = mode
+            .parse::<RoundMode>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let mut out = self.clone();
+        out.e.dec_round(scale, mode);
+        Ok(out)
+    }
+
+    #[cfg(feature = "map")]
+    pub fn struct_field(&self, name: String) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.struct_field(name);
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    #[pyo3(signature=(names, others=None))]
+    pub fn to_struct(
+        &self,
+        names: Vec<String>,
+        others: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        let others = if let Some(others) = others {
+            unsafe { parse_expr_list(others, false)? }
+        } else {
+            vec![]
+        };
+        let obj_vec = others.iter().map(|e| e.obj()).collect_trusted();
+        let mut out = self.clone();
+        out.e
+            .to_struct(others.into_iter().map(|e| e.e).collect_trusted(), names);
+        Ok(out.add_obj_vec_into(obj_vec))
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn median(&self, axis: i32, par: bool) -> Self {
+    pub fn median(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.median(axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn all(&self, axis: i32, par: bool) -> Self {
+    pub fn all(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.all(axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn any(&self, axis: i32, par: bool) -> Self {
+    pub fn any(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.any(axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "map")]
@@ -1269,44 +1382,44 @@ impl PyExpr {
     ///
     /// Safety
     /// the data for the array view should exist
-    pub unsafe fn diag(&self) -> Self {
+    pub unsafe fn diag(&self) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.diag();
-        out
+        out.checked()
     }
 
     #[cfg(feature = "map")]
     /// Insert new array axis at axis and return the result.
-    pub unsafe fn insert_axis(&self, axis: i32) -> Self {
+    pub unsafe fn insert_axis(&self, axis: i32) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.insert_axis(axis);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "map")]
     /// Remove new array axis at axis and return the result.
-    pub unsafe fn remove_axis(&self, axis: i32) -> Self {
+    pub unsafe fn remove_axis(&self, axis: i32) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.remove_axis(axis);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "map")]
     /// Return a transposed view of the array.
-    pub unsafe fn t(&self) -> Self {
+    pub unsafe fn t(&self) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.t();
-        out
+        out.checked()
     }
 
     #[cfg(feature = "map")]
     /// Swap axes ax and bx.
     ///
     /// This does not move any data, it just adjusts the array’s dimensions and strides.
-    pub unsafe fn swap_axes(&self, ax: i32, bx: i32) -> Self {
+    pub unsafe fn swap_axes(&self, ax: i32, bx: i32) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.swap_axes(ax, bx);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "map")]
@@ -1349,74 +1462,74 @@ impl PyExpr {
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn max(&self, axis: i32, par: bool) -> Self {
+    pub fn max(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.max(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn min(&self, axis: i32, par: bool) -> Self {
+    pub fn min(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.min(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn argmin(&self, axis: i32, par: bool) -> Self {
+    pub fn argmin(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.argmin(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn argmax(&self, axis: i32, par: bool) -> Self {
+    pub fn argmax(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.argmax(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn sum(&self, axis: i32, par: bool) -> Self {
+    pub fn sum(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.sum(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     #[pyo3(signature=(stable=false, axis=0, par=false))]
-    pub fn cumsum(&self, stable: bool, axis: i32, par: bool) -> Self {
+    pub fn cumsum(&self, stable: bool, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.cumsum(stable, axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn prod(&self, axis: i32, par: bool) -> Self {
+    pub fn prod(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.prod(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     #[pyo3(signature=(axis=0, par=false))]
-    pub fn cumprod(&self, axis: i32, par: bool) -> Self {
+    pub fn cumprod(&self, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.cumprod(axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(min_periods=1, axis=0, par=false))]
-    pub fn mean(&self, min_periods: usize, axis: i32, par: bool) -> Self {
+    pub fn mean(&self, min_periods: usize, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.mean(min_periods, axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(all(feature = "map", feature = "agg"))]
@@ -1456,87 +1569,107 @@ impl PyExpr {
     ) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.winsorize(method.0, method_params, axis, par);
-        Ok(e)
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(min_periods=1, axis=0, par=false))]
-    pub fn var(&self, min_periods: usize, axis: i32, par: bool) -> Self {
+    pub fn var(&self, min_periods: usize, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.var(min_periods, axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(min_periods=2, axis=0, par=false))]
-    pub fn std(&self, min_periods: usize, axis: i32, par: bool) -> Self {
+    pub fn std(&self, min_periods: usize, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.std(min_periods, axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(min_periods=3, axis=0, par=false))]
-    pub fn skew(&self, min_periods: usize, axis: i32, par: bool) -> Self {
+    pub fn skew(&self, min_periods: usize, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.skew(min_periods, axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(min_periods=4, axis=0, par=false))]
-    pub fn kurt(&self, min_periods: usize, axis: i32, par: bool) -> Self {
+    pub fn kurt(&self, min_periods: usize, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.kurt(min_periods, axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(all(feature = "map", feature = "agg"))]
     #[pyo3(signature=(pct=false, rev=false, axis=0, par=false))]
-    pub fn rank(&self, pct: bool, rev: bool, axis: i32, par: bool) -> Self {
+    pub fn rank(&self, pct: bool, rev: bool, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.rank(pct, rev, axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
     #[pyo3(signature=(q, method=Wrap(QuantileMethod::Linear), axis=0, par=false))]
-    pub fn quantile(&self, q: f64, method: Wrap<QuantileMethod>, axis: i32, par: bool) -> Self {
+    pub fn quantile(
+        &self,
+        q: f64,
+        method: Wrap<QuantileMethod>,
+        axis: i32,
+        par: bool,
+    ) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.quantile(q, method.0, axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "map")]
     #[pyo3(signature=(rev=false, axis=0, par=false))]
-    pub fn argsort(&self, rev: bool, axis: i32, par: bool) -> Self {
+    pub fn argsort(&self, rev: bool, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.argsort(rev, axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(all(feature = "map", feature = "agg"))]
     #[pyo3(signature=(kth, sort=true, rev=false, axis=0, par=false))]
-    pub fn arg_partition(&self, kth: usize, sort: bool, rev: bool, axis: i32, par: bool) -> Self {
+    pub fn arg_partition(
+        &self,
+        kth: usize,
+        sort: bool,
+        rev: bool,
+        axis: i32,
+        par: bool,
+    ) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.arg_partition(kth, sort, rev, axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(all(feature = "map", feature = "agg"))]
     #[pyo3(signature=(kth, sort=true, rev=false, axis=0, par=false))]
-    pub fn partition(&self, kth: usize, sort: bool, rev: bool, axis: i32, par: bool) -> Self {
+    pub fn partition(
+        &self,
+        kth: usize,
+        sort: bool,
+        rev: bool,
+        axis: i32,
+        par: bool,
+    ) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.partition(kth, sort, rev, axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(all(feature = "map", feature = "agg"))]
     #[pyo3(signature=(group, rev=false, axis=0, par=false))]
-    pub fn split_group(&self, group: usize, rev: bool, axis: i32, par: bool) -> Self {
+    pub fn split_group(&self, group: usize, rev: bool, axis: i32, par: bool) -> PyResult<Self> {
         let mut e = self.clone();
         e.e.split_group(group, rev, axis, par);
-        e
+        e.checked()
     }
 
     #[cfg(feature = "agg")]
@@ -1586,10 +1719,10 @@ impl PyExpr {
     }
 
     #[cfg(feature = "groupby")]
-    pub fn sorted_unique(&self) -> Self {
+    pub fn sorted_unique(&self) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.sorted_unique();
-        out
+        out.checked()
     }
 
     #[cfg(feature = "groupby")]
@@ -1697,10 +1830,10 @@ impl PyExpr {
         min_periods: Option<usize>,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_argmin(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
@@ -1711,26 +1844,38 @@ impl PyExpr {
         min_periods: Option<usize>,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_argmax(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
     #[pyo3(signature=(window, min_periods=None, axis=0, par=false))]
-    pub fn ts_min(&self, window: usize, min_periods: Option<usize>, axis: i32, par: bool) -> Self {
+    pub fn ts_min(
+        &self,
+        window: usize,
+        min_periods: Option<usize>,
+        axis: i32,
+        par: bool,
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_min(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
     #[pyo3(signature=(window, min_periods=None, axis=0, par=false))]
-    pub fn ts_max(&self, window: usize, min_periods: Option<usize>, axis: i32, par: bool) -> Self {
+    pub fn ts_max(
+        &self,
+        window: usize,
+        min_periods: Option<usize>,
+        axis: i32,
+        par: bool,
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_max(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
@@ -1743,10 +1888,10 @@ impl PyExpr {
         rev: bool,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_rank(window, min_periods, pct, rev, axis, par);
-        out
+        out.checked()
     }
 
     // #[cfg(feature = "rolling")]
@@ -1773,10 +1918,10 @@ impl PyExpr {
         min_periods: Option<usize>,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_minmaxnorm(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
@@ -1910,10 +2055,10 @@ impl PyExpr {
         // stable: bool,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_sum(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
@@ -1925,10 +2070,10 @@ impl PyExpr {
         // stable: bool,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_mean(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
@@ -1940,10 +2085,10 @@ impl PyExpr {
         // stable: bool,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_ewm(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
@@ -1955,10 +2100,10 @@ impl PyExpr {
         // stable: bool,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_wma(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
@@ -1970,10 +2115,10 @@ impl PyExpr {
         // stable: bool,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_std(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
@@ -1985,10 +2130,10 @@ impl PyExpr {
         // stable: bool,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_var(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
@@ -2000,10 +2145,10 @@ impl PyExpr {
         // stable: bool,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_skew(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
@@ -2015,10 +2160,10 @@ impl PyExpr {
         // stable: bool,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_kurt(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     // #[cfg(feature = "rolling")]
@@ -2045,26 +2190,38 @@ impl PyExpr {
         // stable: bool,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_zscore(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
     #[pyo3(signature=(window, min_periods=None, axis=0, par=false))]
-    pub fn ts_reg(&self, window: usize, min_periods: Option<usize>, axis: i32, par: bool) -> Self {
+    pub fn ts_reg(
+        &self,
+        window: usize,
+        min_periods: Option<usize>,
+        axis: i32,
+        par: bool,
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_reg(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
     #[pyo3(signature=(window, min_periods=None, axis=0, par=false))]
-    pub fn ts_tsf(&self, window: usize, min_periods: Option<usize>, axis: i32, par: bool) -> Self {
+    pub fn ts_tsf(
+        &self,
+        window: usize,
+        min_periods: Option<usize>,
+        axis: i32,
+        par: bool,
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_tsf(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
@@ -2075,10 +2232,10 @@ impl PyExpr {
         min_periods: Option<usize>,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_reg_slope(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
@@ -2089,10 +2246,24 @@ impl PyExpr {
         min_periods: Option<usize>,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_reg_resid_mean(window, min_periods, axis, par);
-        out
+        out.checked()
+    }
+
+    #[cfg(all(feature = "rolling", feature = "map"))]
+    #[pyo3(signature=(window, min_periods=None, axis=0, par=false))]
+    pub fn ts_reg_params(
+        &self,
+        window: usize,
+        min_periods: Option<usize>,
+        axis: i32,
+        par: bool,
+    ) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.ts_reg_params(window, min_periods, axis, par);
+        out.checked()
     }
 
     #[cfg(feature = "rolling")]
@@ -2103,10 +2274,10 @@ impl PyExpr {
         min_periods: Option<usize>,
         axis: i32,
         par: bool,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ts_reg_intercept(window, min_periods, axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(feature = "map")]
@@ -2132,10 +2303,10 @@ impl PyExpr {
 
     #[cfg(feature = "map")]
     #[pyo3(signature=(min, max, axis=0, par=false))]
-    pub fn clip(&self, min: f64, max: f64, axis: i32, par: bool) -> Self {
+    pub fn clip(&self, min: f64, max: f64, axis: i32, par: bool) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.clip(min.into(), max.into(), axis, par);
-        out
+        out.checked()
     }
 
     #[cfg(all(feature = "map", feature = "agg"))]
@@ -2178,7 +2349,7 @@ impl PyExpr {
     pub unsafe fn norm_cdf(&self, mean: Option<f64>, std: Option<f64>) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.norm_cdf(mean, std);
-        Ok(out)
+        out.checked()
     }
 
     #[cfg(feature = "blas")]
@@ -2285,35 +2456,35 @@ impl PyExpr {
     pub fn params(&self) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.params();
-        Ok(out)
+        out.checked()
     }
 
     #[cfg(feature = "blas")]
     pub fn singular_values(&self) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.singular_values();
-        Ok(out)
+        out.checked()
     }
 
     #[cfg(feature = "blas")]
     pub fn ols_rank(&self) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.ols_rank();
-        Ok(out)
+        out.checked()
     }
 
     #[cfg(feature = "blas")]
     pub fn sse(&self) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.sse();
-        Ok(out)
+        out.checked()
     }
 
     #[cfg(feature = "blas")]
     pub fn fitted_values(&self) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.fitted_values();
-        Ok(out)
+        out.checked()
     }
 
     #[cfg(feature = "blas")]
@@ -2424,25 +2595,176 @@ impl PyExpr {
         Ok((out.e + delta).to_py(self.obj()))
     }
 
+    #[cfg(feature = "map")]
+    #[pyo3(signature=(pat, literal=false))]
+    pub fn str_contains(&self, pat: String, literal: bool) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.str_contains(pat, literal);
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    pub fn str_starts_with(&self, prefix: String) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.str_starts_with(prefix);
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    pub fn str_ends_with(&self, suffix: String) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.str_ends_with(suffix);
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    #[pyo3(signature=(pat, value, literal=false, n=None))]
+    pub fn str_replace(
+        &self,
+        pat: String,
+        value: String,
+        literal: bool,
+        n: Option<usize>,
+    ) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.str_replace(pat, value, literal, n);
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    #[pyo3(signature=(pat, group=1))]
+    pub fn str_extract(&self, pat: String, group: usize) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.str_extract(pat, group);
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    pub fn str_split(&self, sep: String) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.str_split(sep);
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    #[pyo3(signature=(start, length=None))]
+    pub fn str_slice(&self, start: i64, length: Option<usize>) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.str_slice(start, length);
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    pub fn str_to_upper(&self) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.str_to_upper();
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    pub fn str_to_lower(&self) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.str_to_lower();
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    #[pyo3(signature=(chars=None))]
+    pub fn str_strip(&self, chars: Option<String>) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.str_strip(chars);
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    pub fn str_len(&self) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.str_len();
+        out.checked()
+    }
+
+    #[cfg(feature = "map")]
+    #[pyo3(signature=(width, side="left", fillchar=' '))]
+    pub fn str_pad(&self, width: usize, side: &str, fillchar: char) -> PyResult<Self> {
+        let side = side
+            .parse::<PadSide>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let mut out = self.clone();
+        out.e.str_pad(width, side, fillchar);
+        Ok(out)
+    }
+
     #[cfg(all(feature = "map", feature = "time"))]
     pub fn strptime(&self, fmt: Option<String>) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.strptime(fmt);
-        Ok(out)
+        out.checked()
+    }
+
+    #[cfg(all(feature = "map", feature = "time"))]
+    pub fn strptime_date(&self, fmt: Option<String>) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.strptime_date(fmt);
+        out.checked()
     }
 
     #[cfg(all(feature = "map", feature = "time"))]
     pub fn strftime(&self, fmt: Option<String>) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.strftime(fmt);
+        out.checked()
+    }
+
+    #[cfg(all(feature = "map", feature = "time"))]
+    pub fn convert_tz(&self, tz: &str) -> PyResult<Self> {
+        let tz = TimeZone::parse(tz).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let mut out = self.clone();
+        out.e.convert_tz(tz);
         Ok(out)
     }
 
+    #[cfg(all(feature = "map", feature = "time"))]
+    #[pyo3(signature=(tz=None))]
+    pub fn replace_tz(&self, tz: Option<&str>) -> PyResult<Self> {
+        let tz = tz
+            .map(TimeZone::parse)
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let mut out = self.clone();
+        out.e.replace_tz(tz);
+        Ok(out)
+    }
+
+    #[cfg(all(feature = "map", feature = "time"))]
+    #[pyo3(signature=(duration, offset=None))]
+    pub fn duration_trunc(&self, duration: &str, offset: Option<&str>) -> PyResult<Self> {
+        let parse = |s| TimeDelta::parse(s).map_err(|e| PyValueError::new_err(e.to_string()));
+        let duration = parse(duration)?;
+        let offset = offset.map(parse).transpose()?;
+        let mut out = self.clone();
+        out.e.duration_trunc(duration, offset);
+        Ok(out)
+    }
+
+    #[cfg(all(feature = "map", feature = "time"))]
+    pub fn offset_business_days(&self, n: i32) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.offset_business_days(n);
+        out.checked()
+    }
+
+    #[cfg(all(feature = "map", feature = "time"))]
+    pub fn is_business_day(&self) -> PyResult<Self> {
+        let mut out = self.clone();
+        out.e.is_business_day();
+        out.checked()
+    }
+
     #[cfg(feature = "map")]
     pub fn round(&self, precision: u32) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.round(precision);
-        Ok(out)
+        out.checked()
     }
 
     // pub fn round_string(&self, precision: usize) -> PyResult<Self> {
@@ -2475,7 +2797,7 @@ impl PyExpr {
     ) -> PyResult<Self> {
         let mut out = self.clone();
         out.e.get_time_rolling_idx(duration, start_by);
-        Ok(out)
+        out.checked()
     }
 
     #[cfg(feature = "groupby")]
@@ -2563,17 +2885,22 @@ impl PyExpr {
         Ok(out)
     }
 
-    #[pyo3(signature=(duration, closed="right".to_owned(), split=true))]
+    #[pyo3(signature=(duration, closed="right".to_owned(), split=true, offset=None))]
     #[cfg(all(feature = "time", feature = "groupby"))]
     pub unsafe fn _get_group_by_time_idx(
         &self,
         duration: &str,
         closed: String,
         split: bool,
+        offset: Option<&str>,
         py: Python,
     ) -> PyResult<PyObject> {
+        let offset = offset
+            .map(TimeDelta::parse)
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
         let mut out = self.clone();
-        out.e.get_group_by_time_idx(duration, closed);
+        out.e.get_group_by_time_idx(duration, closed, offset);
         if split {
             let out = out
                 .e
@@ -3196,3 +3523,121 @@ impl PyExpr {
         Ok(out.add_obj_into(obj))
     }
 }
+
+/// Copy an array into a numpy array, list arrays are copied into a python
+/// list of numpy arrays (None for null rows) and struct arrays into a dict of
+/// their fields.
+#[allow(unreachable_patterns)]
+fn arr_to_py(arr: &ArrOk<'_>, py: Python) -> PyResult<PyObject> {
+    if let ArrOk::List(a) = arr {
+        // null rows are None
+        let rows = (0..a.len())
+            .map(|i| {
+                if a.is_valid(i) {
+                    arr_to_py(&a.row(i), py)
+                } else {
+                    Ok(py.None())
+                }
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        return rows.into_py_any(py);
+    }
+    if let ArrOk::Struct(a) = arr {
+        let out = PyDict::new(py);
+        for (name, field) in a.names.iter().zip(&a.fields) {
+            out.set_item(name, arr_to_py(&field.to_dense(), py)?)?;
+        }
+        return out.into_py_any(py);
+    }
+    if let ArrOk::Decimal(a) = arr {
+        // decimals are converted into `decimal.Decimal` exactly, null is None
+        let dec = py.import("decimal")?.getattr("Decimal")?;
+        let strings = a.to_strings();
+        let values = strings
+            .0
+            .iter()
+            .map(|s| {
+                if s.is_empty() {
+                    Ok(Object(py.None()))
+                } else {
+                    Ok(Object(dec.call1((s.as_str(),))?.unbind()))
+                }
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        let out = ndarray::ArrayD::from_shape_vec(strings.raw_dim(), values).unwrap();
+        return PyArray::from_owned_array(py, out).no_dim0(py);
+    }
+    Ok(match_arrok!(
+        arr;
+        (PureNumeric | SmallInt | Bool | U8 | U64 | Object)(a) => {
+            Ok(PyArray::from_owned_array(
+                py,
+                a.view().to_owned().0
+            )
+            .no_dim0(py)?)
+        },
+        #[cfg(feature = "time")]
+        DateTimeMs(a) => {
+            let a: &ArbArray<'_, NPDatetime<units::Milliseconds>> = unsafe{std::mem::transmute(a)};
+            Ok(PyArray::from_owned_array(
+                py,
+                a.view().to_owned().0
+            )
+            .no_dim0(py)?)
+        },
+        #[cfg(feature = "time")]
+        DateTimeUs(a) => {
+            let a: &ArbArray<'_, NPDatetime<units::Microseconds>> = unsafe{std::mem::transmute(a)};
+            Ok(PyArray::from_owned_array(
+                py,
+                a.view().to_owned().0
+            )
+            .no_dim0(py)?)
+        },
+        #[cfg(feature = "time")]
+        DateTimeNs(a) => {
+            let a: &ArbArray<'_, NPDatetime<units::Nanoseconds>> = unsafe{std::mem::transmute(a)};
+            Ok(PyArray::from_owned_array(
+                py,
+                a.view().to_owned().0
+            )
+            .no_dim0(py)?)
+        },
+        // can not view string, timedelta and categorical dtype for technical reasons
+        String(a) => {
+            return PyArray::from_owned_array(
+                py,
+                a.view().cast::<Object>().0,
+            )
+            .no_dim0(py);
+        },
+        #[cfg(feature = "time")]
+        DateTimeTz(a) => {
+            let a: &ArbArray<'_, NPDatetime<units::Nanoseconds>> = unsafe{std::mem::transmute(&a.utc)};
+            Ok(PyArray::from_owned_array(
+                py,
+                a.view().to_owned().0
+            )
+            .no_dim0(py)?)
+        },
+        #[cfg(feature = "time")]
+        Date(a) => {
+            return PyArray::from_owned_array(py, a.view().to_np_date().0).no_dim0(py);
+        },
+        #[cfg(feature = "time")]
+        TimeDelta(a) => {
+            return PyArray::from_owned_array(
+                py,
+                a.view().cast::<Object>().0,
+            )
+            .no_dim0(py);
+        },
+        Categorical(a) => {
+            return PyArray::from_owned_array(
+                py,
+                a.decode_opt().view().cast::<Object>().0,
+            )
+            .no_dim0(py);
+        },
+    ).unwrap())
+}
@@ -3,19 +3,35 @@ mod export;
 // mod groupby;
 mod impl_pyexpr;
 mod pyexpr;
+#[cfg(all(feature = "arw", feature = "io"))]
+mod pyffi;
 mod pyfunc;
+#[cfg(all(feature = "arw", feature = "io"))]
+mod pystream;
 pub mod wrap;
 
 // pub use datadict::PyDataDict;
 pub use impl_pyexpr::expr_register;
 pub use pyexpr::{ExprToPy, IntoPyExpr};
 pub use pyexpr::{PyExpr, RefObj};
+#[cfg(all(feature = "arw", feature = "io"))]
+pub use pyffi::{from_arrow, to_arrow_c_array, to_arrow_c_stream};
 pub use pyfunc::*;
+#[cfg(all(feature = "arw", feature = "io"))]
+pub use pystream::{PyIpcBatchReader, PyIpcBatchWriter};
 
 use pyo3::prelude::*;
 
 pub(crate) fn add_lazy(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    // so that the expressions can be rebuilt from their definitions
+    tea_ext::register_ops();
+    #[cfg(feature = "groupby")]
+    tea_groupby::register_ops();
     m.add_class::<PyExpr>()?;
+    #[cfg(all(feature = "arw", feature = "io"))]
+    m.add_class::<PyIpcBatchReader>()?;
+    #[cfg(all(feature = "arw", feature = "io"))]
+    m.add_class::<PyIpcBatchWriter>()?;
     // m.add_class::<PyDataDict>()?;
     // m.add_class::<PyGroupBy>()?;
     m.add_function(wrap_pyfunction!(expr_register, m)?)?;
@@ -24,6 +40,8 @@ pub(crate) fn add_lazy(m: &Bound<'_, PyModule>) -> PyResult<()> {
     #[cfg(all(feature = "concat", feature = "map"))]
     m.add_function(wrap_pyfunction!(stack_expr_py, m)?)?;
     m.add_function(wrap_pyfunction!(eval_exprs, m)?)?;
+    m.add_function(wrap_pyfunction!(cse_exprs, m)?)?;
+    m.add_function(wrap_pyfunction!(schema_exprs, m)?)?;
     // m.add_function(wrap_pyfunction!(eval_dicts, m)?)?;
     #[cfg(feature = "agg")]
     m.add_function(wrap_pyfunction!(corr_py, m)?)?;
@@ -37,7 +55,7 @@ pub(crate) fn add_lazy(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(datetime, m)?)?;
     #[cfg(feature = "time")]
     m.add_function(wrap_pyfunction!(timedelta, m)?)?;
-    // m.add_function(wrap_pyfunction!(from_dataframe, m)?)?;
+    m.add_function(wrap_pyfunction!(from_dataframe, m)?)?;
     #[cfg(feature = "blas")]
     m.add_function(wrap_pyfunction!(get_newey_west_adjust_s, m)?)?;
     m.add_function(wrap_pyfunction!(parse_expr, m)?)?;
@@ -47,5 +65,31 @@ pub(crate) fn add_lazy(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // m.add_function(wrap_pyfunction!(read_ipc, m)?)?;
     #[cfg(all(feature = "arw", feature = "io"))]
     m.add_function(wrap_pyfunction!(scan_ipc, m)?)?;
+    #[cfg(all(feature = "arw", feature = "io"))]
+    m.add_function(wrap_pyfunction!(scan_dataset, m)?)?;
+    #[cfg(all(feature = "arw", feature = "io"))]
+    m.add_function(wrap_pyfunction!(write_ipc, m)?)?;
+    #[cfg(all(feature = "arw", feature = "io"))]
+    m.add_function(wrap_pyfunction!(to_arrow_c_array, m)?)?;
+    #[cfg(all(feature = "arw", feature = "io"))]
+    m.add_function(wrap_pyfunction!(to_arrow_c_stream, m)?)?;
+    #[cfg(all(feature = "arw", feature = "io"))]
+    m.add_function(wrap_pyfunction!(from_arrow, m)?)?;
+    #[cfg(all(feature = "parquet", feature = "io"))]
+    m.add_function(wrap_pyfunction!(scan_parquet, m)?)?;
+    #[cfg(all(feature = "parquet", feature = "io"))]
+    m.add_function(wrap_pyfunction!(write_parquet, m)?)?;
+    #[cfg(all(feature = "csv", feature = "io"))]
+    m.add_function(wrap_pyfunction!(read_csv, m)?)?;
+    #[cfg(all(feature = "csv", feature = "io"))]
+    m.add_function(wrap_pyfunction!(write_csv, m)?)?;
+    #[cfg(all(feature = "npy", feature = "io"))]
+    m.add_function(wrap_pyfunction!(read_npy, m)?)?;
+    #[cfg(all(feature = "npy", feature = "io"))]
+    m.add_function(wrap_pyfunction!(write_npy, m)?)?;
+    #[cfg(all(feature = "npy", feature = "io"))]
+    m.add_function(wrap_pyfunction!(read_npz, m)?)?;
+    #[cfg(all(feature = "npy", feature = "io"))]
+    m.add_function(wrap_pyfunction!(write_npz, m)?)?;
     Ok(())
 }
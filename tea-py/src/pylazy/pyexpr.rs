@@ -2,6 +2,7 @@ use crate::from_py::PyContext;
 use std::fmt::Debug;
 
 use super::export::*;
+use pyo3::exceptions::PyTypeError;
 use tea_lazy::{Expr, ExprElement};
 
 pub type RefObj = Option<Vec<Py<PyAny>>>;
@@ -123,6 +124,15 @@ impl PyExpr {
         self
     }
 
+    /// Raise a `TypeError` if the last operation doesn't accept the dtype of its input.
+    #[inline]
+    pub fn checked(self) -> PyResult<Self> {
+        self.e
+            .check_type()
+            .map_err(|e| PyTypeError::new_err(e.to_string()))?;
+        Ok(self)
+    }
+
     // #[allow(unreachable_patterns, dead_code)]
     // pub fn eval(mut self, context: Option<&PyAny>, freeze: bool) -> PyResult<Self> {
     //     self.eval_inplace(context, freeze)?;
@@ -170,10 +180,15 @@ impl PyExpr {
             "f32" => expr.e.cast_f32(),
             "int" | "i32" => expr.e.cast_i32(),
             "i64" => expr.e.cast_i64(),
+            "i8" => expr.e.cast_i8(),
+            "i16" => expr.e.cast_i16(),
+            "u16" => expr.e.cast_u16(),
+            "u32" => expr.e.cast_u32(),
             "usize" | "uint" => expr.e.cast_usize(),
             "bool" => expr.e.cast_bool(),
             "object" => expr.e.cast_object(),
             "str" => expr.e.cast_string(),
+            "category" | "categorical" => expr.e.cast_categorical(),
             #[cfg(feature = "time")]
             "datetime" => expr.e.cast_datetime(None),
             #[cfg(feature = "time")]
@@ -185,11 +200,28 @@ impl PyExpr {
             #[cfg(feature = "time")]
             "datetime(s)" => unimplemented!("datetime(s) is not implemented"),
             #[cfg(feature = "time")]
+            "date" => expr.e.cast_date(),
+            #[cfg(feature = "time")]
             "timedelta" => expr.e.cast_timedelta(),
             "optusize" | "opt<usize>" | "opt(usize)" => expr.e.cast_optusize(),
+            "optu8" | "opt<u8>" | "opt(u8)" => expr.e.cast_opt_u8(),
+            "optu64" | "opt<u64>" | "opt(u64)" => expr.e.cast_opt_u64(),
+            name if name.starts_with("decimal(") && name.ends_with(')') => {
+                // decimal(precision, scale)
+                let args = name["decimal(".len()..name.len() - 1]
+                    .split(',')
+                    .map(|v| v.trim().parse::<u8>())
+                    .collect::<Result<Vec<_>, _>>();
+                match args.as_deref() {
+                    Ok([precision, scale]) => expr.e.cast_decimal(*precision, *scale),
+                    _ => Err(PyValueError::new_err(format!(
+                        "expect decimal(precision, scale), got {ty_name}"
+                    )))?,
+                }
+            }
             _ => Err(PyValueError::new_err(format!(
                 "cast to type: {:?} is not implemented",
-                &ty_name
+                ty_name
             )))?,
         };
         Ok(expr)
@@ -0,0 +1,102 @@
+use super::export::*;
+use pyo3::exceptions::PyTypeError;
+use pyo3::types::PyCapsule;
+use std::ffi::CString;
+use tea_io::ffi::{self, ArrowArray, ArrowArrayStream, ArrowSchema};
+use tea_lazy::Expr;
+use teapy_core::prelude::TResult;
+
+/// The arrow ffi structs are moved out by the consumer of the capsule and
+/// released by their drop if the capsule is never consumed.
+#[repr(transparent)]
+struct ArrowCapsule<T>(T);
+
+unsafe impl<T> Send for ArrowCapsule<T> {}
+
+#[inline]
+fn new_capsule<'py, T: 'static>(
+    py: Python<'py>,
+    value: T,
+    name: &str,
+) -> PyResult<Bound<'py, PyCapsule>> {
+    PyCapsule::new(py, ArrowCapsule(value), Some(CString::new(name).unwrap()))
+}
+
+/// Get the pointer of a capsule and check its name.
+fn capsule_pointer<T>(capsule: &Bound<'_, PyAny>, name: &str) -> PyResult<*mut T> {
+    let capsule = capsule.downcast::<PyCapsule>()?;
+    let capsule_name = capsule.name()?.and_then(|n| n.to_str().ok());
+    if capsule_name != Some(name) {
+        return Err(PyValueError::new_err(format!(
+            "expect a capsule named {name}, got {capsule_name:?}"
+        )));
+    }
+    Ok(capsule.pointer() as *mut T)
+}
+
+/// Export the expressions as a struct array through the `__arrow_c_array__` protocol.
+#[pyfunction]
+pub fn to_arrow_c_array(
+    mut exprs: Vec<PyExpr>,
+    py: Python<'_>,
+) -> PyResult<(Bound<'_, PyCapsule>, Bound<'_, PyCapsule>)> {
+    let names = super::pyfunc::eval_for_write(&mut exprs)?;
+    let arrs = exprs
+        .iter()
+        .map(|e| ffi::expr_to_arrow(&e.e))
+        .collect::<TResult<Vec<_>>>()
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let names = names.iter().map(|n| n.as_str()).collect::<Vec<_>>();
+    let (schema, array) =
+        ffi::export_columns(arrs, &names).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok((
+        new_capsule(py, schema, "arrow_schema")?,
+        new_capsule(py, array, "arrow_array")?,
+    ))
+}
+
+/// Export the expressions through the `__arrow_c_stream__` protocol.
+#[pyfunction]
+pub fn to_arrow_c_stream(mut exprs: Vec<PyExpr>, py: Python<'_>) -> PyResult<Bound<'_, PyCapsule>> {
+    let names = super::pyfunc::eval_for_write(&mut exprs)?;
+    let arrs = exprs
+        .iter()
+        .map(|e| ffi::expr_to_arrow(&e.e))
+        .collect::<TResult<Vec<_>>>()
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let names = names.iter().map(|n| n.as_str()).collect::<Vec<_>>();
+    let stream =
+        ffi::export_stream(arrs, &names).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    new_capsule(py, stream, "arrow_array_stream")
+}
+
+/// Import an object implementing the arrow PyCapsule interface, the buffers
+/// of primitive arrays without nulls are shared rather than copied.
+#[pyfunction]
+pub fn from_arrow(obj: &Bound<'_, PyAny>) -> PyResult<Vec<PyExpr>> {
+    let (names, arrs) = if obj.hasattr("__arrow_c_stream__")? {
+        let capsule = obj.call_method0("__arrow_c_stream__")?;
+        let ptr = capsule_pointer::<ArrowArrayStream>(&capsule, "arrow_array_stream")?;
+        // the stream is moved out and the released one is left in the capsule
+        let stream = Box::new(unsafe { std::ptr::replace(ptr, ArrowArrayStream::empty()) });
+        unsafe { ffi::import_stream(stream) }
+    } else if obj.hasattr("__arrow_c_array__")? {
+        let (schema, array): (Bound<'_, PyAny>, Bound<'_, PyAny>) =
+            obj.call_method0("__arrow_c_array__")?.extract()?;
+        let schema = capsule_pointer::<ArrowSchema>(&schema, "arrow_schema")?;
+        let array = capsule_pointer::<ArrowArray>(&array, "arrow_array")?;
+        let array = unsafe { std::ptr::replace(array, ArrowArray::empty()) };
+        unsafe { ffi::import_array(&*schema, array) }
+    } else {
+        return Err(PyTypeError::new_err(format!(
+            "{} doesn't implement the arrow PyCapsule interface",
+            obj.get_type().name()?
+        )));
+    }
+    .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(names
+        .into_iter()
+        .zip(arrs)
+        .map(|(name, a)| Expr::new_from_arr(a, Some(name)).into())
+        .collect())
+}
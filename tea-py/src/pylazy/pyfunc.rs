@@ -1,12 +1,14 @@
-use super::super::from_py::{PyArrayOk, PyList};
+use super::super::from_py::{infer_object_arr, masked_strings, PyArrayOk, PyList};
 use super::export::*;
-use numpy::PyArrayMethods;
-use pyo3::types::{PyList as PyList3, PyTuple};
+use numpy::{PyArray1, PyArrayMethods};
+use pyo3::exceptions::PyTypeError;
+use pyo3::types::{PyList as PyList3, PySlice, PyTuple};
 use pyo3::IntoPyObjectExt;
 use std::borrow::Cow;
-use tea_lazy::{ColumnSelector, Data, Expr};
+use tea_lazy::{ColumnSelector, CommonSubexprs, Data, DataDict, Expr, ExprElement};
 use teapy_core::prelude::WrapNdarray;
 use teapy_core::prelude::*;
+use teapy_core::utils::CollectTrustedToVec;
 
 #[cfg(feature = "agg")]
 use tea_ext::agg::corr;
@@ -25,7 +27,6 @@ pub fn parse_expr_nocopy(obj: &Bound<'_, PyAny>) -> PyResult<PyExpr> {
 }
 
 #[pyfunction]
-#[allow(clippy::needless_return)]
 #[pyo3(signature=(obj, copy=false))]
 /// A util function to convert python object to PyExpr
 ///
@@ -35,6 +36,22 @@ pub fn parse_expr_nocopy(obj: &Bound<'_, PyAny>) -> PyResult<PyExpr> {
 ///
 /// we need python object to be alive when we are using the PyExpr if copy is false
 pub unsafe fn parse_expr(obj: &Bound<'_, PyAny>, copy: bool) -> PyResult<PyExpr> {
+    parse_expr_with(obj, copy, true)
+}
+
+/// Same as `parse_expr`, object arrays of `str`, `bool` or `int` with nulls
+/// are converted to masked `String`, `OptBool` or `OptI64` if `infer_object`
+/// is true.
+///
+/// # Safety
+///
+/// see `parse_expr`
+#[allow(clippy::needless_return)]
+pub unsafe fn parse_expr_with(
+    obj: &Bound<'_, PyAny>,
+    copy: bool,
+    infer_object: bool,
+) -> PyResult<PyExpr> {
     if let Ok(expr) = obj.extract::<PyExpr>() {
         Ok(expr)
     } else if obj.get_type().qualname()? == "Expr" {
@@ -70,13 +87,13 @@ pub unsafe fn parse_expr(obj: &Bound<'_, PyAny>, copy: bool) -> PyResult<PyExpr>
         let module_name = module_name.split('.').next().unwrap();
         if module_name == "pandas" {
             let obj = obj.getattr("values")?;
-            return parse_expr(&obj, copy);
+            return parse_expr_with(&obj, copy, infer_object);
         } else if module_name == "polars" {
             let kwargs = PyDict::new(obj.py());
             kwargs.set_item("writable", false)?;
             let obj = obj.call_method("to_numpy", (), Some(&kwargs))?;
             // let obj = obj.getattr("to_numpy")?.call((), Some(kwargs))?;
-            return parse_expr(&obj, copy);
+            return parse_expr_with(&obj, copy, infer_object);
         } else {
             return Err(PyValueError::new_err(format!(
                 "DataFrame of module {module_name} is not supported"
@@ -90,7 +107,7 @@ pub unsafe fn parse_expr(obj: &Bound<'_, PyAny>, copy: bool) -> PyResult<PyExpr>
         if module_name == "pandas" {
             // dbg!("parse pd Series");
             let obj = obj.getattr("values")?;
-            return parse_expr(&obj, copy);
+            return parse_expr_with(&obj, copy, infer_object);
         } else if module_name == "polars" {
             let kwargs = PyDict::new(obj.py());
             kwargs.set_item("writable", false)?;
@@ -99,7 +116,7 @@ pub unsafe fn parse_expr(obj: &Bound<'_, PyAny>, copy: bool) -> PyResult<PyExpr>
             if dtype == "Utf8" {
                 obj = obj.call_method("astype", ("str",), None)?;
             }
-            return parse_expr(&obj, copy);
+            return parse_expr_with(&obj, copy, infer_object);
         } else {
             return Err(PyValueError::new_err(format!(
                 "Series of module {module_name} is not supported"
@@ -109,6 +126,11 @@ pub unsafe fn parse_expr(obj: &Bound<'_, PyAny>, copy: bool) -> PyResult<PyExpr>
         // cast numpy.ndarray to PyExpr
         if pyarr.is_object() {
             let arr = pyarr.into_object()?;
+            if infer_object {
+                if let Some(arr) = infer_object_arr(&arr) {
+                    return Ok(Expr::new_from_arr(arr, None).into());
+                }
+            }
             if copy {
                 let e: Expr<'static> = arr.to_owned_array().wrap().into();
                 return Ok(e.into());
@@ -139,15 +161,19 @@ pub unsafe fn parse_expr(obj: &Bound<'_, PyAny>, copy: bool) -> PyResult<PyExpr>
             Ok(match_pyarray!(
                 pyarr;
                 (PureNumeric | Bool | Object)(arr) => { Ok(Expr::new_from_owned(arr.to_owned_array().wrap(), None).into()) },
-                Time(arr) => {
+                (SmallInt | Time)(arr) => {
                     let arrok: ArrOk<'_> = arr.to_owned_array().wrap().into();
                     Ok(Expr::new_from_arr(arrok, None).into())
                 },
+                Date(arr) => {
+                    let arrok: ArrOk<'_> = arr.readonly().as_array().wrap().np_date_to_date().into();
+                    Ok(Expr::new_from_arr(arrok, None).into())
+                },
             ).unwrap())
         } else {
             Ok(match_pyarray!(
                 pyarr;
-                (PureNumeric | Bool | Object | Time)(arr) => {
+                (PureNumeric | SmallInt | Bool | Object | Time)(arr) => {
                     let arr_res = arr.try_readwrite();
                     if let Ok(mut arr) = arr_res {
                         let arr_write = arr.as_array_mut();
@@ -172,9 +198,38 @@ pub unsafe fn parse_expr(obj: &Bound<'_, PyAny>, copy: bool) -> PyResult<PyExpr>
                         )
                     }
                 },
+                // numpy stores dates in i64, so they are always copied
+                Date(arr) => {
+                    let arrok: ArrOk<'_> = arr.readonly().as_array().wrap().np_date_to_date().into();
+                    Ok(Expr::new_from_arr(arrok, None).into())
+                },
             )
             .unwrap())
         }
+    } else if let Some(rows) = extract_array_rows(obj) {
+        // a list of numpy arrays is parsed as a list array
+        #[cfg(feature = "concat")]
+        {
+            let rows = rows
+                .iter()
+                .map(|o| parse_expr(o, false))
+                .collect::<PyResult<Vec<_>>>()?;
+            let arr_vec = rows
+                .iter()
+                .map(|r| r.e.view_arr(None).map(Cow::into_owned))
+                .collect::<TResult<Vec<_>>>()
+                .map_err(|e| PyValueError::new_err(e.to_string()))?;
+            let list = ListArray::from_arrays(arr_vec)
+                .map_err(|e| PyValueError::new_err(e.to_string()))?;
+            Ok(Expr::new_from_arr(list.into(), None).into())
+        }
+        #[cfg(not(feature = "concat"))]
+        {
+            let _ = rows;
+            Err(PyValueError::new_err(
+                "the concat feature is required to parse a list of arrays",
+            ))
+        }
     } else if let Ok(pylist) = obj.extract::<PyList>() {
         match_pylist!(pylist, l, {
             Ok(Expr::new_from_owned(Arr1::from_vec(l).into_dyn(), None).into())
@@ -193,6 +248,14 @@ pub unsafe fn parse_expr(obj: &Bound<'_, PyAny>, copy: bool) -> PyResult<PyExpr>
     }
 }
 
+/// The items of a non-empty python list if all of them are numpy arrays.
+fn extract_array_rows<'py>(obj: &Bound<'py, PyAny>) -> Option<Vec<Bound<'py, PyAny>>> {
+    let list = obj.downcast::<PyList3>().ok()?;
+    let rows: Vec<_> = list.iter().collect();
+    (!rows.is_empty() && Iterator::all(&mut rows.iter(), |o| o.extract::<PyArrayOk>().is_ok()))
+        .then_some(rows)
+}
+
 #[pyfunction]
 #[allow(clippy::missing_safety_doc, clippy::needless_return)]
 #[pyo3(signature=(obj, copy=false))]
@@ -306,15 +369,52 @@ pub fn corr_py(
 }
 
 #[pyfunction]
-#[pyo3(signature=(exprs, inplace=false, freeze=true))]
+/// Share the sub-expressions common to the expressions so that they are
+/// evaluated only once, return the number of deduplicated nodes.
+pub fn cse_exprs(exprs: Vec<PyExpr>) -> usize {
+    let exprs = exprs.iter().map(|e| &e.e).collect_trusted();
+    CommonSubexprs::new(&exprs).dedup
+}
+
+#[pyfunction]
+/// The dtypes of the expressions inferred without evaluation, the context
+/// references between the expressions are resolved by their names.
+pub fn schema_exprs(exprs: Vec<PyExpr>) -> PyResult<Vec<String>> {
+    let dd = DataDict::new(exprs.into_iter().map(|e| e.e).collect(), None);
+    let schema = dd
+        .schema()
+        .map_err(|e| PyTypeError::new_err(e.to_string()))?;
+    Ok(schema
+        .into_iter()
+        .map(|ty| ty.map_or_else(|| "Unknown".to_string(), |ty| ty.dtype()))
+        .collect())
+}
+
+#[pyfunction]
+#[pyo3(signature=(exprs, inplace=false, freeze=true, context=false, columns=None))]
+/// context: whether to resolve the context references between the expressions
+/// by their names, a referenced expression is evaluated before the expressions
+/// using it.
+///
+/// columns: the columns to evaluate with the context, the columns they reference
+/// are also evaluated. All the columns are evaluated if it is None.
 pub fn eval_exprs(
     mut exprs: Vec<PyExpr>,
     inplace: bool,
     freeze: bool,
+    context: bool,
+    columns: Option<&Bound<'_, PyAny>>,
 ) -> PyResult<Option<Vec<PyExpr>>> {
-    exprs
-        .par_iter_mut()
-        .try_for_each(|e| e.eval_inplace(None, freeze))?;
+    if context {
+        let mut dd = DataDict::new(exprs.iter().map(|e| e.e.clone()).collect(), None);
+        dd.eval_inplace(ColumnSelector::from(columns), true)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        zip(&mut exprs, dd.data).for_each(|(e, new)| e.e = new);
+    } else {
+        exprs
+            .par_iter_mut()
+            .try_for_each(|e| e.eval_inplace(None, freeze))?;
+    }
     if inplace {
         Ok(None)
     } else {
@@ -445,16 +545,185 @@ pub fn get_newey_west_adjust_s(x: PyExpr, resid: PyExpr, lag: PyExpr) -> PyResul
     Ok(out)
 }
 
-// #[pyfunction]
-// pub fn from_dataframe(df: &Bound<'_, PyAny>) -> PyResult<PyDataDict> {
-//     let columns = df.getattr("columns")?.extract::<Vec<String>>()?;
-//     let mut data = Vec::with_capacity(columns.len());
-//     for col in &columns {
-//         data.push(parse_expr_nocopy(df.get_item(col)?)?);
-//     }
-//     let (data, obj_map) = data.into_rs(Some(columns.clone()))?;
-//     Ok(DataDict::new(data, Some(columns)).to_py(obj_map))
-// }
+/// Build an `Opt*` column from the values filled with a placeholder and the null mask.
+fn masked_column<T>(values: &Bound<'_, PyAny>, mask: &Bound<'_, PyAny>) -> PyResult<PyExpr>
+where
+    T: numpy::Element + Copy + ExprElement + 'static,
+    Option<T>: ExprElement,
+    for<'a> ArrOk<'a>: From<ArbArray<'a, Option<T>>>,
+{
+    let values = values.downcast::<PyArray1<T>>()?.readonly();
+    let mask = mask.downcast::<PyArray1<bool>>()?.readonly();
+    let data = zip(values.as_array(), mask.as_array())
+        .map(|(v, m)| (!m).then_some(*v))
+        .collect_trusted();
+    Ok(Expr::new_from_owned(Arr1::from_vec(data).into_dyn(), None).into())
+}
+
+/// Read a list of strings, the strings with nulls are masked.
+#[inline]
+fn string_column(values: &Bound<'_, PyAny>) -> PyResult<PyExpr> {
+    let data = values.extract::<Vec<Option<String>>>()?;
+    Ok(Expr::new_from_arr(masked_strings(data), None).into())
+}
+
+/// Convert a pandas Series into an expression according to its dtype,
+/// nullable extension dtypes are mapped to the `Opt*` dtypes.
+unsafe fn from_pd_series(
+    series: &Bound<'_, PyAny>,
+    copy: bool,
+    infer_object: bool,
+) -> PyResult<PyExpr> {
+    let py = series.py();
+    let dtype = series.getattr("dtype")?.str()?.to_string();
+    let to_numpy = |dtype: &str, na_value: Bound<'_, PyAny>| {
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("dtype", dtype)?;
+        kwargs.set_item("na_value", na_value)?;
+        series.call_method("to_numpy", (), Some(&kwargs))
+    };
+    let mask = || series.call_method0("isna")?.call_method0("to_numpy");
+    match dtype.as_str() {
+        "Int8" | "Int16" | "Int32" | "UInt8" | "UInt16" => {
+            masked_column::<i32>(&to_numpy("int32", 0.into_bound_py_any(py)?)?, &mask()?)
+        }
+        "Int64" | "UInt32" | "UInt64" => {
+            masked_column::<i64>(&to_numpy("int64", 0.into_bound_py_any(py)?)?, &mask()?)
+        }
+        "boolean" => {
+            masked_column::<bool>(&to_numpy("bool", false.into_bound_py_any(py)?)?, &mask()?)
+        }
+        "Float32" | "Float64" => {
+            let values = to_numpy(&dtype.to_lowercase(), f64::NAN.into_bound_py_any(py)?)?;
+            parse_expr(&values, false)
+        }
+        dtype if dtype.starts_with("string") => {
+            string_column(&to_numpy("object", py.None().into_bound(py))?.call_method0("tolist")?)
+        }
+        "datetime64[s]" => parse_expr(
+            &series
+                .getattr("values")?
+                .call_method1("astype", ("datetime64[ms]",))?,
+            false,
+        ),
+        // timezone aware datetimes are read as utc and the time zone is attached
+        #[cfg(feature = "time")]
+        dtype if dtype.starts_with("datetime64[") && dtype.contains(',') => {
+            let (unit, tz) = dtype["datetime64[".len()..dtype.len() - 1]
+                .split_once(',')
+                .unwrap();
+            let unit = if unit == "s" { "ms" } else { unit };
+            let values = to_numpy(
+                &format!("datetime64[{unit}]"),
+                py.import("pandas")?.getattr("NaT")?,
+            )?;
+            with_tz(parse_expr(&values, false)?, tz.trim())
+        }
+        _ => parse_expr_with(&series.getattr("values")?, copy, infer_object),
+    }
+}
+
+/// Convert a polars Series into an expression according to its dtype,
+/// integer and boolean columns with nulls are mapped to the `Opt*` dtypes.
+unsafe fn from_pl_series<'py>(
+    series: &Bound<'py, PyAny>,
+    copy: bool,
+    infer_object: bool,
+) -> PyResult<PyExpr> {
+    let py = series.py();
+    let dtype = series.getattr("dtype")?.str()?.to_string();
+    let has_null = series.call_method0("null_count")?.extract::<usize>()? > 0;
+    let filled = |value: Bound<'py, PyAny>| {
+        series
+            .call_method1("fill_null", (value,))?
+            .call_method0("to_numpy")
+    };
+    let mask = || series.call_method0("is_null")?.call_method0("to_numpy");
+    match dtype.as_str() {
+        "Int8" | "Int16" | "Int32" | "UInt8" | "UInt16" if has_null => {
+            let values = filled(0.into_bound_py_any(py)?)?.call_method1("astype", ("int32",))?;
+            masked_column::<i32>(&values, &mask()?)
+        }
+        "Int64" | "UInt32" | "UInt64" if has_null => {
+            let values = filled(0.into_bound_py_any(py)?)?.call_method1("astype", ("int64",))?;
+            masked_column::<i64>(&values, &mask()?)
+        }
+        "Boolean" if has_null => {
+            masked_column::<bool>(&filled(false.into_bound_py_any(py)?)?, &mask()?)
+        }
+        "String" | "Utf8" => string_column(&series.call_method0("to_list")?),
+        // polars converts timezone aware datetimes to utc in numpy
+        #[cfg(feature = "time")]
+        dtype if dtype.starts_with("Datetime(") && dtype.contains("time_zone='") => {
+            let tz = dtype.split("time_zone='").nth(1).unwrap();
+            let tz = &tz[..tz.find('\'').unwrap()];
+            with_tz(parse_expr(&series.call_method0("to_numpy")?, true)?, tz)
+        }
+        _ => {
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("writable", false)?;
+            parse_expr_with(
+                &series.call_method("to_numpy", (), Some(&kwargs))?,
+                copy,
+                infer_object,
+            )
+        }
+    }
+}
+
+/// Attach the time zone to the utc datetimes read from a DataFrame.
+#[cfg(feature = "time")]
+fn with_tz(mut e: PyExpr, tz: &str) -> PyResult<PyExpr> {
+    let tz = TimeZone::parse(tz).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    e.e.chain_f_ctx(move |(data, ctx)| {
+        let arr = data.into_arr(ctx.clone())?;
+        Ok((ArrOk::from(arr.convert_tz(tz)).into(), ctx))
+    });
+    Ok(e)
+}
+
+#[pyfunction]
+#[pyo3(signature=(df, copy=false, infer_object=true))]
+/// Convert a pandas or polars DataFrame into expressions column by column,
+/// the names and the order of the columns are preserved.
+///
+/// infer_object: whether to convert the object columns of `str`, `bool` or
+/// `int` with nulls to masked `String`, `OptBool` or `OptI64`
+///
+/// # Safety
+///
+/// we need the DataFrame to be alive when we are using the expressions if copy is false
+pub unsafe fn from_dataframe(
+    df: &Bound<'_, PyAny>,
+    copy: bool,
+    infer_object: bool,
+) -> PyResult<Vec<PyExpr>> {
+    let module_name = df.get_type().module()?.to_string();
+    let columns = df.getattr("columns")?;
+    let names = columns
+        .try_iter()?
+        .map(|c| Ok(c?.str()?.to_string()))
+        .collect::<PyResult<Vec<String>>>()?;
+    let mut out = Vec::with_capacity(names.len());
+    for (i, name) in names.into_iter().enumerate() {
+        let mut e = match module_name.split('.').next().unwrap() {
+            "pandas" => from_pd_series(
+                &df.getattr("iloc")?.get_item((PySlice::full(df.py()), i))?,
+                copy,
+                infer_object,
+            )?,
+            "polars" => from_pl_series(&df.call_method1("to_series", (i,))?, copy, infer_object)?,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "DataFrame of module {module_name} is not supported"
+                )))
+            }
+        };
+        e.e.rename(name);
+        out.push(e);
+    }
+    Ok(out)
+}
 
 #[pyfunction]
 pub fn context<'py>(s: Option<&Bound<'py, PyAny>>) -> PyResult<PyExpr> {
@@ -479,12 +748,227 @@ pub fn context<'py>(s: Option<&Bound<'py, PyAny>>) -> PyResult<PyExpr> {
 
 #[cfg(all(feature = "arw", feature = "io"))]
 #[pyfunction]
-pub fn scan_ipc(path: String, columns: PyColSelect) -> PyResult<Vec<PyExpr>> {
-    use tea_io::scan_ipc_lazy;
-    let out: Vec<PyExpr> = scan_ipc_lazy(path, columns.0)
+#[pyo3(signature=(path, columns, offset=0, length=None, predicate=None))]
+pub fn scan_ipc(
+    path: String,
+    columns: PyColSelect,
+    offset: usize,
+    length: Option<usize>,
+    predicate: Option<Wrap<tea_io::ScanPredicate>>,
+) -> PyResult<Vec<PyExpr>> {
+    use tea_io::{scan_ipc_lazy, IpcScanOptions};
+    let options = IpcScanOptions {
+        columns: columns.0,
+        offset,
+        length,
+        predicate: predicate.map(|p| p.0),
+    };
+    let out: Vec<PyExpr> = scan_ipc_lazy(path, options)
+        .map_err(|e| PyValueError::new_err(e.to_string()))?
+        .into_iter()
+        .map(|e| e.into())
+        .collect();
+    Ok(out)
+}
+
+#[cfg(all(feature = "arw", feature = "io"))]
+#[pyfunction]
+pub fn scan_dataset(pattern: &str, columns: PyColSelect) -> PyResult<Vec<PyExpr>> {
+    let out: Vec<PyExpr> = tea_io::scan_dataset_lazy(pattern, columns.0)
+        .map_err(|e| PyValueError::new_err(e.to_string()))?
+        .into_iter()
+        .map(|e| e.into())
+        .collect();
+    Ok(out)
+}
+
+/// Evaluate the expressions and get the column names for writing
+#[cfg(all(feature = "arw", feature = "io"))]
+pub(super) fn eval_for_write(exprs: &mut [PyExpr]) -> PyResult<Vec<String>> {
+    exprs
+        .par_iter_mut()
+        .try_for_each(|e| e.eval_inplace(None, false))?;
+    let names = exprs
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            e.e.prepare();
+            e.e.name().unwrap_or_else(|| format!("column_{i}"))
+        })
+        .collect::<Vec<_>>();
+    Ok(names)
+}
+
+#[cfg(all(feature = "arw", feature = "io"))]
+#[pyfunction]
+#[pyo3(signature=(exprs, path, compression=None))]
+pub fn write_ipc(
+    mut exprs: Vec<PyExpr>,
+    path: String,
+    compression: Option<Wrap<tea_io::IpcCompression>>,
+) -> PyResult<()> {
+    let names = eval_for_write(&mut exprs)?;
+    let arrs = exprs
+        .iter()
+        .map(|e| e.e.view_raw_arr(None))
+        .collect::<TResult<Vec<_>>>()
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let names = names.iter().map(|n| n.as_str()).collect::<Vec<_>>();
+    tea_io::write_ipc(path, &arrs, &names, compression.map(|c| c.0))
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+#[cfg(all(feature = "parquet", feature = "io"))]
+#[pyfunction]
+pub fn scan_parquet(path: String, columns: PyColSelect) -> PyResult<Vec<PyExpr>> {
+    use tea_io::scan_parquet_lazy;
+    let out: Vec<PyExpr> = scan_parquet_lazy(path, columns.0)
         .map_err(|e| PyValueError::new_err(e.to_string()))?
         .into_iter()
         .map(|e| e.into())
         .collect();
     Ok(out)
 }
+
+#[cfg(all(feature = "parquet", feature = "io"))]
+#[pyfunction]
+#[pyo3(signature=(exprs, path, compression=None))]
+pub fn write_parquet(
+    mut exprs: Vec<PyExpr>,
+    path: String,
+    compression: Option<Wrap<tea_io::ParquetCompression>>,
+) -> PyResult<()> {
+    let names = eval_for_write(&mut exprs)?;
+    let arrs = exprs
+        .iter()
+        .map(|e| e.e.view_raw_arr(None))
+        .collect::<TResult<Vec<_>>>()
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let names = names.iter().map(|n| n.as_str()).collect::<Vec<_>>();
+    tea_io::write_parquet(path, &arrs, &names, compression.map(|c| c.0))
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+#[cfg(all(feature = "csv", feature = "io"))]
+fn csv_delimiter(delimiter: &str) -> PyResult<u8> {
+    match delimiter.as_bytes() {
+        [d] => Ok(*d),
+        _ => Err(PyValueError::new_err(
+            "delimiter of csv should be a single byte character",
+        )),
+    }
+}
+
+#[cfg(all(feature = "csv", feature = "io"))]
+#[pyfunction]
+#[pyo3(signature=(path, columns, delimiter=",", has_header=true, null_values=None, dtypes=None, parse_dates=false, datetime_fmt=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn read_csv(
+    path: String,
+    columns: PyColSelect,
+    delimiter: &str,
+    has_header: bool,
+    null_values: Option<Vec<String>>,
+    dtypes: Option<std::collections::HashMap<String, Wrap<DataType>>>,
+    parse_dates: bool,
+    datetime_fmt: Option<String>,
+) -> PyResult<Vec<PyExpr>> {
+    let options = tea_io::CsvReadOptions {
+        delimiter: csv_delimiter(delimiter)?,
+        has_header,
+        null_values: null_values.unwrap_or_default(),
+        dtypes: dtypes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, dtype)| (name, dtype.0))
+            .collect(),
+        parse_dates,
+        datetime_fmt,
+        columns: columns.0,
+    };
+    let (names, arrs) =
+        tea_io::read_csv(path, options).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let out = names
+        .into_iter()
+        .zip(arrs)
+        .map(|(name, arr)| Expr::new_from_arr(arr, Some(name)).into())
+        .collect();
+    Ok(out)
+}
+
+#[cfg(all(feature = "csv", feature = "io"))]
+#[pyfunction]
+#[pyo3(signature=(exprs, path, delimiter=",", has_header=true, null_value="", datetime_fmt=None))]
+pub fn write_csv(
+    mut exprs: Vec<PyExpr>,
+    path: String,
+    delimiter: &str,
+    has_header: bool,
+    null_value: &str,
+    datetime_fmt: Option<String>,
+) -> PyResult<()> {
+    let options = tea_io::CsvWriteOptions {
+        delimiter: csv_delimiter(delimiter)?,
+        has_header,
+        null_value: null_value.to_owned(),
+        datetime_fmt,
+    };
+    let names = eval_for_write(&mut exprs)?;
+    // masked strings are formatted without converting them to python objects
+    let arrs = exprs
+        .iter()
+        .map(|e| e.e.view_raw_arr(None))
+        .collect::<TResult<Vec<_>>>()
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let names = names.iter().map(|n| n.as_str()).collect::<Vec<_>>();
+    tea_io::write_csv(path, &arrs, &names, options)
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+#[cfg(all(feature = "npy", feature = "io"))]
+#[pyfunction]
+pub fn read_npy(path: String) -> PyResult<PyExpr> {
+    let arr = tea_io::read_npy(path).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(Expr::new_from_arr(arr, None).into())
+}
+
+#[cfg(all(feature = "npy", feature = "io"))]
+#[pyfunction]
+pub fn write_npy(mut expr: PyExpr, path: String) -> PyResult<()> {
+    expr.eval_inplace(None, false)?;
+    expr.e.prepare();
+    let arr = expr
+        .e
+        .view_arr(None)
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    tea_io::write_npy(path, &arr).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+#[cfg(all(feature = "npy", feature = "io"))]
+#[pyfunction]
+pub fn read_npz(path: String, columns: PyColSelect) -> PyResult<Vec<PyExpr>> {
+    let (names, arrs) =
+        tea_io::read_npz(path, columns.0).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let out = names
+        .into_iter()
+        .zip(arrs)
+        .map(|(name, arr)| Expr::new_from_arr(arr, Some(name)).into())
+        .collect();
+    Ok(out)
+}
+
+#[cfg(all(feature = "npy", feature = "io"))]
+#[pyfunction]
+#[pyo3(signature=(exprs, path, compress=false))]
+pub fn write_npz(mut exprs: Vec<PyExpr>, path: String, compress: bool) -> PyResult<()> {
+    let names = eval_for_write(&mut exprs)?;
+    let arrs = exprs
+        .iter()
+        .map(|e| e.e.view_arr(None))
+        .collect::<TResult<Vec<_>>>()
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let arrs = arrs.iter().map(|a| &**a).collect::<Vec<_>>();
+    let names = names.iter().map(|n| n.as_str()).collect::<Vec<_>>();
+    tea_io::write_npz(path, &arrs, &names, compress)
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
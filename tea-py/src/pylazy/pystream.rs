@@ -0,0 +1,84 @@
+use super::export::*;
+use crate::from_py::PyColSelect;
+use tea_io::{IpcBatchReader, IpcBatchWriter, IpcCompression};
+use tea_lazy::Expr;
+use teapy_core::prelude::TResult;
+
+#[pyclass(module = "teapy", name = "IpcBatchReader")]
+pub struct PyIpcBatchReader {
+    reader: IpcBatchReader,
+}
+
+#[pymethods]
+impl PyIpcBatchReader {
+    #[new]
+    #[pyo3(signature=(path, columns, batch_rows=None))]
+    fn new(path: String, columns: PyColSelect, batch_rows: Option<usize>) -> PyResult<Self> {
+        let reader = IpcBatchReader::try_new(path, columns.0, batch_rows)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyIpcBatchReader { reader })
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<Vec<PyExpr>>> {
+        let Some(arrs) = self.reader.next() else {
+            return Ok(None);
+        };
+        let arrs = arrs.map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let out = self
+            .reader
+            .columns()
+            .into_iter()
+            .zip(arrs)
+            .map(|(name, a)| Expr::new_from_arr(a, Some(name.to_owned())).into())
+            .collect();
+        Ok(Some(out))
+    }
+}
+
+#[pyclass(module = "teapy", name = "IpcBatchWriter")]
+pub struct PyIpcBatchWriter {
+    writer: Option<IpcBatchWriter>,
+}
+
+#[pymethods]
+impl PyIpcBatchWriter {
+    #[new]
+    #[pyo3(signature=(path, compression=None))]
+    fn new(path: String, compression: Option<Wrap<IpcCompression>>) -> PyResult<Self> {
+        let writer = IpcBatchWriter::try_new(path, compression.map(|c| c.0))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyIpcBatchWriter {
+            writer: Some(writer),
+        })
+    }
+
+    /// Evaluate the expressions and append them as a record batch.
+    fn write(&mut self, mut exprs: Vec<PyExpr>) -> PyResult<()> {
+        let Some(writer) = self.writer.as_mut() else {
+            return Err(PyValueError::new_err("the ipc writer is already finished"));
+        };
+        let names = super::pyfunc::eval_for_write(&mut exprs)?;
+        let arrs = exprs
+            .iter()
+            .map(|e| e.e.view_raw_arr(None))
+            .collect::<TResult<Vec<_>>>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let names = names.iter().map(|n| n.as_str()).collect::<Vec<_>>();
+        writer
+            .write(&arrs, &names)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn finish(&mut self) -> PyResult<()> {
+        if let Some(writer) = self.writer.take() {
+            writer
+                .finish()
+                .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        }
+        Ok(())
+    }
+}
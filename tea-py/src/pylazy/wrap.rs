@@ -83,6 +83,137 @@ impl<'source> FromPyObject<'source> for Wrap<QuantileMethod> {
     }
 }
 
+#[cfg(feature = "arw")]
+impl<'source> FromPyObject<'source> for Wrap<tea_io::IpcCompression> {
+    fn extract_bound(ob: &Bound<'source, PyAny>) -> PyResult<Self> {
+        use tea_io::IpcCompression;
+        let s: std::borrow::Cow<'_, str> = ob.extract()?;
+        let out = match s.to_lowercase().as_str() {
+            "lz4" => Wrap(IpcCompression::LZ4),
+            "zstd" => Wrap(IpcCompression::ZSTD),
+            _ => Err(PyValueError::new_err(format!(
+                "Not supported ipc compression: {s}"
+            )))?,
+        };
+        Ok(out)
+    }
+}
+
+#[cfg(feature = "arw")]
+fn extract_scan_datetime(ob: &Bound<'_, PyAny>) -> PyResult<DateTime<unit::Nanosecond>> {
+    if let Ok(dt) = ob.extract::<chrono::NaiveDateTime>() {
+        Ok(dt.into())
+    } else if ob.get_type().name()? == "datetime64" {
+        // numpy datetime64 scalar of any unit, `NaT` is `i64::MIN`
+        let ns: i64 = ob
+            .call_method1("astype", ("datetime64[ns]",))?
+            .call_method1("astype", ("int64",))?
+            .extract()?;
+        Ok(DateTime::new(ns))
+    } else {
+        let s: std::borrow::Cow<'_, str> = ob.extract()?;
+        DateTime::parse(&s, None).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+#[cfg(feature = "arw")]
+impl<'source> FromPyObject<'source> for Wrap<tea_io::ScanValue> {
+    fn extract_bound(ob: &Bound<'source, PyAny>) -> PyResult<Self> {
+        use pyo3::types::{PyBool, PyFloat, PyInt, PyString};
+        use tea_io::ScanValue;
+        let out = if ob.is_instance_of::<PyBool>() {
+            ScanValue::Bool(ob.extract()?)
+        } else if ob.is_instance_of::<PyInt>() {
+            ScanValue::I64(ob.extract()?)
+        } else if ob.is_instance_of::<PyFloat>() {
+            ScanValue::F64(ob.extract()?)
+        } else if ob.is_instance_of::<PyString>() {
+            ScanValue::String(ob.extract()?)
+        } else {
+            ScanValue::DateTime(extract_scan_datetime(ob)?)
+        };
+        Ok(Wrap(out))
+    }
+}
+
+/// A predicate is a tuple `(column, op, value)` where op is one of `==`, `!=`,
+/// `>`, `>=`, `<`, `<=`, or `(column, "between", (start, end))` for a datetime range.
+#[cfg(feature = "arw")]
+impl<'source> FromPyObject<'source> for Wrap<tea_io::ScanPredicate> {
+    fn extract_bound(ob: &Bound<'source, PyAny>) -> PyResult<Self> {
+        use tea_io::{CmpOp, ScanPredicate};
+        let (column, op, value): (String, String, Bound<'_, PyAny>) = ob.extract()?;
+        let op = match op.as_str() {
+            "==" => CmpOp::Eq,
+            "!=" => CmpOp::Ne,
+            ">" => CmpOp::Gt,
+            ">=" => CmpOp::Ge,
+            "<" => CmpOp::Lt,
+            "<=" => CmpOp::Le,
+            "between" => {
+                let (start, end): (Bound<'_, PyAny>, Bound<'_, PyAny>) = value.extract()?;
+                let bound = |b: Bound<'_, PyAny>| {
+                    (!b.is_none())
+                        .then(|| extract_scan_datetime(&b))
+                        .transpose()
+                };
+                return Ok(Wrap(ScanPredicate::DateTimeRange {
+                    column,
+                    start: bound(start)?,
+                    end: bound(end)?,
+                }));
+            }
+            _ => Err(PyValueError::new_err(format!(
+                "Not supported predicate operator: {op}"
+            )))?,
+        };
+        let value = value.extract::<Wrap<tea_io::ScanValue>>()?.0;
+        Ok(Wrap(ScanPredicate::Cmp { column, op, value }))
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl<'source> FromPyObject<'source> for Wrap<tea_io::ParquetCompression> {
+    fn extract_bound(ob: &Bound<'source, PyAny>) -> PyResult<Self> {
+        use tea_io::ParquetCompression;
+        let s: std::borrow::Cow<'_, str> = ob.extract()?;
+        let out = match s.to_lowercase().as_str() {
+            "uncompressed" => Wrap(ParquetCompression::Uncompressed),
+            "snappy" => Wrap(ParquetCompression::Snappy),
+            "gzip" => Wrap(ParquetCompression::Gzip(None)),
+            "lz4" => Wrap(ParquetCompression::Lz4Raw),
+            "zstd" => Wrap(ParquetCompression::Zstd(None)),
+            "brotli" => Wrap(ParquetCompression::Brotli(None)),
+            _ => Err(PyValueError::new_err(format!(
+                "Not supported parquet compression: {s}"
+            )))?,
+        };
+        Ok(out)
+    }
+}
+
+impl<'source> FromPyObject<'source> for Wrap<DataType> {
+    fn extract_bound(ob: &Bound<'source, PyAny>) -> PyResult<Self> {
+        let s: std::borrow::Cow<'_, str> = ob.extract()?;
+        let out = match s.to_lowercase().as_str() {
+            "float" | "f64" => DataType::F64,
+            "f32" => DataType::F32,
+            "int" | "i32" => DataType::I32,
+            "i64" => DataType::I64,
+            "opt<i32>" | "opt(i32)" => DataType::OptI32,
+            "opt<i64>" | "opt(i64)" => DataType::OptI64,
+            "bool" => DataType::Bool,
+            "opt<bool>" | "opt(bool)" => DataType::OptBool,
+            "str" => DataType::String,
+            "datetime" | "datetime(ns)" => DataType::DateTime(TimeUnit::Nanosecond),
+            "datetime(us)" => DataType::DateTime(TimeUnit::Microsecond),
+            "datetime(ms)" => DataType::DateTime(TimeUnit::Millisecond),
+            _ => Err(PyValueError::new_err(format!("Not supported dtype: {s}")))?,
+        };
+        Ok(Wrap(out))
+    }
+}
+
 // #[cfg(all(feature = "lazy", feature = "map", feature = "agg"))]
 // impl<'source> FromPyObject<'source> for DropNaMethod {
 //     fn extract(ob: &'source PyAny) -> PyResult<Self> {
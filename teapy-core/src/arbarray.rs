@@ -23,6 +23,8 @@ pub enum ViewBase<'a, T> {
     ArbArray(ArbArray<'a, T>),
     #[cfg(feature = "arw")]
     Arrow(Box<dyn arrow::array::Array>),
+    /// Any other owner of the memory, e.g. a memory-mapped file.
+    Owner(Box<dyn std::any::Any + Send + Sync>),
 }
 
 pub struct ViewOnBase<'a, T> {
@@ -75,6 +77,24 @@ impl<'a, T> ViewOnBase<'a, T> {
         };
         Box::pin(out)
     }
+
+    /// Create a view whose memory is kept alive by `owner`.
+    ///
+    /// # Safety
+    ///
+    /// The memory of `view` should be owned by `owner` and not moved when `owner` is moved.
+    #[inline]
+    pub unsafe fn new_from_owner<O: std::any::Any + Send + Sync>(
+        owner: O,
+        view: ArrViewD<'a, T>,
+    ) -> Pin<Box<Self>> {
+        let out = Self {
+            base: ViewBase::Owner(Box::new(owner)),
+            view: Some(view),
+            _pin: PhantomPinned,
+        };
+        Box::pin(out)
+    }
 }
 
 impl<'a, T: std::fmt::Debug> std::fmt::Debug for ArbArray<'a, T> {
@@ -119,6 +139,7 @@ macro_rules! match_arbarray {
         }
     };
 }
+#[allow(unused_imports)]
 pub(crate) use match_arbarray;
 
 impl<'a, T> ArbArray<'a, T> {
@@ -157,6 +178,68 @@ impl<'a, T> ArbArray<'a, T> {
         }
     }
 
+    /// Return the arrow array this array is read from, the buffers are shared
+    /// rather than copied.
+    #[cfg(feature = "arw")]
+    pub fn arrow_base(&self) -> Option<Box<dyn arrow::array::Array>>
+    where
+        T: arrow::types::NativeType,
+    {
+        self.arrow_base_as::<T>()
+    }
+
+    /// Return the arrow array this array is read from, where the arrow array
+    /// stores the elements as the physical type `P`, e.g. `i32` for dates.
+    #[cfg(feature = "arw")]
+    pub fn arrow_base_as<P: arrow::types::NativeType>(
+        &self,
+    ) -> Option<Box<dyn arrow::array::Array>> {
+        use arrow::array::PrimitiveArray;
+        if std::mem::size_of::<P>() != std::mem::size_of::<T>() {
+            return None;
+        }
+        if let ArbArray::ViewOnBase(vb) = self {
+            if let (ViewBase::Arrow(base), Some(view)) = (&vb.base, vb.view.as_ref()) {
+                let a = base.as_any().downcast_ref::<PrimitiveArray<P>>()?;
+                // the view should cover the whole arrow array
+                if view.ndim() == 1
+                    && a.values().as_ptr() as *const u8 == view.as_ptr() as *const u8
+                    && a.len() == view.len()
+                {
+                    return Some(base.clone());
+                }
+            }
+        }
+        None
+    }
+
+    /// Move a 1-d array into an arrow array without copying the data.
+    ///
+    /// This only succeeds if the array is read from an arrow array or is an
+    /// owned contiguous array, otherwise the array is returned unchanged.
+    #[cfg(feature = "arw")]
+    pub fn try_into_arrow(self) -> Result<Box<dyn arrow::array::Array>, Self>
+    where
+        T: arrow::types::NativeType,
+    {
+        use arrow::array::PrimitiveArray;
+        if let Some(arr) = self.arrow_base() {
+            return Ok(arr);
+        }
+        match self {
+            ArbArray::Owned(arr) if arr.0.ndim() == 1 && arr.0.is_standard_layout() => {
+                let len = arr.0.len();
+                let (mut vec, offset) = arr.0.into_raw_vec_and_offset();
+                // the array may be a slice of the vector
+                vec.drain(..offset.unwrap_or(0));
+                vec.truncate(len);
+                let out = PrimitiveArray::new(T::PRIMITIVE.into(), vec.into(), None);
+                Ok(Box::new(out))
+            }
+            arr => Err(arr),
+        }
+    }
+
     #[allow(unreachable_patterns)]
     #[inline]
     pub fn ndim(&self) -> usize {
@@ -1,11 +1,23 @@
 use super::arbarray::ArbArray;
+#[cfg(feature = "arw")]
+use super::bitmap::Bitmap;
+use super::categorical::CatArray;
+#[cfg(feature = "time")]
+use super::date::Date;
+use super::decimal::{DecArray, RoundMode};
+use super::list::ListArray;
+use super::masked::MaskedArray;
 use super::py_dtype::Object;
+use super::struct_array::StructArray;
+#[cfg(feature = "time")]
+use super::tz::{TimeZone, TzArray};
 #[cfg(feature = "arw")]
 use super::view::ArrViewD;
 #[cfg(any(feature = "concat", feature = "arw"))]
 use crate::{own::Arr1, utils::CollectTrustedToVec};
 use derive_more::From;
 use ndarray::{Axis, IxDyn, SliceArg};
+use std::borrow::Cow;
 use std::fmt::Debug;
 use tevec::prelude::*;
 
@@ -17,6 +29,10 @@ pub enum ArrOk<'a> {
     Bool(ArbArray<'a, bool>),
     U8(ArbArray<'a, u8>),
     U64(ArbArray<'a, u64>),
+    I8(ArbArray<'a, i8>),
+    I16(ArbArray<'a, i16>),
+    U16(ArbArray<'a, u16>),
+    U32(ArbArray<'a, u32>),
     Usize(ArbArray<'a, usize>),
     OptUsize(ArbArray<'a, Option<usize>>),
     F32(ArbArray<'a, f32>),
@@ -28,6 +44,8 @@ pub enum ArrOk<'a> {
     OptF64(ArbArray<'a, Option<f64>>),
     OptI32(ArbArray<'a, Option<i32>>),
     OptI64(ArbArray<'a, Option<i64>>),
+    OptU8(ArbArray<'a, Option<u8>>),
+    OptU64(ArbArray<'a, Option<u64>>),
     String(ArbArray<'a, String>),
     // Str(ArbArray<'a, &'a str>),
     Object(ArbArray<'a, Object>),
@@ -38,9 +56,18 @@ pub enum ArrOk<'a> {
     DateTimeUs(ArbArray<'a, DateTime<unit::Microsecond>>),
     #[cfg(feature = "time")]
     DateTimeNs(ArbArray<'a, DateTime<unit::Nanosecond>>),
+    #[cfg(feature = "time")]
+    Date(ArbArray<'a, Date>),
+    #[cfg(feature = "time")]
+    DateTimeTz(TzArray<'a>),
 
     #[cfg(feature = "time")]
     TimeDelta(ArbArray<'a, TimeDelta>),
+    Categorical(CatArray<'a>),
+    Decimal(DecArray<'a>),
+    Masked(MaskedArray<'a>),
+    List(ListArray<'a>),
+    Struct(StructArray<'a>),
 }
 
 #[macro_export]
@@ -53,7 +80,7 @@ macro_rules! match_arrok {
 impl<'a> Debug for ArrOk<'a> {
     #[allow(unreachable_patterns)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match_arrok!(self; Dynamic(a) => { Ok(a.fmt(f)) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact | Wrapped)(a) => { Ok(a.fmt(f)) },).unwrap()
     }
 }
 
@@ -61,82 +88,93 @@ impl<'a> ArrOk<'a> {
     #[allow(unreachable_patterns)]
     #[inline]
     pub fn raw_dim(&self) -> IxDyn {
-        match_arrok!(self; Dynamic(a) => { Ok(a.raw_dim()) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact | Wrapped)(a) => { Ok(a.raw_dim()) },).unwrap()
     }
 
     #[allow(unreachable_patterns)]
     #[inline]
     pub fn ndim(&self) -> usize {
-        match_arrok!(self; Dynamic(a) => { Ok(a.ndim()) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact | Wrapped)(a) => { Ok(a.ndim()) },).unwrap()
     }
 
     #[allow(unreachable_patterns, clippy::len_without_is_empty)]
     #[inline]
     pub fn len(&self) -> usize {
-        match_arrok!(self; Dynamic(a) => { Ok(a.len()) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact | Wrapped)(a) => { Ok(a.len()) },).unwrap()
     }
 
     #[allow(unreachable_patterns)]
     #[inline]
     pub fn len_of(&self, axis: Axis) -> usize {
-        match_arrok!(self; Dynamic(a) => { Ok(a.len_of(axis)) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact | Wrapped)(a) => { Ok(a.len_of(axis)) },).unwrap()
     }
 
     #[allow(unreachable_patterns)]
     #[inline]
     pub fn shape(&self) -> &[usize] {
-        match_arrok!(self; Dynamic(a) => { Ok(a.shape()) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact | Wrapped)(a) => { Ok(a.shape()) },).unwrap()
     }
 
     #[allow(unreachable_patterns)]
     #[inline]
     pub fn norm_axis(&self, axis: i32) -> Axis {
-        match_arrok!(self; Dynamic(a) => { Ok(a.norm_axis(axis)) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact | Wrapped)(a) => { Ok(a.norm_axis(axis)) },).unwrap()
     }
 
     #[allow(unreachable_patterns)]
     pub fn prepare(&mut self) {
-        match_arrok!(self; Dynamic(a) => {
+        match_arrok!(self; (Dynamic | Compact)(a) => {
             a.prepare();
             Ok(())
-        },)
+        },
+        Wrapped(_a) => { Ok(()) },)
         .unwrap()
     }
 
     #[allow(unreachable_patterns)]
     #[inline]
     pub fn get_type(&self) -> &'static str {
-        match_arrok!(self; Dynamic(a) => { Ok(a.get_type()) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact | Wrapped)(a) => { Ok(a.get_type()) },).unwrap()
     }
 
+    /// The dtype the kernels compute on, the compact integer dtypes are
+    /// reported as the dtypes they are widened to.
     #[allow(unreachable_patterns)]
     #[inline]
     pub fn dtype(&self) -> DataType {
-        match_arrok!(self; Dynamic(a) => { Ok(a.dtype()) },).unwrap()
+        match self {
+            ArrOk::I8(_) | ArrOk::I16(_) | ArrOk::U16(_) => DataType::I32,
+            ArrOk::U32(_) => DataType::I64,
+            ArrOk::OptU8(_) => DataType::OptI32,
+            ArrOk::OptU64(_) => DataType::OptUsize,
+            _ => match_arrok!(self; Dynamic(a) => { Ok(a.dtype()) }, Masked(a) => { Ok(a.dtype()) }, (Compact | Wrapped)(_a) => { Ok(DataType::Unknown) },).unwrap(),
+        }
     }
 
     #[allow(unreachable_patterns)]
     #[inline]
     pub fn deref(&self) -> ArrOk<'_> {
-        match_arrok!(self; Dynamic(a) => { Ok(a.deref().into()) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact | Wrapped)(a) => { Ok(a.deref().into()) },).unwrap()
     }
 
-    #[allow(unreachable_patterns)]
+    // masked arrays are sliced into their dense array
+    #[allow(unreachable_patterns, clippy::useless_conversion)]
     #[inline]
     pub fn slice<I: SliceArg<IxDyn>>(&self, info: I) -> ArrOk<'_> {
-        match_arrok!(self; Dynamic(a) => { Ok(a.slice(info).into()) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact | Wrapped)(a) => { Ok(a.slice(info).into()) },)
+            .unwrap()
     }
 
     #[allow(unreachable_patterns)]
     #[inline]
     pub fn is_owned(&self) -> bool {
-        match_arrok!(self; Dynamic(a) => { Ok(a.is_owned()) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact | Wrapped)(a) => { Ok(a.is_owned()) },).unwrap()
     }
 
     #[allow(unreachable_patterns)]
     #[inline]
     pub fn into_owned<'b>(self) -> ArrOk<'b> {
-        match_arrok!(self; Dynamic(a) => {
+        match_arrok!(self; (Dynamic | Compact | Wrapped)(a) => {
             let a: ArrOk<'a> = a.into_owned().into();
             // this is safe because we only need it for &str type
             // and the lifetime of str should be longer than both
@@ -149,6 +187,78 @@ impl<'a> ArrOk<'a> {
         .unwrap()
     }
 
+    /// The name of the dtype, unlike `dtype` it also names the dtypes
+    /// that `DataType` can't express, e.g. `Categorical`.
+    #[inline]
+    pub fn dtype_name(&self) -> String {
+        match self {
+            ArrOk::I8(_) => "I8".to_string(),
+            ArrOk::I16(_) => "I16".to_string(),
+            ArrOk::U16(_) => "U16".to_string(),
+            ArrOk::U32(_) => "U32".to_string(),
+            ArrOk::OptU8(_) => "OptU8".to_string(),
+            ArrOk::OptU64(_) => "OptU64".to_string(),
+            ArrOk::Categorical(_) => "Categorical".to_string(),
+            ArrOk::Decimal(a) => a.dtype_name(),
+            ArrOk::Masked(a) => a.dtype_name(),
+            ArrOk::List(a) => a.dtype_name(),
+            ArrOk::Struct(a) => a.dtype_name(),
+            #[cfg(feature = "time")]
+            ArrOk::Date(_) => "Date".to_string(),
+            #[cfg(feature = "time")]
+            ArrOk::DateTimeTz(a) => format!("DateTime(Nanosecond, {})", a.tz),
+            _ => format!("{:?}", self.dtype()),
+        }
+    }
+
+    /// Whether the dtype is a compact integer dtype that `DataType` can't
+    /// express, these arrays are widened by the kernels that need to compute on them.
+    #[inline]
+    pub fn is_compact(&self) -> bool {
+        use ArrOk::*;
+        matches!(
+            self,
+            I8(_) | I16(_) | U16(_) | U32(_) | OptU8(_) | OptU64(_)
+        )
+    }
+
+    /// Widen the compact integer dtypes to the nearest dtype supported by the
+    /// kernels, other dtypes are returned unchanged.
+    pub fn widen(self) -> Self {
+        match self {
+            ArrOk::I8(a) => a.view().map(|v| *v as i32).into(),
+            ArrOk::I16(a) => a.view().map(|v| *v as i32).into(),
+            ArrOk::U16(a) => a.view().map(|v| *v as i32).into(),
+            ArrOk::U32(a) => a.view().map(|v| *v as i64).into(),
+            ArrOk::OptU8(a) => a.view().map(|v| v.map(|v| v as i32)).into(),
+            ArrOk::OptU64(a) => a.view().map(|v| v.map(|v| v as usize)).into(),
+            arr => arr,
+        }
+    }
+
+    /// The array the kernels compute on, masked arrays are filled by
+    /// `IsNone::none` and other arrays are borrowed.
+    #[inline]
+    pub fn to_dense(&self) -> Cow<'_, ArrOk<'a>> {
+        match self {
+            ArrOk::Masked(a) => Cow::Owned(a.to_dense()),
+            arr => Cow::Borrowed(arr),
+        }
+    }
+
+    #[inline]
+    pub fn into_dense(self) -> ArrOk<'a> {
+        match self {
+            ArrOk::Masked(a) => a.into_dense(),
+            arr => arr,
+        }
+    }
+
+    #[inline]
+    pub fn is_categorical(&self) -> bool {
+        matches!(self, ArrOk::Categorical(_))
+    }
+
     #[inline]
     pub fn is_float(&self) -> bool {
         self.dtype().is_float()
@@ -156,7 +266,7 @@ impl<'a> ArrOk<'a> {
 
     #[inline]
     pub fn is_int(&self) -> bool {
-        self.dtype().is_int()
+        self.dtype().is_int() || self.is_compact()
     }
 
     #[inline]
@@ -178,6 +288,7 @@ impl<'a> ArrOk<'a> {
             ArrOk::Usize(a) => a.into(),
             ArrOk::U64(a) => a.into(),
             ArrOk::OptUsize(a) => a.into(),
+            arr if arr.is_compact() => arr.widen(),
             _ => self.cast_i64().into(),
         }
     }
@@ -186,14 +297,14 @@ impl<'a> ArrOk<'a> {
     #[inline]
     pub fn as_ptr<T: GetDataType>(&self) -> *const T {
         // we have known the datatype of the enum ,so only one arm will be executed
-        match_arrok!(self; Dynamic(a) => { Ok(a.as_ptr() as *const T) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact)(a) => { Ok(a.as_ptr() as *const T) },).unwrap()
     }
 
     #[allow(unreachable_patterns)]
     #[inline]
     pub fn as_mut_ptr<T: GetDataType>(&mut self) -> *mut T {
         // we have known the datatype of the enum ,so only one arm will be executed
-        match_arrok!(self; Dynamic(a) => { Ok(a.as_mut_ptr() as *mut T) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact)(a) => { Ok(a.as_mut_ptr() as *mut T) },).unwrap()
     }
 
     // /// reinterpret ArrOk to ArbArray<'a, T> directly.
@@ -244,11 +355,48 @@ impl<'a> ArrOk<'a> {
     #[allow(unreachable_patterns)]
     #[inline]
     pub fn view(&self) -> ArrOk<'_> {
-        match_arrok!(self; Dynamic(a) => { Ok(a.view().into()) },).unwrap()
+        match_arrok!(self; (Dynamic | Compact | Wrapped)(a) => { Ok(a.view().into()) },).unwrap()
     }
 
+    /// Dictionary-encode the array, other dtypes are cast to string first.
+    #[inline]
+    pub fn cast_categorical(self) -> CatArray<'a> {
+        match self {
+            ArrOk::Categorical(a) => a,
+            arr => CatArray::encode(arr.cast_string().view()),
+        }
+    }
+
+    /// Cast to a decimal array, integers and strings are converted exactly
+    /// and other dtypes are converted from f64.
+    pub fn cast_decimal(self, precision: u8, scale: u8) -> TResult<DecArray<'a>> {
+        match self {
+            ArrOk::Decimal(a) => {
+                let out = a.rescale(scale, RoundMode::HalfEven)?;
+                DecArray::new(out.values, precision, scale)?.check_precision()
+            }
+            ArrOk::Masked(a) => a.into_dense().cast_decimal(precision, scale),
+            ArrOk::String(a) => DecArray::from_strings(a.view(), precision, scale),
+            ArrOk::Categorical(a) => DecArray::from_strings(a.decode().view(), precision, scale),
+            arr if arr.is_int() => DecArray::from_i64(arr.cast_i64().view(), precision, scale),
+            arr => DecArray::from_f64(arr.cast_f64().view(), precision, scale),
+        }
+    }
+
+    /// Cast to a naive datetime array, timezone-aware datetimes are cast to
+    /// their local wall times.
     #[cfg(feature = "time")]
     pub fn cast_datetime(self, unit: Option<TimeUnit>) -> Self {
+        if let ArrOk::Masked(_) = self {
+            return self.into_dense().cast_datetime(unit);
+        }
+        match &self {
+            ArrOk::Date(a) => return a.view().to_datetime(unit),
+            ArrOk::DateTimeTz(a) => {
+                return ArrOk::from(a.to_local()).cast_datetime(unit).into_owned()
+            }
+            _ => {}
+        }
         if let Some(unit) = unit {
             match_arrok!(
                 &self;
@@ -285,6 +433,52 @@ impl<'a> ArrOk<'a> {
             }
         }
     }
+
+    /// Cast to `Date`, datetimes are truncated to their date, strings are
+    /// parsed and numbers are read as the days since the epoch.
+    #[cfg(feature = "time")]
+    pub fn cast_date(self) -> ArbArray<'a, Date> {
+        if let ArrOk::Masked(_) = self {
+            return self.into_dense().cast_date();
+        }
+        if self.is_compact() {
+            return self.widen().cast_date();
+        }
+        if let ArrOk::DateTimeTz(_) = self {
+            return self.cast_datetime(None).cast_date();
+        }
+        match_arrok!(self;
+            Date(a) => { Ok(a) },
+            Time(a) => { Ok(a.view().map(|v| Date::from_datetime(*v)).into()) },
+            Cast(a) => { Ok(a.cast::<Date>()) },
+            Categorical(a) => { Ok(ArbArray::from(a.decode()).cast::<Date>()) },
+        )
+        .unwrap()
+    }
+
+    /// Convert to the time zone keeping the instants, naive datetimes are
+    /// read as utc.
+    #[cfg(feature = "time")]
+    pub fn convert_tz(self, tz: TimeZone) -> TzArray<'a> {
+        match self {
+            ArrOk::DateTimeTz(a) => TzArray::new(a.utc, tz),
+            arr => match arr.cast_datetime(Some(TimeUnit::Nanosecond)) {
+                ArrOk::DateTimeNs(a) => TzArray::new(a, tz),
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    /// Set the time zone keeping the local wall times, naive datetimes are
+    /// read as local wall times. The time zone is removed if `tz` is `None`.
+    #[cfg(feature = "time")]
+    pub fn replace_tz(self, tz: Option<TimeZone>) -> Self {
+        let local = self.cast_datetime(Some(TimeUnit::Nanosecond));
+        match (local, tz) {
+            (ArrOk::DateTimeNs(a), Some(tz)) => TzArray::from_local(a.view(), tz).into(),
+            (local, _) => local,
+        }
+    }
 }
 
 #[cfg(feature = "concat")]
@@ -296,6 +490,10 @@ macro_rules! impl_same_dtype_concat_1d {
                 if arr_vec.is_empty() {
                     Default::default()
                 } else {
+                    // chunks without nulls are not masked when read from arrow
+                    if Iterator::any(&mut arr_vec.iter(), |a| matches!(a, ArrOk::Masked(_))) {
+                        return MaskedArray::concat_1d(arr_vec).into();
+                    }
                     let o1 = unsafe{arr_vec.get_unchecked(0)};
                     let ndim = o1.ndim();
                     use ArrOk::*;
@@ -316,6 +514,38 @@ macro_rules! impl_same_dtype_concat_1d {
                                 Arr1::from_vec(out).into_dyn().into()
                             }
                         }),*
+                        Categorical(_) => {
+                            let arr_vec = arr_vec.into_iter().map(|a| {
+                                if let Categorical(a) = a {a} else {unreachable!()}
+                            }).collect();
+                            CatArray::concat_1d(arr_vec).into()
+                        }
+                        #[cfg(feature = "time")]
+                        DateTimeTz(_) => {
+                            let arr_vec = arr_vec.into_iter().map(|a| {
+                                if let DateTimeTz(a) = a {a} else {unreachable!()}
+                            }).collect();
+                            TzArray::concat_1d(arr_vec).into()
+                        }
+                        Decimal(_) => {
+                            let arr_vec = arr_vec.into_iter().map(|a| {
+                                if let Decimal(a) = a {a} else {unreachable!()}
+                            }).collect();
+                            DecArray::concat_1d(arr_vec).unwrap().into()
+                        }
+                        List(_) => {
+                            let arr_vec = arr_vec.into_iter().map(|a| {
+                                if let List(a) = a {a} else {unreachable!()}
+                            }).collect();
+                            ListArray::concat_1d(arr_vec).into()
+                        }
+                        Struct(_) => {
+                            let arr_vec = arr_vec.into_iter().map(|a| {
+                                if let Struct(a) = a {a} else {unreachable!()}
+                            }).collect();
+                            StructArray::concat_1d(arr_vec).into()
+                        }
+                        Masked(_) => unreachable!(),
                         // _ => unimplemented!()
                     }
 
@@ -331,6 +561,10 @@ impl_same_dtype_concat_1d!(
     Bool,
     U8,
     U64,
+    I8,
+    I16,
+    U16,
+    U32,
     F32,
     F64,
     I32,
@@ -345,6 +579,8 @@ impl_same_dtype_concat_1d!(
     OptF32,
     OptF64,
     OptUsize,
+    OptU8,
+    OptU64,
     VecUsize,
     #[cfg(feature = "time")]
     DateTimeMs,
@@ -353,6 +589,8 @@ impl_same_dtype_concat_1d!(
     #[cfg(feature = "time")]
     DateTimeNs,
     #[cfg(feature = "time")]
+    Date,
+    #[cfg(feature = "time")]
     TimeDelta,
 );
 
@@ -366,6 +604,14 @@ impl_same_dtype_concat_1d!(
 //     }
 // }
 
+#[cfg(feature = "time")]
+impl<'a> Cast<ArbArray<'a, Date>> for ArrOk<'a> {
+    #[inline]
+    fn cast(self) -> ArbArray<'a, Date> {
+        self.cast_date()
+    }
+}
+
 impl<'a> Cast<ArbArray<'a, Vec<usize>>> for ArrOk<'a> {
     #[inline]
     fn cast(self) -> ArbArray<'a, Vec<usize>> {
@@ -376,9 +622,60 @@ impl<'a> Cast<ArbArray<'a, Vec<usize>>> for ArrOk<'a> {
     }
 }
 
+/// View the values of a primitive arrow array without copying, the array is
+/// masked if it has a validity bitmap.
+///
+/// # Safety
+///
+/// `U` must have the same layout as `T`.
+#[cfg(feature = "arw")]
+unsafe fn from_arrow_primitive<'a, T, U: 'a>(arr: Box<dyn arrow::array::Array>) -> ArrOk<'a>
+where
+    T: arrow::types::NativeType,
+    ArbArray<'a, U>: Into<ArrOk<'a>>,
+{
+    use crate::prelude::ViewOnBase;
+    let a = arr
+        .as_any()
+        .downcast_ref::<arrow::array::PrimitiveArray<T>>()
+        .unwrap();
+    // a validity bitmap marks a nullable column even if there is no null
+    let validity = a.validity().map(Bitmap::from_arrow);
+    let data: &[T] = a.values();
+    let view: ArrViewD<'a, U> =
+        std::mem::transmute(ArrView1::from_slice(data.len(), data).into_dyn());
+    let values: ArrOk<'a> = ArbArray::from(ViewOnBase::new_from_arrow(arr, view)).into();
+    match validity {
+        Some(validity) => MaskedArray::new(values, validity).unwrap().into(),
+        None => values,
+    }
+}
+
+/// Copy the strings of an arrow array, the array is masked if it has a
+/// validity bitmap.
+#[cfg(feature = "arw")]
+fn from_arrow_utf8<'a, O: arrow::offset::Offset>(arr: &dyn arrow::array::Array) -> ArrOk<'a> {
+    // todo: remove unneeded clone here
+    let a = arr
+        .as_any()
+        .downcast_ref::<arrow::array::Utf8Array<O>>()
+        .unwrap();
+    let data = a.values_iter().map(|s| s.to_string()).collect_trusted();
+    let values: ArrOk<'a> = Arr1::from_vec(data).into_dyn().into();
+    match a.validity() {
+        Some(validity) => MaskedArray::new(values, Bitmap::from_arrow(validity))
+            .unwrap()
+            .into(),
+        None => values,
+    }
+}
+
 #[cfg(feature = "arw")]
 macro_rules! impl_from_arrow {
-    ($([$arrow_dt: ident, $arrow_array: ident, $real: ty]),*) => {
+    (
+        float: $([$arrow_dt: ident, $arrow_array: ident, $real: ty]),*;
+        int: $([$arrow_int_dt: ident, $arrow_int_array: ident, $int_real: ty]),* $(;)?
+    ) => {
         impl<'a> ArrOk<'a> {
             // pub fn from_arrow_vec(arr_vec: Vec<Box<dyn arrow::array::Array>>) -> ArrOk<'a> {
             //     use arrow::datatypes::DataType as ArrowDT;
@@ -396,121 +693,120 @@ macro_rules! impl_from_arrow {
             //     }
             // }
 
+            /// Create an `ArrOk` from an arrow array.
+            ///
+            /// The buffers of primitive arrays are shared rather than copied, arrays
+            /// with nulls are read as masked arrays that keep the validity bitmap.
             pub fn from_arrow(arr: Box<dyn arrow::array::Array>) -> ArrOk<'a> {
                 use arrow::datatypes::DataType as ArrowDT;
-                use crate::prelude::ViewOnBase;
                 use arrow::array::PrimitiveArray;
                 match arr.data_type() {
-                    $(ArrowDT::$arrow_dt => {
-                        let a = arr.as_any().downcast_ref::<PrimitiveArray<$real>>().unwrap();
-                        let data: &[$real] = a.values();
-                        let nulls = a.validity();
-                        if nulls.is_some() {
-                            let data = a.into_iter().map(|v| v.cloned().unwrap_or_else(|| <$real>::none())).collect_trusted();
-                            Arr1::from_vec(data).into_dyn().into()
-                        } else {
-                            let view: ArrViewD<'a, $real> = unsafe {
-                                std::mem::transmute(ArrView1::from_slice(data.len(), data).into_dyn())
-                            };
-                            let out = ViewOnBase::new_from_arrow(arr, view);
-                            out.into()
-                        }
-
-                    }),*
-                    ArrowDT::Int32 => {
-                        let a = arr.as_any().downcast_ref::<PrimitiveArray<i32>>().unwrap();
-                        let data: &[i32] = a.values();
-                        let nulls = a.validity();
-                        if nulls.is_some() {
-                            let data = a.into_iter().map(|v| v.map(|v| v.f64()).unwrap_or(f64::NAN)).collect_trusted();
-                            Arr1::from_vec(data).into_dyn().into()
-                        } else {
-                            let view: ArrViewD<'a, i32> = unsafe {
-                                std::mem::transmute(ArrView1::from_slice(data.len(), data).into_dyn())
-                            };
-                            let out = ViewOnBase::new_from_arrow(arr, view);
-                            out.into()
-                        }
-
-                    },
-                    ArrowDT::Int64 => {
-                        let a = arr.as_any().downcast_ref::<PrimitiveArray<i64>>().unwrap();
-                        let data: &[i64] = a.values();
-                        let nulls = a.validity();
-                        if nulls.is_some() {
-                            let data = a.into_iter().map(|v| v.map(|v| v.f64()).unwrap_or(f64::NAN)).collect_trusted();
-                            Arr1::from_vec(data).into_dyn().into()
-                        } else {
-                            let view: ArrViewD<'a, i64> = unsafe {
-                                std::mem::transmute(ArrView1::from_slice(data.len(), data).into_dyn())
-                            };
-                            let out = ViewOnBase::new_from_arrow(arr, view);
-                            out.into()
-                        }
-
+                    $(ArrowDT::$arrow_dt => unsafe { from_arrow_primitive::<$real, $real>(arr) },)*
+                    $(ArrowDT::$arrow_int_dt => unsafe { from_arrow_primitive::<$int_real, $int_real>(arr) },)*
+                    // safety: usize has the layout of u64 on 64-bit targets
+                    ArrowDT::Extension(name, ..) if is_usize_extension(name) => unsafe {
+                        from_arrow_primitive::<u64, usize>(arr)
                     },
                     ArrowDT::Boolean => {
                         let a = arr.as_any().downcast_ref::<arrow::array::BooleanArray>().unwrap();
-                        let (data, start, len) = a.values().as_slice();
-                        if start != 0 {
-                            unreachable!("start index of the boolean is not 0")
-                        }
-                        let nulls = a.validity();
-                        if nulls.is_some() {
-                            unreachable!("can not read feather with null bool");
+                        if a.validity().is_some() {
+                            let data: Vec<Option<bool>> = a.into_iter().collect();
+                            Arr1::from_vec(data).into_dyn().into()
                         } else {
-                            let view: ArrViewD<'a, u8> = unsafe {
-                                std::mem::transmute(ArrView1::from_slice(len, data).into_dyn())
-                            };
-                            let out = ViewOnBase::new_from_arrow(arr, view);
-                            out.into()
+                            let data: Vec<bool> = a.values_iter().collect();
+                            Arr1::from_vec(data).into_dyn().into()
                         }
                     },
-                    ArrowDT::Utf8 => {
-                        // todo: remove unneeded clone here
-                        let a = arr.as_any().downcast_ref::<arrow::array::Utf8Array<i32>>().unwrap();
-                        let data = a.into_iter().map(|s| s.map(|s| s.to_string()).unwrap_or_default()).collect_trusted();
-                        Arr1::from_vec(data).into_dyn().into()
-                    },
-                    ArrowDT::LargeUtf8 => {
-                        let a = arr.as_any().downcast_ref::<arrow::array::Utf8Array<i64>>().unwrap();
-                        let data = a.into_iter().map(|s| s.map(|s| s.to_string()).unwrap_or_default()).collect_trusted();
+                    ArrowDT::Utf8 => from_arrow_utf8::<i32>(arr.as_ref()),
+                    ArrowDT::LargeUtf8 => from_arrow_utf8::<i64>(arr.as_ref()),
+                    ArrowDT::LargeList(field) if field.data_type() == &ArrowDT::UInt64 => {
+                        let a = arr.as_any().downcast_ref::<arrow::array::ListArray<i64>>().unwrap();
+                        let data = a.iter().map(|v| {
+                            v.map(|v| {
+                                let v = v.as_any().downcast_ref::<PrimitiveArray<u64>>().unwrap();
+                                v.values_iter().map(|i| *i as usize).collect::<Vec<_>>()
+                            }).unwrap_or_default()
+                        }).collect_trusted();
                         Arr1::from_vec(data).into_dyn().into()
                     }
                     #[cfg(feature="time")]
                     ArrowDT::Timestamp(arw_unit, arw_tz) => {
                         use arrow::datatypes::TimeUnit;
-                        assert!(arw_tz.is_none(), "Timezone is not supported yet");
-                        let a = arr.as_any().downcast_ref::<PrimitiveArray<i64>>().unwrap();
+                        // timezone-aware timestamps are utc instants, store them in nanoseconds
+                        if let Some(tz) = arw_tz {
+                            let a = arr.as_any().downcast_ref::<PrimitiveArray<i64>>().unwrap();
+                            // an unknown time zone is returned as an error by `try_from_arrow`
+                            let tz = crate::prelude::TimeZone::parse(tz).expect("Unknown time zone");
+                            let factor = match arw_unit {
+                                TimeUnit::Second => 1_000_000_000,
+                                TimeUnit::Millisecond => 1_000_000,
+                                TimeUnit::Microsecond => 1_000,
+                                TimeUnit::Nanosecond => 1,
+                            };
+                            let data = a.into_iter().map(|v| {
+                                DateTime::<unit::Nanosecond>::from_opt_i64(v.map(|v| v * factor))
+                            }).collect_trusted();
+                            return TzArray::new(Arr1::from_vec(data).into_dyn().into(), tz).into();
+                        }
+                        // safety: DateTime is a transparent wrapper of the timestamp in i64
                         match arw_unit {
+                            // there is no second unit, store them in milliseconds
                             TimeUnit::Second => {
-                                unimplemented!("Timeunit second is not supported yet")
-                                // let data = a.into_iter().map(|v| {
-                                //     DateTime::<unit::Second>::from_opt_i64(v.cloned())
-                                // }).collect_trusted();
-                                // Arr1::from_vec(data).into_dyn().into()
-                            },
-                            TimeUnit::Millisecond => {
+                                let a = arr.as_any().downcast_ref::<PrimitiveArray<i64>>().unwrap();
                                 let data = a.into_iter().map(|v| {
-                                    DateTime::<unit::Millisecond>::from_opt_i64(v.cloned())
+                                    DateTime::<unit::Millisecond>::from_opt_i64(v.map(|v| v * 1000))
                                 }).collect_trusted();
                                 Arr1::from_vec(data).into_dyn().into()
                             },
-                            TimeUnit::Microsecond => {
-                                let data = a.into_iter().map(|v| {
-                                    DateTime::<unit::Microsecond>::from_opt_i64(v.cloned())
-                                }).collect_trusted();
-                                Arr1::from_vec(data).into_dyn().into()
+                            TimeUnit::Millisecond => unsafe {
+                                from_arrow_primitive::<i64, DateTime<unit::Millisecond>>(arr)
                             },
-                            TimeUnit::Nanosecond => {
-                                let data = a.into_iter().map(|v| {
-                                    DateTime::<unit::Nanosecond>::from_opt_i64(v.cloned())
-                                }).collect_trusted();
-                                Arr1::from_vec(data).into_dyn().into()
+                            TimeUnit::Microsecond => unsafe {
+                                from_arrow_primitive::<i64, DateTime<unit::Microsecond>>(arr)
+                            },
+                            TimeUnit::Nanosecond => unsafe {
+                                from_arrow_primitive::<i64, DateTime<unit::Nanosecond>>(arr)
                             },
                         }
-                        // Arr1::from_vec(data).into_dyn().into()
                     }
+                    // safety: Date is a transparent wrapper of the days in i32
+                    #[cfg(feature="time")]
+                    ArrowDT::Date32 => unsafe { from_arrow_primitive::<i32, Date>(arr) },
+                    #[cfg(feature="time")]
+                    ArrowDT::Duration(arw_unit) => {
+                        use arrow::datatypes::TimeUnit;
+                        let a = arr.as_any().downcast_ref::<PrimitiveArray<i64>>().unwrap();
+                        let factor = match arw_unit {
+                            TimeUnit::Second => 1_000_000_000,
+                            TimeUnit::Millisecond => 1_000_000,
+                            TimeUnit::Microsecond => 1_000,
+                            TimeUnit::Nanosecond => 1,
+                        };
+                        let data = a.into_iter().map(|v| {
+                            TimeDelta::from(v.map(|v| v * factor))
+                        }).collect_trusted();
+                        Arr1::from_vec(data).into_dyn().into()
+                    }
+                    #[cfg(feature="time")]
+                    ArrowDT::Interval(arrow::datatypes::IntervalUnit::MonthDayNano) => {
+                        use arrow::types::months_days_ns;
+                        let a = arr.as_any().downcast_ref::<PrimitiveArray<months_days_ns>>().unwrap();
+                        let data = a.into_iter().map(|v| {
+                            if let Some(v) = v {
+                                let mut td = TimeDelta::from(v.days() as i64 * 86_400_000_000_000 + v.ns());
+                                td.months = v.months();
+                                td
+                            } else {
+                                TimeDelta::nat()
+                            }
+                        }).collect_trusted();
+                        Arr1::from_vec(data).into_dyn().into()
+                    }
+                    ArrowDT::Dictionary(..) => CatArray::from_arrow(arr.as_ref()).unwrap().into(),
+                    ArrowDT::List(_) => ListArray::from_arrow::<i32>(arr.as_ref()).unwrap().into(),
+                    ArrowDT::LargeList(_) => ListArray::from_arrow::<i64>(arr.as_ref()).unwrap().into(),
+                    ArrowDT::Struct(_) => StructArray::from_arrow(arr.as_ref()).unwrap().into(),
+                    ArrowDT::Decimal(..) => DecArray::from_arrow(arr.as_ref()).unwrap().into(),
                     _ => unimplemented!("Arrow datatype {:?} is not supported yet", arr.data_type())
                 }
             }
@@ -520,19 +816,381 @@ macro_rules! impl_from_arrow {
 
 #[cfg(feature = "arw")]
 impl_from_arrow!(
-    [Float32, Float32Array, f32],
-    [Float64, Float64Array, f64] // [Int32, Int32Array, i32], [Int64, Int64Array, i64]
+    float: [Float32, Float32Array, f32], [Float64, Float64Array, f64];
+    int:
+        [Int8, Int8Array, i8],
+        [Int16, Int16Array, i16],
+        [Int32, Int32Array, i32],
+        [Int64, Int64Array, i64],
+        [UInt8, UInt8Array, u8],
+        [UInt16, UInt16Array, u16],
+        [UInt32, UInt32Array, u32],
+        [UInt64, UInt64Array, u64];
 );
 
+/// The arrow extension name of `Usize` arrays, which are stored as uint64
+/// so that they are not read back as `U64`.
+#[cfg(feature = "arw")]
+const USIZE_EXTENSION: &str = "teapy.usize";
+
+#[cfg(feature = "arw")]
+#[inline]
+fn is_usize_extension(name: &str) -> bool {
+    cfg!(target_pointer_width = "64") && name == USIZE_EXTENSION
+}
+
+/// Whether the arrow datatype can be read into an `ArrOk`.
+#[cfg(feature = "arw")]
+fn arrow_supported(dt: &arrow::datatypes::DataType) -> bool {
+    use arrow::datatypes::DataType as ArrowDT;
+    match dt {
+        ArrowDT::Extension(name, inner, _) => {
+            is_usize_extension(name) && inner.as_ref() == &ArrowDT::UInt64
+        }
+        ArrowDT::Boolean
+        | ArrowDT::Int8
+        | ArrowDT::Int16
+        | ArrowDT::UInt8
+        | ArrowDT::UInt16
+        | ArrowDT::UInt32
+        | ArrowDT::UInt64
+        | ArrowDT::Int32
+        | ArrowDT::Int64
+        | ArrowDT::Float32
+        | ArrowDT::Float64
+        | ArrowDT::Utf8
+        | ArrowDT::LargeUtf8 => true,
+        ArrowDT::Decimal(precision, scale) => {
+            (1..=crate::decimal::MAX_PRECISION as usize).contains(precision) && scale <= precision
+        }
+        // nested lists are not supported
+        ArrowDT::List(field) | ArrowDT::LargeList(field) => {
+            !matches!(field.data_type(), ArrowDT::List(_) | ArrowDT::LargeList(_))
+                && arrow_supported(field.data_type())
+        }
+        ArrowDT::Struct(fields) => {
+            !fields.is_empty()
+                && Iterator::all(&mut fields.iter(), |f| arrow_supported(f.data_type()))
+        }
+        ArrowDT::Dictionary(_, values, _) => {
+            matches!(values.as_ref(), ArrowDT::Utf8 | ArrowDT::LargeUtf8)
+        }
+        #[cfg(feature = "time")]
+        ArrowDT::Timestamp(_, tz) => tz
+            .as_ref()
+            .is_none_or(|tz| crate::prelude::TimeZone::parse(tz).is_ok()),
+        #[cfg(feature = "time")]
+        ArrowDT::Date32
+        | ArrowDT::Duration(_)
+        | ArrowDT::Interval(arrow::datatypes::IntervalUnit::MonthDayNano) => true,
+        _ => false,
+    }
+}
+
+#[cfg(feature = "arw")]
+impl<'a> ArrOk<'a> {
+    /// Convert a 1-d (or 0-d) `ArrOk` into an arrow array.
+    ///
+    /// `None` and `NaT` are written as nulls in the validity bitmap, while
+    /// `NaN` in float arrays is kept as a valid value.
+    pub fn to_arrow(&self) -> TResult<Box<dyn arrow::array::Array>> {
+        use arrow::array::{Array, BooleanArray, ListArray, PrimitiveArray, Utf8Array};
+        use arrow::bitmap::Bitmap;
+        use arrow::datatypes::DataType as ArrowDT;
+        use arrow::types::NativeType;
+
+        #[inline]
+        fn validity<I: Iterator<Item = bool>>(iter: I) -> Option<Bitmap> {
+            let bitmap: Bitmap = iter.collect();
+            (bitmap.unset_bits() > 0).then_some(bitmap)
+        }
+
+        #[inline]
+        fn primitive<T: NativeType>(
+            values: Vec<T>,
+            validity: Option<Bitmap>,
+            dtype: Option<ArrowDT>,
+        ) -> Box<dyn Array> {
+            let dtype = dtype.unwrap_or_else(|| T::PRIMITIVE.into());
+            Box::new(PrimitiveArray::new(dtype, values.into(), validity))
+        }
+
+        if self.get_type() == "ArrowChunk Array" {
+            tbail!("ArrowChunk array should be rechunked before converting to arrow")
+        }
+        if let Some(arr) = self.arrow_base() {
+            return Ok(arr);
+        }
+        if self.ndim() > 1 {
+            tbail!(
+                "Only 1-d array can be converted to arrow, got ndim {}",
+                self.ndim()
+            )
+        }
+        let out = match self {
+            ArrOk::Bool(a) => {
+                let values: Bitmap = a.view().0.iter().copied().collect();
+                Box::new(BooleanArray::new(ArrowDT::Boolean, values, None)) as Box<dyn Array>
+            }
+            ArrOk::U8(a) => primitive(a.view().0.iter().copied().collect(), None, None),
+            ArrOk::U64(a) => primitive(a.view().0.iter().copied().collect(), None, None),
+            ArrOk::I8(a) => primitive(a.view().0.iter().copied().collect(), None, None),
+            ArrOk::I16(a) => primitive(a.view().0.iter().copied().collect(), None, None),
+            ArrOk::U16(a) => primitive(a.view().0.iter().copied().collect(), None, None),
+            ArrOk::U32(a) => primitive(a.view().0.iter().copied().collect(), None, None),
+            ArrOk::I32(a) => primitive(a.view().0.iter().copied().collect(), None, None),
+            ArrOk::I64(a) => primitive(a.view().0.iter().copied().collect(), None, None),
+            ArrOk::F32(a) => primitive(a.view().0.iter().copied().collect(), None, None),
+            ArrOk::F64(a) => primitive(a.view().0.iter().copied().collect(), None, None),
+            ArrOk::Usize(a) => primitive(
+                a.view().0.iter().map(|v| *v as u64).collect(),
+                None,
+                Some(ArrowDT::Extension(
+                    USIZE_EXTENSION.to_string(),
+                    Box::new(ArrowDT::UInt64),
+                    None,
+                )),
+            ),
+            ArrOk::OptBool(_)
+            | ArrOk::OptI32(_)
+            | ArrOk::OptI64(_)
+            | ArrOk::OptU8(_)
+            | ArrOk::OptU64(_)
+            | ArrOk::OptUsize(_)
+            | ArrOk::OptF32(_)
+            | ArrOk::OptF64(_) => MaskedArray::from_opt(self)?.to_arrow()?,
+            ArrOk::String(a) => Box::new(Utf8Array::<i64>::from_iter_values(a.view().0.iter())),
+            ArrOk::VecUsize(a) => {
+                let view = a.view();
+                let mut offsets = Vec::with_capacity(view.len() + 1);
+                offsets.push(0_i64);
+                let mut values = Vec::new();
+                for v in view.0.iter() {
+                    values.extend(v.iter().map(|i| *i as u64));
+                    offsets.push(values.len() as i64);
+                }
+                let values = primitive(values, None, None);
+                Box::new(ListArray::<i64>::new(
+                    ListArray::<i64>::default_datatype(ArrowDT::UInt64),
+                    offsets.try_into().map_err(|e| terr!("{:?}", e))?,
+                    values,
+                    None,
+                ))
+            }
+            ArrOk::Object(_) => tbail!("Object array can not be converted to arrow"),
+            ArrOk::Categorical(a) => a.to_arrow()?,
+            ArrOk::Masked(a) => a.to_arrow()?,
+            ArrOk::List(a) => a.to_arrow()?,
+            ArrOk::Struct(a) => a.to_arrow()?,
+            ArrOk::Decimal(a) => a.to_arrow()?,
+            #[cfg(feature = "time")]
+            ArrOk::DateTimeTz(a) => a.to_arrow()?,
+            #[cfg(feature = "time")]
+            ArrOk::DateTimeMs(a) => {
+                use arrow::datatypes::TimeUnit;
+                let view = a.view();
+                let validity = validity(view.0.iter().map(|v| v.is_not_nat()));
+                primitive(
+                    view.0.iter().map(|v| v.into_i64()).collect(),
+                    validity,
+                    Some(ArrowDT::Timestamp(TimeUnit::Millisecond, None)),
+                )
+            }
+            #[cfg(feature = "time")]
+            ArrOk::DateTimeUs(a) => {
+                use arrow::datatypes::TimeUnit;
+                let view = a.view();
+                let validity = validity(view.0.iter().map(|v| v.is_not_nat()));
+                primitive(
+                    view.0.iter().map(|v| v.into_i64()).collect(),
+                    validity,
+                    Some(ArrowDT::Timestamp(TimeUnit::Microsecond, None)),
+                )
+            }
+            #[cfg(feature = "time")]
+            ArrOk::DateTimeNs(a) => {
+                use arrow::datatypes::TimeUnit;
+                let view = a.view();
+                let validity = validity(view.0.iter().map(|v| v.is_not_nat()));
+                primitive(
+                    view.0.iter().map(|v| v.into_i64()).collect(),
+                    validity,
+                    Some(ArrowDT::Timestamp(TimeUnit::Nanosecond, None)),
+                )
+            }
+            #[cfg(feature = "time")]
+            ArrOk::Date(a) => {
+                let view = a.view();
+                let validity = validity(view.0.iter().map(|v| v.is_not_nat()));
+                primitive(
+                    view.0.iter().map(|v| v.0).collect(),
+                    validity,
+                    Some(ArrowDT::Date32),
+                )
+            }
+            #[cfg(feature = "time")]
+            ArrOk::TimeDelta(a) => {
+                use arrow::datatypes::{IntervalUnit, TimeUnit};
+                use arrow::types::months_days_ns;
+                let view = a.view();
+                let validity = validity(view.0.iter().map(|v| v.is_not_nat()));
+                let nanos = |v: &TimeDelta| -> TResult<i64> {
+                    if v.is_nat() {
+                        Ok(0)
+                    } else {
+                        v.inner
+                            .num_nanoseconds()
+                            .ok_or_else(|| terr!("TimeDelta {:?} overflows nanoseconds", v))
+                    }
+                };
+                // durations with calendar months can only be stored as an interval
+                if Iterator::all(&mut view.0.iter(), |v| v.is_nat() || v.months == 0) {
+                    let values = view.0.iter().map(nanos).collect::<TResult<Vec<_>>>()?;
+                    primitive(
+                        values,
+                        validity,
+                        Some(ArrowDT::Duration(TimeUnit::Nanosecond)),
+                    )
+                } else {
+                    let values = view
+                        .0
+                        .iter()
+                        .map(|v| {
+                            let months = if v.is_nat() { 0 } else { v.months };
+                            Ok(months_days_ns::new(months, 0, nanos(v)?))
+                        })
+                        .collect::<TResult<Vec<_>>>()?;
+                    primitive(
+                        values,
+                        validity,
+                        Some(ArrowDT::Interval(IntervalUnit::MonthDayNano)),
+                    )
+                }
+            }
+        };
+        Ok(out)
+    }
+
+    /// Create an `ArrOk` from an arrow array, return an error rather than
+    /// panic if the arrow datatype is not supported.
+    pub fn try_from_arrow(arr: Box<dyn arrow::array::Array>) -> TResult<ArrOk<'a>> {
+        #[cfg(feature = "time")]
+        if let arrow::datatypes::DataType::Timestamp(_, Some(tz)) = arr.data_type() {
+            crate::prelude::TimeZone::parse(tz)?;
+        }
+        if !arrow_supported(arr.data_type()) {
+            tbail!("Arrow datatype {:?} is not supported yet", arr.data_type())
+        }
+        Ok(ArrOk::from_arrow(arr))
+    }
+
+    /// Return the arrow array a primitive array is read from without copying.
+    pub fn arrow_base(&self) -> Option<Box<dyn arrow::array::Array>> {
+        match self {
+            ArrOk::U8(a) => a.arrow_base(),
+            ArrOk::U64(a) => a.arrow_base(),
+            ArrOk::I8(a) => a.arrow_base(),
+            ArrOk::I16(a) => a.arrow_base(),
+            ArrOk::U16(a) => a.arrow_base(),
+            ArrOk::U32(a) => a.arrow_base(),
+            ArrOk::I32(a) => a.arrow_base(),
+            ArrOk::I64(a) => a.arrow_base(),
+            ArrOk::F32(a) => a.arrow_base(),
+            ArrOk::F64(a) => a.arrow_base(),
+            #[cfg(feature = "time")]
+            ArrOk::Date(a) => a.arrow_base_as::<i32>(),
+            #[cfg(feature = "time")]
+            ArrOk::DateTimeMs(a) => a.arrow_base_as::<i64>(),
+            #[cfg(feature = "time")]
+            ArrOk::DateTimeUs(a) => a.arrow_base_as::<i64>(),
+            #[cfg(feature = "time")]
+            ArrOk::DateTimeNs(a) => a.arrow_base_as::<i64>(),
+            _ => None,
+        }
+    }
+
+    /// Convert a 1-d `ArrOk` into an arrow array, primitive arrays that are
+    /// read from arrow or owned are moved without copying the data, as well
+    /// as the values of masked arrays.
+    pub fn into_arrow(self) -> TResult<Box<dyn arrow::array::Array>> {
+        macro_rules! try_move {
+            ($($arm: ident),*) => {
+                match self {
+                    $(ArrOk::$arm(a) => a.try_into_arrow().or_else(|a| ArrOk::from(a).to_arrow()),)*
+                    ArrOk::Masked(a) => a.into_arrow(),
+                    arr => arr.to_arrow(),
+                }
+            };
+        }
+        try_move!(U8, U64, I8, I16, U16, U32, I32, I64, F32, F64)
+    }
+
+    /// Convert a 1-d `ArrOk` into an arrow array that shares the buffers with it.
+    ///
+    /// Primitive arrays and masked arrays are moved into the arrow array and
+    /// replaced by a view of it, so that later conversions don't copy either.
+    /// Other arrays are converted by [`ArrOk::to_arrow`] and left unchanged.
+    pub fn share_arrow(&mut self) -> TResult<Box<dyn arrow::array::Array>> {
+        let values = match &*self {
+            ArrOk::Masked(a) => a.values.as_ref(),
+            arr => arr,
+        };
+        if values.arrow_base().is_some() {
+            return self.to_arrow();
+        }
+        let shareable = matches!(
+            values,
+            ArrOk::U8(_)
+                | ArrOk::U64(_)
+                | ArrOk::I8(_)
+                | ArrOk::I16(_)
+                | ArrOk::U16(_)
+                | ArrOk::U32(_)
+                | ArrOk::I32(_)
+                | ArrOk::I64(_)
+                | ArrOk::F32(_)
+                | ArrOk::F64(_)
+        );
+        if !shareable || self.ndim() != 1 {
+            return self.to_arrow();
+        }
+        let arr = std::mem::replace(self, ArrOk::U8(Default::default())).into_arrow()?;
+        *self = ArrOk::from_arrow(arr.clone());
+        Ok(arr)
+    }
+}
+
 macro_rules! impl_arrok_cast {
-    ($($(#[$meta: meta])? $T: ty: $cast_func: ident),* $(,)? ) => {
+    // decimals are cast through f64 unless the dtype gives an exact conversion
+    (@decimal $a: ident) => { $a.to_f64() };
+    (@decimal $a: ident $dec_func: ident) => { $a.$dec_func() };
+    // categoricals are cast through strings unless the dtype can hold nulls
+    (@categorical $a: ident $T: ty) => { ArbArray::from($a.decode()).cast::<$T>() };
+    (@categorical $a: ident $T: ty, $cat_func: ident) => {
+        ArbArray::from($a.$cat_func().view().cast::<$T>())
+    };
+    ($($(#[$meta: meta])? $T: ty: $cast_func: ident $(=> $dec_func: ident)? $(| $cat_func: ident)?),* $(,)? ) => {
         $(
             $(#[$meta])?
             impl<'a> Cast<ArbArray<'a, $T>> for ArrOk<'a>
             {
                 #[inline]
                 fn cast(self) -> ArbArray<'a, $T> {
+                    if let ArrOk::Masked(_) = self {
+                        return self.into_dense().cast();
+                    }
+                    if self.is_compact() {
+                        return self.widen().cast();
+                    }
                     match_arrok!(self; Cast(a) => { Ok(a.cast::<$T>()) },
+                        Categorical(a) => {
+                            Ok(impl_arrok_cast!(@categorical a $T $(, $cat_func)?))
+                        },
+                        Decimal(a) => {
+                            Ok(ArbArray::from(impl_arrok_cast!(@decimal a $($dec_func)?)).cast::<$T>())
+                        },
+                        #[cfg(feature="time")] Date(a) => { Ok(a.cast::<$T>()) },
+                        #[cfg(feature="time")] DateTimeTz(a) => { Ok(ArrOk::DateTimeNs(a.utc).cast()) },
                         // U8, F32, F64, I32, I64, U64, Usize, OptUsize, Bool, String, Str, Object,
                         // #[cfg(feature="time")] DateTime,
                         // #[cfg(feature="time")] TimeDelta
@@ -569,7 +1227,7 @@ impl_arrok_cast!(
     u64: cast_u64,
     usize: cast_usize,
     bool: cast_bool,
-    String: cast_string,
+    String: cast_string => to_strings,
     Option<bool>: cast_opt_bool,
     Option<f32>: cast_opt_f32,
     Option<f64>: cast_opt_f64,
@@ -582,7 +1240,41 @@ impl_arrok_cast!(
     #[cfg(feature="time")]
     DateTime<unit::Nanosecond>: cast_datetime_ns,
     Option<usize>: cast_optusize,
-    Object: cast_object,
+    Object: cast_object | decode_opt,
     #[cfg(feature="time")]
     TimeDelta: cast_timedelta
 );
+
+/// The compact dtypes can't be cast by tea-dtype, so they are cast from the
+/// nearest wider dtype.
+macro_rules! impl_compact_cast {
+    ($($arm: ident: $T: ty, $cast_func: ident => $wide_func: ident |$v: ident| $narrow: expr),* $(,)?) => {
+        $(
+            impl<'a> Cast<ArbArray<'a, $T>> for ArrOk<'a> {
+                #[inline]
+                fn cast(self) -> ArbArray<'a, $T> {
+                    match self {
+                        ArrOk::$arm(a) => a,
+                        arr => arr.$wide_func().view().map(|$v| $narrow).into(),
+                    }
+                }
+            }
+
+            impl<'a> ArrOk<'a> {
+                #[inline]
+                pub fn $cast_func(self) -> ArbArray<'a, $T> {
+                    self.cast()
+                }
+            }
+        )*
+    };
+}
+
+impl_compact_cast!(
+    I8: i8, cast_i8 => cast_i32 |v| *v as i8,
+    I16: i16, cast_i16 => cast_i32 |v| *v as i16,
+    U16: u16, cast_u16 => cast_i32 |v| *v as u16,
+    U32: u32, cast_u32 => cast_i64 |v| *v as u32,
+    OptU8: Option<u8>, cast_opt_u8 => cast_opt_i32 |v| v.map(|v| v as u8),
+    OptU64: Option<u64>, cast_opt_u64 => cast_optusize |v| v.map(|v| v as u64),
+);
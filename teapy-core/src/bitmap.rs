@@ -0,0 +1,170 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+#[derive(Clone)]
+enum BitmapBytes {
+    Owned(Arc<[u8]>),
+    /// The buffer of an arrow bitmap, shared rather than copied.
+    #[cfg(feature = "arw")]
+    Arrow(arrow::bitmap::Bitmap),
+}
+
+/// A packed validity bitmap, bit `i` is set if the element `i` is valid.
+///
+/// The bits are stored in the same layout as arrow (least significant bit
+/// first), so a bitmap read from arrow is shared rather than copied.
+#[derive(Clone)]
+pub struct Bitmap {
+    bytes: BitmapBytes,
+    offset: usize,
+    len: usize,
+    unset_bits: usize,
+}
+
+impl Debug for Bitmap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<bool> for Bitmap {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bytes = Vec::new();
+        let (mut len, mut unset_bits) = (0, 0);
+        for valid in iter {
+            if len % 8 == 0 {
+                bytes.push(0_u8);
+            }
+            if valid {
+                *bytes.last_mut().unwrap() |= 1 << (len % 8);
+            } else {
+                unset_bits += 1;
+            }
+            len += 1;
+        }
+        Bitmap {
+            bytes: BitmapBytes::Owned(bytes.into()),
+            offset: 0,
+            len,
+            unset_bits,
+        }
+    }
+}
+
+impl Bitmap {
+    /// A bitmap of `len` valid elements.
+    #[inline]
+    pub fn new_valid(len: usize) -> Self {
+        Bitmap {
+            bytes: BitmapBytes::Owned(vec![u8::MAX; len.div_ceil(8)].into()),
+            offset: 0,
+            len,
+            unset_bits: 0,
+        }
+    }
+
+    #[allow(clippy::len_without_is_empty)]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The number of invalid elements.
+    #[inline]
+    pub fn unset_bits(&self) -> usize {
+        self.unset_bits
+    }
+
+    /// The underlying bytes and the bit offset of the first element.
+    #[inline]
+    fn raw(&self) -> (&[u8], usize) {
+        match &self.bytes {
+            BitmapBytes::Owned(bytes) => (bytes, self.offset),
+            #[cfg(feature = "arw")]
+            BitmapBytes::Arrow(bitmap) => {
+                let (bytes, offset, _) = bitmap.as_slice();
+                (bytes, offset + self.offset)
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, i: usize) -> bool {
+        assert!(
+            i < self.len,
+            "index {i} out of bounds of bitmap of length {}",
+            self.len
+        );
+        unsafe { self.get_unchecked(i) }
+    }
+
+    /// # Safety
+    ///
+    /// `i` should be less than the length of the bitmap.
+    #[inline]
+    pub unsafe fn get_unchecked(&self, i: usize) -> bool {
+        let (bytes, offset) = self.raw();
+        let i = offset + i;
+        bytes.get_unchecked(i / 8) & (1 << (i % 8)) != 0
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = bool> + ExactSizeIterator + '_ {
+        (0..self.len).map(|i| unsafe { self.get_unchecked(i) })
+    }
+
+    /// Slice the bitmap without copying the bytes.
+    pub fn sliced(&self, offset: usize, len: usize) -> Self {
+        assert!(
+            offset + len <= self.len,
+            "the slice {}..{} is out of bounds of bitmap of length {}",
+            offset,
+            offset + len,
+            self.len
+        );
+        let mut out = Bitmap {
+            bytes: self.bytes.clone(),
+            offset: self.offset + offset,
+            len,
+            unset_bits: 0,
+        };
+        out.unset_bits = if out.len == self.len {
+            self.unset_bits
+        } else {
+            out.iter().filter(|v| !v).count()
+        };
+        out
+    }
+
+    /// Concatenate the bitmaps into a new one.
+    #[inline]
+    pub fn concat(bitmaps: &[&Bitmap]) -> Self {
+        bitmaps.iter().flat_map(|b| b.iter()).collect()
+    }
+}
+
+#[cfg(feature = "arw")]
+impl Bitmap {
+    /// Share the buffer of an arrow bitmap.
+    #[inline]
+    pub fn from_arrow(bitmap: &arrow::bitmap::Bitmap) -> Self {
+        Bitmap {
+            bytes: BitmapBytes::Arrow(bitmap.clone()),
+            offset: 0,
+            len: bitmap.len(),
+            unset_bits: bitmap.unset_bits(),
+        }
+    }
+
+    /// Convert into an arrow bitmap, the buffer is shared if the bitmap is
+    /// read from arrow.
+    pub fn to_arrow(&self) -> arrow::bitmap::Bitmap {
+        match &self.bytes {
+            BitmapBytes::Arrow(bitmap) => bitmap.clone().sliced(self.offset, self.len),
+            BitmapBytes::Owned(bytes) => {
+                arrow::bitmap::Bitmap::from_u8_slice(bytes, self.offset + self.len)
+                    .sliced(self.offset, self.len)
+            }
+        }
+    }
+}
@@ -0,0 +1,316 @@
+use super::arbarray::ArbArray;
+use super::own::ArrD;
+use super::traits::WrapNdarray;
+use super::view::ArrViewD;
+use ndarray::{Axis, IxDyn, SliceArg};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+#[cfg(feature = "arw")]
+use tevec::prelude::*;
+
+/// The code of null elements in a [`CatArray`], it doesn't point to any category.
+pub const NULL_CODE: u32 = u32::MAX;
+
+/// A dictionary-encoded string array: `u32` codes into a shared list of categories.
+///
+/// Arrays sliced or viewed from the same `CatArray` share the categories, so
+/// the codes can be compared directly. Null elements have the code [`NULL_CODE`].
+#[derive(Clone)]
+pub struct CatArray<'a> {
+    pub codes: ArbArray<'a, u32>,
+    pub categories: Arc<Vec<String>>,
+}
+
+impl<'a> Debug for CatArray<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Categorical{:?}", self.decode().0)
+    }
+}
+
+impl<'a> CatArray<'a> {
+    #[inline]
+    pub fn new(codes: ArbArray<'a, u32>, categories: Arc<Vec<String>>) -> Self {
+        Self { codes, categories }
+    }
+
+    /// Encode a string array, categories are sorted by their first appearance.
+    pub fn encode<'b>(arr: ArrViewD<'_, String>) -> CatArray<'b> {
+        let mut map: HashMap<&str, u32> = HashMap::new();
+        let mut categories = Vec::new();
+        let codes = arr
+            .0
+            .iter()
+            .map(|s| {
+                *map.entry(s.as_str()).or_insert_with(|| {
+                    categories.push(s.clone());
+                    (categories.len() - 1) as u32
+                })
+            })
+            .collect::<Vec<_>>();
+        let codes = ndarray::ArrayD::from_shape_vec(arr.raw_dim(), codes)
+            .unwrap()
+            .wrap();
+        CatArray::new(codes.into(), Arc::new(categories))
+    }
+
+    /// The category of a code, `None` for [`NULL_CODE`].
+    #[inline]
+    pub fn category(&self, code: u32) -> Option<&str> {
+        (code != NULL_CODE).then(|| self.categories[code as usize].as_str())
+    }
+
+    /// Decode the categorical array into a string array, null elements are
+    /// decoded as empty strings.
+    pub fn decode(&self) -> ArrD<String> {
+        self.codes
+            .view()
+            .0
+            .map(|c| self.category(*c).unwrap_or_default().to_owned())
+            .wrap()
+    }
+
+    /// Decode the categorical array into an array of optional strings.
+    pub fn decode_opt(&self) -> ArrD<Option<String>> {
+        self.codes
+            .view()
+            .0
+            .map(|c| self.category(*c).map(str::to_owned))
+            .wrap()
+    }
+
+    /// Encode `other` with the categories of `self`, categories that only
+    /// exist in `other` are appended, so the codes of `self` are still valid
+    /// with the returned categories.
+    pub fn recode<'b>(&self, other: &CatArray<'_>) -> CatArray<'b> {
+        if Arc::ptr_eq(&self.categories, &other.categories) {
+            return CatArray::new(
+                other.codes.view().to_owned().into(),
+                self.categories.clone(),
+            );
+        }
+        let mut categories = self.categories.as_ref().clone();
+        let mut map: HashMap<&str, u32> = self
+            .categories
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.as_str(), i as u32))
+            .collect();
+        let remap = other
+            .categories
+            .iter()
+            .map(|s| {
+                *map.entry(s.as_str()).or_insert_with(|| {
+                    categories.push(s.clone());
+                    (categories.len() - 1) as u32
+                })
+            })
+            .collect::<Vec<_>>();
+        let codes = other
+            .codes
+            .view()
+            .0
+            .map(|c| {
+                if *c == NULL_CODE {
+                    NULL_CODE
+                } else {
+                    remap[*c as usize]
+                }
+            })
+            .wrap();
+        CatArray::new(codes.into(), Arc::new(categories))
+    }
+
+    /// The codes as an `usize` array, used as the keys when grouping and joining,
+    /// so null elements are grouped together.
+    #[inline]
+    pub fn codes_usize(&self) -> ArrD<usize> {
+        self.codes.view().0.map(|c| *c as usize).wrap()
+    }
+
+    #[inline]
+    pub fn raw_dim(&self) -> IxDyn {
+        self.codes.raw_dim()
+    }
+
+    #[inline]
+    pub fn ndim(&self) -> usize {
+        self.codes.ndim()
+    }
+
+    #[inline]
+    pub fn shape(&self) -> &[usize] {
+        self.codes.shape()
+    }
+
+    #[allow(clippy::len_without_is_empty)]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    #[inline]
+    pub fn len_of(&self, axis: Axis) -> usize {
+        self.codes.len_of(axis)
+    }
+
+    #[inline]
+    pub fn norm_axis(&self, axis: i32) -> Axis {
+        self.codes.norm_axis(axis)
+    }
+
+    #[inline]
+    pub fn get_type(&self) -> &'static str {
+        self.codes.get_type()
+    }
+
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        self.codes.is_owned()
+    }
+
+    #[inline]
+    pub fn deref(&self) -> CatArray<'_> {
+        CatArray::new(self.codes.deref(), self.categories.clone())
+    }
+
+    #[inline]
+    pub fn view(&self) -> CatArray<'_> {
+        CatArray::new(self.codes.view().into(), self.categories.clone())
+    }
+
+    #[inline]
+    pub fn slice<I: SliceArg<IxDyn>>(&self, info: I) -> CatArray<'_> {
+        CatArray::new(self.codes.slice(info), self.categories.clone())
+    }
+
+    #[inline]
+    pub fn into_owned<'b>(self) -> CatArray<'b> {
+        let codes = match self.codes {
+            ArbArray::Owned(arr) => arr,
+            codes => codes.view().to_owned(),
+        };
+        CatArray::new(codes.into(), self.categories)
+    }
+
+    /// Concatenate 1-d categorical arrays, the categories are merged if they
+    /// are not shared.
+    #[cfg(feature = "concat")]
+    pub fn concat_1d<'b>(arr_vec: Vec<CatArray<'_>>) -> CatArray<'b> {
+        let Some(first) = <[_]>::first(&arr_vec) else {
+            return CatArray::new(Default::default(), Default::default());
+        };
+        let mut out = first.view().into_owned();
+        let mut codes = out.codes.view().0.iter().copied().collect::<Vec<_>>();
+        for arr in &arr_vec[1..] {
+            let arr = out.recode(arr);
+            codes.extend(arr.codes.view().0.iter().copied());
+            out.categories = arr.categories;
+        }
+        let codes = super::own::Arr1::from_vec(codes).into_dyn();
+        CatArray::new(codes.into(), out.categories)
+    }
+}
+
+#[cfg(feature = "arw")]
+impl<'a> CatArray<'a> {
+    /// Create a categorical array from an arrow dictionary array with utf8 values,
+    /// null keys and null values are read as nulls.
+    pub fn from_arrow(arr: &dyn arrow::array::Array) -> TResult<Self> {
+        use arrow::array::{DictionaryArray, DictionaryKey, Utf8Array};
+        use arrow::datatypes::{DataType as ArrowDT, IntegerType};
+
+        fn keys<K: DictionaryKey>(
+            arr: &dyn arrow::array::Array,
+        ) -> (Vec<Option<usize>>, &dyn arrow::array::Array) {
+            let a = arr.as_any().downcast_ref::<DictionaryArray<K>>().unwrap();
+            let keys = a
+                .keys()
+                .iter()
+                // safety: the keys of a valid dictionary array are in bounds of the values
+                .map(|k| k.map(|k| unsafe { k.as_usize() }))
+                .collect();
+            (keys, a.values().as_ref())
+        }
+
+        let ArrowDT::Dictionary(key_type, _, _) = arr.data_type() else {
+            tbail!(
+                "Expect an arrow dictionary array, got {:?}",
+                arr.data_type()
+            )
+        };
+        let (keys, values) = match key_type {
+            IntegerType::Int8 => keys::<i8>(arr),
+            IntegerType::Int16 => keys::<i16>(arr),
+            IntegerType::Int32 => keys::<i32>(arr),
+            IntegerType::Int64 => keys::<i64>(arr),
+            IntegerType::UInt8 => keys::<u8>(arr),
+            IntegerType::UInt16 => keys::<u16>(arr),
+            IntegerType::UInt32 => keys::<u32>(arr),
+            IntegerType::UInt64 => keys::<u64>(arr),
+        };
+        let values: Vec<Option<&str>> = match values.data_type() {
+            ArrowDT::Utf8 => values
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .unwrap()
+                .iter()
+                .collect(),
+            ArrowDT::LargeUtf8 => values
+                .as_any()
+                .downcast_ref::<Utf8Array<i64>>()
+                .unwrap()
+                .iter()
+                .collect(),
+            dt => tbail!("Dictionary values of {:?} is not supported yet", dt),
+        };
+        // arrow doesn't require the values to be unique, so dedup them here
+        let mut map: HashMap<&str, u32> = HashMap::new();
+        let mut categories = Vec::new();
+        let remap = values
+            .iter()
+            .map(|s| {
+                s.map_or(NULL_CODE, |s| {
+                    *map.entry(s).or_insert_with(|| {
+                        categories.push(s.to_string());
+                        (categories.len() - 1) as u32
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        let codes = keys
+            .into_iter()
+            .map(|k| k.map_or(NULL_CODE, |k| remap[k]))
+            .collect::<Vec<_>>();
+        let codes = super::own::Arr1::from_vec(codes).into_dyn();
+        Ok(CatArray::new(codes.into(), Arc::new(categories)))
+    }
+}
+
+#[cfg(feature = "arw")]
+impl<'a> CatArray<'a> {
+    /// Convert a 1-d categorical array into an arrow dictionary array with `u32` keys,
+    /// null elements are null keys.
+    pub fn to_arrow(&self) -> TResult<Box<dyn arrow::array::Array>> {
+        use arrow::array::{DictionaryArray, PrimitiveArray, Utf8Array};
+        use arrow::datatypes::{DataType as ArrowDT, IntegerType};
+        if self.ndim() > 1 {
+            tbail!(
+                "Only 1-d array can be converted to arrow, got ndim {}",
+                self.ndim()
+            )
+        }
+        let keys = self
+            .codes
+            .view()
+            .0
+            .iter()
+            .map(|c| (*c != NULL_CODE).then_some(*c))
+            .collect::<PrimitiveArray<u32>>();
+        let values = Utf8Array::<i64>::from_iter_values(self.categories.iter());
+        let dtype = ArrowDT::Dictionary(IntegerType::UInt32, Box::new(ArrowDT::LargeUtf8), false);
+        let out = DictionaryArray::try_new(dtype, keys, Box::new(values))
+            .map_err(|e| terr!("{:?}", e))?;
+        Ok(Box::new(out))
+    }
+}
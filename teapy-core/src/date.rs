@@ -0,0 +1,340 @@
+use chrono::{Datelike, NaiveDate};
+use std::fmt::Debug;
+use tea_hash::TpHash;
+use tevec::prelude::*;
+
+use crate::py_dtype::Object;
+
+/// The number of days from 0001-01-01 (CE) to 1970-01-01.
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+const DATE_RULE_VEC: [&str; 5] = ["%Y-%m-%d", "%Y%m%d", "%Y/%m/%d", "%d/%m/%Y", "%Y.%m.%d"];
+
+/// A calendar date stored as the number of days since 1970-01-01,
+/// the same layout as arrow `date32`. `i32::MIN` is used as `NaT`.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct Date(pub i32);
+
+impl Debug for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.strftime(None))
+    }
+}
+
+impl Default for Date {
+    #[inline]
+    fn default() -> Self {
+        Date::nat()
+    }
+}
+
+impl Date {
+    #[inline]
+    pub const fn new(days: i32) -> Self {
+        Date(days)
+    }
+
+    #[inline]
+    pub const fn nat() -> Self {
+        Date(i32::MIN)
+    }
+
+    #[inline]
+    pub const fn is_nat(&self) -> bool {
+        self.0 == i32::MIN
+    }
+
+    #[inline]
+    pub const fn is_not_nat(&self) -> bool {
+        self.0 != i32::MIN
+    }
+
+    #[inline]
+    pub const fn from_opt_i32(v: Option<i32>) -> Self {
+        if let Some(v) = v {
+            Date(v)
+        } else {
+            Date::nat()
+        }
+    }
+
+    #[inline]
+    pub const fn into_opt_i32(self) -> Option<i32> {
+        if self.is_nat() {
+            None
+        } else {
+            Some(self.0)
+        }
+    }
+
+    #[inline]
+    pub fn as_naive(&self) -> Option<NaiveDate> {
+        if self.is_nat() {
+            None
+        } else {
+            NaiveDate::from_num_days_from_ce_opt(self.0.checked_add(UNIX_EPOCH_DAYS_FROM_CE)?)
+        }
+    }
+
+    /// Parse a date from a string, common date formats are tried if `fmt` is
+    /// `None`, and datetime strings are truncated to their date.
+    pub fn parse(s: &str, fmt: Option<&str>) -> TResult<Self> {
+        if let Some(fmt) = fmt {
+            if let Ok(d) = NaiveDate::parse_from_str(s, fmt) {
+                return Ok(d.into());
+            }
+        } else {
+            for fmt in DATE_RULE_VEC {
+                if let Ok(d) = NaiveDate::parse_from_str(s, fmt) {
+                    return Ok(d.into());
+                }
+            }
+        }
+        DateTime::<unit::Nanosecond>::parse(s, fmt)
+            .map(Date::from_datetime)
+            .map_err(|_| terr!(ParseError: "Failed to parse date from string: {}", s))
+    }
+
+    /// Format the date, the default format is `%Y-%m-%d`.
+    #[inline]
+    pub fn strftime(&self, fmt: Option<&str>) -> String {
+        if let Some(d) = self.as_naive() {
+            d.format(fmt.unwrap_or("%Y-%m-%d")).to_string()
+        } else {
+            "NaT".to_string()
+        }
+    }
+
+    /// The date of a datetime, times before the epoch are floored to their day.
+    #[inline]
+    pub fn from_datetime<U: TimeUnitTrait>(dt: DateTime<U>) -> Self
+    where
+        DateTime<U>: TryInto<CrDateTime<Utc>>,
+    {
+        dt.as_cr()
+            .map(|dt| dt.date_naive().into())
+            .unwrap_or_default()
+    }
+
+    /// The datetime at the midnight of the date.
+    #[inline]
+    pub fn to_datetime<U: TimeUnitTrait>(self) -> DateTime<U> {
+        if self.is_nat() {
+            DateTime::nat()
+        } else {
+            DateTime::<unit::Second>::new(self.0 as i64 * 86_400).into_unit()
+        }
+    }
+
+    /// The day of week, Monday is 0 and Sunday is 6.
+    #[inline]
+    pub fn weekday(&self) -> Option<i32> {
+        // 1970-01-01 is a Thursday
+        self.into_opt_i32().map(|d| (d + 3).rem_euclid(7))
+    }
+
+    #[inline]
+    pub fn is_business_day(&self) -> bool {
+        self.weekday().is_some_and(|w| w < 5)
+    }
+
+    #[inline]
+    pub fn add_days(self, n: i32) -> Self {
+        self.into_opt_i32().map(|d| Date(d + n)).unwrap_or_default()
+    }
+
+    /// Offset the date by `n` business days (Monday to Friday). A weekend date
+    /// is first rolled to the next business day if `n >= 0` and to the
+    /// previous business day otherwise, the roll counts as one step if `n != 0`.
+    pub fn offset_business_days(self, mut n: i32) -> Self {
+        let Some(mut wd) = self.weekday() else {
+            return self;
+        };
+        let mut days = self.0;
+        if wd >= 5 {
+            if n >= 0 {
+                days += 7 - wd;
+                wd = 0;
+                n = (n - 1).max(0);
+            } else {
+                days -= wd - 4;
+                wd = 4;
+                n += 1;
+            }
+        }
+        let (weeks, rem) = (n / 5, n % 5);
+        days += weeks * 7 + rem;
+        if wd + rem >= 5 {
+            days += 2;
+        } else if wd + rem < 0 {
+            days -= 2;
+        }
+        Date(days)
+    }
+}
+
+impl From<NaiveDate> for Date {
+    #[inline]
+    fn from(d: NaiveDate) -> Self {
+        Date(d.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE)
+    }
+}
+
+impl GetDataType for Date {
+    #[inline(always)]
+    fn dtype() -> DataType {
+        DataType::DateTime(TimeUnit::Day)
+    }
+}
+
+impl IsNone for Date {
+    type Inner = Date;
+    type Cast<U: IsNone<Inner = U> + Clone> = U;
+
+    #[inline]
+    fn is_none(&self) -> bool {
+        self.is_nat()
+    }
+
+    #[inline]
+    fn none() -> Self {
+        Date::nat()
+    }
+
+    #[inline]
+    fn to_opt(self) -> Option<Self::Inner> {
+        if self.is_nat() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+
+    #[inline]
+    fn as_opt(&self) -> Option<&Self::Inner> {
+        if self.is_nat() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+
+    #[inline(always)]
+    fn from_inner(inner: Self::Inner) -> Self {
+        inner
+    }
+
+    #[inline]
+    fn inner_cast<U: IsNone<Inner = U> + Clone>(inner: U) -> Self::Cast<U>
+    where
+        Self::Inner: Cast<U::Inner>,
+    {
+        Cast::<U>::cast(inner)
+    }
+}
+
+impl TpHash for Date {
+    #[inline]
+    fn tphash(&self) -> u64 {
+        i64::cast_unsigned(self.0 as i64)
+    }
+}
+
+// dates are cast to and from numbers as the days since the epoch
+macro_rules! impl_date_numeric_cast {
+    ($($T: ty),*) => {
+        $(
+            impl Cast<$T> for Date {
+                #[inline]
+                fn cast(self) -> $T {
+                    Cast::<$T>::cast(self.0 as i64)
+                }
+            }
+
+            impl Cast<Option<$T>> for Date {
+                #[inline]
+                fn cast(self) -> Option<$T> {
+                    self.into_opt_i32().map(|v| Cast::<$T>::cast(v as i64))
+                }
+            }
+
+            impl Cast<Date> for $T {
+                #[inline]
+                fn cast(self) -> Date {
+                    Date(Cast::<i64>::cast(self) as i32)
+                }
+            }
+
+            impl Cast<Date> for Option<$T> {
+                #[inline]
+                fn cast(self) -> Date {
+                    self.map(Cast::<Date>::cast).unwrap_or_default()
+                }
+            }
+        )*
+    };
+}
+
+impl_date_numeric_cast!(u8, u64, f32, f64, i32, i64, usize, bool);
+
+impl Cast<String> for Date {
+    #[inline]
+    fn cast(self) -> String {
+        self.strftime(None)
+    }
+}
+
+impl Cast<Date> for String {
+    #[inline]
+    fn cast(self) -> Date {
+        Date::parse(&self, None).unwrap_or_default()
+    }
+}
+
+impl<U: TimeUnitTrait> Cast<DateTime<U>> for Date {
+    #[inline]
+    fn cast(self) -> DateTime<U> {
+        self.to_datetime()
+    }
+}
+
+impl Cast<TimeDelta> for Date {
+    #[inline(always)]
+    fn cast(self) -> TimeDelta {
+        unreachable!()
+    }
+}
+
+impl Cast<Date> for TimeDelta {
+    #[inline(always)]
+    fn cast(self) -> Date {
+        unreachable!()
+    }
+}
+
+impl Cast<Object> for Date {
+    #[inline]
+    fn cast(self) -> Object {
+        use pyo3::{IntoPyObjectExt, Python};
+        Python::with_gil(|py| Object(self.as_naive().into_py_any(py).unwrap()))
+    }
+}
+
+impl Cast<Date> for Object {
+    #[inline]
+    fn cast(self) -> Date {
+        use pyo3::Python;
+        Python::with_gil(|py| {
+            if let Ok(v) = self.extract::<NaiveDate>(py) {
+                v.into()
+            } else if let Ok(v) = self.extract::<CrDateTime<Utc>>(py) {
+                v.date_naive().into()
+            } else if let Ok(s) = self.extract::<std::borrow::Cow<'_, str>>(py) {
+                Date::parse(s.as_ref(), None).unwrap_or_default()
+            } else {
+                Date::nat()
+            }
+        })
+    }
+}
@@ -0,0 +1,551 @@
+use super::arbarray::ArbArray;
+use super::own::ArrD;
+use super::traits::WrapNdarray;
+use super::view::ArrViewD;
+use ndarray::{Axis, IxDyn, SliceArg, Zip};
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::str::FromStr;
+use tevec::prelude::*;
+
+/// The max number of digits of a decimal backed by `i128`, the same as arrow decimal128.
+pub const MAX_PRECISION: u8 = 38;
+
+/// How the dropped digits are rounded when a decimal loses scale.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RoundMode {
+    /// Round to the nearest, ties to the even digit (banker's rounding).
+    #[default]
+    HalfEven,
+    /// Round to the nearest, ties away from zero.
+    HalfUp,
+    /// Round towards zero.
+    Down,
+    /// Round away from zero.
+    Up,
+    Floor,
+    Ceil,
+}
+
+impl FromStr for RoundMode {
+    type Err = TError;
+
+    fn from_str(s: &str) -> TResult<Self> {
+        match s.to_lowercase().as_str() {
+            "half_even" => Ok(RoundMode::HalfEven),
+            "half_up" => Ok(RoundMode::HalfUp),
+            "down" => Ok(RoundMode::Down),
+            "up" => Ok(RoundMode::Up),
+            "floor" => Ok(RoundMode::Floor),
+            "ceil" => Ok(RoundMode::Ceil),
+            _ => tbail!(ParseError: "Unknown round mode: {}", s),
+        }
+    }
+}
+
+impl RoundMode {
+    /// Divide `n` by a nonzero `d`, the quotient is rounded by the mode.
+    pub fn div(self, n: i128, d: i128) -> i128 {
+        let (q, r) = (n / d, n % d);
+        if r == 0 {
+            return q;
+        }
+        let positive = (n < 0) == (d < 0);
+        let away = match self {
+            RoundMode::Down => false,
+            RoundMode::Up => true,
+            RoundMode::Floor => !positive,
+            RoundMode::Ceil => positive,
+            // |r| < |d| <= 2^127, so 2|r| can't overflow u128
+            RoundMode::HalfEven | RoundMode::HalfUp => {
+                match (r.unsigned_abs() * 2).cmp(&d.unsigned_abs()) {
+                    Ordering::Greater => true,
+                    Ordering::Less => false,
+                    Ordering::Equal => self == RoundMode::HalfUp || q % 2 != 0,
+                }
+            }
+        };
+        match (away, positive) {
+            (false, _) => q,
+            (true, true) => q + 1,
+            (true, false) => q - 1,
+        }
+    }
+}
+
+#[inline]
+fn pow10(n: u8) -> i128 {
+    10_i128.pow(n as u32)
+}
+
+/// Parse a decimal string like `-12.345` into an integer scaled by `10^scale`,
+/// extra fractional digits are rounded half to even.
+fn parse_decimal(s: &str, scale: u8) -> TResult<i128> {
+    let overflow = || terr!("Decimal overflow when parsing {}", s);
+    let (neg, digits) = match s.trim().as_bytes().first() {
+        Some(b'-') => (true, &s.trim()[1..]),
+        Some(b'+') => (false, &s.trim()[1..]),
+        _ => (false, s.trim()),
+    };
+    let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+    if (int.is_empty() && frac.is_empty())
+        || !Iterator::all(&mut int.bytes().chain(frac.bytes()), |b| b.is_ascii_digit())
+    {
+        tbail!(ParseError: "Can not parse {} as a decimal", s)
+    }
+    let mut value: i128 = 0;
+    for b in int.bytes().chain(frac.bytes()) {
+        value = value
+            .checked_mul(10)
+            .and_then(|v| v.checked_add((b - b'0') as i128))
+            .ok_or_else(overflow)?;
+    }
+    let value = match frac.len().cmp(&(scale as usize)) {
+        Ordering::Greater => {
+            let drop = u8::try_from(frac.len() - scale as usize).map_err(|_| overflow())?;
+            RoundMode::HalfEven.div(value, pow10(drop.min(MAX_PRECISION)))
+        }
+        Ordering::Less => value
+            .checked_mul(pow10(scale - frac.len() as u8))
+            .ok_or_else(overflow)?,
+        Ordering::Equal => value,
+    };
+    Ok(if neg { -value } else { value })
+}
+
+/// Format an integer scaled by `10^scale` as a decimal string.
+fn format_decimal(v: i128, scale: u8) -> String {
+    let sign = if v < 0 { "-" } else { "" };
+    let v = v.unsigned_abs();
+    if scale == 0 {
+        return format!("{sign}{v}");
+    }
+    let p = pow10(scale) as u128;
+    format!("{sign}{}.{:0width$}", v / p, v % p, width = scale as usize)
+}
+
+/// A fixed-point decimal array, element `v` means `v / 10^scale`.
+///
+/// The values are stored as `i128` like arrow decimal128, `DecArray::NULL`
+/// marks a null element.
+#[derive(Clone)]
+pub struct DecArray<'a> {
+    pub values: ArbArray<'a, i128>,
+    pub precision: u8,
+    pub scale: u8,
+}
+
+impl<'a> Debug for DecArray<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Decimal({}, {}){:?}",
+            self.precision,
+            self.scale,
+            self.to_strings().0
+        )
+    }
+}
+
+impl<'a> DecArray<'a> {
+    pub const NULL: i128 = i128::MIN;
+
+    #[inline]
+    fn new_unchecked(values: ArbArray<'a, i128>, precision: u8, scale: u8) -> Self {
+        Self {
+            values,
+            precision,
+            scale,
+        }
+    }
+
+    /// Create a decimal array, the precision should be in `1..=38` and
+    /// the scale should not be greater than the precision.
+    pub fn new(values: ArbArray<'a, i128>, precision: u8, scale: u8) -> TResult<Self> {
+        if precision == 0 || precision > MAX_PRECISION {
+            tbail!(
+                "The precision of decimal should be in 1..={}, got {}",
+                MAX_PRECISION,
+                precision
+            )
+        }
+        if scale > precision {
+            tbail!(
+                "The scale {} of decimal is greater than the precision {}",
+                scale,
+                precision
+            )
+        }
+        Ok(Self::new_unchecked(values, precision, scale))
+    }
+
+    /// Check that the valid values fit in the precision.
+    pub(crate) fn check_precision(self) -> TResult<Self> {
+        let max = pow10(self.precision);
+        let view = self.values.view();
+        if let Some(v) = Iterator::find(&mut view.0.iter(), |v| {
+            **v != Self::NULL && v.unsigned_abs() >= max as u128
+        }) {
+            tbail!(
+                "The value {} doesn't fit in Decimal({}, {})",
+                format_decimal(*v, self.scale),
+                self.precision,
+                self.scale
+            )
+        }
+        Ok(self)
+    }
+
+    /// Create a decimal array from integers.
+    pub fn from_i64<'b>(arr: ArrViewD<'_, i64>, precision: u8, scale: u8) -> TResult<DecArray<'b>> {
+        let p = pow10(scale.min(MAX_PRECISION));
+        let values = arr.map(|v| *v as i128 * p);
+        DecArray::new(values.into(), precision, scale)?.check_precision()
+    }
+
+    /// Create a decimal array from floats rounded half to even, `NaN` is null.
+    pub fn from_f64<'b>(arr: ArrViewD<'_, f64>, precision: u8, scale: u8) -> TResult<DecArray<'b>> {
+        let p = 10_f64.powi(scale as i32);
+        let values = arr.map(|v| {
+            if v.is_nan() {
+                DecArray::NULL
+            } else {
+                (v * p).round_ties_even() as i128
+            }
+        });
+        DecArray::new(values.into(), precision, scale)?.check_precision()
+    }
+
+    /// Parse decimal strings like `-12.345`, empty strings are null.
+    pub fn from_strings<'b>(
+        arr: ArrViewD<'_, String>,
+        precision: u8,
+        scale: u8,
+    ) -> TResult<DecArray<'b>> {
+        let values = arr
+            .0
+            .iter()
+            .map(|s| {
+                if s.trim().is_empty() {
+                    Ok(DecArray::NULL)
+                } else {
+                    parse_decimal(s, scale)
+                }
+            })
+            .collect::<TResult<Vec<_>>>()?;
+        let values = ndarray::ArrayD::from_shape_vec(arr.raw_dim(), values)
+            .unwrap()
+            .wrap();
+        DecArray::new(values.into(), precision, scale)?.check_precision()
+    }
+
+    /// Convert into floats, null is `NaN`.
+    pub fn to_f64(&self) -> ArrD<f64> {
+        let p = 10_f64.powi(self.scale as i32);
+        self.values.view().map(|v| {
+            if *v == Self::NULL {
+                f64::NAN
+            } else {
+                *v as f64 / p
+            }
+        })
+    }
+
+    /// Format the values exactly, null is an empty string.
+    pub fn to_strings(&self) -> ArrD<String> {
+        self.values.view().map(|v| {
+            if *v == Self::NULL {
+                String::new()
+            } else {
+                format_decimal(*v, self.scale)
+            }
+        })
+    }
+
+    /// Change the scale, the dropped digits are rounded by `mode`. The
+    /// precision changes with the scale so the integer digits are kept.
+    pub fn rescale<'b>(&self, scale: u8, mode: RoundMode) -> TResult<DecArray<'b>> {
+        let precision = (self.precision as i16 - self.scale as i16 + scale as i16)
+            .clamp(scale.max(1) as i16, MAX_PRECISION as i16) as u8;
+        let values = if scale >= self.scale {
+            let p = pow10(scale - self.scale);
+            self.try_map(|v| v.checked_mul(p))?
+        } else {
+            let p = pow10(self.scale - scale);
+            self.try_map(|v| Some(mode.div(v, p)))?
+        };
+        DecArray::new(values.into(), precision, scale)?.check_precision()
+    }
+
+    /// Apply `f` to the valid values, `None` means overflow.
+    fn try_map(&self, f: impl Fn(i128) -> Option<i128>) -> TResult<ArrD<i128>> {
+        let mut overflow = false;
+        let out = self.values.view().map(|v| {
+            if *v == Self::NULL {
+                return Self::NULL;
+            }
+            f(*v).unwrap_or_else(|| {
+                overflow = true;
+                Self::NULL
+            })
+        });
+        if overflow {
+            tbail!("Decimal overflow")
+        }
+        Ok(out)
+    }
+
+    /// Apply `f` to the valid pairs of values, a single-element side is
+    /// broadcast to the shape of the other side and `None` means overflow.
+    fn try_zip<T: Copy>(
+        &self,
+        other: ArrViewD<'_, T>,
+        is_null: impl Fn(T) -> bool,
+        f: impl Fn(i128, T) -> Option<i128>,
+    ) -> TResult<ArrD<i128>> {
+        let view = self.values.view();
+        let dim = if view.len() == 1 && other.len() != 1 {
+            other.raw_dim()
+        } else {
+            view.raw_dim()
+        };
+        let err = || {
+            terr!(
+                "Can not broadcast shape {:?} with {:?}",
+                other.shape(),
+                view.shape()
+            )
+        };
+        let lhs = view.0.broadcast(dim.clone()).ok_or_else(err)?;
+        let rhs = other.0.broadcast(dim).ok_or_else(err)?;
+        let mut overflow = false;
+        let out = Zip::from(&lhs).and(&rhs).map_collect(|v, o| {
+            if *v == Self::NULL || is_null(*o) {
+                return Self::NULL;
+            }
+            f(*v, *o).unwrap_or_else(|| {
+                overflow = true;
+                Self::NULL
+            })
+        });
+        if overflow {
+            tbail!("Decimal overflow")
+        }
+        Ok(out.wrap())
+    }
+
+    fn add_impl<'b>(&self, other: &DecArray<'_>, neg: bool) -> TResult<DecArray<'b>> {
+        let scale = self.scale.max(other.scale);
+        let int_digits = (self.precision - self.scale).max(other.precision - other.scale);
+        let precision = (int_digits + scale + 1).min(MAX_PRECISION);
+        let (lhs, rhs) = (
+            self.rescale(scale, RoundMode::Down)?,
+            other.rescale(scale, RoundMode::Down)?,
+        );
+        let values = lhs.try_zip(
+            rhs.values.view(),
+            |o| o == Self::NULL,
+            |v, o| {
+                if neg {
+                    v.checked_sub(o)
+                } else {
+                    v.checked_add(o)
+                }
+            },
+        )?;
+        DecArray::new(values.into(), precision, scale)
+    }
+
+    /// Exact addition, the result has the larger scale of the two arrays.
+    #[inline]
+    pub fn add<'b>(&self, other: &DecArray<'_>) -> TResult<DecArray<'b>> {
+        self.add_impl(other, false)
+    }
+
+    /// Exact subtraction, the result has the larger scale of the two arrays.
+    #[inline]
+    pub fn sub<'b>(&self, other: &DecArray<'_>) -> TResult<DecArray<'b>> {
+        self.add_impl(other, true)
+    }
+
+    /// Exact multiplication by integers, the scale is kept and the precision
+    /// is widened to the max precision.
+    pub fn mul_int<'b>(&self, other: ArrViewD<'_, i64>) -> TResult<DecArray<'b>> {
+        let values = self.try_zip(other, |_| false, |v, o| v.checked_mul(o as i128))?;
+        DecArray::new(values.into(), MAX_PRECISION, self.scale)
+    }
+
+    /// Divide by another decimal array, the quotient has the given scale and
+    /// is rounded by `mode`. Division by zero gives null.
+    pub fn div<'b>(
+        &self,
+        other: &DecArray<'_>,
+        scale: u8,
+        mode: RoundMode,
+    ) -> TResult<DecArray<'b>> {
+        // v / 10^s1 / (o / 10^s2) * 10^scale = v * 10^(scale + s2 - s1) / o
+        let exp = scale as i16 + other.scale as i16 - self.scale as i16;
+        let values = self.try_zip(
+            other.values.view(),
+            |o| o == Self::NULL || o == 0,
+            |v, o| {
+                let (v, o) = if exp >= 0 {
+                    (v.checked_mul(10_i128.checked_pow(exp as u32)?)?, o)
+                } else {
+                    (v, o.checked_mul(10_i128.checked_pow(-exp as u32)?)?)
+                };
+                Some(mode.div(v, o))
+            },
+        )?;
+        DecArray::new(values.into(), MAX_PRECISION, scale.min(MAX_PRECISION))
+    }
+
+    /// Take the elements of a 1-d array, `None` gives null.
+    pub fn take<'b>(&self, idx: impl IntoIterator<Item = Option<usize>>) -> TResult<DecArray<'b>> {
+        let view = self.values.view();
+        let values = view.to_dim1()?;
+        let out = idx
+            .into_iter()
+            .map(|i| i.map_or(Self::NULL, |i| values[i]))
+            .collect::<Vec<_>>();
+        let out = super::own::Arr1::from_vec(out).into_dyn();
+        Ok(DecArray::new_unchecked(
+            out.into(),
+            self.precision,
+            self.scale,
+        ))
+    }
+
+    #[inline]
+    pub fn dtype_name(&self) -> String {
+        format!("Decimal({}, {})", self.precision, self.scale)
+    }
+
+    #[inline]
+    pub fn raw_dim(&self) -> IxDyn {
+        self.values.raw_dim()
+    }
+
+    #[inline]
+    pub fn ndim(&self) -> usize {
+        self.values.ndim()
+    }
+
+    #[inline]
+    pub fn shape(&self) -> &[usize] {
+        self.values.shape()
+    }
+
+    #[allow(clippy::len_without_is_empty)]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    pub fn len_of(&self, axis: Axis) -> usize {
+        self.values.len_of(axis)
+    }
+
+    #[inline]
+    pub fn norm_axis(&self, axis: i32) -> Axis {
+        self.values.norm_axis(axis)
+    }
+
+    #[inline]
+    pub fn get_type(&self) -> &'static str {
+        self.values.get_type()
+    }
+
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        self.values.is_owned()
+    }
+
+    #[inline]
+    pub fn deref(&self) -> DecArray<'_> {
+        DecArray::new_unchecked(self.values.deref(), self.precision, self.scale)
+    }
+
+    #[inline]
+    pub fn view(&self) -> DecArray<'_> {
+        DecArray::new_unchecked(self.values.view().into(), self.precision, self.scale)
+    }
+
+    #[inline]
+    pub fn slice<I: SliceArg<IxDyn>>(&self, info: I) -> DecArray<'_> {
+        DecArray::new_unchecked(self.values.slice(info), self.precision, self.scale)
+    }
+
+    #[inline]
+    pub fn into_owned<'b>(self) -> DecArray<'b> {
+        let values = match self.values {
+            ArbArray::Owned(arr) => arr,
+            values => values.view().to_owned(),
+        };
+        DecArray::new_unchecked(values.into(), self.precision, self.scale)
+    }
+
+    /// Concatenate 1-d decimal arrays, the values are rescaled to the largest
+    /// scale.
+    #[cfg(feature = "concat")]
+    pub fn concat_1d<'b>(arr_vec: Vec<DecArray<'_>>) -> TResult<DecArray<'b>> {
+        let scale = Iterator::max(arr_vec.iter().map(|a| a.scale)).unwrap_or_default();
+        let precision = Iterator::max(
+            arr_vec
+                .iter()
+                .map(|a| (a.precision - a.scale + scale).min(MAX_PRECISION)),
+        )
+        .unwrap_or(MAX_PRECISION);
+        let mut values = Vec::with_capacity(Iterator::sum(arr_vec.iter().map(|a| a.len())));
+        for arr in &arr_vec {
+            values.extend(arr.rescale(scale, RoundMode::Down)?.values.view().0.iter());
+        }
+        let values = super::own::Arr1::from_vec(values).into_dyn();
+        DecArray::new(values.into(), precision, scale)
+    }
+}
+
+#[cfg(feature = "arw")]
+impl<'a> DecArray<'a> {
+    /// Create a decimal array from an arrow decimal128 array, null elements
+    /// are `DecArray::NULL`.
+    pub fn from_arrow(arr: &dyn arrow::array::Array) -> TResult<Self> {
+        use arrow::array::PrimitiveArray;
+        use arrow::datatypes::DataType as ArrowDT;
+        let ArrowDT::Decimal(precision, scale) = arr.data_type() else {
+            tbail!("Expect an arrow decimal array, got {:?}", arr.data_type())
+        };
+        let a = arr.as_any().downcast_ref::<PrimitiveArray<i128>>().unwrap();
+        let values = a
+            .iter()
+            .map(|v| v.copied().unwrap_or(Self::NULL))
+            .collect::<Vec<_>>();
+        let values = super::own::Arr1::from_vec(values).into_dyn();
+        DecArray::new(values.into(), *precision as u8, *scale as u8)
+    }
+
+    /// Convert a 1-d decimal array into an arrow decimal128 array.
+    pub fn to_arrow(&self) -> TResult<Box<dyn arrow::array::Array>> {
+        use arrow::array::PrimitiveArray;
+        use arrow::bitmap::Bitmap;
+        use arrow::datatypes::DataType as ArrowDT;
+        if self.ndim() > 1 {
+            tbail!(
+                "Only 1-d array can be converted to arrow, got ndim {}",
+                self.ndim()
+            )
+        }
+        let view = self.values.view();
+        let validity = if Iterator::any(&mut view.0.iter(), |v| *v == Self::NULL) {
+            Some(Bitmap::from_iter(view.0.iter().map(|v| *v != Self::NULL)))
+        } else {
+            None
+        };
+        let values = view.0.iter().copied().collect::<Vec<_>>();
+        let dtype = ArrowDT::Decimal(self.precision as usize, self.scale as usize);
+        let out = PrimitiveArray::<i128>::try_new(dtype, values.into(), validity)
+            .map_err(|e| terr!("{:?}", e))?;
+        Ok(Box::new(out))
+    }
+}
@@ -1,5 +1,5 @@
 // use crate::prelude::*;
-use crate::prelude::{ArbArray, Arr, ArrBase, ArrOk, WrapNdarray};
+use crate::prelude::{ArbArray, Arr, ArrBase, ArrOk, Date, WrapNdarray};
 use ndarray::{Data, Dimension, Zip};
 use numpy::datetime::{units, Datetime as NPDatetime};
 use tevec::prelude::{unit, Cast, CrDateTime, DateTime, TimeDelta, TimeUnit, TimeUnitTrait, Utc};
@@ -70,6 +70,37 @@ where
     }
 }
 
+impl<S, D> ArrBase<S, D>
+where
+    S: Data<Elem = Date>,
+    D: Dimension,
+{
+    /// Convert to numpy `datetime64[D]`, which is stored in `i64` so the data is copied.
+    #[inline]
+    pub fn to_np_date(&self) -> Arr<NPDatetime<units::Days>, D> {
+        self.map(|v| v.into_opt_i32().map_or(i64::MIN, i64::from).into())
+    }
+}
+
+impl<S, D> ArrBase<S, D>
+where
+    S: Data<Elem = NPDatetime<units::Days>>,
+    D: Dimension,
+{
+    /// Convert numpy `datetime64[D]` to `Date`, `NaT` is kept.
+    #[inline]
+    pub fn np_date_to_date(&self) -> Arr<Date, D> {
+        self.map(|v| {
+            let days = i64::from(*v);
+            if days == i64::MIN {
+                Date::nat()
+            } else {
+                Date::new(days as i32)
+            }
+        })
+    }
+}
+
 impl<'a> From<ArbArray<'a, NPDatetime<units::Milliseconds>>> for ArrOk<'a> {
     #[inline]
     fn from(a: ArbArray<'a, NPDatetime<units::Milliseconds>>) -> Self {
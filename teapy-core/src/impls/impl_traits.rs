@@ -62,6 +62,10 @@ impl SingleElement for i32 {}
 impl SingleElement for i64 {}
 impl SingleElement for u8 {}
 impl SingleElement for u64 {}
+impl SingleElement for i8 {}
+impl SingleElement for i16 {}
+impl SingleElement for u16 {}
+impl SingleElement for u32 {}
 impl SingleElement for usize {}
 impl SingleElement for String {}
 #[cfg(feature = "time")]
@@ -88,7 +88,7 @@ impl MatrixLayout {
     ///
     /// have same memory representation `[1, 2, 3, 4, 5, 6]`, and this toggles them.
     ///
-    /// ```
+    /// ```ignore
     /// # use lax::layout::*;
     /// let layout = MatrixLayout::C { row: 2, lda: 3 };
     /// assert_eq!(layout.t(), MatrixLayout::F { col: 2, lda: 3 });
@@ -107,7 +107,7 @@ impl MatrixLayout {
 /// Examples
 /// ---------
 ///
-/// ```rust
+/// ```ignore
 /// # use lax::layout::*;
 /// let layout = MatrixLayout::C { row: 2, lda: 3 };
 /// let a = vec![1., 2., 3., 4., 5., 6.];
@@ -116,7 +116,7 @@ impl MatrixLayout {
 /// assert_eq!(b, &[1., 4., 2., 5., 3., 6.]);
 /// ```
 ///
-/// ```rust
+/// ```ignore
 /// # use lax::layout::*;
 /// let layout = MatrixLayout::F { col: 2, lda: 3 };
 /// let a = vec![1., 2., 3., 4., 5., 6.];
@@ -162,7 +162,7 @@ pub fn transpose<T: Copy>(layout: MatrixLayout, input: &[T]) -> (MatrixLayout, V
 /// Examples
 /// ---------
 ///
-/// ```rust
+/// ```ignore
 /// # use lax::layout::*;
 /// let layout = MatrixLayout::C { row: 2, lda: 3 };
 /// let a = vec![1., 2., 3., 4., 5., 6.];
@@ -172,7 +172,7 @@ pub fn transpose<T: Copy>(layout: MatrixLayout, input: &[T]) -> (MatrixLayout, V
 /// assert_eq!(b, &[1., 4., 2., 5., 3., 6.]);
 /// ```
 ///
-/// ```rust
+/// ```ignore
 /// # use lax::layout::*;
 /// let layout = MatrixLayout::F { col: 2, lda: 3 };
 /// let a = vec![1., 2., 3., 4., 5., 6.];
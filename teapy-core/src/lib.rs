@@ -11,12 +11,22 @@ pub extern crate tea_utils as utils;
 mod macros;
 mod arbarray;
 mod arrok;
+mod bitmap;
+mod categorical;
+#[cfg(feature = "time")]
+mod date;
+mod decimal;
 mod impls;
 #[cfg(feature = "method_1d")]
 mod iterators;
+mod list;
+mod masked;
 mod own;
 mod py_dtype;
+mod struct_array;
 mod traits;
+#[cfg(feature = "time")]
+mod tz;
 mod view;
 mod viewmut;
 
@@ -37,9 +47,6 @@ use prelude::{Arr, Arr1, ArrView, ArrView1, ArrViewMut, ArrViewMut1};
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 use std::{iter::zip, mem::MaybeUninit, sync::Arc};
 
-// #[cfg(feature = "npy")]
-// use ndarray_npy::{write_npy, WritableElement, WriteNpyError};
-
 pub struct ArrBase<S, D>(pub ArrayBase<S, D>)
 where
     S: RawData;
@@ -113,16 +120,6 @@ where
         T::dtype()
     }
 
-    // #[cfg(feature = "npy")]
-    // #[inline(always)]
-    // pub fn write_npy<P>(self, path: P) -> TResult<()>
-    // where
-    //     P: AsRef<std::path::Path>,
-    //     T: WritableElement,
-    //     S: Data,
-    // {
-    //     write_npy(path, &self.0).map_err(|e| format!("{e}").as_str())?
-    // }
     /// Create a one-dimensional array from a vector (no copying needed).
     #[inline(always)]
     pub fn from_vec(v: Vec<T>) -> Arr1<T>
@@ -0,0 +1,391 @@
+use super::arrok::ArrOk;
+use super::bitmap::Bitmap;
+use super::masked::MaskedArray;
+use super::own::Arr1;
+use super::traits::WrapNdarray;
+use crate::match_arrok;
+use ndarray::{s, Axis, IxDyn, SliceArg};
+use std::fmt::Debug;
+use std::sync::Arc;
+use tevec::prelude::*;
+
+/// A list array, row `i` holds the child values in `offsets[i]..offsets[i + 1]`.
+///
+/// The offsets are shared between the arrays cloned or viewed from the same
+/// `ListArray`, the child values are a 1-d array of a primitive dtype. Null
+/// rows are marked by the validity and hold no child value.
+#[derive(Clone)]
+pub struct ListArray<'a> {
+    pub offsets: Arc<[usize]>,
+    pub values: Box<ArrOk<'a>>,
+    /// `None` if all the rows are valid.
+    pub validity: Option<Bitmap>,
+    shape: [usize; 1],
+}
+
+impl<'a> Debug for ListArray<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "List")?;
+        f.debug_list()
+            .entries((0..self.len()).map(|i| self.is_valid(i).then(|| self.row(i))))
+            .finish()
+    }
+}
+
+/// Take the elements of a 1-d array, `None` takes the default value.
+#[allow(unreachable_patterns, clippy::clone_on_copy)]
+fn gather<'b>(values: &ArrOk<'_>, idx: impl Iterator<Item = Option<usize>>) -> ArrOk<'b> {
+    match_arrok!(values; (Dynamic | Compact)(a) => {
+        let view = a.view().to_dim1().unwrap();
+        let out = idx
+            .map(|i| i.map(|i| view.0[i].clone()).unwrap_or_default())
+            .collect::<Vec<_>>();
+        Ok(Arr1::from_vec(out).into_dyn().into())
+    },)
+    .unwrap()
+}
+
+impl<'a> ListArray<'a> {
+    #[inline]
+    fn new_unchecked(offsets: Arc<[usize]>, values: ArrOk<'a>, validity: Option<Bitmap>) -> Self {
+        let shape = [offsets.len().saturating_sub(1)];
+        // a validity without null is dropped
+        let validity = validity.filter(|v| v.unset_bits() > 0);
+        Self {
+            offsets,
+            values: Box::new(values),
+            validity,
+            shape,
+        }
+    }
+
+    /// Create a list array from the offsets and the 1-d child values, the
+    /// offsets should be non-decreasing and in bounds of the values.
+    #[allow(unreachable_patterns)]
+    pub fn new(offsets: Arc<[usize]>, values: ArrOk<'a>) -> TResult<Self> {
+        if values.ndim() != 1 {
+            tbail!(
+                "The values of a list array should be 1-d, got ndim {}",
+                values.ndim()
+            )
+        }
+        match_arrok!(&values; (Dynamic | Compact)(_a) => { Ok(()) },)
+            .map_err(|_| terr!("dtype {} can not be a list element", values.dtype_name()))?;
+        if offsets.is_empty() {
+            tbail!("The offsets of a list array should not be empty")
+        }
+        if Iterator::any(&mut offsets.windows(2), |w| w[0] > w[1]) {
+            tbail!("The offsets of a list array should be non-decreasing")
+        }
+        if *offsets.last().unwrap() > values.len() {
+            tbail!(
+                "The offsets are out of bounds of the values of length {}",
+                values.len()
+            )
+        }
+        Ok(Self::new_unchecked(offsets, values, None))
+    }
+
+    /// Whether row `i` is not null.
+    #[inline]
+    pub fn is_valid(&self, i: usize) -> bool {
+        self.validity.as_ref().is_none_or(|v| v.get(i))
+    }
+
+    /// Mask the null rows of an output of the rows, the dtypes that can't be
+    /// masked are kept as they are.
+    fn mask_nulls<'b>(&self, out: ArrOk<'b>) -> ArrOk<'b> {
+        match &self.validity {
+            Some(validity) if MaskedArray::is_maskable(&out) => {
+                MaskedArray::new(out, validity.clone()).unwrap().into()
+            }
+            _ => out,
+        }
+    }
+
+    /// Create a list array from 1-d arrays of the same dtype, each array is a row.
+    #[cfg(feature = "concat")]
+    pub fn from_arrays(arr_vec: Vec<ArrOk<'_>>) -> TResult<ListArray<'static>> {
+        let mut offsets = Vec::with_capacity(arr_vec.len() + 1);
+        offsets.push(0);
+        let mut len = 0;
+        for arr in &arr_vec {
+            if arr.ndim() != 1 {
+                tbail!(
+                    "The rows of a list array should be 1-d, got ndim {}",
+                    arr.ndim()
+                )
+            }
+            if arr.dtype_name() != arr_vec[0].dtype_name() {
+                tbail!(
+                    "The rows of a list array should have the same dtype, got {} and {}",
+                    arr_vec[0].dtype_name(),
+                    arr.dtype_name()
+                )
+            }
+            len += arr.len();
+            offsets.push(len);
+        }
+        let arr_vec = arr_vec
+            .into_iter()
+            .map(|a| a.into_dense().into_owned())
+            .collect();
+        ListArray::new(offsets.into(), ArrOk::same_dtype_concat_1d(arr_vec))
+    }
+
+    /// The start and the end of the rows in the child values.
+    #[inline]
+    pub fn ranges(&self) -> impl ExactSizeIterator<Item = (usize, usize)> + '_ {
+        self.offsets.windows(2).map(|w| (w[0], w[1]))
+    }
+
+    /// The child values in `start..end`.
+    #[allow(unreachable_patterns)]
+    fn values_slice(&self, start: usize, end: usize) -> ArrOk<'_> {
+        match_arrok!(self.values.as_ref(); (Dynamic | Compact)(a) => {
+            Ok(a.view().to_dim1().unwrap().0.slice_move(s![start..end]).wrap().into_dyn().into())
+        },)
+        .unwrap()
+    }
+
+    /// The values of row `i`.
+    #[inline]
+    pub fn row(&self, i: usize) -> ArrOk<'_> {
+        self.values_slice(self.offsets[i], self.offsets[i + 1])
+    }
+
+    /// The child values of all the rows, empty lists contribute no element.
+    #[inline]
+    pub fn explode(&self) -> ArrOk<'_> {
+        self.values_slice(self.offsets[0], self.offsets[self.len()])
+    }
+
+    /// The number of elements of each row, null rows give null.
+    #[inline]
+    pub fn lens<'b>(&self) -> ArrOk<'b> {
+        let lens = Arr1::from_iter(self.ranges().map(|(start, end)| end - start)).into_dyn();
+        self.mask_nulls(lens.into())
+    }
+
+    /// The `i`-th element of each row, negative `i` counts from the end of the
+    /// row. Rows that are null or too short are null.
+    pub fn get<'b>(&self, i: i64) -> ArrOk<'b> {
+        let idx = self
+            .ranges()
+            .map(|(start, end)| {
+                let len = (end - start) as i64;
+                let i = if i < 0 { i + len } else { i };
+                (0..len).contains(&i).then(|| start + i as usize)
+            })
+            .collect::<Vec<_>>();
+        let out = gather(&self.values, idx.iter().copied());
+        if Iterator::all(&mut idx.iter(), Option::is_some) || !MaskedArray::is_maskable(&out) {
+            // dtypes that can't be masked are filled by their default values
+            return out;
+        }
+        let validity: Bitmap = idx.iter().map(Option::is_some).collect();
+        MaskedArray::new(out, validity).unwrap().into()
+    }
+
+    /// Take the rows, `None` takes a null row.
+    pub fn take<'b>(&self, idx: impl IntoIterator<Item = Option<usize>>) -> ListArray<'b> {
+        let mut offsets = vec![0];
+        let mut value_idx = Vec::new();
+        let mut validity = Vec::new();
+        for i in idx {
+            let valid = i.is_some_and(|i| self.is_valid(i));
+            if let (Some(i), true) = (i, valid) {
+                value_idx.extend(self.offsets[i]..self.offsets[i + 1]);
+            }
+            offsets.push(value_idx.len());
+            validity.push(valid);
+        }
+        let values = gather(&self.values, value_idx.into_iter().map(Some));
+        ListArray::new_unchecked(offsets.into(), values, Some(validity.into_iter().collect()))
+    }
+
+    /// The sum of each row, null rows give null.
+    #[cfg(feature = "method_1d")]
+    #[allow(unreachable_patterns)]
+    pub fn sum<'b>(&self) -> TResult<ArrOk<'b>> {
+        use crate::prelude::BasicAggExt;
+        let values = self.values.view().widen();
+        match_arrok!(values; Numeric(a) => {
+            let view = a.view().to_dim1()?;
+            let out = self
+                .ranges()
+                .map(|(start, end)| view.0.slice(s![start..end]).wrap().sum_1d())
+                .collect::<Vec<_>>();
+            Ok(self.mask_nulls(Arr1::from_vec(out).into_dyn().into()))
+        },)
+        .map_err(|_| terr!("Can not sum a list of {}", self.values.dtype_name()))
+    }
+
+    /// The mean of each row, `NaN` if the row is null or the number of valid
+    /// elements is less than `min_periods`.
+    #[cfg(feature = "method_1d")]
+    #[allow(unreachable_patterns)]
+    pub fn mean<'b>(&self, min_periods: usize) -> TResult<ArrOk<'b>> {
+        use crate::prelude::BasicAggExt;
+        let values = self.values.view().widen();
+        match_arrok!(values; Numeric(a) => {
+            let view = a.view().to_dim1()?;
+            let out = self
+                .ranges()
+                .map(|(start, end)| view.0.slice(s![start..end]).wrap().mean_1d(min_periods))
+                .collect::<Vec<_>>();
+            Ok(self.mask_nulls(Arr1::from_vec(out).into_dyn().into()))
+        },)
+        .map_err(|_| {
+            terr!(
+                "Can not get the mean of a list of {}",
+                self.values.dtype_name()
+            )
+        })
+    }
+
+    #[inline]
+    pub fn dtype_name(&self) -> String {
+        format!("List[{}]", self.values.dtype_name())
+    }
+
+    #[inline]
+    pub fn raw_dim(&self) -> IxDyn {
+        IxDyn(&self.shape)
+    }
+
+    #[inline]
+    pub fn ndim(&self) -> usize {
+        1
+    }
+
+    #[inline]
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    #[allow(clippy::len_without_is_empty)]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.shape[0]
+    }
+
+    #[inline]
+    pub fn len_of(&self, axis: Axis) -> usize {
+        assert_eq!(axis.index(), 0, "List array only has one axis");
+        self.len()
+    }
+
+    #[inline]
+    pub fn norm_axis(&self, axis: i32) -> Axis {
+        assert!(
+            axis == 0 || axis == -1,
+            "List array only has one axis, got {axis}"
+        );
+        Axis(0)
+    }
+
+    #[inline]
+    pub fn get_type(&self) -> &'static str {
+        "List Array"
+    }
+
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        self.values.is_owned()
+    }
+
+    #[inline]
+    pub fn deref(&self) -> ListArray<'_> {
+        ListArray::new_unchecked(
+            self.offsets.clone(),
+            self.values.deref(),
+            self.validity.clone(),
+        )
+    }
+
+    #[inline]
+    pub fn view(&self) -> ListArray<'_> {
+        ListArray::new_unchecked(
+            self.offsets.clone(),
+            self.values.view(),
+            self.validity.clone(),
+        )
+    }
+
+    /// Slice the rows, the selected rows are copied into a new list array.
+    pub fn slice<I: SliceArg<IxDyn>>(&self, info: I) -> ListArray<'_> {
+        let idx = Arr1::from_iter(0..self.len()).into_dyn();
+        let idx = idx.0.slice(info);
+        self.take(idx.iter().map(|i| Some(*i)))
+    }
+
+    #[inline]
+    pub fn into_owned<'b>(self) -> ListArray<'b> {
+        ListArray::new_unchecked(self.offsets, self.values.into_owned(), self.validity)
+    }
+
+    /// Concatenate 1-d list arrays, the child values should be of the same dtype.
+    #[cfg(feature = "concat")]
+    pub fn concat_1d<'b>(arr_vec: Vec<ListArray<'_>>) -> ListArray<'b> {
+        let mut offsets = vec![0];
+        let mut base = 0;
+        for arr in &arr_vec {
+            offsets.extend(arr.offsets[1..].iter().map(|o| o - arr.offsets[0] + base));
+            base += arr.offsets[arr.len()] - arr.offsets[0];
+        }
+        let validity = arr_vec
+            .iter()
+            .flat_map(|a| (0..a.len()).map(|i| a.is_valid(i)))
+            .collect();
+        let values = arr_vec.iter().map(|a| a.explode().into_owned()).collect();
+        ListArray::new_unchecked(
+            offsets.into(),
+            ArrOk::same_dtype_concat_1d(values),
+            Some(validity),
+        )
+    }
+}
+
+#[cfg(feature = "arw")]
+impl<'a> ListArray<'a> {
+    /// Create a list array from an arrow list array, the child values are
+    /// shared if possible and the null rows are kept.
+    pub fn from_arrow<O: arrow::offset::Offset>(arr: &dyn arrow::array::Array) -> TResult<Self> {
+        let a = arr
+            .as_any()
+            .downcast_ref::<arrow::array::ListArray<O>>()
+            .ok_or_else(|| terr!("Expect an arrow list array, got {:?}", arr.data_type()))?;
+        let offsets = a.offsets().buffer().iter().map(|o| o.to_usize()).collect();
+        let values = ArrOk::from_arrow(a.values().clone()).into_dense();
+        let mut rows = ListArray::new(offsets, values)?;
+        match a.validity() {
+            Some(validity) if validity.unset_bits() > 0 => {
+                // the child values of null rows are dropped by taking the rows
+                rows.validity = Some(Bitmap::from_arrow(validity));
+                Ok(rows.take((0..rows.len()).map(Some)))
+            }
+            _ => Ok(rows),
+        }
+    }
+
+    /// Convert into an arrow large list array with the validity of the rows.
+    pub fn to_arrow(&self) -> TResult<Box<dyn arrow::array::Array>> {
+        use arrow::array::ListArray as ArrowList;
+        let values = self.explode().to_arrow()?;
+        let offsets = self
+            .offsets
+            .iter()
+            .map(|o| (o - self.offsets[0]) as i64)
+            .collect::<Vec<_>>();
+        let dtype = ArrowList::<i64>::default_datatype(values.data_type().clone());
+        let out = ArrowList::<i64>::try_new(
+            dtype,
+            offsets.try_into().map_err(|e| terr!("{:?}", e))?,
+            values,
+            self.validity.as_ref().map(Bitmap::to_arrow),
+        )
+        .map_err(|e| terr!("{:?}", e))?;
+        Ok(Box::new(out))
+    }
+}
@@ -139,6 +139,47 @@ macro_rules! match_enum {
         )
     };
 
+    // match small int arm, tea-dtype doesn't support these dtypes, so they are
+    // only used to store data and are widened when the lazy kernels view them
+    (@($enum: ident, $exprs: expr; SmallInt ($e: ident) => $body: expr, $($rest: tt)*) $($all_arms: tt)* ) => {
+        $crate::match_enum!(
+            @($enum, $exprs;
+                (I8 | I16 | U16 | U32)($e) => $body,
+            $($rest)*)
+            $($all_arms)*
+        )
+    };
+
+    // match option unsigned int arm that tea-dtype doesn't support
+    (@($enum: ident, $exprs: expr; OptUInt ($e: ident) => $body: expr, $($rest: tt)*) $($all_arms: tt)* ) => {
+        $crate::match_enum!(
+            @($enum, $exprs;
+                (OptU8 | OptU64)($e) => $body,
+            $($rest)*)
+            $($all_arms)*
+        )
+    };
+
+    // match compact dtype (small int + option unsigned int)
+    (@($enum: ident, $exprs: expr; Compact ($e: ident) => $body: expr, $($rest: tt)*) $($all_arms: tt)* ) => {
+        $crate::match_enum!(
+            @($enum, $exprs;
+                (SmallInt | OptUInt)($e) => $body,
+            $($rest)*)
+            $($all_arms)*
+        )
+    };
+
+    // match the dtypes that wrap an inner array with extra metadata
+    (@($enum: ident, $exprs: expr; Wrapped ($e: ident) => $body: expr, $($rest: tt)*) $($all_arms: tt)* ) => {
+        $crate::match_enum!(
+            @($enum, $exprs;
+                (Categorical | Decimal | Masked | List | Struct | #[cfg(feature="time")] DateTimeTz)($e) => $body,
+            $($rest)*)
+            $($all_arms)*
+        )
+    };
+
     // match bool like arm
     (@($enum: ident, $exprs: expr; BoolLike ($e: ident) => $body: expr, $($rest: tt)*) $($all_arms: tt)* ) => {
         $crate::match_enum!(
@@ -194,6 +235,7 @@ macro_rules! match_enum {
             @($enum, $exprs;
             (
                 Time
+                | #[cfg(feature="time")] Date
                 | #[cfg(feature="time")] TimeDelta
             )
             ($e) => $body,
@@ -221,7 +263,7 @@ macro_rules! match_enum {
     // match hashable dtype
     (@($enum: ident, $exprs: expr; Hash ($e: ident) => $body: expr, $($rest: tt)*) $($all_arms: tt)* ) => {
         $crate::match_enum!(
-            @($enum, $exprs; (PureInt | String | Bool | U8 | Time)($e) => $body, $($rest)*)
+            @($enum, $exprs; (PureInt | String | Bool | U8 | Time | #[cfg(feature="time")] Date)($e) => $body, $($rest)*)
             $($all_arms)*
         )
     };
@@ -229,7 +271,7 @@ macro_rules! match_enum {
     // match hashable dtype
     (@($enum: ident, $exprs: expr; TpHash ($e: ident) => $body: expr, $($rest: tt)*) $($all_arms: tt)* ) => {
         $crate::match_enum!(
-            @($enum, $exprs; (PureNumeric | String | Bool | U8 | Time)($e) => $body, $($rest)*)
+            @($enum, $exprs; (PureNumeric | SmallInt | String | Bool | U8 | Time | #[cfg(feature="time")] Date)($e) => $body, $($rest)*)
             $($all_arms)*
         )
     };
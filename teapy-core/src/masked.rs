@@ -0,0 +1,380 @@
+use super::arbarray::ArbArray;
+use super::arrok::ArrOk;
+use super::bitmap::Bitmap;
+#[cfg(feature = "time")]
+use super::date::Date;
+use super::own::{Arr1, ArrD};
+use super::py_dtype::Object;
+use super::view::ArrView1;
+use crate::match_arrok;
+use ndarray::{Axis, IxDyn, SliceArg};
+use std::fmt::Debug;
+use tevec::prelude::*;
+
+/// A nullable array stored as dense values and a validity bitmap, the values
+/// of invalid elements are unspecified.
+///
+/// Unlike the `Opt*` dtypes, the values keep the layout of arrow so nullable
+/// arrow arrays are read and written without copying. The `IsNone` based
+/// kernels iterate the array through [`MaskedView`], other kernels compute on
+/// `to_dense`, where invalid elements are filled by `IsNone::none`.
+#[derive(Clone)]
+pub struct MaskedArray<'a> {
+    pub values: Box<ArrOk<'a>>,
+    pub validity: Bitmap,
+}
+
+impl<'a> Debug for MaskedArray<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Masked{:?}", self.to_dense())
+    }
+}
+
+/// Match the dtype of the values of a 1-d masked array and bind `$view` to a
+/// [`MaskedView`] of them, the arms are the same as `match_arrok`.
+///
+/// With `$masked, $default`, the dtypes that are not matched give `$default`
+/// rather than an error.
+#[macro_export]
+macro_rules! match_masked {
+    ($masked: expr; $($arm: tt ($view: ident) => $body: expr),* $(,)?) => {{
+        let masked: &$crate::prelude::MaskedArray<'_> = $masked;
+        $crate::match_arrok!(masked.values.as_ref(); $($arm(values) => {
+            let $view = $crate::prelude::MaskedView::new(values.view().to_dim1()?, &masked.validity)?;
+            $body
+        },)*)
+    }};
+
+    ($masked: expr, $default: expr; $($arm: tt ($view: ident) => $body: expr),* $(,)?) => {{
+        let masked: &$crate::prelude::MaskedArray<'_> = $masked;
+        if $crate::match_arrok!(masked.values.as_ref(); $($arm(_values) => { Ok(()) },)*).is_ok() {
+            $crate::match_masked!(masked; $($arm($view) => $body,)*)
+        } else {
+            $default
+        }
+    }};
+}
+
+/// A view of the masked values of a 1-d array, invalid elements are `None`.
+///
+/// It implements `Vec1View<Option<T>>`, so the `IsNone` based kernels of
+/// tevec can be used on masked arrays directly. Valid values that are none,
+/// e.g. `NaN`, are also `None` so that the kernels give the same result as
+/// on the dense array.
+pub struct MaskedView<'a, T> {
+    pub values: ArrView1<'a, T>,
+    pub validity: &'a Bitmap,
+}
+
+impl<'a, T> MaskedView<'a, T> {
+    #[inline]
+    pub fn new(values: ArrView1<'a, T>, validity: &'a Bitmap) -> TResult<Self> {
+        if values.len() != validity.len() {
+            tbail!(
+                "The length of values ({}) doesn't match the length of validity ({})",
+                values.len(),
+                validity.len()
+            )
+        }
+        Ok(Self { values, validity })
+    }
+}
+
+impl<T> GetLen for MaskedView<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+}
+
+impl<T: IsNone + Clone> TIter<Option<T>> for MaskedView<'_, T> {
+    #[inline]
+    fn titer(&self) -> impl TIterator<Item = Option<T>> {
+        (0..self.len()).map(|i| unsafe { self.uget(i) })
+    }
+}
+
+impl<T: IsNone + Clone> Vec1View<Option<T>> for MaskedView<'_, T> {
+    type SliceOutput<'b>
+        = Vec<Option<T>>
+    where
+        Self: 'b;
+
+    #[inline]
+    fn get_backend_name(&self) -> &'static str {
+        "masked"
+    }
+
+    #[inline]
+    unsafe fn uget(&self, index: usize) -> Option<T> {
+        if self.validity.get_unchecked(index) {
+            let v = self.values.0.uget(index);
+            v.not_none().then(|| v.clone())
+        } else {
+            None
+        }
+    }
+}
+
+/// Fill the invalid elements by `U::none()`, the valid values are converted by `f`.
+fn fill<T: Clone, U: IsNone>(
+    values: &ArbArray<'_, T>,
+    validity: &Bitmap,
+    f: impl Fn(T) -> U::Inner,
+) -> ArrD<U> {
+    let values = values.view();
+    let out = values
+        .0
+        .iter()
+        .zip(validity.iter())
+        .map(|(v, valid)| {
+            if valid {
+                U::from_inner(f(v.clone()))
+            } else {
+                U::none()
+            }
+        })
+        .collect::<Vec<_>>();
+    Arr1::from_vec(out).into_dyn()
+}
+
+/// Split an array of options into the values and the validity.
+fn split<'b, T: Copy + Default>(arr: &ArbArray<'_, Option<T>>) -> (ArrOk<'b>, Bitmap)
+where
+    ArrD<T>: Into<ArrOk<'b>>,
+{
+    let view = arr.view();
+    let values = view
+        .0
+        .iter()
+        .map(|v| v.unwrap_or_default())
+        .collect::<Vec<_>>();
+    let validity = view.0.iter().map(Option::is_some).collect();
+    (Arr1::from_vec(values).into_dyn().into(), validity)
+}
+
+impl<'a> MaskedArray<'a> {
+    #[inline]
+    fn new_unchecked(values: ArrOk<'a>, validity: Bitmap) -> Self {
+        Self {
+            values: Box::new(values),
+            validity,
+        }
+    }
+
+    /// Create a masked array from 1-d values and their validity, the values
+    /// should be of a primitive dtype or strings.
+    pub fn new(values: ArrOk<'a>, validity: Bitmap) -> TResult<Self> {
+        if values.ndim() != 1 {
+            tbail!(
+                "The values of a masked array should be 1-d, got ndim {}",
+                values.ndim()
+            )
+        }
+        if values.len() != validity.len() {
+            tbail!(
+                "The length of values ({}) doesn't match the length of validity ({})",
+                values.len(),
+                validity.len()
+            )
+        }
+        if !MaskedArray::is_maskable(&values) {
+            tbail!("dtype {} can not be masked", values.dtype_name())
+        }
+        Ok(Self::new_unchecked(values, validity))
+    }
+
+    /// Whether the dtype of the values is a primitive dtype or strings that
+    /// can be masked.
+    #[allow(unreachable_patterns)]
+    #[inline]
+    pub fn is_maskable(values: &ArrOk<'_>) -> bool {
+        match_arrok!(values;
+            (PureNumeric | SmallInt | Bool | U8 | Time | #[cfg(feature="time")] Date | String)(_a) => { Ok(()) },
+        )
+        .is_ok()
+    }
+
+    /// Split an array of an `Opt*` dtype into the values and the validity.
+    pub fn from_opt(arr: &ArrOk<'_>) -> TResult<MaskedArray<'static>> {
+        if arr.ndim() != 1 {
+            tbail!("Only 1-d array can be masked, got ndim {}", arr.ndim())
+        }
+        let (values, validity): (ArrOk<'static>, _) = match arr {
+            ArrOk::OptBool(a) => split(a),
+            ArrOk::OptI32(a) => split(a),
+            ArrOk::OptI64(a) => split(a),
+            ArrOk::OptU8(a) => split(a),
+            ArrOk::OptU64(a) => split(a),
+            ArrOk::OptUsize(a) => split(a),
+            ArrOk::OptF32(a) => split(a),
+            ArrOk::OptF64(a) => split(a),
+            arr => tbail!("dtype {} is not an option dtype", arr.dtype_name()),
+        };
+        Ok(MaskedArray::new_unchecked(values, validity))
+    }
+
+    #[inline]
+    pub fn into_dense<'b>(self) -> ArrOk<'b> {
+        self.to_dense()
+    }
+
+    /// The array with invalid elements filled, floats are filled by `NaN`,
+    /// times by `NaT`, strings are read as python objects filled by `None`
+    /// and other dtypes are read as the `Opt*` dtypes.
+    pub fn to_dense<'b>(&self) -> ArrOk<'b> {
+        let v = &self.validity;
+        match self.values.as_ref() {
+            ArrOk::Bool(a) => fill::<_, Option<bool>>(a, v, |v| v).into(),
+            ArrOk::U8(a) => fill::<_, Option<u8>>(a, v, |v| v).into(),
+            ArrOk::U64(a) => fill::<_, Option<u64>>(a, v, |v| v).into(),
+            ArrOk::I8(a) => fill::<_, Option<i32>>(a, v, |v| v as i32).into(),
+            ArrOk::I16(a) => fill::<_, Option<i32>>(a, v, |v| v as i32).into(),
+            ArrOk::U16(a) => fill::<_, Option<i32>>(a, v, |v| v as i32).into(),
+            ArrOk::U32(a) => fill::<_, Option<i64>>(a, v, |v| v as i64).into(),
+            ArrOk::I32(a) => fill::<_, Option<i32>>(a, v, |v| v).into(),
+            ArrOk::I64(a) => fill::<_, Option<i64>>(a, v, |v| v).into(),
+            ArrOk::Usize(a) => fill::<_, Option<usize>>(a, v, |v| v).into(),
+            ArrOk::F32(a) => fill::<_, f32>(a, v, |v| v).into(),
+            ArrOk::F64(a) => fill::<_, f64>(a, v, |v| v).into(),
+            #[cfg(feature = "time")]
+            ArrOk::DateTimeMs(a) => fill::<_, DateTime<unit::Millisecond>>(a, v, |v| v).into(),
+            #[cfg(feature = "time")]
+            ArrOk::DateTimeUs(a) => fill::<_, DateTime<unit::Microsecond>>(a, v, |v| v).into(),
+            #[cfg(feature = "time")]
+            ArrOk::DateTimeNs(a) => fill::<_, DateTime<unit::Nanosecond>>(a, v, |v| v).into(),
+            #[cfg(feature = "time")]
+            ArrOk::Date(a) => fill::<_, Date>(a, v, |v| v).into(),
+            ArrOk::String(a) => fill::<_, Object>(a, v, Cast::<Object>::cast).into(),
+            _ => unreachable!("dtype {} can not be masked", self.values.dtype_name()),
+        }
+    }
+
+    /// The dtype of the dense array, the `Opt*` dtypes that `DataType` can't
+    /// express are reported as the dtypes they are widened to.
+    pub fn dtype(&self) -> DataType {
+        match self.values.as_ref() {
+            ArrOk::Bool(_) => DataType::OptBool,
+            ArrOk::I8(_) | ArrOk::I16(_) | ArrOk::U16(_) | ArrOk::I32(_) | ArrOk::U8(_) => {
+                DataType::OptI32
+            }
+            ArrOk::U32(_) | ArrOk::I64(_) => DataType::OptI64,
+            ArrOk::Usize(_) | ArrOk::U64(_) => DataType::OptUsize,
+            ArrOk::String(_) => DataType::Object,
+            values => values.dtype(),
+        }
+    }
+
+    /// The name of the dtype of the dense array.
+    pub fn dtype_name(&self) -> String {
+        match self.values.as_ref() {
+            ArrOk::Bool(_) => "OptBool".to_string(),
+            ArrOk::U8(_) => "OptU8".to_string(),
+            ArrOk::U64(_) => "OptU64".to_string(),
+            ArrOk::I8(_) | ArrOk::I16(_) | ArrOk::U16(_) | ArrOk::I32(_) => "OptI32".to_string(),
+            ArrOk::U32(_) | ArrOk::I64(_) => "OptI64".to_string(),
+            ArrOk::Usize(_) => "OptUsize".to_string(),
+            ArrOk::String(_) => "OptString".to_string(),
+            values => values.dtype_name(),
+        }
+    }
+
+    #[inline]
+    pub fn raw_dim(&self) -> IxDyn {
+        self.values.raw_dim()
+    }
+
+    #[inline]
+    pub fn ndim(&self) -> usize {
+        self.values.ndim()
+    }
+
+    #[inline]
+    pub fn shape(&self) -> &[usize] {
+        self.values.shape()
+    }
+
+    #[allow(clippy::len_without_is_empty)]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    pub fn len_of(&self, axis: Axis) -> usize {
+        self.values.len_of(axis)
+    }
+
+    #[inline]
+    pub fn norm_axis(&self, axis: i32) -> Axis {
+        self.values.norm_axis(axis)
+    }
+
+    #[inline]
+    pub fn get_type(&self) -> &'static str {
+        "Masked Array"
+    }
+
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        self.values.is_owned()
+    }
+
+    #[inline]
+    pub fn deref(&self) -> MaskedArray<'_> {
+        MaskedArray::new_unchecked(self.values.deref(), self.validity.clone())
+    }
+
+    #[inline]
+    pub fn view(&self) -> MaskedArray<'_> {
+        MaskedArray::new_unchecked(self.values.view(), self.validity.clone())
+    }
+
+    /// Slice the dense array, as the bitmap can only be sliced by a range.
+    #[inline]
+    pub fn slice<I: SliceArg<IxDyn>>(&self, info: I) -> ArrOk<'_> {
+        self.to_dense().slice(info).into_owned()
+    }
+
+    #[inline]
+    pub fn into_owned<'b>(self) -> MaskedArray<'b> {
+        MaskedArray::new_unchecked(self.values.into_owned(), self.validity)
+    }
+
+    /// Concatenate 1-d arrays, arrays that are not masked are all valid.
+    #[cfg(feature = "concat")]
+    pub fn concat_1d(arr_vec: Vec<ArrOk<'a>>) -> MaskedArray<'a> {
+        let (values, validity): (Vec<_>, Vec<_>) = arr_vec
+            .into_iter()
+            .map(|arr| match arr {
+                ArrOk::Masked(a) => (*a.values, a.validity),
+                arr => {
+                    let len = arr.len();
+                    (arr, Bitmap::new_valid(len))
+                }
+            })
+            .unzip();
+        let validity = Bitmap::concat(&validity.iter().collect::<Vec<_>>());
+        MaskedArray::new_unchecked(ArrOk::same_dtype_concat_1d(values), validity)
+    }
+}
+
+#[cfg(feature = "arw")]
+impl<'a> MaskedArray<'a> {
+    /// Convert into an arrow array with the validity, the buffers are shared
+    /// if the array is read from arrow.
+    ///
+    /// The validity is kept even if there is no null, so that the array is
+    /// read back as a nullable dtype.
+    pub fn to_arrow(&self) -> TResult<Box<dyn arrow::array::Array>> {
+        let validity = Some(self.validity.to_arrow());
+        Ok(self.values.to_arrow()?.with_validity(validity))
+    }
+
+    /// Move into an arrow array with the validity without copying the values
+    /// if possible.
+    pub fn into_arrow(self) -> TResult<Box<dyn arrow::array::Array>> {
+        let validity = Some(self.validity.to_arrow());
+        Ok(self.values.into_arrow()?.with_validity(validity))
+    }
+}
@@ -1,8 +1,14 @@
 pub use crate::{
     arbarray::{ArbArray, ViewOnBase},
     arrok::ArrOk,
+    bitmap::Bitmap,
+    categorical::{CatArray, NULL_CODE},
+    decimal::{DecArray, RoundMode},
+    list::ListArray,
+    masked::{MaskedArray, MaskedView},
     match_arrok,
     py_dtype::Object,
+    struct_array::StructArray,
     ArrBase, Dim1,
 };
 
@@ -19,4 +25,9 @@ pub use super::impls::{conjugate, replicate, LeastSquaresResult};
 #[cfg(feature = "method_1d")]
 pub use super::iterators::{Iter, IterMut};
 
+#[cfg(feature = "time")]
+pub use super::date::Date;
+#[cfg(feature = "time")]
+pub use super::tz::{TimeZone, TzArray};
+
 pub use tevec::prelude::*;
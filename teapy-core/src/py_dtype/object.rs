@@ -150,7 +150,7 @@ macro_rules! impl_object_cast {
                 #[inline]
                 fn cast(self) -> Object {
                     if let Some(v) = self {
-                        v.cast()
+                        Cast::<Object>::cast(v)
                     } else {
                         Object::none()
                     }
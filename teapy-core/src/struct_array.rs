@@ -0,0 +1,222 @@
+use super::arrok::ArrOk;
+use ndarray::{Axis, Dimension, IxDyn, SliceArg};
+use std::fmt::Debug;
+use std::sync::Arc;
+use tevec::prelude::*;
+
+/// A struct array, named child arrays of the same shape.
+///
+/// The names are shared between the arrays cloned or viewed from the same
+/// `StructArray`, element `i` of the struct is made of element `i` of each field.
+#[derive(Clone)]
+pub struct StructArray<'a> {
+    pub names: Arc<[String]>,
+    pub fields: Vec<ArrOk<'a>>,
+    dim: IxDyn,
+}
+
+impl<'a> Debug for StructArray<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Struct")?;
+        f.debug_map()
+            .entries(self.names.iter().zip(&self.fields))
+            .finish()
+    }
+}
+
+impl<'a> StructArray<'a> {
+    #[inline]
+    fn new_unchecked(names: Arc<[String]>, fields: Vec<ArrOk<'a>>) -> Self {
+        let dim = fields[0].raw_dim();
+        Self { names, fields, dim }
+    }
+
+    /// Create a struct array from the names and the fields, the names should
+    /// be unique and the fields should have the same shape.
+    pub fn new(names: Arc<[String]>, fields: Vec<ArrOk<'a>>) -> TResult<Self> {
+        if fields.is_empty() {
+            tbail!("A struct array should have at least one field")
+        }
+        if names.len() != fields.len() {
+            tbail!(
+                "The number of names {} does not match the number of fields {}",
+                names.len(),
+                fields.len()
+            )
+        }
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].contains(name) {
+                tbail!("The field name {} of the struct array is duplicated", name)
+            }
+        }
+        let shape = fields[0].shape();
+        for (name, field) in names.iter().zip(&fields) {
+            if field.shape() != shape {
+                tbail!(
+                    "The field {} has shape {:?}, expect the same shape {:?} as field {}",
+                    name,
+                    field.shape(),
+                    shape,
+                    names[0]
+                )
+            }
+        }
+        Ok(Self::new_unchecked(names, fields))
+    }
+
+    /// The field named `name`.
+    pub fn field(&self, name: &str) -> TResult<&ArrOk<'a>> {
+        self.names
+            .iter()
+            .position(|n| n == name)
+            .map(|i| &self.fields[i])
+            .ok_or_else(|| {
+                terr!(
+                    "The struct array has no field {}, the fields are {:?}",
+                    name,
+                    self.names
+                )
+            })
+    }
+
+    /// Apply `f` to each field, the names are kept.
+    pub fn map_fields<'b, F>(&self, f: F) -> TResult<StructArray<'b>>
+    where
+        F: Fn(&ArrOk<'a>) -> TResult<ArrOk<'b>>,
+    {
+        let fields = self.fields.iter().map(f).collect::<TResult<Vec<_>>>()?;
+        StructArray::new(self.names.clone(), fields)
+    }
+
+    #[inline]
+    pub fn dtype_name(&self) -> String {
+        let fields = self
+            .names
+            .iter()
+            .zip(&self.fields)
+            .map(|(name, field)| format!("{}: {}", name, field.dtype_name()))
+            .collect::<Vec<_>>();
+        format!("Struct{{{}}}", fields.join(", "))
+    }
+
+    #[inline]
+    pub fn raw_dim(&self) -> IxDyn {
+        self.dim.clone()
+    }
+
+    #[inline]
+    pub fn ndim(&self) -> usize {
+        self.dim.ndim()
+    }
+
+    #[inline]
+    pub fn shape(&self) -> &[usize] {
+        self.dim.slice()
+    }
+
+    #[allow(clippy::len_without_is_empty)]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.dim.size()
+    }
+
+    #[inline]
+    pub fn len_of(&self, axis: Axis) -> usize {
+        self.dim[axis.index()]
+    }
+
+    #[inline]
+    pub fn norm_axis(&self, axis: i32) -> Axis {
+        self.fields[0].norm_axis(axis)
+    }
+
+    #[inline]
+    pub fn get_type(&self) -> &'static str {
+        "Struct Array"
+    }
+
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        Iterator::all(&mut self.fields.iter(), ArrOk::is_owned)
+    }
+
+    #[inline]
+    pub fn deref(&self) -> StructArray<'_> {
+        let fields = self.fields.iter().map(ArrOk::deref).collect();
+        StructArray::new_unchecked(self.names.clone(), fields)
+    }
+
+    #[inline]
+    pub fn view(&self) -> StructArray<'_> {
+        let fields = self.fields.iter().map(ArrOk::view).collect();
+        StructArray::new_unchecked(self.names.clone(), fields)
+    }
+
+    /// Slice each field, masked fields are sliced into their dense array.
+    pub fn slice<I: SliceArg<IxDyn>>(&self, info: I) -> StructArray<'_> {
+        let info = info.as_ref();
+        let fields = self.fields.iter().map(|f| f.slice(info)).collect();
+        StructArray::new_unchecked(self.names.clone(), fields)
+    }
+
+    #[inline]
+    pub fn into_owned<'b>(self) -> StructArray<'b> {
+        let fields = self.fields.into_iter().map(ArrOk::into_owned).collect();
+        StructArray::new_unchecked(self.names, fields)
+    }
+
+    /// Concatenate 1-d struct arrays, the fields should have the same names
+    /// and dtypes.
+    #[cfg(feature = "concat")]
+    pub fn concat_1d<'b>(arr_vec: Vec<StructArray<'_>>) -> StructArray<'b> {
+        let names = arr_vec[0].names.clone();
+        let fields = (0..names.len())
+            .map(|i| {
+                let field_vec = arr_vec
+                    .iter()
+                    .map(|a| a.fields[i].view().into_owned())
+                    .collect();
+                ArrOk::same_dtype_concat_1d(field_vec)
+            })
+            .collect();
+        StructArray::new_unchecked(names, fields)
+    }
+}
+
+#[cfg(feature = "arw")]
+impl<'a> StructArray<'a> {
+    /// Create a struct array from an arrow struct array, null structs are
+    /// read from the values of their fields.
+    pub fn from_arrow(arr: &dyn arrow::array::Array) -> TResult<Self> {
+        let a = arr
+            .as_any()
+            .downcast_ref::<arrow::array::StructArray>()
+            .ok_or_else(|| terr!("Expect an arrow struct array, got {:?}", arr.data_type()))?;
+        let names = a.fields().iter().map(|f| f.name.clone()).collect();
+        let fields = a
+            .values()
+            .iter()
+            .map(|v| ArrOk::from_arrow(v.clone()))
+            .collect();
+        StructArray::new(names, fields)
+    }
+
+    /// Convert into an arrow struct array.
+    pub fn to_arrow(&self) -> TResult<Box<dyn arrow::array::Array>> {
+        use arrow::datatypes::{DataType as ArrowDT, Field};
+        let values = self
+            .fields
+            .iter()
+            .map(ArrOk::to_arrow)
+            .collect::<TResult<Vec<_>>>()?;
+        let fields = self
+            .names
+            .iter()
+            .zip(&values)
+            .map(|(name, v)| Field::new(name, v.data_type().clone(), true))
+            .collect();
+        let out = arrow::array::StructArray::try_new(ArrowDT::Struct(fields), values, None)
+            .map_err(|e| terr!("{:?}", e))?;
+        Ok(Box::new(out))
+    }
+}
@@ -0,0 +1,318 @@
+use super::arbarray::ArbArray;
+use super::own::ArrD;
+use super::view::ArrViewD;
+use chrono::{FixedOffset, Offset, TimeZone as _};
+use chrono_tz::Tz;
+use ndarray::{Axis, IxDyn, SliceArg};
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+use tevec::prelude::*;
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// A time zone, either a IANA time zone like `Asia/Shanghai` or a fixed
+/// offset like `+08:00`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TimeZone {
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+impl Default for TimeZone {
+    #[inline]
+    fn default() -> Self {
+        TimeZone::Named(Tz::UTC)
+    }
+}
+
+impl Debug for TimeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Display for TimeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for TimeZone {
+    type Err = TError;
+
+    #[inline]
+    fn from_str(s: &str) -> TResult<Self> {
+        TimeZone::parse(s)
+    }
+}
+
+impl TimeZone {
+    /// Parse a IANA time zone name or a fixed offset like `+08:00`, `+0800`
+    /// or `UTC+08:00`.
+    pub fn parse(s: &str) -> TResult<Self> {
+        if let Ok(tz) = s.parse::<Tz>() {
+            return Ok(TimeZone::Named(tz));
+        }
+        let offset = s
+            .strip_prefix("UTC")
+            .or_else(|| s.strip_prefix("GMT"))
+            .unwrap_or(s);
+        let (sign, hm) = match offset.as_bytes().first() {
+            Some(b'+') => (1, &offset[1..]),
+            Some(b'-') => (-1, &offset[1..]),
+            _ => tbail!(ParseError: "Unknown time zone: {}", s),
+        };
+        let hm = hm.replace(':', "");
+        let (h, m) = match hm.len() {
+            1 | 2 => (hm.as_str(), "0"),
+            4 => hm.split_at(2),
+            _ => tbail!(ParseError: "Unknown time zone: {}", s),
+        };
+        let secs = match (h.parse::<i32>(), m.parse::<i32>()) {
+            (Ok(h), Ok(m)) if m < 60 => sign * (h * 3600 + m * 60),
+            _ => tbail!(ParseError: "Unknown time zone: {}", s),
+        };
+        FixedOffset::east_opt(secs)
+            .map(TimeZone::Fixed)
+            .ok_or_else(|| terr!(ParseError: "Unknown time zone: {}", s))
+    }
+
+    /// The name of the time zone, it can be parsed back by `TimeZone::parse`.
+    #[inline]
+    pub fn name(&self) -> String {
+        match self {
+            TimeZone::Named(tz) => tz.name().to_string(),
+            TimeZone::Fixed(offset) => offset.to_string(),
+        }
+    }
+
+    /// The offset from utc in seconds at an utc instant.
+    #[inline]
+    pub fn offset_at_utc(&self, utc: DateTime<unit::Nanosecond>) -> Option<i32> {
+        let dt = utc.as_cr()?.naive_utc();
+        Some(match self {
+            TimeZone::Named(tz) => tz.offset_from_utc_datetime(&dt).fix().local_minus_utc(),
+            TimeZone::Fixed(offset) => offset.local_minus_utc(),
+        })
+    }
+
+    /// The offset from utc in seconds of a local wall time, the earlier offset is
+    /// used for an ambiguous time and `None` is returned for a nonexistent time.
+    #[inline]
+    pub fn offset_at_local(&self, local: DateTime<unit::Nanosecond>) -> Option<i32> {
+        let dt = local.as_cr()?.naive_utc();
+        match self {
+            TimeZone::Named(tz) => tz
+                .offset_from_local_datetime(&dt)
+                .earliest()
+                .map(|o| o.fix().local_minus_utc()),
+            TimeZone::Fixed(offset) => Some(offset.local_minus_utc()),
+        }
+    }
+
+    /// Convert an utc instant to the local wall time.
+    #[inline]
+    pub fn utc_to_local(&self, utc: DateTime<unit::Nanosecond>) -> DateTime<unit::Nanosecond> {
+        self.offset_at_utc(utc)
+            .map(|o| DateTime::new(utc.into_i64() + o as i64 * NANOS_PER_SEC))
+            .unwrap_or_default()
+    }
+
+    /// Convert a local wall time to the utc instant, `NaT` is returned if the
+    /// local time doesn't exist in the time zone.
+    #[inline]
+    pub fn local_to_utc(&self, local: DateTime<unit::Nanosecond>) -> DateTime<unit::Nanosecond> {
+        self.offset_at_local(local)
+            .map(|o| DateTime::new(local.into_i64() - o as i64 * NANOS_PER_SEC))
+            .unwrap_or_default()
+    }
+
+    /// Format an utc instant in the time zone, `%z` formats the utc offset.
+    #[inline]
+    pub fn strftime(&self, utc: DateTime<unit::Nanosecond>, fmt: Option<&str>) -> String {
+        let (Some(dt), Some(offset)) = (utc.as_cr(), self.offset_at_utc(utc)) else {
+            return "NaT".to_string();
+        };
+        let offset = FixedOffset::east_opt(offset).unwrap();
+        dt.with_timezone(&offset)
+            .format(fmt.unwrap_or("%Y-%m-%d %H:%M:%S.%f%:z"))
+            .to_string()
+    }
+}
+
+/// A timezone-aware datetime array, the values are the utc instants in
+/// nanoseconds and the time zone is only used to get the local wall time.
+#[derive(Clone)]
+pub struct TzArray<'a> {
+    pub utc: ArbArray<'a, DateTime<unit::Nanosecond>>,
+    pub tz: TimeZone,
+}
+
+impl<'a> Debug for TzArray<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DateTime[{}]{:?}", self.tz, self.strftime(None).0)
+    }
+}
+
+impl<'a> TzArray<'a> {
+    #[inline]
+    pub fn new(utc: ArbArray<'a, DateTime<unit::Nanosecond>>, tz: TimeZone) -> Self {
+        Self { utc, tz }
+    }
+
+    /// Localize naive local wall times to the time zone, nonexistent local
+    /// times are `NaT` and ambiguous local times use the earlier instant.
+    pub fn from_local<'b>(
+        local: ArrViewD<'_, DateTime<unit::Nanosecond>>,
+        tz: TimeZone,
+    ) -> TzArray<'b> {
+        TzArray::new(local.map(|v| tz.local_to_utc(*v)).into(), tz)
+    }
+
+    /// The local wall times as naive datetimes.
+    #[inline]
+    pub fn to_local(&self) -> ArrD<DateTime<unit::Nanosecond>> {
+        self.utc.view().map(|v| self.tz.utc_to_local(*v))
+    }
+
+    /// The same instants in another time zone.
+    #[inline]
+    pub fn convert_tz(&self, tz: TimeZone) -> TzArray<'_> {
+        TzArray::new(self.utc.view().into(), tz)
+    }
+
+    /// The same local wall times in another time zone.
+    #[inline]
+    pub fn replace_tz<'b>(&self, tz: TimeZone) -> TzArray<'b> {
+        TzArray::from_local(self.to_local().view(), tz)
+    }
+
+    /// Truncate the local wall times, the start of a bucket is shifted by
+    /// `offset` (e.g. a session open), so daily buckets split at local
+    /// midnight rather than utc midnight.
+    pub fn duration_trunc<'b>(
+        &self,
+        duration: TimeDelta,
+        offset: Option<TimeDelta>,
+    ) -> TzArray<'b> {
+        let utc = self.utc.view().map(|v| {
+            let local = self.tz.utc_to_local(*v);
+            let trunc = match offset {
+                Some(offset) => (local - offset).duration_trunc(duration) + offset,
+                None => local.duration_trunc(duration),
+            };
+            self.tz.local_to_utc(trunc)
+        });
+        TzArray::new(utc.into(), self.tz)
+    }
+
+    #[inline]
+    pub fn strftime(&self, fmt: Option<&str>) -> ArrD<String> {
+        self.utc.view().map(|v| self.tz.strftime(*v, fmt))
+    }
+
+    #[inline]
+    pub fn raw_dim(&self) -> IxDyn {
+        self.utc.raw_dim()
+    }
+
+    #[inline]
+    pub fn ndim(&self) -> usize {
+        self.utc.ndim()
+    }
+
+    #[inline]
+    pub fn shape(&self) -> &[usize] {
+        self.utc.shape()
+    }
+
+    #[allow(clippy::len_without_is_empty)]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.utc.len()
+    }
+
+    #[inline]
+    pub fn len_of(&self, axis: Axis) -> usize {
+        self.utc.len_of(axis)
+    }
+
+    #[inline]
+    pub fn norm_axis(&self, axis: i32) -> Axis {
+        self.utc.norm_axis(axis)
+    }
+
+    #[inline]
+    pub fn get_type(&self) -> &'static str {
+        self.utc.get_type()
+    }
+
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        self.utc.is_owned()
+    }
+
+    #[inline]
+    pub fn deref(&self) -> TzArray<'_> {
+        TzArray::new(self.utc.deref(), self.tz)
+    }
+
+    #[inline]
+    pub fn view(&self) -> TzArray<'_> {
+        TzArray::new(self.utc.view().into(), self.tz)
+    }
+
+    #[inline]
+    pub fn slice<I: SliceArg<IxDyn>>(&self, info: I) -> TzArray<'_> {
+        TzArray::new(self.utc.slice(info), self.tz)
+    }
+
+    #[inline]
+    pub fn into_owned<'b>(self) -> TzArray<'b> {
+        let utc = match self.utc {
+            ArbArray::Owned(arr) => arr,
+            utc => utc.view().to_owned(),
+        };
+        TzArray::new(utc.into(), self.tz)
+    }
+
+    /// Concatenate 1-d timezone-aware arrays, the instants are kept and the
+    /// time zone of the first array is used.
+    #[cfg(feature = "concat")]
+    pub fn concat_1d<'b>(arr_vec: Vec<TzArray<'_>>) -> TzArray<'b> {
+        let tz = <[_]>::first(&arr_vec).map(|a| a.tz).unwrap_or_default();
+        let utc = arr_vec
+            .iter()
+            .flat_map(|a| a.utc.view().0.iter().copied().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        TzArray::new(super::own::Arr1::from_vec(utc).into_dyn().into(), tz)
+    }
+}
+
+#[cfg(feature = "arw")]
+impl<'a> TzArray<'a> {
+    /// Convert a 1-d timezone-aware array into an arrow timestamp array with
+    /// the time zone.
+    pub fn to_arrow(&self) -> TResult<Box<dyn arrow::array::Array>> {
+        use arrow::array::PrimitiveArray;
+        use arrow::bitmap::Bitmap;
+        use arrow::datatypes::{DataType as ArrowDT, TimeUnit};
+        if self.ndim() > 1 {
+            tbail!(
+                "Only 1-d array can be converted to arrow, got ndim {}",
+                self.ndim()
+            )
+        }
+        let view = self.utc.view();
+        let validity = if Iterator::any(&mut view.0.iter(), |v| v.is_nat()) {
+            Some(Bitmap::from_iter(view.0.iter().map(|v| v.is_not_nat())))
+        } else {
+            None
+        };
+        let values = view.0.iter().map(|v| v.into_i64()).collect::<Vec<_>>();
+        let dtype = ArrowDT::Timestamp(TimeUnit::Nanosecond, Some(self.tz.name()));
+        let out = PrimitiveArray::<i64>::try_new(dtype, values.into(), validity)
+            .map_err(|e| terr!("{:?}", e))?;
+        Ok(Box::new(out))
+    }
+}